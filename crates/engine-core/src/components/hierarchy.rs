@@ -0,0 +1,53 @@
+use crate::ecs::{Component, EntityId};
+
+/// Parentコンポーネント
+/// 親Entityを表す（`World::set_parent` で設定する）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Parent(EntityId);
+
+impl Parent {
+    pub(crate) fn new(parent: EntityId) -> Self {
+        Self(parent)
+    }
+
+    /// 親Entityを取得
+    pub fn get(&self) -> EntityId {
+        self.0
+    }
+}
+
+impl Component for Parent {}
+
+/// Childrenコンポーネント
+/// 子Entityの一覧を表す（`World::set_parent` により自動で更新される）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Children(Vec<EntityId>);
+
+impl Children {
+    /// 子Entityをスライスとして取得（追加順）
+    pub fn as_slice(&self) -> &[EntityId] {
+        &self.0
+    }
+
+    /// 子Entity数
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// 子Entityがないか
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn push(&mut self, child: EntityId) {
+        if !self.0.contains(&child) {
+            self.0.push(child);
+        }
+    }
+
+    pub(crate) fn remove(&mut self, child: EntityId) {
+        self.0.retain(|&c| c != child);
+    }
+}
+
+impl Component for Children {}
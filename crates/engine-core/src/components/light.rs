@@ -0,0 +1,164 @@
+use crate::ecs::Component;
+use crate::math::Vec3;
+
+/// ライトの影の設定
+///
+/// バイアスはシャドウマップのテクセル単位で、表面を光の方向（`bias`）と
+/// 法線方向（`normal_bias`）にずらしてから深度を比較する。
+/// 大きいほど影のちらつき（シャドウアクネ）が減るが、接地部分の影が離れる
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowSettings {
+    /// 影を落とすか
+    pub enabled: bool,
+    pub bias: f32,
+    pub normal_bias: f32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            bias: 1.0,
+            normal_bias: 1.5,
+        }
+    }
+}
+
+/// DirectionalLightコンポーネント
+/// 無限遠からの平行光源（太陽光など）
+///
+/// 光はEntityのローカル -Z 方向に進む（glTFのKHR_lights_punctualと同じ）
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectionalLight {
+    /// 光の色（リニアRGB）
+    pub color: Vec3,
+    /// 強さ（色と乗算される）
+    pub intensity: f32,
+    /// 影の設定（カスケードシャドウマップ）
+    pub shadow: ShadowSettings,
+}
+
+impl Default for DirectionalLight {
+    fn default() -> Self {
+        Self {
+            color: Vec3::ONE,
+            intensity: 3.0,
+            shadow: ShadowSettings::default(),
+        }
+    }
+}
+
+impl Component for DirectionalLight {}
+
+/// PointLightコンポーネント
+/// Entityの位置から全方向に広がる点光源
+///
+/// 明るさは距離の2乗に反比例し、`range` で0になるよう滑らかに減衰する。
+/// 点光源は影を落とさない
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointLight {
+    /// 光の色（リニアRGB）
+    pub color: Vec3,
+    /// 強さ（色と乗算される）
+    pub intensity: f32,
+    /// 光が届く距離
+    pub range: f32,
+}
+
+impl Default for PointLight {
+    fn default() -> Self {
+        Self {
+            color: Vec3::ONE,
+            intensity: 10.0,
+            range: 10.0,
+        }
+    }
+}
+
+impl Component for PointLight {}
+
+/// SpotLightコンポーネント
+/// Entityの位置からローカル -Z 方向に円錐状に照らす光源
+///
+/// 内側の角度までは一定の明るさで、外側の角度に向けて0まで減衰する
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpotLight {
+    /// 光の色（リニアRGB）
+    pub color: Vec3,
+    /// 強さ（色と乗算される）
+    pub intensity: f32,
+    /// 光が届く距離
+    pub range: f32,
+    /// 減衰が始まる角度（中心軸からのラジアン）
+    pub inner_cone_angle: f32,
+    /// 光が届かなくなる角度（中心軸からのラジアン、π/2以下）
+    pub outer_cone_angle: f32,
+    /// 影の設定
+    pub shadow: ShadowSettings,
+}
+
+impl Default for SpotLight {
+    fn default() -> Self {
+        Self {
+            color: Vec3::ONE,
+            intensity: 10.0,
+            range: 10.0,
+            inner_cone_angle: 0.0,
+            outer_cone_angle: std::f32::consts::FRAC_PI_4,
+            shadow: ShadowSettings::default(),
+        }
+    }
+}
+
+impl Component for SpotLight {}
+
+/// ShadowFlagsコンポーネント
+/// Entityが影を落とすか・受けるか
+///
+/// ShadowFlagsを持たないEntityは影を落とし、受ける
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShadowFlags {
+    pub casts_shadows: bool,
+    pub receives_shadows: bool,
+}
+
+impl Default for ShadowFlags {
+    fn default() -> Self {
+        Self {
+            casts_shadows: true,
+            receives_shadows: true,
+        }
+    }
+}
+
+impl Component for ShadowFlags {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults() {
+        assert_eq!(DirectionalLight::default().color, Vec3::ONE);
+        assert!(PointLight::default().range > 0.0);
+        let spot = SpotLight::default();
+        assert!(spot.inner_cone_angle < spot.outer_cone_angle);
+        assert!(spot.outer_cone_angle <= std::f32::consts::FRAC_PI_2);
+        assert!(spot.shadow.enabled);
+
+        let flags = ShadowFlags::default();
+        assert!(flags.casts_shadows && flags.receives_shadows);
+    }
+
+    #[test]
+    fn test_queryable_by_name() {
+        use crate::ecs::World;
+
+        let mut world = World::new();
+        let entity = world.spawn();
+        world.insert(entity, PointLight::default());
+        assert!(world.has_component(entity, "PointLight"));
+        assert!(!world.has_component(entity, "SpotLight"));
+        assert!(World::component_type_id("DirectionalLight").is_some());
+    }
+}
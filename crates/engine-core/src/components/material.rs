@@ -0,0 +1,118 @@
+use crate::ecs::Component;
+use crate::math::{Vec3, Vec4};
+
+/// テクスチャハンドル
+/// Rendererのテクスチャストアに登録されたテクスチャを参照する
+///
+/// 解放済みのテクスチャを参照した場合は既定のテクスチャ（白、平坦な法線）で描画される
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TextureHandle(u32);
+
+impl TextureHandle {
+    /// ハンドルIDから作成
+    pub fn new(id: u32) -> Self {
+        Self(id)
+    }
+
+    /// ハンドルIDを取得
+    pub fn id(&self) -> u32 {
+        self.0
+    }
+}
+
+/// Materialコンポーネント
+/// メタリック・ラフネスモデルのPBRマテリアル
+///
+/// Materialを持たないEntityは `Material::default()` で描画される。
+/// 1フレームで描画できる異なるマテリアルは最大256個（影を受けないEntityは別に数える）で、
+/// 超えた分は既定のマテリアルで描画される
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    /// ベースカラー（リニアRGBA、頂点色と乗算される）
+    pub base_color: Vec4,
+    /// 金属度（0.0〜1.0）
+    pub metallic: f32,
+    /// 粗さ（0.0〜1.0）
+    pub roughness: f32,
+    /// 自己発光色（リニアRGB）
+    pub emissive: Vec3,
+    /// trueならライティングを行わずベースカラーをそのまま出力する
+    pub unlit: bool,
+    /// ベースカラーテクスチャ（sRGB、base_colorと乗算される）
+    pub albedo_texture: Option<TextureHandle>,
+    /// 接空間の法線マップ（リニア）
+    pub normal_texture: Option<TextureHandle>,
+    /// 粗さ・金属度テクスチャ（glTFと同じくG=粗さ、B=金属度、各係数と乗算される）
+    pub roughness_texture: Option<TextureHandle>,
+}
+
+impl Material {
+    /// ベースカラーのみ指定したMaterialを作成
+    pub fn from_color(base_color: Vec4) -> Self {
+        Self {
+            base_color,
+            ..Self::default()
+        }
+    }
+
+    /// ライティングを行わないMaterialを作成
+    pub fn unlit(base_color: Vec4) -> Self {
+        Self {
+            base_color,
+            unlit: true,
+            ..Self::default()
+        }
+    }
+
+    /// 参照しているテクスチャ（albedo, normal, roughness の順）
+    pub fn textures(&self) -> [Option<TextureHandle>; 3] {
+        [self.albedo_texture, self.normal_texture, self.roughness_texture]
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            base_color: Vec4::ONE,
+            metallic: 0.0,
+            roughness: 0.5,
+            emissive: Vec3::ZERO,
+            unlit: false,
+            albedo_texture: None,
+            normal_texture: None,
+            roughness_texture: None,
+        }
+    }
+}
+
+impl Component for Material {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_white_dielectric() {
+        let material = Material::default();
+        assert_eq!(material.base_color, Vec4::ONE);
+        assert_eq!(material.metallic, 0.0);
+        assert!(!material.unlit);
+    }
+
+    #[test]
+    fn test_constructors() {
+        let red = Vec4::new(1.0, 0.0, 0.0, 1.0);
+        assert_eq!(Material::from_color(red).base_color, red);
+        assert!(Material::unlit(red).unlit);
+    }
+
+    #[test]
+    fn test_textures() {
+        let material = Material {
+            normal_texture: Some(TextureHandle::new(3)),
+            ..Material::default()
+        };
+        assert_eq!(material.textures(), [None, Some(TextureHandle::new(3)), None]);
+        assert_eq!(Material::default().textures(), [None; 3]);
+    }
+}
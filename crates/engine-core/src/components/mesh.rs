@@ -0,0 +1,60 @@
+use crate::ecs::Component;
+
+/// MeshHandleコンポーネント
+/// Rendererのメッシュストアに登録されたメッシュを参照する
+///
+/// MeshHandleを持たないEntityは組み込みのCubeで描画される。
+/// `MeshHandle::NONE` を持つEntityは描画されない（階層のグループノードなど）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MeshHandle(u32);
+
+impl MeshHandle {
+    /// 組み込みのCubeメッシュ
+    pub const CUBE: MeshHandle = MeshHandle(0);
+
+    /// メッシュなし（描画・ピッキングの対象外）
+    pub const NONE: MeshHandle = MeshHandle(u32::MAX);
+
+    /// ハンドルIDから作成
+    pub fn new(id: u32) -> Self {
+        Self(id)
+    }
+
+    /// ハンドルIDを取得
+    pub fn id(&self) -> u32 {
+        self.0
+    }
+
+    /// メッシュなしか
+    pub fn is_none(&self) -> bool {
+        *self == Self::NONE
+    }
+}
+
+impl Default for MeshHandle {
+    fn default() -> Self {
+        Self::CUBE
+    }
+}
+
+impl Component for MeshHandle {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let handle = MeshHandle::new(7);
+        assert_eq!(handle.id(), 7);
+        assert_ne!(handle, MeshHandle::CUBE);
+    }
+
+    #[test]
+    fn test_default_is_cube() {
+        assert_eq!(MeshHandle::default(), MeshHandle::CUBE);
+        assert_eq!(MeshHandle::CUBE.id(), 0);
+        assert!(!MeshHandle::default().is_none());
+        assert!(MeshHandle::NONE.is_none());
+    }
+}
@@ -1,5 +1,13 @@
 pub mod transform;
 pub mod name;
+pub mod hierarchy;
+pub mod mesh;
+pub mod material;
+pub mod light;
 
 pub use transform::{Transform, ModelUniform};
 pub use name::Name;
+pub use hierarchy::{Parent, Children};
+pub use mesh::MeshHandle;
+pub use material::{Material, TextureHandle};
+pub use light::{DirectionalLight, PointLight, ShadowFlags, ShadowSettings, SpotLight};
@@ -0,0 +1,146 @@
+//! 変更追跡
+//!
+//! Worldへの変更（Entity生成・削除、コンポーネントの追加・変更・削除）を
+//! Entity単位で記録する。同じEntityへの複数回の変更は1件にまとめられる。
+//! Entityに属さない変更（カメラ位置）はフラグとして記録する。
+
+use std::any::TypeId;
+use std::collections::{HashMap, HashSet};
+
+use super::entity::EntityId;
+
+/// 単一Entityの変更内容
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntityChange {
+    /// 生成された
+    pub spawned: bool,
+    /// 削除された
+    pub despawned: bool,
+    /// 追加・変更・削除されたコンポーネントの型
+    pub components: HashSet<TypeId>,
+}
+
+impl EntityChange {
+    /// 指定コンポーネントが変更されたか
+    pub fn touches(&self, type_id: TypeId) -> bool {
+        self.components.contains(&type_id)
+    }
+}
+
+/// 変更の集合
+#[derive(Debug, Clone, Default)]
+pub struct ChangeSet {
+    entities: HashMap<EntityId, EntityChange>,
+    camera_moved: bool,
+}
+
+impl ChangeSet {
+    /// 空の変更集合を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Entity生成を記録
+    pub fn record_spawn(&mut self, entity: EntityId) {
+        self.entities.entry(entity).or_default().spawned = true;
+    }
+
+    /// Entity削除を記録
+    pub fn record_despawn(&mut self, entity: EntityId) {
+        self.entities.entry(entity).or_default().despawned = true;
+    }
+
+    /// コンポーネントの追加・変更・削除を記録
+    pub fn record_component(&mut self, entity: EntityId, type_id: TypeId) {
+        self.entities
+            .entry(entity)
+            .or_default()
+            .components
+            .insert(type_id);
+    }
+
+    /// カメラ位置の変更を記録
+    pub fn record_camera_moved(&mut self) {
+        self.camera_moved = true;
+    }
+
+    /// カメラ位置が変更されたか
+    pub fn camera_moved(&self) -> bool {
+        self.camera_moved
+    }
+
+    /// 別の変更集合を統合
+    pub fn merge(&mut self, other: ChangeSet) {
+        self.camera_moved |= other.camera_moved;
+        for (entity, change) in other.entities {
+            let entry = self.entities.entry(entity).or_default();
+            entry.spawned |= change.spawned;
+            entry.despawned |= change.despawned;
+            entry.components.extend(change.components);
+        }
+    }
+
+    /// 変更されたEntityとその内容をイテレート
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &EntityChange)> {
+        self.entities.iter().map(|(&entity, change)| (entity, change))
+    }
+
+    /// 指定Entityの変更内容を取得
+    pub fn get(&self, entity: EntityId) -> Option<&EntityChange> {
+        self.entities.get(&entity)
+    }
+
+    /// 変更されたEntity数
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// 変更がないか
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty() && !self.camera_moved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct A;
+    struct B;
+
+    #[test]
+    fn test_record_coalesces_per_entity() {
+        let e = EntityId::new(0, 1);
+        let mut changes = ChangeSet::new();
+        changes.record_spawn(e);
+        changes.record_component(e, TypeId::of::<A>());
+        changes.record_component(e, TypeId::of::<A>());
+        changes.record_component(e, TypeId::of::<B>());
+
+        assert_eq!(changes.len(), 1);
+        let change = changes.get(e).unwrap();
+        assert!(change.spawned);
+        assert!(!change.despawned);
+        assert_eq!(change.components.len(), 2);
+        assert!(change.touches(TypeId::of::<A>()));
+    }
+
+    #[test]
+    fn test_merge() {
+        let e1 = EntityId::new(0, 1);
+        let e2 = EntityId::new(1, 1);
+        let mut a = ChangeSet::new();
+        a.record_component(e1, TypeId::of::<A>());
+        let mut b = ChangeSet::new();
+        b.record_despawn(e1);
+        b.record_spawn(e2);
+        b.record_camera_moved();
+
+        a.merge(b);
+        assert!(a.camera_moved());
+        assert_eq!(a.len(), 2);
+        assert!(a.get(e1).unwrap().despawned);
+        assert!(a.get(e1).unwrap().touches(TypeId::of::<A>()));
+        assert!(a.get(e2).unwrap().spawned);
+    }
+}
@@ -0,0 +1,619 @@
+//! セカンダリインデックス
+//!
+//! クエリの候補Entityを全件スキャンせずに絞り込むためのオプトインのインデックス。
+//! - Hashインデックス: `name == "Player"` のような等価検索
+//! - B-treeインデックス: `position.x > 0` のような範囲検索（等価検索も可）
+//! - コンポーネントビットセット: `with_components` の絞り込み
+//!
+//! フィールドインデックスはコンポーネント変更時に該当Entityを「保留」にし、
+//! 次の可変操作の先頭で最新値から再登録する（`get_mut` の参照経由の変更に対応するため）。
+//! 保留中のEntityは常に候補に含まれるので、クエリ結果は全件スキャンと一致する。
+
+use std::any::TypeId;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::ops::Bound;
+
+use serde::{Deserialize, Serialize};
+
+use super::entity::EntityId;
+use super::query::{CompareOp, FilterValue, QueryDescriptor};
+
+// ============================================================================
+// IndexKind - インデックス種別
+// ============================================================================
+
+/// フィールドインデックスの種別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexKind {
+    /// 等価検索用
+    Hash,
+    /// 範囲検索用
+    BTree,
+}
+
+impl std::str::FromStr for IndexKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hash" => Ok(IndexKind::Hash),
+            "btree" => Ok(IndexKind::BTree),
+            _ => Err(format!("unknown index kind '{}' (expected 'hash' or 'btree')", s)),
+        }
+    }
+}
+
+impl fmt::Display for IndexKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexKind::Hash => f.write_str("hash"),
+            IndexKind::BTree => f.write_str("btree"),
+        }
+    }
+}
+
+// ============================================================================
+// IndexKey - インデックスキー
+// ============================================================================
+
+/// 全順序付きのインデックスキー
+/// 型の順序は Null < Bool < Number < String
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum IndexKey {
+    Null,
+    Bool(bool),
+    Number(OrderedF64),
+    String(String),
+}
+
+impl IndexKey {
+    fn from_value(value: &FilterValue) -> Self {
+        match value {
+            FilterValue::Null => IndexKey::Null,
+            FilterValue::Bool(b) => IndexKey::Bool(*b),
+            FilterValue::Number(n) => IndexKey::Number(OrderedF64::new(*n)),
+            FilterValue::String(s) => IndexKey::String(s.clone()),
+        }
+    }
+
+    /// 同じ型の中での範囲（下限, 上限）
+    fn type_range(&self) -> (Bound<IndexKey>, Bound<IndexKey>) {
+        match self {
+            IndexKey::Null => (Bound::Included(IndexKey::Null), Bound::Included(IndexKey::Null)),
+            IndexKey::Bool(_) => (
+                Bound::Included(IndexKey::Bool(false)),
+                Bound::Included(IndexKey::Bool(true)),
+            ),
+            IndexKey::Number(_) => (
+                Bound::Included(IndexKey::Number(OrderedF64::new(f64::NEG_INFINITY))),
+                Bound::Included(IndexKey::Number(OrderedF64::new(f64::INFINITY))),
+            ),
+            IndexKey::String(_) => (Bound::Included(IndexKey::String(String::new())), Bound::Unbounded),
+        }
+    }
+}
+
+/// total_cmpで全順序を持つf64（-0.0は0.0に正規化）
+#[derive(Debug, Clone, Copy)]
+struct OrderedF64(f64);
+
+impl OrderedF64 {
+    fn new(value: f64) -> Self {
+        Self(if value == 0.0 { 0.0 } else { value })
+    }
+}
+
+impl PartialEq for OrderedF64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OrderedF64 {}
+
+impl PartialOrd for OrderedF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl std::hash::Hash for OrderedF64 {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+// ============================================================================
+// FieldIndex - フィールドインデックス
+// ============================================================================
+
+/// キー -> Entity集合
+enum IndexEntries {
+    Hash(HashMap<IndexKey, HashSet<EntityId>>),
+    BTree(BTreeMap<IndexKey, HashSet<EntityId>>),
+}
+
+/// 単一フィールドのインデックス
+struct FieldIndex {
+    field: String,
+    kind: IndexKind,
+    /// 値の取得元コンポーネント
+    source: TypeId,
+    entries: IndexEntries,
+    /// Entity -> 登録済みキー（削除用）
+    keys: HashMap<EntityId, IndexKey>,
+    /// 変更後、まだ再登録されていないEntity
+    pending: HashSet<EntityId>,
+}
+
+impl FieldIndex {
+    fn new(field: String, kind: IndexKind, source: TypeId) -> Self {
+        let entries = match kind {
+            IndexKind::Hash => IndexEntries::Hash(HashMap::new()),
+            IndexKind::BTree => IndexEntries::BTree(BTreeMap::new()),
+        };
+        Self {
+            field,
+            kind,
+            source,
+            entries,
+            keys: HashMap::new(),
+            pending: HashSet::new(),
+        }
+    }
+
+    fn insert(&mut self, entity: EntityId, key: IndexKey) {
+        let bucket = match &mut self.entries {
+            IndexEntries::Hash(map) => map.entry(key.clone()).or_default(),
+            IndexEntries::BTree(map) => map.entry(key.clone()).or_default(),
+        };
+        bucket.insert(entity);
+        self.keys.insert(entity, key);
+    }
+
+    fn remove(&mut self, entity: EntityId) {
+        let Some(key) = self.keys.remove(&entity) else {
+            return;
+        };
+        let emptied = match &mut self.entries {
+            IndexEntries::Hash(map) => map.get_mut(&key).map(|bucket| {
+                bucket.remove(&entity);
+                bucket.is_empty()
+            }),
+            IndexEntries::BTree(map) => map.get_mut(&key).map(|bucket| {
+                bucket.remove(&entity);
+                bucket.is_empty()
+            }),
+        };
+        if emptied == Some(true) {
+            match &mut self.entries {
+                IndexEntries::Hash(map) => map.remove(&key),
+                IndexEntries::BTree(map) => map.remove(&key),
+            };
+        }
+    }
+
+    /// 比較条件に一致する登録済みEntity（保留分を除く）
+    /// このインデックスで扱えない条件の場合はNone
+    fn lookup(&self, op: CompareOp, value: &FilterValue) -> Option<Vec<EntityId>> {
+        let key = IndexKey::from_value(value);
+        let is_nan = value.as_f64().is_some_and(f64::is_nan);
+        if op != CompareOp::Ne && (is_nan || (key == IndexKey::Null && op != CompareOp::Eq)) {
+            // NaNとの比較、Nullの範囲比較は常に不一致
+            return Some(Vec::new());
+        }
+        match (&self.entries, op) {
+            (IndexEntries::Hash(map), CompareOp::Eq) => {
+                Some(map.get(&key).map(|b| b.iter().copied().collect()).unwrap_or_default())
+            }
+            (IndexEntries::BTree(map), _) => {
+                let (type_lower, type_upper) = key.type_range();
+                let range = match op {
+                    CompareOp::Eq => (Bound::Included(key.clone()), Bound::Included(key)),
+                    CompareOp::Lt => (type_lower, Bound::Excluded(key)),
+                    CompareOp::Le => (type_lower, Bound::Included(key)),
+                    CompareOp::Gt => (Bound::Excluded(key), type_upper),
+                    CompareOp::Ge => (Bound::Included(key), type_upper),
+                    CompareOp::Ne => return None,
+                };
+                Some(
+                    map.range(range)
+                        .flat_map(|(_, bucket)| bucket.iter().copied())
+                        .collect(),
+                )
+            }
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// ComponentIndex - コンポーネントビットセット
+// ============================================================================
+
+/// コンポーネント所持のビットセット（EntityIdのindexをビット位置とする）
+struct ComponentIndex {
+    component: String,
+    source: TypeId,
+    bits: Vec<u64>,
+    count: usize,
+}
+
+impl ComponentIndex {
+    fn new(component: String, source: TypeId) -> Self {
+        Self {
+            component,
+            source,
+            bits: Vec::new(),
+            count: 0,
+        }
+    }
+
+    fn set(&mut self, index: u32) {
+        let (word, bit) = (index as usize / 64, index % 64);
+        if word >= self.bits.len() {
+            self.bits.resize(word + 1, 0);
+        }
+        if self.bits[word] & (1 << bit) == 0 {
+            self.bits[word] |= 1 << bit;
+            self.count += 1;
+        }
+    }
+
+    fn clear(&mut self, index: u32) {
+        let (word, bit) = (index as usize / 64, index % 64);
+        if let Some(w) = self.bits.get_mut(word)
+            && *w & (1 << bit) != 0
+        {
+            *w &= !(1 << bit);
+            self.count -= 1;
+        }
+    }
+
+    fn indices(&self) -> impl Iterator<Item = u32> + '_ {
+        self.bits.iter().enumerate().flat_map(|(word, &bits)| {
+            (0..64)
+                .filter(move |bit| bits & (1 << bit) != 0)
+                .map(move |bit| (word * 64 + bit) as u32)
+        })
+    }
+}
+
+// ============================================================================
+// QueryPlan - 実行計画
+// ============================================================================
+
+/// 候補Entityの取得方法
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AccessPath {
+    /// 全生存Entityを走査
+    FullScan,
+    /// フィールドインデックスを使用
+    FieldIndex {
+        field: String,
+        kind: IndexKind,
+        op: CompareOp,
+        value: FilterValue,
+    },
+    /// コンポーネントビットセットを使用
+    ComponentBitset { component: String },
+}
+
+/// クエリの実行計画
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryPlan {
+    /// 選択された取得方法
+    pub access: AccessPath,
+    /// 取得方法が返す候補数（フィルター適用前）
+    pub estimated_candidates: usize,
+    /// 生存Entity数
+    pub total_entities: usize,
+}
+
+impl fmt::Display for QueryPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.access {
+            AccessPath::FullScan => write!(f, "Full Scan")?,
+            AccessPath::FieldIndex { field, kind, op, value } => {
+                let filter = super::query::FilterExpr::new(field.clone(), *op, value.clone());
+                write!(f, "Index Scan using {}({}) where {}", kind, field, filter)?
+            }
+            AccessPath::ComponentBitset { component } => {
+                write!(f, "Bitset Scan using component({})", component)?
+            }
+        }
+        write!(
+            f,
+            " ({} of {} entities)",
+            self.estimated_candidates, self.total_entities
+        )
+    }
+}
+
+// ============================================================================
+// IndexRegistry - インデックス管理
+// ============================================================================
+
+/// World が保持するインデックス群
+#[derive(Default)]
+pub(crate) struct IndexRegistry {
+    fields: Vec<FieldIndex>,
+    components: Vec<ComponentIndex>,
+}
+
+impl IndexRegistry {
+    /// インデックスが1つもないか
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.components.is_empty()
+    }
+
+    /// 保留中のEntityがあるか
+    pub fn has_pending(&self) -> bool {
+        self.fields.iter().any(|index| !index.pending.is_empty())
+    }
+
+    /// フィールドインデックスが存在するか
+    pub fn has_field_index(&self, field: &str) -> bool {
+        self.fields.iter().any(|index| index.field == field)
+    }
+
+    /// コンポーネントインデックスが存在するか
+    pub fn has_component_index(&self, component: &str) -> bool {
+        self.components.iter().any(|index| index.component == component)
+    }
+
+    /// フィールドインデックスを追加（全Entityを保留として登録し、flushで構築する）
+    pub fn add_field_index(
+        &mut self,
+        field: &str,
+        kind: IndexKind,
+        source: TypeId,
+        entities: impl Iterator<Item = EntityId>,
+    ) {
+        let mut index = FieldIndex::new(field.to_string(), kind, source);
+        index.pending.extend(entities);
+        self.fields.push(index);
+    }
+
+    /// コンポーネントインデックスを追加
+    pub fn add_component_index(
+        &mut self,
+        component: &str,
+        source: TypeId,
+        entities: impl Iterator<Item = EntityId>,
+    ) {
+        let mut index = ComponentIndex::new(component.to_string(), source);
+        for entity in entities {
+            index.set(entity.index());
+        }
+        self.components.push(index);
+    }
+
+    /// インデックスを削除（フィールド名またはコンポーネント名）
+    pub fn drop_index(&mut self, name: &str) -> bool {
+        let before = self.fields.len() + self.components.len();
+        self.fields.retain(|index| index.field != name);
+        self.components.retain(|index| index.component != name);
+        before != self.fields.len() + self.components.len()
+    }
+
+    /// インデックス名の一覧
+    pub fn names(&self) -> Vec<String> {
+        self.fields
+            .iter()
+            .map(|index| format!("{}({})", index.kind, index.field))
+            .chain(
+                self.components
+                    .iter()
+                    .map(|index| format!("component({})", index.component)),
+            )
+            .collect()
+    }
+
+    /// コンポーネントの値が変更された（または追加された）
+    pub fn on_changed(&mut self, source: TypeId, entity: EntityId) {
+        for index in self.fields.iter_mut().filter(|index| index.source == source) {
+            index.remove(entity);
+            index.pending.insert(entity);
+        }
+    }
+
+    /// コンポーネントが追加された
+    pub fn on_added(&mut self, source: TypeId, entity: EntityId) {
+        for index in self.components.iter_mut().filter(|index| index.source == source) {
+            index.set(entity.index());
+        }
+        self.on_changed(source, entity);
+    }
+
+    /// コンポーネントが削除された
+    pub fn on_removed(&mut self, source: TypeId, entity: EntityId) {
+        for index in self.components.iter_mut().filter(|index| index.source == source) {
+            index.clear(entity.index());
+        }
+        for index in self.fields.iter_mut().filter(|index| index.source == source) {
+            index.remove(entity);
+            index.pending.remove(&entity);
+        }
+    }
+
+    /// Entityが削除された
+    pub fn on_despawn(&mut self, entity: EntityId) {
+        for index in &mut self.components {
+            index.clear(entity.index());
+        }
+        for index in &mut self.fields {
+            index.remove(entity);
+            index.pending.remove(&entity);
+        }
+    }
+
+    /// 保留中のEntityを最新値で再登録
+    /// `extract` はフィールド値を返す（フィールドなしの場合None = 登録しない）
+    pub fn flush(&mut self, extract: impl Fn(EntityId, &str) -> Option<FilterValue>) {
+        for index in &mut self.fields {
+            let pending: Vec<EntityId> = index.pending.drain().collect();
+            for entity in pending {
+                if let Some(value) = extract(entity, &index.field) {
+                    index.insert(entity, IndexKey::from_value(&value));
+                }
+            }
+        }
+    }
+
+    /// 実行計画を作成し、候補Entity（indexのみ、重複あり）を返す
+    /// FullScanの場合、候補はNone
+    pub fn plan(
+        &self,
+        query: &QueryDescriptor,
+        total_entities: usize,
+    ) -> (QueryPlan, Option<Vec<u32>>) {
+        let mut best: Option<(AccessPath, Vec<u32>)> = None;
+        let mut consider = |access: AccessPath, candidates: Vec<u32>| {
+            if best.as_ref().is_none_or(|(_, b)| candidates.len() < b.len()) {
+                best = Some((access, candidates));
+            }
+        };
+
+        for filter in &query.filters {
+            for index in self.fields.iter().filter(|index| index.field == filter.field) {
+                let Some(found) = index.lookup(filter.op, &filter.value) else {
+                    continue;
+                };
+                let candidates = found
+                    .iter()
+                    .chain(index.pending.iter())
+                    .map(|entity| entity.index())
+                    .collect();
+                consider(
+                    AccessPath::FieldIndex {
+                        field: index.field.clone(),
+                        kind: index.kind,
+                        op: filter.op,
+                        value: filter.value.clone(),
+                    },
+                    candidates,
+                );
+            }
+        }
+
+        for component in &query.with_components {
+            if let Some(index) = self.components.iter().find(|index| &index.component == component) {
+                consider(
+                    AccessPath::ComponentBitset {
+                        component: component.clone(),
+                    },
+                    index.indices().collect(),
+                );
+            }
+        }
+
+        match best {
+            Some((access, candidates)) if candidates.len() < total_entities => {
+                let plan = QueryPlan {
+                    access,
+                    estimated_candidates: candidates.len(),
+                    total_entities,
+                };
+                (plan, Some(candidates))
+            }
+            _ => {
+                let plan = QueryPlan {
+                    access: AccessPath::FullScan,
+                    estimated_candidates: total_entities,
+                    total_entities,
+                };
+                (plan, None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(index: u32) -> EntityId {
+        EntityId::new(index, 1)
+    }
+
+    fn sorted(v: Vec<EntityId>) -> Vec<u32> {
+        let mut v: Vec<u32> = v.into_iter().map(|e| e.index()).collect();
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn test_ordered_f64() {
+        assert_eq!(OrderedF64::new(-0.0), OrderedF64::new(0.0));
+        assert!(OrderedF64::new(-1.0) < OrderedF64::new(2.0));
+        assert!(IndexKey::Bool(true) < IndexKey::Number(OrderedF64::new(-5.0)));
+        assert!(IndexKey::Number(OrderedF64::new(1e9)) < IndexKey::String(String::new()));
+    }
+
+    #[test]
+    fn test_hash_lookup() {
+        let mut index = FieldIndex::new("name".into(), IndexKind::Hash, TypeId::of::<()>());
+        index.insert(entity(0), IndexKey::String("A".into()));
+        index.insert(entity(1), IndexKey::String("B".into()));
+        index.insert(entity(2), IndexKey::String("A".into()));
+
+        let found = index.lookup(CompareOp::Eq, &FilterValue::String("A".into())).unwrap();
+        assert_eq!(sorted(found), vec![0, 2]);
+        // Hashは範囲検索不可
+        assert!(index.lookup(CompareOp::Lt, &FilterValue::String("B".into())).is_none());
+
+        index.remove(entity(0));
+        let found = index.lookup(CompareOp::Eq, &FilterValue::String("A".into())).unwrap();
+        assert_eq!(sorted(found), vec![2]);
+    }
+
+    #[test]
+    fn test_btree_range() {
+        let mut index = FieldIndex::new("position.x".into(), IndexKind::BTree, TypeId::of::<()>());
+        for (i, x) in [-2.0, -1.0, 0.0, 1.0, 2.0].into_iter().enumerate() {
+            index.insert(entity(i as u32), IndexKey::Number(OrderedF64::new(x)));
+        }
+        index.insert(entity(9), IndexKey::String("not a number".into()));
+
+        let gt = index.lookup(CompareOp::Gt, &FilterValue::Number(0.0)).unwrap();
+        assert_eq!(sorted(gt), vec![3, 4]);
+        let le = index.lookup(CompareOp::Le, &FilterValue::Number(-1.0)).unwrap();
+        assert_eq!(sorted(le), vec![0, 1]);
+        let eq = index.lookup(CompareOp::Eq, &FilterValue::Number(-0.0)).unwrap();
+        assert_eq!(sorted(eq), vec![2]);
+        assert!(index.lookup(CompareOp::Ne, &FilterValue::Number(0.0)).is_none());
+    }
+
+    #[test]
+    fn test_component_bitset() {
+        let mut index = ComponentIndex::new("Transform".into(), TypeId::of::<()>());
+        index.set(3);
+        index.set(70);
+        index.set(3);
+        assert_eq!(index.count, 2);
+        assert_eq!(index.indices().collect::<Vec<_>>(), vec![3, 70]);
+
+        index.clear(3);
+        index.clear(500);
+        assert_eq!(index.count, 1);
+        assert_eq!(index.indices().collect::<Vec<_>>(), vec![70]);
+    }
+
+    #[test]
+    fn test_index_kind_from_str() {
+        assert_eq!("BTree".parse::<IndexKind>(), Ok(IndexKind::BTree));
+        assert_eq!("hash".parse::<IndexKind>(), Ok(IndexKind::Hash));
+        assert!("bitmap".parse::<IndexKind>().is_err());
+    }
+}
@@ -3,13 +3,20 @@ pub mod component;
 pub mod storage;
 pub mod world;
 pub mod query;
+pub mod index;
+pub mod change;
 
 pub use entity::EntityId;
 pub use component::{Component, AsAny};
 pub use storage::ComponentStorage;
 pub use world::World;
+pub use index::{IndexKind, QueryPlan, AccessPath};
+pub use change::{ChangeSet, EntityChange};
 pub use query::{
     QueryDescriptor, FilterExpr, FilterValue, CompareOp,
     ComponentFilter, OrderBy, SortDirection,
-    QueryResult, QueryResultRow,
+    QueryResult, QueryResultRow, ParseError, parse_query, LiveQuery, QueryDelta,
+    QueryValidationError, ComputedField, ValueType, Column, ColumnarResult, PreparedQuery,
+    RelationFilter, SlicedQuery, QueryProgress, QueryStage, QueryProfile, ProfileStage,
+    StageReport,
 };
@@ -0,0 +1,346 @@
+//! 列指向のクエリ結果
+//!
+//! 行ごとのオブジェクトを作らずに、フィールドごとの配列として結果を返す。
+//! JS側では数値列を `Float32Array` / `Uint32Array` としてそのまま使える。
+//!
+//! - `id` と `parent` などのEntityIdは `Uint32` 列（値がない行は `NO_ENTITY`）
+//! - 数値フィールドは `Float32` 列（値がない行は NaN）
+//! - 文字列フィールドは `String` 列（値がない行は None）
+//! - `position` などの複合値は `position.x` のように要素ごとの列に展開
+//! - それ以外は `Json` 列
+//!
+//! select の2つのフィールドが同じ名前の列を作る場合（`["id", "id"]`、`["position", "position.x"]` など）は
+//! 列を区別できないため `QueryError::DuplicateColumn` を返す
+
+use serde::Serialize;
+
+use crate::ecs::entity::EntityId;
+use crate::ecs::world::World;
+
+use super::prepared::FieldAccessor;
+use super::{QueryError, QueryValidationError, ValueType};
+
+/// EntityId列で値がない行（親がない場合など）の値
+pub const NO_ENTITY: u32 = u32::MAX;
+
+/// 結果の1列
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "values", rename_all = "snake_case")]
+pub enum Column {
+    Float32(Vec<f32>),
+    Uint32(Vec<u32>),
+    String(Vec<Option<String>>),
+    Json(Vec<serde_json::Value>),
+}
+
+impl Column {
+    /// 行数
+    pub fn len(&self) -> usize {
+        match self {
+            Column::Float32(v) => v.len(),
+            Column::Uint32(v) => v.len(),
+            Column::String(v) => v.len(),
+            Column::Json(v) => v.len(),
+        }
+    }
+
+    /// 行がないか
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// 列指向のクエリ結果
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ColumnarResult {
+    /// 各行のEntityId (u32形式)
+    pub ids: Vec<u32>,
+    /// (列名, 列) の組（select の順序）
+    pub columns: Vec<(String, Column)>,
+    /// limit適用前の総件数
+    pub total_count: usize,
+}
+
+impl ColumnarResult {
+    /// Entity列から結果を構築
+    ///
+    /// `select` はフィールド名と解決済みのアクセサの組（未知のフィールドはNone）。
+    /// 展開後の列名が重なる場合はエラー
+    pub(crate) fn build<'a>(
+        world: &World,
+        entities: &[EntityId],
+        select: impl IntoIterator<Item = (&'a str, Option<&'a FieldAccessor>)>,
+        total_count: usize,
+    ) -> Result<Self, QueryValidationError> {
+        let ids: Vec<u32> = entities.iter().map(|e| e.to_u32()).collect();
+        let mut columns: Vec<(String, Column)> = Vec::new();
+        // 各列を作ったselectのフィールド（columnsと同じ順）
+        let mut sources: Vec<&str> = Vec::new();
+        let mut errors = Vec::new();
+
+        for (field, accessor) in select {
+            let start = columns.len();
+            Self::push_columns(&mut columns, world, entities, &ids, field, accessor);
+            for (column, _) in &columns[start..] {
+                if let Some(i) = columns[..start].iter().position(|(name, _)| name == column) {
+                    errors.push(QueryError::DuplicateColumn {
+                        column: column.clone(),
+                        field: field.to_string(),
+                        other: sources[i].to_string(),
+                    });
+                }
+            }
+            sources.resize(columns.len(), field);
+        }
+
+        if !errors.is_empty() {
+            return Err(QueryValidationError { errors });
+        }
+        Ok(Self {
+            ids,
+            columns,
+            total_count,
+        })
+    }
+
+    /// 1つのフィールドの列を追加（複合値は要素ごとの列）
+    fn push_columns(
+        columns: &mut Vec<(String, Column)>,
+        world: &World,
+        entities: &[EntityId],
+        ids: &[u32],
+        field: &str,
+        accessor: Option<&FieldAccessor>,
+    ) {
+        if field == "id" {
+            columns.push((field.to_string(), Column::Uint32(ids.to_vec())));
+            return;
+        }
+        let read = |e: EntityId| accessor.and_then(|a| world.read_field(e, a));
+        match world.field_type(field) {
+            // 世代を上位ビットに持つためf32では桁落ちする
+            Some(ValueType::EntityId) => {
+                let values = entities
+                    .iter()
+                    .map(|&e| {
+                        read(e)
+                            .and_then(|v| v.as_u64())
+                            .map_or(NO_ENTITY, |id| id as u32)
+                    })
+                    .collect();
+                columns.push((field.to_string(), Column::Uint32(values)));
+            }
+            Some(ValueType::Number) => {
+                let values = entities.iter().map(|&e| number(read(e).as_ref())).collect();
+                columns.push((field.to_string(), Column::Float32(values)));
+            }
+            Some(ValueType::String) => {
+                let values = entities
+                    .iter()
+                    .map(|&e| read(e).and_then(|v| v.as_str().map(str::to_string)))
+                    .collect();
+                columns.push((field.to_string(), Column::String(values)));
+            }
+            Some(ValueType::Object) => {
+                let values: Vec<Option<serde_json::Value>> =
+                    entities.iter().map(|&e| read(e)).collect();
+                columns.extend(object_columns(field, &values));
+            }
+            _ => {
+                let values = entities
+                    .iter()
+                    .map(|&e| read(e).unwrap_or_default())
+                    .collect();
+                columns.push((field.to_string(), Column::Json(values)));
+            }
+        }
+    }
+
+    /// 行数
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// 結果が空かどうか
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// 列を名前で取得
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns
+            .iter()
+            .find(|(column_name, _)| column_name == name)
+            .map(|(_, column)| column)
+    }
+}
+
+/// JSON数値をf32に変換（値がない場合はNaN）
+fn number(value: Option<&serde_json::Value>) -> f32 {
+    value.and_then(|v| v.as_f64()).map_or(f32::NAN, |n| n as f32)
+}
+
+/// 複合値の要素を取得
+fn element<'a>(value: &'a Option<serde_json::Value>, key: &str) -> Option<&'a serde_json::Value> {
+    value.as_ref().and_then(|v| v.get(key))
+}
+
+/// 複合値を要素ごとの列に展開（x, y, z, w を先頭に、それ以外は名前順）
+fn object_columns(field: &str, values: &[Option<serde_json::Value>]) -> Vec<(String, Column)> {
+    let mut keys: Vec<&str> = Vec::new();
+    for object in values.iter().flatten().filter_map(|v| v.as_object()) {
+        for key in object.keys() {
+            if !keys.contains(&key.as_str()) {
+                keys.push(key);
+            }
+        }
+    }
+    keys.sort_by_key(|key| {
+        let axis = ["x", "y", "z", "w"].iter().position(|a| a == key);
+        (axis.unwrap_or(usize::MAX), key.to_string())
+    });
+
+    keys.into_iter()
+        .map(|key| {
+            let numeric = values
+                .iter()
+                .filter_map(|v| element(v, key))
+                .all(serde_json::Value::is_number);
+            let column = if numeric {
+                Column::Float32(values.iter().map(|v| number(element(v, key))).collect())
+            } else {
+                Column::Json(
+                    values
+                        .iter()
+                        .map(|v| element(v, key).cloned().unwrap_or_default())
+                        .collect(),
+                )
+            };
+            (format!("{}.{}", field, key), column)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Name, Transform};
+    use crate::ecs::query::{OrderBy, QueryDescriptor};
+    use glam::{Quat, Vec3};
+
+    #[test]
+    fn test_columnar_matches_rows() {
+        let mut world = World::new();
+        let a = world.spawn();
+        world.insert(a, Name::new("a"));
+        world.insert(
+            a,
+            Transform::new(Vec3::new(1.0, 2.0, 3.0), Quat::IDENTITY, Vec3::ONE),
+        );
+        // Transformなし
+        let b = world.spawn();
+        world.insert(b, Name::new("b"));
+
+        let query = QueryDescriptor::new()
+            .select(["id", "name", "position.y", "rotation"])
+            .order_by(OrderBy::desc("name"));
+        let result = world.execute_query_columnar(&query).unwrap();
+
+        assert_eq!(result.ids, vec![b.to_u32(), a.to_u32()]);
+        assert_eq!(result.total_count, 2);
+        let names: Vec<&str> = result.columns.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["id", "name", "position.y", "rotation.x", "rotation.y", "rotation.z", "rotation.w"]
+        );
+        assert_eq!(result.column("id"), Some(&Column::Uint32(result.ids.clone())));
+        assert_eq!(
+            result.column("name"),
+            Some(&Column::String(vec![Some("b".into()), Some("a".into())]))
+        );
+        match result.column("position.y") {
+            Some(Column::Float32(values)) => {
+                assert!(values[0].is_nan());
+                assert_eq!(values[1], 2.0);
+            }
+            other => panic!("unexpected column: {:?}", other),
+        }
+        match result.column("rotation.w") {
+            Some(Column::Float32(values)) => assert_eq!(values[1], 1.0),
+            other => panic!("unexpected column: {:?}", other),
+        }
+        assert!(result.columns.iter().all(|(_, c)| c.len() == 2));
+    }
+
+    #[test]
+    fn test_entity_id_columns_keep_generation() {
+        let mut world = World::new();
+        let child = world.spawn();
+        world.insert(child, Name::new("child"));
+        // 同じスロットを使い回して世代を上げ、f32で表せないID（2^24以上の奇数）にする
+        let mut parent = world.spawn();
+        while parent.generation() < 20 {
+            world.despawn(parent);
+            parent = world.spawn();
+        }
+        assert_ne!((parent.to_u32() as f32) as u32, parent.to_u32());
+        world.insert(parent, Name::new("parent"));
+        assert!(world.set_parent(child, Some(parent)));
+
+        let query = QueryDescriptor::new()
+            .select(["id", "parent", "parent.id"])
+            .order_by(OrderBy::asc("name"));
+        let result = world.execute_query_columnar(&query).unwrap();
+        assert_eq!(result.ids, vec![child.to_u32(), parent.to_u32()]);
+        assert_eq!(result.column("parent"), Some(&Column::Uint32(vec![parent.to_u32(), NO_ENTITY])));
+        assert_eq!(result.column("parent.id"), Some(&Column::Uint32(vec![parent.to_u32(), NO_ENTITY])));
+    }
+
+    #[test]
+    fn test_duplicate_columns() {
+        let mut world = World::new();
+        let entity = world.spawn();
+        world.insert(entity, Transform::default());
+        let columnar = |select: &[&str]| {
+            world.execute_query_columnar(&QueryDescriptor::new().select(select.iter().copied()))
+        };
+
+        // 計算フィールドの要素は別の列名になる
+        let result = columnar(&["rotation", "rotation.euler"]).unwrap();
+        let names: Vec<&str> = result.columns.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "rotation.x",
+                "rotation.y",
+                "rotation.z",
+                "rotation.w",
+                "rotation.euler.x",
+                "rotation.euler.y",
+                "rotation.euler.z"
+            ]
+        );
+
+        assert_eq!(
+            columnar(&["position", "position.x"]).map_err(|e| e.errors),
+            Err(vec![QueryError::DuplicateColumn {
+                column: "position.x".into(),
+                field: "position.x".into(),
+                other: "position".into()
+            }])
+        );
+        assert_eq!(
+            columnar(&["id", "name", "id"]).map_err(|e| e.errors),
+            Err(vec![QueryError::DuplicateColumn {
+                column: "id".into(),
+                field: "id".into(),
+                other: "id".into()
+            }])
+        );
+
+        // 行形式では同じselectを使える
+        let query = QueryDescriptor::new().select(["position", "position.x"]);
+        assert_eq!(query.validate(&world), Ok(()));
+        assert_eq!(world.execute_query(&query).rows.len(), 1);
+    }
+}
@@ -0,0 +1,303 @@
+//! 計算フィールド
+//!
+//! コンポーネントから導出される値をクエリのフィールドとして提供する。
+//! select / filters / order_by のいずれでも通常のフィールドと同様に使える。
+//!
+//! 組み込み:
+//! - `rotation.euler` / `rotation.euler.{x,y,z}`: オイラー角（度、エディタと同じZYX順）
+//! - `world_position` / `world_position.{x,y,z}`: 祖先のTransformを合成したワールド座標
+//! - `scale.uniform`: 各軸スケールの幾何平均（一様スケール s なら s）
+//! - `distance_to_camera`: カメラ位置（`World::set_camera_position`）からワールド座標までの距離
+//! - `component_count`: Entityが持つコンポーネント数
+
+use std::any::TypeId;
+use std::collections::HashMap;
+
+use glam::{EulerRot, Vec3};
+
+use crate::components::{Parent, Transform};
+use crate::ecs::component::Component;
+use crate::ecs::entity::EntityId;
+use crate::ecs::world::World;
+
+use super::ValueType;
+
+/// 値を計算する関数
+pub type FieldFn = dyn Fn(&World, EntityId) -> Option<serde_json::Value> + Send + Sync;
+
+/// 計算フィールドの定義
+pub struct ComputedField {
+    value_type: ValueType,
+    /// 参照するコンポーネント（Noneの場合は不明として全コンポーネントに依存）
+    components: Option<Vec<TypeId>>,
+    /// カメラ位置を参照するか
+    uses_camera: bool,
+    /// 祖先など他のEntityのコンポーネントを参照するか
+    uses_hierarchy: bool,
+    compute: Box<FieldFn>,
+}
+
+impl ComputedField {
+    /// 計算フィールドを作成
+    ///
+    /// 参照するコンポーネントを `reads` で宣言すると、
+    /// 購読やインデックスがそのコンポーネントの変更時のみ再計算する
+    pub fn new(
+        value_type: ValueType,
+        compute: impl Fn(&World, EntityId) -> Option<serde_json::Value> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            value_type,
+            components: None,
+            uses_camera: false,
+            uses_hierarchy: false,
+            compute: Box::new(compute),
+        }
+    }
+
+    /// 参照するコンポーネントを宣言
+    pub fn reads<T: Component>(mut self) -> Self {
+        self.components
+            .get_or_insert_with(Vec::new)
+            .push(TypeId::of::<T>());
+        self
+    }
+
+    /// カメラ位置を参照することを宣言
+    pub fn reads_camera(mut self) -> Self {
+        self.uses_camera = true;
+        self
+    }
+
+    /// 祖先など他のEntityのコンポーネントを参照することを宣言
+    ///
+    /// 購読は変更のたびに全件再評価し、インデックスは作成できなくなる
+    pub fn reads_hierarchy(mut self) -> Self {
+        self.uses_hierarchy = true;
+        self
+    }
+
+    /// 値の型
+    pub fn value_type(&self) -> ValueType {
+        self.value_type
+    }
+
+    /// 参照するコンポーネント（不明な場合はNone）
+    pub fn components(&self) -> Option<&[TypeId]> {
+        self.components.as_deref()
+    }
+
+    /// カメラ位置を参照するか
+    pub fn uses_camera(&self) -> bool {
+        self.uses_camera
+    }
+
+    /// 他のEntityのコンポーネントを参照するか
+    pub fn uses_hierarchy(&self) -> bool {
+        self.uses_hierarchy
+    }
+
+    /// 値を計算
+    pub fn compute(&self, world: &World, entity: EntityId) -> Option<serde_json::Value> {
+        (self.compute)(world, entity)
+    }
+}
+
+/// 計算フィールドの登録先
+pub(crate) struct FieldRegistry {
+    fields: HashMap<String, ComputedField>,
+}
+
+impl FieldRegistry {
+    /// 登録済みのフィールドを取得
+    pub fn get(&self, name: &str) -> Option<&ComputedField> {
+        self.fields.get(name)
+    }
+
+    /// フィールドを登録（同名のフィールドは置き換え）
+    pub fn register(&mut self, name: impl Into<String>, field: ComputedField) {
+        self.fields.insert(name.into(), field);
+    }
+
+    /// 登録済みのフィールド名（ソート済み）
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.fields.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+impl Default for FieldRegistry {
+    /// 組み込みフィールドを登録した状態で作成
+    fn default() -> Self {
+        let mut registry = Self {
+            fields: HashMap::new(),
+        };
+
+        registry.register(
+            "rotation.euler",
+            transform_field(ValueType::Object, |t| {
+                let euler = euler_degrees(t);
+                serde_json::json!({ "x": euler.x, "y": euler.y, "z": euler.z })
+            }),
+        );
+        for (axis, name) in ["rotation.euler.x", "rotation.euler.y", "rotation.euler.z"]
+            .into_iter()
+            .enumerate()
+        {
+            registry.register(
+                name,
+                transform_field(ValueType::Number, move |t| {
+                    serde_json::json!(euler_degrees(t)[axis])
+                }),
+            );
+        }
+
+        registry.register(
+            "world_position",
+            world_position_field(ValueType::Object, |p| {
+                serde_json::json!({ "x": p.x, "y": p.y, "z": p.z })
+            }),
+        );
+        for (axis, name) in ["world_position.x", "world_position.y", "world_position.z"]
+            .into_iter()
+            .enumerate()
+        {
+            registry.register(
+                name,
+                world_position_field(ValueType::Number, move |p| serde_json::json!(p[axis])),
+            );
+        }
+
+        registry.register(
+            "scale.uniform",
+            transform_field(ValueType::Number, |t| {
+                let s = t.scale.abs();
+                serde_json::json!((s.x * s.y * s.z).cbrt())
+            }),
+        );
+
+        registry.register(
+            "distance_to_camera",
+            ComputedField::new(ValueType::Number, |world, entity| {
+                let camera = world.camera_position()?;
+                let position = world.world_position(entity)?;
+                Some(serde_json::json!(position.distance(camera)))
+            })
+            .reads::<Transform>()
+            .reads::<Parent>()
+            .reads_camera()
+            .reads_hierarchy(),
+        );
+
+        registry.register(
+            "component_count",
+            ComputedField::new(ValueType::Number, |world, entity| {
+                world
+                    .is_alive(entity)
+                    .then(|| serde_json::json!(world.component_count(entity)))
+            }),
+        );
+
+        registry
+    }
+}
+
+/// Transformのみから計算するフィールド
+fn transform_field(
+    value_type: ValueType,
+    compute: impl Fn(&Transform) -> serde_json::Value + Send + Sync + 'static,
+) -> ComputedField {
+    ComputedField::new(value_type, move |world, entity| {
+        world.get::<Transform>(entity).map(&compute)
+    })
+    .reads::<Transform>()
+}
+
+/// ワールド座標から計算するフィールド
+fn world_position_field(
+    value_type: ValueType,
+    compute: impl Fn(Vec3) -> serde_json::Value + Send + Sync + 'static,
+) -> ComputedField {
+    ComputedField::new(value_type, move |world, entity| {
+        world.world_position(entity).map(&compute)
+    })
+    .reads::<Transform>()
+    .reads::<Parent>()
+    .reads_hierarchy()
+}
+
+/// 回転をオイラー角（度）に変換（X: roll, Y: pitch, Z: yaw）
+fn euler_degrees(transform: &Transform) -> Vec3 {
+    let (z, y, x) = transform.rotation.to_euler(EulerRot::ZYX);
+    Vec3::new(x, y, z) * (180.0 / std::f32::consts::PI)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Quat;
+
+    fn number(value: Option<serde_json::Value>) -> f32 {
+        value.and_then(|v| v.as_f64()).unwrap() as f32
+    }
+
+    #[test]
+    fn test_builtin_fields() {
+        let registry = FieldRegistry::default();
+        let mut world = World::new();
+        let e = world.spawn();
+        world.insert(
+            e,
+            Transform::new(
+                Vec3::new(1.0, 2.0, 3.0),
+                Quat::from_rotation_y(90f32.to_radians()),
+                Vec3::new(2.0, 4.0, 1.0),
+            ),
+        );
+
+        let field = |name: &str| registry.get(name).unwrap().compute(&world, e);
+        assert!((number(field("rotation.euler.y")) - 90.0).abs() < 1e-3);
+        assert!(number(field("rotation.euler.x")).abs() < 1e-3);
+        assert_eq!(number(field("world_position.z")), 3.0);
+        assert!((number(field("scale.uniform")) - 2.0).abs() < 1e-5);
+        assert_eq!(number(field("component_count")), 1.0);
+        // カメラ未設定
+        assert_eq!(field("distance_to_camera"), None);
+
+        let distance = registry.get("distance_to_camera").unwrap();
+        assert!(distance.uses_camera());
+        assert!(distance.uses_hierarchy());
+        assert_eq!(
+            distance.components(),
+            Some(&[TypeId::of::<Transform>(), TypeId::of::<Parent>()][..])
+        );
+        assert_eq!(registry.get("component_count").unwrap().components(), None);
+        assert!(registry.get("world_position").unwrap().uses_hierarchy());
+    }
+
+    #[test]
+    fn test_world_position_composes_parents() {
+        let registry = FieldRegistry::default();
+        let mut world = World::new();
+        let root = world.spawn();
+        world.insert(
+            root,
+            Transform::new(
+                Vec3::new(10.0, 0.0, 0.0),
+                Quat::from_rotation_y(90f32.to_radians()),
+                Vec3::splat(2.0),
+            ),
+        );
+        let child = world.spawn();
+        world.insert(child, Transform::from_position(Vec3::new(1.0, 0.0, 0.0)));
+        world.set_parent(child, Some(root));
+
+        let value = registry.get("world_position").unwrap().compute(&world, child).unwrap();
+        let x = value["x"].as_f64().unwrap() as f32;
+        let z = value["z"].as_f64().unwrap() as f32;
+        // 親の回転（Y軸90度）とスケール2で (1, 0, 0) -> (0, 0, -2)
+        assert!((x - 10.0).abs() < 1e-4);
+        assert!((z + 2.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,328 @@
+//! クエリ結果の差分
+//!
+//! 前回の結果と新しい結果を比較し、追加・削除・更新・移動を求める。
+//!
+//! 受け取り側は次の手順で前回の結果を新しい結果に更新できる（`QueryDelta::apply_to`）:
+//! 1. `removed` と `moved` のIDの行を取り除く
+//! 2. `updated` の各行のフィールドを上書きし、`removed` のフィールドを取り除く
+//! 3. `added` と `moved` の行を `index` の昇順に、その位置へ挿入する
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{QueryResult, QueryResultRow};
+
+/// 追加された行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddedRow {
+    /// 新しい結果での位置
+    pub index: usize,
+    pub id: u32,
+    pub fields: HashMap<String, serde_json::Value>,
+}
+
+/// 更新された行
+///
+/// 値がnullになったフィールドは `fields` にnullとして入り、
+/// 行から無くなったフィールドは `removed` に入る
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UpdatedRow {
+    pub id: u32,
+    /// 変更されたフィールドの新しい値
+    pub fields: HashMap<String, serde_json::Value>,
+    /// 無くなったフィールド
+    #[serde(default)]
+    pub removed: Vec<String>,
+}
+
+/// 位置が変わった行
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MovedRow {
+    pub id: u32,
+    /// 前回の結果での位置
+    pub from: usize,
+    /// 新しい結果での位置
+    pub to: usize,
+}
+
+/// クエリ結果の差分
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryDelta {
+    /// 追加された行
+    pub added: Vec<AddedRow>,
+    /// 削除された行のID
+    pub removed: Vec<u32>,
+    /// 更新された行（変更されたフィールドのみ）
+    pub updated: Vec<UpdatedRow>,
+    /// 位置が変わった行
+    pub moved: Vec<MovedRow>,
+    /// limit適用前の総件数
+    pub total_count: usize,
+}
+
+impl QueryDelta {
+    /// 2つの結果の差分を計算
+    pub fn between(prev: &QueryResult, next: &QueryResult) -> Self {
+        let prev_index: HashMap<u32, usize> = prev
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| (row.id, i))
+            .collect();
+        let next_ids: HashMap<u32, usize> = next
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| (row.id, i))
+            .collect();
+
+        let removed = prev
+            .rows
+            .iter()
+            .filter(|row| !next_ids.contains_key(&row.id))
+            .map(|row| row.id)
+            .collect();
+
+        let mut added = Vec::new();
+        let mut updated = Vec::new();
+        // 両方に存在する行の (新しい位置, 前回の位置)
+        let mut retained = Vec::new();
+        for (index, row) in next.rows.iter().enumerate() {
+            match prev_index.get(&row.id) {
+                None => added.push(AddedRow {
+                    index,
+                    id: row.id,
+                    fields: row.fields.clone(),
+                }),
+                Some(&from) => {
+                    retained.push((index, from));
+                    if let Some(changed) = changed_fields(&prev.rows[from], row) {
+                        updated.push(changed);
+                    }
+                }
+            }
+        }
+
+        // 前回の順序を保てる最大の行集合は動かさず、残りを移動として扱う
+        let stable = longest_increasing(&retained);
+        let moved = retained
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !stable[*i])
+            .map(|(_, &(to, from))| MovedRow {
+                id: next.rows[to].id,
+                from,
+                to,
+            })
+            .collect();
+
+        Self {
+            added,
+            removed,
+            updated,
+            moved,
+            total_count: next.total_count,
+        }
+    }
+
+    /// 変更がないか
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.updated.is_empty()
+            && self.moved.is_empty()
+    }
+
+    /// 前回の結果に差分を適用
+    pub fn apply_to(&self, result: &mut QueryResult) {
+        let mut rows: Vec<QueryResultRow> = std::mem::take(&mut result.rows);
+
+        let mut moved_rows: HashMap<u32, QueryResultRow> = HashMap::new();
+        rows.retain_mut(|row| {
+            if self.removed.contains(&row.id) {
+                return false;
+            }
+            if self.moved.iter().any(|m| m.id == row.id) {
+                moved_rows.insert(row.id, std::mem::replace(row, QueryResultRow::new(row.id)));
+                return false;
+            }
+            true
+        });
+
+        for update in &self.updated {
+            let target = rows
+                .iter_mut()
+                .chain(moved_rows.values_mut())
+                .find(|row| row.id == update.id);
+            if let Some(target) = target {
+                for (name, value) in &update.fields {
+                    target.fields.insert(name.clone(), value.clone());
+                }
+                for name in &update.removed {
+                    target.fields.remove(name);
+                }
+            }
+        }
+
+        let mut inserts: Vec<(usize, QueryResultRow)> = self
+            .added
+            .iter()
+            .map(|a| {
+                (
+                    a.index,
+                    QueryResultRow {
+                        id: a.id,
+                        fields: a.fields.clone(),
+                    },
+                )
+            })
+            .chain(
+                self.moved
+                    .iter()
+                    .filter_map(|m| moved_rows.remove(&m.id).map(|row| (m.to, row))),
+            )
+            .collect();
+        inserts.sort_by_key(|(index, _)| *index);
+        for (index, row) in inserts {
+            rows.insert(index.min(rows.len()), row);
+        }
+
+        result.rows = rows;
+        result.total_count = self.total_count;
+    }
+}
+
+/// 変更されたフィールドのみを持つ行（変更がなければNone）
+fn changed_fields(prev: &QueryResultRow, next: &QueryResultRow) -> Option<UpdatedRow> {
+    let mut changed = UpdatedRow {
+        id: next.id,
+        ..UpdatedRow::default()
+    };
+    for (name, value) in &next.fields {
+        if prev.fields.get(name) != Some(value) {
+            changed.fields.insert(name.clone(), value.clone());
+        }
+    }
+    for name in prev.fields.keys() {
+        if !next.fields.contains_key(name) {
+            changed.removed.push(name.clone());
+        }
+    }
+    changed.removed.sort();
+    (!changed.fields.is_empty() || !changed.removed.is_empty()).then_some(changed)
+}
+
+/// 前回の位置が単調増加となる最長部分列に含まれるかを返す
+fn longest_increasing(pairs: &[(usize, usize)]) -> Vec<bool> {
+    // tails[k] = 長さk+1の部分列の末尾要素の添字
+    let mut tails: Vec<usize> = Vec::new();
+    let mut parent: Vec<Option<usize>> = vec![None; pairs.len()];
+    for (i, &(_, from)) in pairs.iter().enumerate() {
+        let pos = tails.partition_point(|&t| pairs[t].1 < from);
+        parent[i] = pos.checked_sub(1).map(|p| tails[p]);
+        if pos == tails.len() {
+            tails.push(i);
+        } else {
+            tails[pos] = i;
+        }
+    }
+
+    let mut stable = vec![false; pairs.len()];
+    let mut cursor = tails.last().copied();
+    while let Some(i) = cursor {
+        stable[i] = true;
+        cursor = parent[i];
+    }
+    stable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn result(rows: &[(u32, i64)]) -> QueryResult {
+        QueryResult {
+            rows: rows
+                .iter()
+                .map(|&(id, v)| QueryResultRow::new(id).with_field("v", json!(v)))
+                .collect(),
+            total_count: rows.len(),
+        }
+    }
+
+    fn assert_round_trip(prev: &QueryResult, next: &QueryResult) -> QueryDelta {
+        let delta = QueryDelta::between(prev, next);
+        let mut patched = prev.clone();
+        delta.apply_to(&mut patched);
+        assert_eq!(&patched, next);
+        delta
+    }
+
+    #[test]
+    fn test_no_change() {
+        let r = result(&[(1, 1), (2, 2)]);
+        assert!(QueryDelta::between(&r, &r).is_empty());
+    }
+
+    #[test]
+    fn test_added_removed_updated() {
+        let prev = result(&[(1, 1), (2, 2), (3, 3)]);
+        let next = result(&[(1, 1), (3, 30), (4, 4)]);
+        let delta = assert_round_trip(&prev, &next);
+
+        assert_eq!(delta.removed, vec![2]);
+        assert_eq!(delta.added.len(), 1);
+        assert_eq!(delta.added[0].index, 2);
+        assert_eq!(delta.updated.len(), 1);
+        assert_eq!(delta.updated[0].id, 3);
+        assert_eq!(delta.updated[0].fields.get("v"), Some(&json!(30)));
+        assert!(delta.updated[0].removed.is_empty());
+        // 削除によるずれは移動として扱わない
+        assert!(delta.moved.is_empty());
+    }
+
+    #[test]
+    fn test_field_becoming_null_is_kept() {
+        // 付け替えでparentがnullになった行は、フィールドを削除せずnullにする
+        let prev = QueryResult {
+            rows: vec![QueryResultRow::new(1).with_field("parent", json!(7))],
+            total_count: 1,
+        };
+        let next = QueryResult {
+            rows: vec![QueryResultRow::new(1).with_field("parent", serde_json::Value::Null)],
+            total_count: 1,
+        };
+        let delta = assert_round_trip(&prev, &next);
+        assert_eq!(delta.updated[0].fields.get("parent"), Some(&serde_json::Value::Null));
+        assert!(delta.updated[0].removed.is_empty());
+
+        let json = serde_json::to_value(&delta).unwrap();
+        assert_eq!(json["updated"][0]["removed"], json!([]));
+    }
+
+    #[test]
+    fn test_moved_is_minimal() {
+        let prev = result(&[(1, 0), (2, 0), (3, 0), (4, 0)]);
+        let next = result(&[(4, 0), (1, 0), (2, 0), (3, 0)]);
+        let delta = assert_round_trip(&prev, &next);
+        assert_eq!(delta.moved, vec![MovedRow { id: 4, from: 3, to: 0 }]);
+    }
+
+    #[test]
+    fn test_round_trip_mixed() {
+        let prev = result(&[(1, 1), (2, 2), (3, 3), (4, 4), (5, 5)]);
+        let next = result(&[(6, 6), (5, 50), (3, 3), (1, 1), (7, 7)]);
+        assert_round_trip(&prev, &next);
+
+        let mut removed_field = next.clone();
+        removed_field.rows[1].fields.clear();
+        let delta = assert_round_trip(&next, &removed_field);
+        assert_eq!(delta.updated[0].removed, vec!["v".to_string()]);
+        assert!(delta.updated[0].fields.is_empty());
+
+        assert_round_trip(&QueryResult::empty(), &next);
+        assert_round_trip(&next, &QueryResult::empty());
+    }
+}
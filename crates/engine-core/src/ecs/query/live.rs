@@ -0,0 +1,604 @@
+//! ライブクエリ
+//!
+//! クエリ結果をキャッシュし、`ChangeSet` に含まれるEntityのうち
+//! クエリが参照するコンポーネントに変更があったものだけを再評価し、
+//! 並び順と結果をその場で更新する。
+//! `parent.name` や空間フィルター・`distance_to(...)` のように他のEntity（祖先）の値を参照するクエリは、
+//! 変更のたびに全件再評価する。
+
+use std::any::TypeId;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::components::Parent;
+use crate::ecs::change::{ChangeSet, EntityChange};
+use crate::ecs::entity::EntityId;
+use crate::ecs::world::World;
+
+use super::delta::QueryDelta;
+use super::order;
+use super::spatial::parse_distance_to;
+use super::{QueryDescriptor, QueryResult, QueryResultRow};
+
+/// 条件を満たしたEntityのキャッシュ
+struct MatchedEntity {
+    entity: EntityId,
+    sort_key: Option<serde_json::Value>,
+    row: QueryResultRow,
+}
+
+/// 変更追跡により差分更新されるクエリ
+pub struct LiveQuery {
+    query: QueryDescriptor,
+    /// 参照するコンポーネント（Noneの場合は全コンポーネント）
+    dependencies: Option<HashSet<TypeId>>,
+    /// カメラ位置を参照するか
+    uses_camera: bool,
+    /// 他のEntityの値を参照するか
+    uses_hierarchy: bool,
+    /// 条件を満たすEntity（Entityのindex順）
+    matched: BTreeMap<u32, MatchedEntity>,
+    /// 条件を満たすEntityのindexを結果の順に並べたもの（limit適用前）
+    order: Vec<u32>,
+    result: QueryResult,
+}
+
+impl LiveQuery {
+    /// クエリを全件評価して作成
+    pub fn new(world: &World, query: QueryDescriptor) -> Self {
+        let Dependencies {
+            components: dependencies,
+            uses_camera,
+            uses_hierarchy,
+        } = dependencies(world, &query);
+        let mut live = Self {
+            dependencies,
+            uses_camera,
+            uses_hierarchy,
+            query,
+            matched: BTreeMap::new(),
+            order: Vec::new(),
+            result: QueryResult::empty(),
+        };
+        live.evaluate_all(world);
+        live.result = live.build_result();
+        live
+    }
+
+    /// クエリ定義
+    pub fn query(&self) -> &QueryDescriptor {
+        &self.query
+    }
+
+    /// 現在の結果
+    pub fn result(&self) -> &QueryResult {
+        &self.result
+    }
+
+    /// 変更が結果に影響しうるか
+    pub fn is_affected_by(&self, changes: &ChangeSet) -> bool {
+        (self.uses_camera && changes.camera_moved())
+            || changes.iter().any(|(_, change)| self.is_relevant(change))
+    }
+
+    /// 変更のあったEntityだけを再評価し、結果が変わった場合 true を返す
+    pub fn apply(&mut self, world: &World, changes: &ChangeSet) -> bool {
+        self.update(world, changes).is_some()
+    }
+
+    /// 変更を適用し、結果が変わった場合は前回の結果との差分を返す
+    pub fn apply_delta(&mut self, world: &World, changes: &ChangeSet) -> Option<QueryDelta> {
+        let prev = self.update(world, changes)?;
+        Some(QueryDelta::between(&prev, &self.result))
+    }
+
+    /// 変更を適用し、結果が変わった場合は前回の結果を返す
+    fn update(&mut self, world: &World, changes: &ChangeSet) -> Option<QueryResult> {
+        let full = (self.uses_camera && changes.camera_moved())
+            || (self.uses_hierarchy && changes.iter().any(|(_, c)| self.is_relevant(c)));
+        if full {
+            // カメラ移動・他Entityの変更は複数Entityの値に影響するため全件再評価
+            self.evaluate_all(world);
+            let result = self.build_result();
+            if result == self.result {
+                return None;
+            }
+            return Some(std::mem::replace(&mut self.result, result));
+        }
+
+        let mut prev = None;
+        for (entity, change) in changes.iter() {
+            if self.is_relevant(change) {
+                self.reevaluate(world, entity, &mut prev);
+            }
+        }
+        // 同じ変更セット内で元に戻った場合は変化なし
+        prev.filter(|prev| *prev != self.result)
+    }
+
+    /// Entityの変更がクエリの参照するコンポーネントに及ぶか
+    fn is_relevant(&self, change: &EntityChange) -> bool {
+        change.spawned
+            || change.despawned
+            || match &self.dependencies {
+                Some(deps) => change.components.iter().any(|t| deps.contains(t)),
+                None => !change.components.is_empty(),
+            }
+    }
+
+    /// 単一Entityを評価し、条件を満たせばキャッシュする内容を返す
+    fn evaluate(&self, world: &World, entity: EntityId) -> Option<MatchedEntity> {
+        if !world.matches_query(entity, &self.query) {
+            return None;
+        }
+        let sort_key = self
+            .query
+            .order_by
+            .as_ref()
+            .and_then(|order| world.extract_field(entity, &order.field));
+        let row = world.build_row(entity, &self.query.select);
+        Some(MatchedEntity {
+            entity,
+            sort_key,
+            row,
+        })
+    }
+
+    /// 全Entityを評価して並び順を作り直す
+    fn evaluate_all(&mut self, world: &World) {
+        self.matched = world
+            .iter_entities()
+            .filter_map(|entity| Some((entity.index(), self.evaluate(world, entity)?)))
+            .collect();
+        let mut order: Vec<u32> = self.matched.keys().copied().collect();
+        order.sort_by(|&a, &b| self.compare(a, &self.matched[&a].sort_key, b, &self.matched[&b].sort_key));
+        self.order = order;
+    }
+
+    /// 単一Entityを再評価し、並び順と結果をその場で更新する
+    ///
+    /// 結果を変更する前に、変更前の結果を `prev` に一度だけ保存する
+    fn reevaluate(&mut self, world: &World, entity: EntityId, prev: &mut Option<QueryResult>) {
+        let index = entity.index();
+        let limit = self.query.limit.unwrap_or(usize::MAX);
+        let new = self.evaluate(world, entity);
+
+        // 同じindexの古いEntity（削除済み）もここで取り除く
+        let old = self
+            .matched
+            .get(&index)
+            .filter(|m| m.entity == entity || !world.is_alive(m.entity));
+        let old_pos = old.map(|m| self.position(index, &m.sort_key).unwrap_or_else(|pos| pos));
+        let unchanged = old
+            .zip(new.as_ref())
+            .is_some_and(|(old, new)| old.sort_key == new.sort_key && old.row == new.row);
+        if unchanged {
+            // 並び位置も行も変わらない
+            self.matched.extend(new.map(|new| (index, new)));
+            return;
+        }
+
+        if let Some(pos) = old_pos {
+            self.matched.remove(&index);
+            self.order.remove(pos);
+        }
+        let new_pos = new.map(|new| {
+            let pos = self.position(index, &new.sort_key).unwrap_or_else(|pos| pos);
+            self.matched.insert(index, new);
+            self.order.insert(pos, index);
+            pos
+        });
+
+        // limit内の行にも件数にも影響しなければ結果はそのまま
+        let visible = |pos: Option<usize>| pos.is_some_and(|pos| pos < limit);
+        if !visible(old_pos) && !visible(new_pos) && self.order.len() == self.result.total_count {
+            return;
+        }
+        prev.get_or_insert_with(|| self.result.clone());
+
+        let rows = &mut self.result.rows;
+        if let Some(pos) = old_pos.filter(|&pos| pos < limit) {
+            rows.remove(pos);
+        }
+        if let Some(pos) = new_pos.filter(|&pos| pos < limit) {
+            rows.insert(pos, self.matched[&index].row.clone());
+        }
+        rows.truncate(limit);
+        // limitで押し出されていた行が繰り上がる
+        if rows.len() < limit
+            && let Some(next) = self.order.get(rows.len())
+        {
+            rows.push(self.matched[next].row.clone());
+        }
+        self.result.total_count = self.order.len();
+    }
+
+    /// 並び順の中での位置（なければ挿入位置をErrで返す）
+    fn position(&self, index: u32, sort_key: &Option<serde_json::Value>) -> Result<usize, usize> {
+        self.order
+            .binary_search_by(|&probe| self.compare(probe, &self.matched[&probe].sort_key, index, sort_key))
+    }
+
+    /// 結果の順序（World::execute_query と同じく、同順位はEntityのindex順）
+    fn compare(
+        &self,
+        a: u32,
+        a_key: &Option<serde_json::Value>,
+        b: u32,
+        b_key: &Option<serde_json::Value>,
+    ) -> Ordering {
+        let cmp = match &self.query.order_by {
+            Some(order) => order::compare_keys(a_key, b_key, order.direction),
+            None => Ordering::Equal,
+        };
+        cmp.then(a.cmp(&b))
+    }
+
+    /// 並び順から結果を構築
+    fn build_result(&self) -> QueryResult {
+        let limit = self.query.limit.unwrap_or(usize::MAX);
+        QueryResult {
+            rows: self
+                .order
+                .iter()
+                .take(limit)
+                .map(|index| self.matched[index].row.clone())
+                .collect(),
+            total_count: self.order.len(),
+        }
+    }
+}
+
+/// クエリの依存関係
+struct Dependencies {
+    /// 参照するコンポーネントの集合（特定できない場合はNone）
+    components: Option<HashSet<TypeId>>,
+    uses_camera: bool,
+    uses_hierarchy: bool,
+}
+
+/// クエリが参照するコンポーネント・カメラ位置・他のEntityを調べる
+fn dependencies(world: &World, query: &QueryDescriptor) -> Dependencies {
+    let mut deps = Some(HashSet::new());
+    let mut uses_camera = false;
+    let mut uses_hierarchy = false;
+
+    // 未知のコンポーネントは常に不一致なので依存に含めない
+    for component in query.with_components.iter().chain(&query.without_components) {
+        if let Some(deps) = &mut deps {
+            deps.extend(World::component_type_id(component));
+        }
+    }
+
+    let fields = query
+        .select
+        .iter()
+        .chain(query.filters.iter().map(|f| &f.field))
+        .chain(query.order_by.iter().map(|o| &o.field));
+    for field in fields {
+        if field == "id" {
+            continue;
+        }
+        // parent.<field> は親のコンポーネントに依存する
+        let mut field = field.as_str();
+        while let Some(inner) = field.strip_prefix("parent.") {
+            uses_hierarchy = true;
+            if let Some(deps) = &mut deps {
+                deps.insert(TypeId::of::<Parent>());
+            }
+            field = inner;
+        }
+        // distance_to(...) はワールド座標を使うため祖先のTransformに依存する
+        if parse_distance_to(field).is_some() {
+            uses_hierarchy = true;
+            if let Some(deps) = &mut deps {
+                deps.insert(TypeId::of::<Parent>());
+            }
+        }
+        let sources = match World::stored_field_source(field)
+            .or_else(|| World::hierarchy_field_source(field))
+        {
+            Some(source) => Some(vec![source]),
+            None => world.computed_field(field).and_then(|computed| {
+                uses_camera |= computed.uses_camera();
+                uses_hierarchy |= computed.uses_hierarchy();
+                computed.components().map(<[TypeId]>::to_vec)
+            }),
+        };
+        match (sources, &mut deps) {
+            (Some(sources), Some(deps)) => deps.extend(sources),
+            _ => deps = None,
+        }
+    }
+
+    // 空間フィルターはワールド行列で判定するため祖先のTransformに依存する
+    if !query.spatial.is_empty() {
+        uses_hierarchy = true;
+        if let Some(deps) = &mut deps {
+            deps.insert(TypeId::of::<crate::components::Transform>());
+            deps.insert(TypeId::of::<Parent>());
+        }
+    }
+
+    if !query.relations.is_empty()
+        && let Some(deps) = &mut deps
+    {
+        deps.insert(TypeId::of::<Parent>());
+    }
+
+    Dependencies {
+        components: deps,
+        uses_camera,
+        uses_hierarchy,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Name, Transform};
+    use crate::ecs::query::{FilterExpr, FilterValue, OrderBy};
+    use glam::Vec3;
+
+    fn spawn(world: &mut World, name: &str, y: f32) -> EntityId {
+        let e = world.spawn();
+        world.insert(e, Name::new(name));
+        world.insert(e, Transform::from_position(Vec3::new(0.0, y, 0.0)));
+        e
+    }
+
+    fn query() -> QueryDescriptor {
+        QueryDescriptor::new()
+            .select(["name", "position.y"])
+            .with(["Transform"])
+            .filter(FilterExpr::gt("position.y", FilterValue::Number(0.0)))
+            .order_by(OrderBy::desc("position.y"))
+            .limit(2)
+    }
+
+    /// 差分更新の結果が全件実行と一致することを確認
+    fn assert_consistent(world: &World, live: &LiveQuery) {
+        assert_eq!(live.result(), &world.execute_query(live.query()));
+    }
+
+    #[test]
+    fn test_incremental_matches_full_execution() {
+        let mut world = World::new();
+        world.set_change_tracking(true);
+        let a = spawn(&mut world, "a", 1.0);
+        let b = spawn(&mut world, "b", -1.0);
+        let mut live = LiveQuery::new(&world, query());
+        world.take_changes();
+        assert_consistent(&world, &live);
+        assert_eq!(live.result().total_count, 1);
+
+        // 条件を満たすようになる
+        world.get_mut::<Transform>(b).unwrap().position.y = 5.0;
+        let changes = world.take_changes();
+        assert!(live.apply(&world, &changes));
+        assert_consistent(&world, &live);
+
+        // 生成と削除
+        let c = spawn(&mut world, "c", 3.0);
+        world.despawn(a);
+        let changes = world.take_changes();
+        assert!(live.apply(&world, &changes));
+        assert_consistent(&world, &live);
+
+        // 名前の変更は select に含まれるので結果が変わる
+        *world.get_mut::<Name>(c).unwrap() = Name::new("renamed");
+        let changes = world.take_changes();
+        assert!(live.apply(&world, &changes));
+        assert_consistent(&world, &live);
+    }
+
+    #[test]
+    fn test_unrelated_changes_are_ignored() {
+        struct Health;
+        impl crate::ecs::Component for Health {}
+
+        let mut world = World::new();
+        world.set_change_tracking(true);
+        let a = spawn(&mut world, "a", 1.0);
+        let mut live = LiveQuery::new(&world, query());
+        world.take_changes();
+
+        world.insert(a, Health);
+        let changes = world.take_changes();
+        assert!(!live.is_affected_by(&changes));
+        assert!(!live.apply(&world, &changes));
+
+        // limit外のEntityの変更は結果を変えない
+        spawn(&mut world, "b", 3.0);
+        spawn(&mut world, "c", 2.0);
+        let changes = world.take_changes();
+        assert!(live.apply(&world, &changes));
+        world.get_mut::<Transform>(a).unwrap().position.y = 1.5;
+        let changes = world.take_changes();
+        assert!(live.is_affected_by(&changes));
+        assert!(!live.apply(&world, &changes));
+        assert_consistent(&world, &live);
+    }
+
+    #[test]
+    fn test_computed_field_dependencies() {
+        let mut world = World::new();
+        world.set_change_tracking(true);
+        let a = spawn(&mut world, "a", 1.0);
+        spawn(&mut world, "b", 5.0);
+        world.set_camera_position(Vec3::ZERO);
+        let query = QueryDescriptor::new()
+            .select(["name", "distance_to_camera"])
+            .order_by(OrderBy::asc("distance_to_camera"));
+        let mut live = LiveQuery::new(&world, query);
+        world.take_changes();
+
+        // 参照しないコンポーネントの変更は無視
+        struct Health;
+        impl crate::ecs::Component for Health {}
+        world.insert(a, Health);
+        let changes = world.take_changes();
+        assert!(!live.is_affected_by(&changes));
+
+        // カメラ移動で全件再評価
+        world.set_camera_position(Vec3::new(0.0, 10.0, 0.0));
+        let changes = world.take_changes();
+        assert!(live.is_affected_by(&changes));
+        assert!(live.apply(&world, &changes));
+        assert_consistent(&world, &live);
+        assert_eq!(live.result().rows[0].get_field("name"), Some(&serde_json::json!("b")));
+
+        // 依存が不明な計算フィールドは任意のコンポーネント変更で再評価
+        let count = LiveQuery::new(&world, QueryDescriptor::new().select(["component_count"]));
+        world.remove::<Health>(a);
+        assert!(count.is_affected_by(&world.take_changes()));
+    }
+
+    #[test]
+    fn test_hierarchy_queries() {
+        let mut world = World::new();
+        world.set_change_tracking(true);
+        let lights = spawn(&mut world, "Lights", 1.0);
+        let props = spawn(&mut world, "Props", 1.0);
+        let lamp = spawn(&mut world, "lamp", 1.0);
+        let bulb = spawn(&mut world, "bulb", 1.0);
+        world.set_parent(lamp, Some(lights));
+        world.set_parent(bulb, Some(lamp));
+
+        let by_parent_name = LiveQuery::new(
+            &world,
+            QueryDescriptor::new()
+                .select(["name", "depth"])
+                .filter(FilterExpr::eq("parent.name", FilterValue::String("Props".into()))),
+        );
+        let descendants = LiveQuery::new(
+            &world,
+            QueryDescriptor::new()
+                .select(["name", "depth"])
+                .relation(crate::ecs::query::RelationFilter::descendant_of(lights)),
+        );
+        let mut live = [by_parent_name, descendants];
+        world.take_changes();
+        assert_eq!(live[0].result().total_count, 0);
+        assert_eq!(live[1].result().total_count, 2);
+
+        // 付け替えで子孫の depth も変わる
+        world.set_parent(lamp, Some(props));
+        let changes = world.take_changes();
+        for live in &mut live {
+            assert!(live.apply(&world, &changes));
+            assert_consistent(&world, live);
+        }
+        assert_eq!(live[0].result().total_count, 1);
+        assert_eq!(live[1].result().total_count, 0);
+
+        // 親の名前の変更は子の parent.name に影響する
+        *world.get_mut::<Name>(props).unwrap() = Name::new("Stage");
+        let changes = world.take_changes();
+        assert!(live[0].apply(&world, &changes));
+        assert_consistent(&world, &live[0]);
+        assert_eq!(live[0].result().total_count, 0);
+    }
+
+    #[test]
+    fn test_spatial_query_follows_parent() {
+        let mut world = World::new();
+        world.set_change_tracking(true);
+        let parent = spawn(&mut world, "parent", 0.0);
+        let child = spawn(&mut world, "child", 1.0);
+        world.set_parent(child, Some(parent));
+
+        let near = |center: Vec3| {
+            QueryDescriptor::new()
+                .select(["name", "distance_to(0, 11, 0)"])
+                .spatial(crate::ecs::query::SpatialFilter::within_sphere(center, 0.5))
+        };
+        let mut live = LiveQuery::new(&world, near(Vec3::new(0.0, 11.0, 0.0)));
+        let mut distance = LiveQuery::new(
+            &world,
+            QueryDescriptor::new()
+                .select(["name", "distance_to(0, 11, 0)"])
+                .filter(FilterExpr::lt("distance_to(0, 11, 0)", FilterValue::Number(0.5))),
+        );
+        world.take_changes();
+        assert_eq!(live.result().total_count, 0);
+
+        // 親だけを動かしても子のワールド座標が変わる
+        world.get_mut::<Transform>(parent).unwrap().position = Vec3::new(0.0, 10.0, 0.0);
+        let changes = world.take_changes();
+        for live in [&mut live, &mut distance] {
+            assert!(live.is_affected_by(&changes));
+            assert!(live.apply(&world, &changes));
+            assert_consistent(&world, live);
+            assert_eq!(live.result().rows.len(), 1);
+            assert_eq!(live.result().rows[0].get_field("name"), Some(&serde_json::json!("child")));
+        }
+    }
+
+    #[test]
+    fn test_in_place_order_matches_full_execution() {
+        let mut world = World::new();
+        world.set_change_tracking(true);
+        let mut entities: Vec<EntityId> = (0..12).map(|i| spawn(&mut world, &format!("e{}", i), (i % 4) as f32)).collect();
+        let mut live = LiveQuery::new(&world, query().limit(4));
+        let mut asc = LiveQuery::new(&world, QueryDescriptor::new().select(["name"]).order_by(OrderBy::asc("position.y")));
+        world.take_changes();
+
+        // 同順位・limit境界をまたぐ移動・生成・削除（スロットの再利用を含む）
+        let mut seed = 7u32;
+        for step in 0..200 {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            let i = (seed >> 8) as usize % entities.len();
+            match step % 5 {
+                3 => {
+                    world.despawn(entities[i]);
+                    entities[i] = spawn(&mut world, "respawned", (seed >> 20 & 3) as f32);
+                }
+                _ => world.get_mut::<Transform>(entities[i]).unwrap().position.y = (seed >> 16 & 7) as f32 - 2.0,
+            }
+            let changes = world.take_changes();
+            let prev = live.result().clone();
+            assert_eq!(live.apply(&world, &changes), prev != *live.result());
+            asc.apply(&world, &changes);
+            assert_consistent(&world, &live);
+            assert_consistent(&world, &asc);
+        }
+    }
+
+    #[test]
+    fn test_apply_delta() {
+        let mut world = World::new();
+        world.set_change_tracking(true);
+        let a = spawn(&mut world, "a", 1.0);
+        let b = spawn(&mut world, "b", 2.0);
+        let mut live = LiveQuery::new(&world, query());
+        world.take_changes();
+        let mut patched = live.result().clone();
+
+        // 順序の入れ替えと値の更新
+        world.get_mut::<Transform>(a).unwrap().position.y = 3.0;
+        let changes = world.take_changes();
+        let delta = live.apply_delta(&world, &changes).unwrap();
+        assert!(delta.added.is_empty() && delta.removed.is_empty());
+        assert_eq!(delta.updated.len(), 1);
+        assert_eq!(delta.updated[0].id, a.to_u32());
+        assert_eq!(delta.moved.len(), 1);
+        delta.apply_to(&mut patched);
+        assert_eq!(&patched, live.result());
+
+        // 削除と、limitにより押し出されていた行の追加
+        spawn(&mut world, "c", 0.5);
+        world.despawn(b);
+        let changes = world.take_changes();
+        let delta = live.apply_delta(&world, &changes).unwrap();
+        assert_eq!(delta.removed, vec![b.to_u32()]);
+        assert_eq!(delta.added.len(), 1);
+        delta.apply_to(&mut patched);
+        assert_eq!(&patched, live.result());
+
+        // 値が変わらなければ差分なし
+        world.get_mut::<Name>(a);
+        let changes = world.take_changes();
+        assert!(live.apply_delta(&world, &changes).is_none());
+    }
+}
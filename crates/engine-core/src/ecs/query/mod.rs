@@ -5,6 +5,31 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub mod columnar;
+pub mod computed;
+pub mod delta;
+pub mod live;
+pub(crate) mod order;
+pub mod parser;
+pub mod prepared;
+pub mod profile;
+pub mod relation;
+pub mod sliced;
+pub mod spatial;
+pub mod validate;
+
+pub use columnar::{Column, ColumnarResult, NO_ENTITY};
+pub use computed::ComputedField;
+pub use delta::{AddedRow, MovedRow, QueryDelta, UpdatedRow};
+pub use live::LiveQuery;
+pub use parser::{parse_query, ParseError};
+pub use prepared::PreparedQuery;
+pub use profile::{ProfileStage, QueryProfile, StageReport};
+pub use relation::RelationFilter;
+pub use sliced::{QueryProgress, QueryStage, SlicedQuery};
+pub use spatial::SpatialFilter;
+pub use validate::{QueryClause, QueryError, QueryValidationError, ValueType};
+
 // ============================================================================
 // CompareOp - 比較演算子
 // ============================================================================
@@ -50,6 +75,18 @@ impl CompareOp {
         }
     }
 
+    /// 演算子の表記 (e.g., "<=")
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        }
+    }
+
     /// FilterValue同士の比較
     pub fn compare_values(&self, left: &FilterValue, right: &FilterValue) -> bool {
         match (left, right) {
@@ -258,6 +295,9 @@ impl OrderBy {
 // QueryDescriptor - クエリ定義
 // ============================================================================
 
+/// limit の上限（テキストクエリの数値はf64のため、正確に表せる整数まで）
+pub const MAX_LIMIT: u64 = (1 << 53) - 1;
+
 /// クエリ定義
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct QueryDescriptor {
@@ -273,6 +313,14 @@ pub struct QueryDescriptor {
     /// フィルター条件
     pub filters: Vec<FilterExpr>,
 
+    /// 空間フィルター条件
+    #[serde(default)]
+    pub spatial: Vec<SpatialFilter>,
+
+    /// 親子関係フィルター条件
+    #[serde(default)]
+    pub relations: Vec<RelationFilter>,
+
     /// ソート条件
     pub order_by: Option<OrderBy>,
 
@@ -310,6 +358,18 @@ impl QueryDescriptor {
         self
     }
 
+    /// 空間フィルター条件を追加
+    pub fn spatial(mut self, filter: SpatialFilter) -> Self {
+        self.spatial.push(filter);
+        self
+    }
+
+    /// 親子関係フィルター条件を追加
+    pub fn relation(mut self, filter: RelationFilter) -> Self {
+        self.relations.push(filter);
+        self
+    }
+
     /// ソート条件を設定
     pub fn order_by(mut self, order: OrderBy) -> Self {
         self.order_by = Some(order);
@@ -340,7 +400,7 @@ impl QueryDescriptor {
 // ============================================================================
 
 /// クエリ結果の1行
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct QueryResultRow {
     /// EntityId (u32形式)
     pub id: u32,
@@ -376,7 +436,7 @@ impl QueryResultRow {
 }
 
 /// クエリ実行結果
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct QueryResult {
     /// 結果の行
     pub rows: Vec<QueryResultRow>,
@@ -0,0 +1,87 @@
+//! 並び替え・件数制限
+//!
+//! `World::execute_query`・準備済みクエリ・分割実行クエリ・ライブクエリが
+//! 同じ順序と total_count になるよう、order_by と limit の処理をここにまとめる。
+//!
+//! - ソートキーは候補ごとに1回だけ取り出す
+//! - 安定ソートのため、同じキーの候補は元の順序（Entityのindex順）を保つ
+//! - total_count は limit 適用前の件数
+
+use std::cmp::Ordering;
+
+use crate::ecs::entity::EntityId;
+use crate::ecs::world::World;
+
+use super::SortDirection;
+
+/// ソートキー付きの候補
+pub(crate) type Keyed = (EntityId, Option<serde_json::Value>);
+
+/// order_by の方向でソートキーを比較
+pub(crate) fn compare_keys(
+    a: &Option<serde_json::Value>,
+    b: &Option<serde_json::Value>,
+    direction: SortDirection,
+) -> Ordering {
+    let cmp = World::compare_json_values(a, b);
+    match direction {
+        SortDirection::Asc => cmp,
+        SortDirection::Desc => cmp.reverse(),
+    }
+}
+
+/// ソートキーで安定ソート
+pub(crate) fn sort_keyed(keyed: &mut [Keyed], direction: SortDirection) {
+    keyed.sort_by(|a, b| compare_keys(&a.1, &b.1, direction));
+}
+
+/// 並び替えて limit を適用し、limit適用前の件数とともに返す
+///
+/// `direction` がNoneなら並び替えない
+pub(crate) fn order_and_limit(
+    mut keyed: Vec<Keyed>,
+    direction: Option<SortDirection>,
+    limit: Option<usize>,
+) -> (Vec<EntityId>, usize) {
+    let total_count = keyed.len();
+    if let Some(direction) = direction {
+        sort_keyed(&mut keyed, direction);
+    }
+    if let Some(limit) = limit {
+        keyed.truncate(limit);
+    }
+    (keyed.into_iter().map(|(entity, _)| entity).collect(), total_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn keyed() -> Vec<Keyed> {
+        [json!(2), json!(3), json!(1), json!(2)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, key)| (EntityId::new(i as u32, 1), Some(key)))
+            .collect()
+    }
+
+    fn indices(entities: &[EntityId]) -> Vec<u32> {
+        entities.iter().map(|e| e.index()).collect()
+    }
+
+    #[test]
+    fn test_order_and_limit() {
+        // 同じキーは元の順序を保つ
+        let (asc, total) = order_and_limit(keyed(), Some(SortDirection::Asc), None);
+        let (desc, _) = order_and_limit(keyed(), Some(SortDirection::Desc), None);
+        assert_eq!(total, 4);
+        assert_eq!(indices(&desc), vec![1, 0, 3, 2]);
+        assert_eq!(indices(&asc), vec![2, 0, 3, 1]);
+
+        // total_count は limit 適用前の件数
+        let (limited, total) = order_and_limit(keyed(), None, Some(2));
+        assert_eq!(indices(&limited), vec![0, 1]);
+        assert_eq!(total, 4);
+    }
+}
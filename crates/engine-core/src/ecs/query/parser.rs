@@ -0,0 +1,909 @@
+//! テキストクエリ言語
+//!
+//! `SELECT id, name WHERE Transform AND position.x > 0 ORDER BY name LIMIT 10`
+//! のようなテキストを `QueryDescriptor` に変換する。
+//!
+//! ```text
+//! query     := [SELECT fields] [WHERE condition] [ORDER BY field [ASC|DESC]] [LIMIT n]
+//! fields    := field ("," field)*
+//! condition := term (AND term)*
+//! term      := [NOT] Component | field op value | spatial | relation
+//! field     := name | name "(" number ("," number)* ")" | `quoted name`
+//! spatial   := within_sphere(...) | within_aabb(...) | in_frustum(...) | intersects_ray(...)
+//! relation  := child_of(id) | descendant_of(id) | is_root()
+//! op        := == | = | != | <> | < | <= | > | >=
+//! value     := number | 'string' | "string" | true | false | null
+//! ```
+//!
+//! - キーワードは大文字小文字を区別しない
+//! - キーワードと同じ名前や空白・記号を含む名前はバッククォートで囲む（`` `Limit` ``、`` `my field` ``）
+//! - 演算子を伴わない識別子はコンポーネント名として扱う（`NOT` で除外）
+//! - 演算子を伴わない関数呼び出しは空間フィルター・親子関係フィルターとして扱う
+//! - SELECT 句を省略した場合は id のみを返す
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::{
+    CompareOp, FilterExpr, FilterValue, OrderBy, QueryDescriptor, RelationFilter, SortDirection,
+    SpatialFilter, MAX_LIMIT,
+};
+
+// ============================================================================
+// ParseError - 構文エラー
+// ============================================================================
+
+/// クエリテキストの構文エラー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// エラー内容
+    pub message: String,
+    /// 行番号（1始まり）
+    pub line: usize,
+    /// 列番号（1始まり）
+    pub column: usize,
+}
+
+impl ParseError {
+    fn new(message: impl Into<String>, pos: Position) -> Self {
+        Self {
+            message: message.into(),
+            line: pos.line,
+            column: pos.column,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// ============================================================================
+// Lexer - 字句解析
+// ============================================================================
+
+/// ソース上の位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+/// トークン種別
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    /// 識別子（`position.x` のようなドット区切りを含む）
+    Ident(String),
+    /// バッククォートで囲んだ識別子（キーワードとして扱わず、引数も取らない）
+    QuotedIdent(String),
+    Number(f64),
+    String(String),
+    Op(CompareOp),
+    Comma,
+    LParen,
+    RParen,
+    Eof,
+}
+
+impl TokenKind {
+    /// エラーメッセージ用の表記
+    fn describe(&self) -> String {
+        match self {
+            TokenKind::Ident(s) => format!("'{}'", s),
+            TokenKind::QuotedIdent(s) => format!("'`{}`'", s),
+            TokenKind::Number(n) => format!("number {}", n),
+            TokenKind::String(s) => format!("string \"{}\"", s),
+            TokenKind::Op(op) => format!("operator '{}'", op.symbol()),
+            TokenKind::Comma => "','".to_string(),
+            TokenKind::LParen => "'('".to_string(),
+            TokenKind::RParen => "')'".to_string(),
+            TokenKind::Eof => "end of input".to_string(),
+        }
+    }
+
+    /// 指定キーワードかどうか（大文字小文字を区別しない）
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, TokenKind::Ident(s) if s.eq_ignore_ascii_case(keyword))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    pos: Position,
+}
+
+/// 予約語（識別子として使えない）
+const KEYWORDS: &[&str] = &[
+    "SELECT", "WHERE", "AND", "OR", "NOT", "ORDER", "BY", "ASC", "DESC", "LIMIT", "TRUE",
+    "FALSE", "NULL",
+];
+
+fn is_keyword(s: &str) -> bool {
+    KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(s))
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    pos: Position,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            chars: source.chars().peekable(),
+            pos: Position { line: 1, column: 1 },
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.pos.line += 1;
+            self.pos.column = 1;
+        } else {
+            self.pos.column += 1;
+        }
+        Some(c)
+    }
+
+    /// 全トークンを読み取る（末尾にEofを含む）
+    fn tokenize(mut self) -> Result<Vec<Token>, ParseError> {
+        let mut tokens = Vec::new();
+        loop {
+            while self.peek().is_some_and(char::is_whitespace) {
+                self.bump();
+            }
+
+            let pos = self.pos;
+            let Some(c) = self.peek() else {
+                tokens.push(Token { kind: TokenKind::Eof, pos });
+                return Ok(tokens);
+            };
+
+            let kind = match c {
+                ',' => {
+                    self.bump();
+                    TokenKind::Comma
+                }
+                '(' => {
+                    self.bump();
+                    TokenKind::LParen
+                }
+                ')' => {
+                    self.bump();
+                    TokenKind::RParen
+                }
+                '=' | '!' | '<' | '>' => self.read_op(pos)?,
+                '\'' | '"' => TokenKind::String(self.read_quoted(pos, "string literal")?),
+                '`' => TokenKind::QuotedIdent(self.read_quoted(pos, "quoted identifier")?),
+                c if c.is_ascii_digit() || c == '-' || c == '.' => self.read_number(pos)?,
+                c if c.is_alphabetic() || c == '_' => self.read_ident(pos)?,
+                c => return Err(ParseError::new(format!("unexpected character '{}'", c), pos)),
+            };
+            tokens.push(Token { kind, pos });
+        }
+    }
+
+    fn read_op(&mut self, pos: Position) -> Result<TokenKind, ParseError> {
+        let first = self.bump().unwrap_or_default();
+        let second = self.peek();
+        let op = match (first, second) {
+            ('=', Some('=')) => Some(CompareOp::Eq),
+            ('!', Some('=')) => Some(CompareOp::Ne),
+            ('<', Some('>')) => Some(CompareOp::Ne),
+            ('<', Some('=')) => Some(CompareOp::Le),
+            ('>', Some('=')) => Some(CompareOp::Ge),
+            _ => None,
+        };
+        if let Some(op) = op {
+            self.bump();
+            return Ok(TokenKind::Op(op));
+        }
+        match first {
+            '=' => Ok(TokenKind::Op(CompareOp::Eq)),
+            '<' => Ok(TokenKind::Op(CompareOp::Lt)),
+            '>' => Ok(TokenKind::Op(CompareOp::Gt)),
+            _ => Err(ParseError::new("expected '=' after '!'", pos)),
+        }
+    }
+
+    /// 引用符で囲まれた文字列・識別子を読み取る（エスケープを解釈する）
+    fn read_quoted(&mut self, pos: Position, what: &str) -> Result<String, ParseError> {
+        let quote = self.bump().unwrap_or_default();
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(ParseError::new(format!("unterminated {}", what), pos)),
+                Some(c) if c == quote => return Ok(value),
+                Some('\\') => match self.bump() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(c @ ('\\' | '\'' | '"' | '`')) => value.push(c),
+                    Some(c) => {
+                        return Err(ParseError::new(
+                            format!("unknown escape sequence '\\{}'", c),
+                            pos,
+                        ));
+                    }
+                    None => return Err(ParseError::new(format!("unterminated {}", what), pos)),
+                },
+                Some(c) => value.push(c),
+            }
+        }
+    }
+
+    fn read_number(&mut self, pos: Position) -> Result<TokenKind, ParseError> {
+        let mut text = String::new();
+        if self.peek() == Some('-') {
+            text.push('-');
+            self.bump();
+        }
+        while let Some(c) = self.peek() {
+            let exponent_sign = (c == '-' || c == '+') && text.ends_with(['e', 'E']);
+            if c.is_ascii_alphanumeric() || c == '.' || exponent_sign {
+                text.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        text.parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(TokenKind::Number)
+            .ok_or_else(|| ParseError::new(format!("invalid number '{}'", text), pos))
+    }
+
+    fn read_ident(&mut self, pos: Position) -> Result<TokenKind, ParseError> {
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_alphanumeric() || c == '_' || c == '.' {
+                text.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        if text.ends_with('.') || text.contains("..") {
+            return Err(ParseError::new(format!("invalid field path '{}'", text), pos));
+        }
+        Ok(TokenKind::Ident(text))
+    }
+}
+
+// ============================================================================
+// Parser - 構文解析
+// ============================================================================
+
+struct Parser {
+    tokens: Vec<Token>,
+    cursor: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.cursor]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.cursor].clone();
+        if self.cursor + 1 < self.tokens.len() {
+            self.cursor += 1;
+        }
+        token
+    }
+
+    fn unexpected(&self, expected: &str) -> ParseError {
+        let token = self.peek();
+        ParseError::new(
+            format!("expected {}, found {}", expected, token.kind.describe()),
+            token.pos,
+        )
+    }
+
+    /// 次のトークンが指定キーワードなら消費してtrue
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek().kind.is_keyword(keyword) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), ParseError> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{}'", keyword)))
+        }
+    }
+
+    /// 次のトークンがバッククォートで囲んだ識別子か
+    fn peek_quoted(&self) -> bool {
+        matches!(self.peek().kind, TokenKind::QuotedIdent(_))
+    }
+
+    /// 予約語以外の識別子を読み取る（バッククォートで囲めば予約語も使える）
+    fn expect_ident(&mut self, expected: &str) -> Result<String, ParseError> {
+        let name = match &self.peek().kind {
+            TokenKind::Ident(s) if !is_keyword(s) => s.clone(),
+            TokenKind::QuotedIdent(s) => s.clone(),
+            _ => return Err(self.unexpected(expected)),
+        };
+        self.advance();
+        Ok(name)
+    }
+
+    fn parse_query(&mut self) -> Result<QueryDescriptor, ParseError> {
+        let mut query = QueryDescriptor::new();
+
+        if self.eat_keyword("SELECT") {
+            query.select.push(self.parse_field()?);
+            while self.peek().kind == TokenKind::Comma {
+                self.advance();
+                query.select.push(self.parse_field()?);
+            }
+        }
+
+        if self.eat_keyword("WHERE") {
+            self.parse_term(&mut query)?;
+            loop {
+                if self.eat_keyword("AND") {
+                    self.parse_term(&mut query)?;
+                } else if self.peek().kind.is_keyword("OR") {
+                    let pos = self.peek().pos;
+                    return Err(ParseError::new("OR is not supported; combine terms with AND", pos));
+                } else {
+                    break;
+                }
+            }
+        }
+
+        if self.eat_keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            let field = self.parse_field()?;
+            let direction = if self.eat_keyword("DESC") {
+                SortDirection::Desc
+            } else {
+                self.eat_keyword("ASC");
+                SortDirection::Asc
+            };
+            query.order_by = Some(OrderBy { field, direction });
+        }
+
+        if self.eat_keyword("LIMIT") {
+            let token = self.advance();
+            match token.kind {
+                TokenKind::Number(n) if n >= 0.0 && n.fract() == 0.0 => {
+                    // 桁あふれを丸めずにエラーにする（usizeが32bitの環境を含む）
+                    let limit = (n <= MAX_LIMIT as f64)
+                        .then(|| usize::try_from(n as u64).ok())
+                        .flatten()
+                        .ok_or_else(|| ParseError::new(format!("LIMIT {} is too large", n), token.pos))?;
+                    query.limit = Some(limit);
+                }
+                other => {
+                    return Err(ParseError::new(
+                        format!("expected non-negative integer, found {}", other.describe()),
+                        token.pos,
+                    ));
+                }
+            }
+        }
+
+        if self.peek().kind != TokenKind::Eof {
+            return Err(self.unexpected("end of input"));
+        }
+
+        Ok(query)
+    }
+
+    /// WHERE句の1項を読み取り、queryに追加
+    fn parse_term(&mut self, query: &mut QueryDescriptor) -> Result<(), ParseError> {
+        if self.eat_keyword("NOT") {
+            let component = self.expect_ident("component name")?;
+            query.without_components.push(component);
+            return Ok(());
+        }
+
+        let pos = self.peek().pos;
+        let quoted = self.peek_quoted();
+        let name = self.expect_ident("component name or field")?;
+        let args = if !quoted && self.peek().kind == TokenKind::LParen {
+            Some(self.parse_arguments()?)
+        } else {
+            None
+        };
+
+        let TokenKind::Op(op) = self.peek().kind else {
+            match args {
+                Some(args) => match RelationFilter::from_function(&name, &args) {
+                    Some(relation) => {
+                        let filter = relation.map_err(|message| ParseError::new(message, pos))?;
+                        query.relations.push(filter);
+                    }
+                    None => {
+                        let filter = SpatialFilter::from_function(&name, &to_f32(&args))
+                            .map_err(|message| ParseError::new(message, pos))?;
+                        query.spatial.push(filter);
+                    }
+                },
+                None => query.with_components.push(name),
+            }
+            return Ok(());
+        };
+        self.advance();
+
+        let field = match args {
+            Some(args) => call_field_name(&name, &to_f32(&args)),
+            None => name,
+        };
+        let value = self.parse_value()?;
+        query.filters.push(FilterExpr::new(field, op, value));
+        Ok(())
+    }
+
+    /// フィールド名を読み取る（`distance_to(1, 2, 3)` のような計算フィールドを含む）
+    fn parse_field(&mut self) -> Result<String, ParseError> {
+        let quoted = self.peek_quoted();
+        let name = self.expect_ident("field name")?;
+        if quoted || self.peek().kind != TokenKind::LParen {
+            return Ok(name);
+        }
+        let args = self.parse_arguments()?;
+        Ok(call_field_name(&name, &to_f32(&args)))
+    }
+
+    /// `(` [number ("," number)*] `)` を読み取る
+    fn parse_arguments(&mut self) -> Result<Vec<f64>, ParseError> {
+        self.advance(); // '('
+        let mut args = Vec::new();
+        if self.peek().kind == TokenKind::RParen {
+            self.advance();
+            return Ok(args);
+        }
+        loop {
+            match self.peek().kind {
+                TokenKind::Number(n) => {
+                    args.push(n);
+                    self.advance();
+                }
+                _ => return Err(self.unexpected("number")),
+            }
+            match self.peek().kind {
+                TokenKind::Comma => {
+                    self.advance();
+                }
+                TokenKind::RParen => {
+                    self.advance();
+                    return Ok(args);
+                }
+                _ => return Err(self.unexpected("',' or ')'")),
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<FilterValue, ParseError> {
+        let value = match &self.peek().kind {
+            TokenKind::Number(n) => FilterValue::Number(*n),
+            TokenKind::String(s) => FilterValue::String(s.clone()),
+            kind if kind.is_keyword("TRUE") => FilterValue::Bool(true),
+            kind if kind.is_keyword("FALSE") => FilterValue::Bool(false),
+            kind if kind.is_keyword("NULL") => FilterValue::Null,
+            _ => return Err(self.unexpected("value")),
+        };
+        self.advance();
+        Ok(value)
+    }
+}
+
+/// 関数呼び出し形式のフィールド名を正規化して作成
+fn call_field_name(name: &str, args: &[f32]) -> String {
+    format!("{}({})", name, join_numbers(args))
+}
+
+/// 引数列をf32に変換（EntityIdの精度を保つため、読み取りはf64で行う）
+fn to_f32(args: &[f64]) -> Vec<f32> {
+    args.iter().map(|&a| a as f32).collect()
+}
+
+/// 数値列を ", " 区切りで連結
+fn join_numbers<T: ToString>(args: &[T]) -> String {
+    args.iter()
+        .map(|a| a.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// クエリテキストを `QueryDescriptor` にパース
+pub fn parse_query(source: &str) -> Result<QueryDescriptor, ParseError> {
+    let tokens = Lexer::new(source).tokenize()?;
+    Parser { tokens, cursor: 0 }.parse_query()
+}
+
+impl FromStr for QueryDescriptor {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_query(s)
+    }
+}
+
+// ============================================================================
+// Pretty printer - テキストへの変換
+// ============================================================================
+
+/// 引用符で囲み、引用符・バックスラッシュ・改行・タブをエスケープして書き出す
+fn write_quoted(f: &mut fmt::Formatter<'_>, s: &str, quote: char) -> fmt::Result {
+    write!(f, "{}", quote)?;
+    for c in s.chars() {
+        match c {
+            c if c == quote => write!(f, "\\{}", c)?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\t' => f.write_str("\\t")?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "{}", quote)
+}
+
+/// FilterValueをリテラル表記で書き出す
+fn write_value(f: &mut fmt::Formatter<'_>, value: &FilterValue) -> fmt::Result {
+    match value {
+        FilterValue::Number(n) => write!(f, "{}", n),
+        FilterValue::String(s) => write_quoted(f, s, '"'),
+        FilterValue::Bool(b) => write!(f, "{}", b),
+        FilterValue::Null => f.write_str("null"),
+    }
+}
+
+/// フィールド名・コンポーネント名の表記
+///
+/// そのまま読み戻して同じ名前にならない場合（予約語・空白や記号を含む名前）はバッククォートで囲む。
+/// `allow_call` がtrueなら `distance_to(0, 1, 2)` のような計算フィールドはそのまま書く
+struct PrintedName<'a> {
+    name: &'a str,
+    allow_call: bool,
+}
+
+impl<'a> PrintedName<'a> {
+    fn field(name: &'a str) -> Self {
+        Self { name, allow_call: true }
+    }
+
+    fn component(name: &'a str) -> Self {
+        Self { name, allow_call: false }
+    }
+
+    /// 引用符なしで読み戻せるか
+    fn is_plain(&self) -> bool {
+        let Ok(tokens) = Lexer::new(self.name).tokenize() else {
+            return false;
+        };
+        if matches!(tokens[0].kind, TokenKind::QuotedIdent(_)) {
+            return false;
+        }
+        let mut parser = Parser { tokens, cursor: 0 };
+        let parsed = if self.allow_call {
+            parser.parse_field()
+        } else {
+            parser.expect_ident("name")
+        };
+        parsed.is_ok_and(|parsed| parsed == self.name) && parser.peek().kind == TokenKind::Eof
+    }
+}
+
+impl fmt::Display for PrintedName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_plain() {
+            f.write_str(self.name)
+        } else {
+            write_quoted(f, self.name, '`')
+        }
+    }
+}
+
+/// クエリテキストとして整形（検証を通るクエリは `parse_query` で元に戻せる）
+impl fmt::Display for QueryDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut clauses: Vec<String> = Vec::new();
+
+        if !self.select.is_empty() {
+            let fields: Vec<String> = self.select.iter().map(|field| PrintedName::field(field).to_string()).collect();
+            clauses.push(format!("SELECT {}", fields.join(", ")));
+        }
+
+        let mut terms: Vec<String> = Vec::new();
+        terms.extend(self.with_components.iter().map(|c| PrintedName::component(c).to_string()));
+        terms.extend(self.without_components.iter().map(|c| format!("NOT {}", PrintedName::component(c))));
+        for filter in &self.filters {
+            terms.push(filter.to_string());
+        }
+        for spatial in &self.spatial {
+            terms.push(format!(
+                "{}({})",
+                spatial.function_name(),
+                join_numbers(&spatial.arguments())
+            ));
+        }
+        for relation in &self.relations {
+            terms.push(format!(
+                "{}({})",
+                relation.function_name(),
+                join_numbers(&relation.arguments())
+            ));
+        }
+        if !terms.is_empty() {
+            clauses.push(format!("WHERE {}", terms.join(" AND ")));
+        }
+
+        if let Some(order) = &self.order_by {
+            let direction = match order.direction {
+                SortDirection::Asc => "ASC",
+                SortDirection::Desc => "DESC",
+            };
+            clauses.push(format!("ORDER BY {} {}", PrintedName::field(&order.field), direction));
+        }
+
+        if let Some(limit) = self.limit {
+            clauses.push(format!("LIMIT {}", limit));
+        }
+
+        f.write_str(&clauses.join(" "))
+    }
+}
+
+impl fmt::Display for FilterExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ", PrintedName::field(&self.field), self.op.symbol())?;
+        write_value(f, &self.value)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_full_query() {
+        let query = parse_query(
+            "SELECT id, name WHERE Transform AND position.x > 0 ORDER BY name LIMIT 10",
+        )
+        .unwrap();
+
+        assert_eq!(query.select, vec!["id", "name"]);
+        assert_eq!(query.with_components, vec!["Transform"]);
+        assert_eq!(query.filters.len(), 1);
+        assert_eq!(query.filters[0].field, "position.x");
+        assert_eq!(query.filters[0].op, CompareOp::Gt);
+        assert_eq!(query.filters[0].value, FilterValue::Number(0.0));
+        let order = query.order_by.unwrap();
+        assert_eq!(order.field, "name");
+        assert_eq!(order.direction, SortDirection::Asc);
+        assert_eq!(query.limit, Some(10));
+    }
+
+    #[test]
+    fn test_parse_keywords_case_insensitive() {
+        let query = parse_query("select name where not Transform order by name desc").unwrap();
+        assert_eq!(query.select, vec!["name"]);
+        assert_eq!(query.without_components, vec!["Transform"]);
+        assert_eq!(query.order_by.unwrap().direction, SortDirection::Desc);
+    }
+
+    #[test]
+    fn test_parse_values() {
+        let query = parse_query(
+            "WHERE name == 'Player' AND scale.y <= -1.5e2 AND name != \"a\\\"b\" AND name = null",
+        )
+        .unwrap();
+
+        assert!(query.select.is_empty());
+        assert_eq!(query.filters[0].value, FilterValue::String("Player".into()));
+        assert_eq!(query.filters[1].op, CompareOp::Le);
+        assert_eq!(query.filters[1].value, FilterValue::Number(-150.0));
+        assert_eq!(query.filters[2].value, FilterValue::String("a\"b".into()));
+        assert_eq!(query.filters[3].op, CompareOp::Eq);
+        assert!(query.filters[3].value.is_null());
+    }
+
+    #[test]
+    fn test_parse_empty_query() {
+        let query = parse_query("   ").unwrap();
+        assert!(query.select.is_empty());
+        assert!(query.filters.is_empty());
+    }
+
+    #[test]
+    fn test_error_position() {
+        let err = parse_query("SELECT name\nWHERE position.x >").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.column, 19);
+        assert!(err.message.contains("expected value"));
+    }
+
+    #[test]
+    fn test_error_unterminated_string() {
+        let err = parse_query("WHERE name == 'abc").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 15));
+        assert!(err.message.contains("unterminated"));
+    }
+
+    #[test]
+    fn test_error_or_unsupported() {
+        let err = parse_query("WHERE Transform OR Name").unwrap_err();
+        assert_eq!(err.column, 17);
+        assert!(err.message.contains("OR"));
+    }
+
+    #[test]
+    fn test_error_trailing_tokens() {
+        let err = parse_query("SELECT name LIMIT 3 name").unwrap_err();
+        assert_eq!(err.column, 21);
+
+        let err = parse_query("LIMIT 1.5").unwrap_err();
+        assert!(err.message.contains("integer"));
+
+        // 大きすぎるLIMITは丸めずにエラー
+        let err = parse_query("LIMIT 1e30").unwrap_err();
+        assert!(err.message.contains("too large"));
+        assert_eq!(parse_query(&format!("LIMIT {}", MAX_LIMIT)).unwrap().limit, usize::try_from(MAX_LIMIT).ok());
+
+        // 非有限の数値は読めない
+        assert!(parse_query("WHERE position.x > inf").is_err());
+        assert!(parse_query("WHERE position.x > NaN").is_err());
+    }
+
+    #[test]
+    fn test_error_display() {
+        let err = parse_query("SELECT ,").unwrap_err();
+        assert_eq!(err.to_string(), "line 1, column 8: expected field name, found ','");
+    }
+
+    #[test]
+    fn test_parse_spatial() {
+        let query = parse_query(
+            "SELECT name, distance_to(1, 2, 3) WHERE within_sphere(0, 0, 0, 5) \
+             AND distance_to(1,2,3) < 2 ORDER BY distance_to( 1 , 2 , 3 )",
+        )
+        .unwrap();
+
+        assert_eq!(query.select, vec!["name", "distance_to(1, 2, 3)"]);
+        assert_eq!(query.spatial, vec![SpatialFilter::within_sphere(glam::Vec3::ZERO, 5.0)]);
+        assert_eq!(query.filters[0].field, "distance_to(1, 2, 3)");
+        assert_eq!(query.order_by.unwrap().field, "distance_to(1, 2, 3)");
+    }
+
+    #[test]
+    fn test_parse_relations() {
+        let query = parse_query(
+            "SELECT id, parent.name WHERE descendant_of(1048577) AND depth <= 2 AND is_root()",
+        )
+        .unwrap();
+
+        assert_eq!(query.select, vec!["id", "parent.name"]);
+        assert_eq!(
+            query.relations,
+            vec![
+                RelationFilter::DescendantOf { ancestor: 1048577 },
+                RelationFilter::Root
+            ]
+        );
+        assert_eq!(query.filters[0].field, "depth");
+
+        let reparsed = parse_query(&query.to_string()).unwrap();
+        assert_eq!(reparsed.relations, query.relations);
+
+        let err = parse_query("WHERE child_of()").unwrap_err();
+        assert!(err.message.contains("entity id"));
+    }
+
+    #[test]
+    fn test_error_spatial_arguments() {
+        let err = parse_query("WHERE Transform AND within_aabb(0, 0, 0)").unwrap_err();
+        assert_eq!(err.column, 21);
+        assert!(err.message.contains("6 arguments"));
+
+        let err = parse_query("WHERE within_sphere(0, 0, 'a', 1)").unwrap_err();
+        assert!(err.message.contains("expected number"));
+    }
+
+    #[test]
+    fn test_pretty_print() {
+        let query = QueryDescriptor::new()
+            .select(["id", "name"])
+            .with(["Transform"])
+            .without(["Hidden"])
+            .filter(FilterExpr::gt("position.x", FilterValue::Number(0.5)))
+            .order_by(OrderBy::desc("name"))
+            .limit(3);
+
+        assert_eq!(
+            query.to_string(),
+            "SELECT id, name WHERE Transform AND NOT Hidden AND position.x > 0.5 \
+             ORDER BY name DESC LIMIT 3"
+        );
+    }
+
+    #[test]
+    fn test_quoted_names() {
+        let query = QueryDescriptor::new()
+            .select(["name", "select", "with space", "distance_to(0, 1, 2)", "é"])
+            .with(["Limit", "not-a-name", "within_sphere(0, 0, 0, 1)"])
+            .without(["Order"])
+            .filter(FilterExpr::eq("tab\tand `tick`", FilterValue::Number(1.0)))
+            .order_by(OrderBy::desc("null"));
+
+        assert_eq!(
+            query.to_string(),
+            "SELECT name, `select`, `with space`, distance_to(0, 1, 2), é \
+             WHERE `Limit` AND `not-a-name` AND `within_sphere(0, 0, 0, 1)` AND NOT `Order` \
+             AND `tab\\tand \\`tick\\`` == 1 ORDER BY `null` DESC"
+        );
+        let reparsed = parse_query(&query.to_string()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&reparsed).unwrap(),
+            serde_json::to_value(&query).unwrap()
+        );
+
+        // 引用した名前は関数呼び出しとして扱わない
+        let query = parse_query("WHERE `near`(1) == 2").unwrap_err();
+        assert!(query.message.contains("found '('"));
+        let err = parse_query("SELECT `name").unwrap_err();
+        assert!(err.message.contains("unterminated quoted identifier"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let sources = [
+            "SELECT id, name WHERE Transform AND position.x > 0 ORDER BY name ASC LIMIT 10",
+            "WHERE NOT Transform AND name == \"quote\\\"d\\n\" AND scale.z != -2.25",
+            "SELECT name ORDER BY position.y DESC",
+            "WHERE name == true AND name != null",
+            "SELECT distance_to(0, 1.5, -2) WHERE Transform AND intersects_ray(0, 0, 10, 0, 0, -1) \
+             ORDER BY distance_to(0, 1.5, -2) DESC",
+            "SELECT `order`, `my field` WHERE `Limit` AND NOT `Hidden Layer` AND `a\\`b\"c` == 1 \
+             ORDER BY `distance_to(0,1,2)` ASC",
+            "",
+        ];
+
+        for source in sources {
+            let query = parse_query(source).unwrap();
+            assert_eq!(query.to_string(), source);
+            let reparsed: QueryDescriptor = query.to_string().parse().unwrap();
+            assert_eq!(
+                serde_json::to_value(&reparsed).unwrap(),
+                serde_json::to_value(&query).unwrap()
+            );
+        }
+
+        // 極端な有限値・上限のLIMITも読み戻せる
+        let limit = usize::try_from(MAX_LIMIT).unwrap_or(usize::MAX);
+        let mut query = QueryDescriptor::new().limit(limit);
+        for n in [f64::MAX, -f64::MIN_POSITIVE, 1e-300] {
+            query = query.filter(FilterExpr::ne("position.x", FilterValue::Number(n)));
+        }
+        let reparsed = parse_query(&query.to_string()).unwrap();
+        assert_eq!(reparsed.limit, Some(limit));
+        let values: Vec<&FilterValue> = reparsed.filters.iter().map(|f| &f.value).collect();
+        assert_eq!(
+            values,
+            query.filters.iter().map(|f| &f.value).collect::<Vec<_>>()
+        );
+    }
+}
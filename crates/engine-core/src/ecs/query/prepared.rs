@@ -0,0 +1,428 @@
+//! 準備済みクエリ
+//!
+//! フィールド名・コンポーネント名を事前に解決したクエリ。
+//! 毎フレーム同じクエリを実行する場合に、JSONのパースと名前解決を省ける。
+//!
+//! フィルター値に `"$minY"` のような `$` で始まる文字列を指定すると
+//! パラメータとなり、実行時に値を渡す。
+
+use std::any::TypeId;
+use std::collections::HashMap;
+
+use glam::Vec3;
+
+use crate::ecs::entity::EntityId;
+use crate::ecs::world::World;
+
+use super::columnar::ColumnarResult;
+use super::order;
+use super::validate::check_comparison;
+use super::{
+    CompareOp, FilterExpr, FilterValue, QueryClause, QueryDescriptor, QueryError, QueryResult,
+    QueryResultRow, QueryValidationError, SortDirection, ValueType,
+};
+
+/// 解決済みのフィールド
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum FieldAccessor {
+    Id,
+    Name,
+    Position,
+    PositionAxis(usize),
+    Rotation,
+    Scale,
+    ScaleAxis(usize),
+    DistanceTo(Vec3),
+    Parent,
+    ChildrenCount,
+    Depth,
+    /// `parent.<field>`
+    ParentField(Box<FieldAccessor>),
+    Computed(String),
+}
+
+impl FieldAccessor {
+    /// 組み込みフィールドを解決（計算フィールドはNone）
+    pub(crate) fn stored(field: &str) -> Option<Self> {
+        Some(match field {
+            "id" => Self::Id,
+            "name" => Self::Name,
+            "position" => Self::Position,
+            "position.x" => Self::PositionAxis(0),
+            "position.y" => Self::PositionAxis(1),
+            "position.z" => Self::PositionAxis(2),
+            "rotation" => Self::Rotation,
+            "scale" => Self::Scale,
+            "scale.x" => Self::ScaleAxis(0),
+            "scale.y" => Self::ScaleAxis(1),
+            "scale.z" => Self::ScaleAxis(2),
+            "parent" => Self::Parent,
+            "children_count" => Self::ChildrenCount,
+            "depth" => Self::Depth,
+            _ => return None,
+        })
+    }
+}
+
+/// フィルターの比較値
+#[derive(Debug, Clone)]
+enum Operand {
+    Value(FilterValue),
+    Param(String),
+}
+
+/// 解決済みのフィルター
+#[derive(Debug, Clone)]
+struct PreparedFilter {
+    field: String,
+    accessor: FieldAccessor,
+    field_type: ValueType,
+    op: CompareOp,
+    operand: Operand,
+}
+
+/// 準備済みクエリ
+#[derive(Debug, Clone)]
+pub struct PreparedQuery {
+    query: QueryDescriptor,
+    params: Vec<String>,
+    /// with_components（未知のコンポーネントはNone = 一致しない）
+    with: Vec<Option<TypeId>>,
+    without: Vec<Option<TypeId>>,
+    filters: Vec<PreparedFilter>,
+    select: Vec<(String, FieldAccessor)>,
+    order_by: Option<(FieldAccessor, SortDirection)>,
+}
+
+impl PreparedQuery {
+    /// クエリを検証し、フィールド・コンポーネントを解決
+    pub fn prepare(world: &World, query: QueryDescriptor) -> Result<Self, QueryValidationError> {
+        // パラメータを含むフィルターは値の型を実行時に検査する
+        let mut literal = query.clone();
+        literal.filters.retain(|f| param_name(&f.value).is_none());
+        let mut errors = match literal.validate(world) {
+            Ok(()) => Vec::new(),
+            Err(e) => e.errors,
+        };
+
+        let mut params = Vec::new();
+        let mut filters = Vec::new();
+        for filter in &query.filters {
+            let resolved = world
+                .resolve_field(&filter.field)
+                .zip(world.field_type(&filter.field));
+            let operand = match param_name(&filter.value) {
+                Some(name) => {
+                    if !params.iter().any(|p| p == name) {
+                        params.push(name.to_string());
+                    }
+                    if resolved.is_none() {
+                        errors.push(QueryError::UnknownField {
+                            field: filter.field.clone(),
+                            clause: QueryClause::Filter,
+                        });
+                    }
+                    Operand::Param(name.to_string())
+                }
+                None => Operand::Value(filter.value.clone()),
+            };
+            if let Some((accessor, field_type)) = resolved {
+                filters.push(PreparedFilter {
+                    field: filter.field.clone(),
+                    accessor,
+                    field_type,
+                    op: filter.op,
+                    operand,
+                });
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(QueryValidationError { errors });
+        }
+
+        // 検証済みのため解決に失敗しない
+        let resolve = |field: &str| world.resolve_field(field).expect("validated field");
+        let component = |name: &String| World::component_type_id(name);
+        Ok(Self {
+            with: query.with_components.iter().map(component).collect(),
+            without: query.without_components.iter().map(component).collect(),
+            select: query
+                .select
+                .iter()
+                .map(|field| (field.clone(), resolve(field)))
+                .collect(),
+            order_by: query
+                .order_by
+                .as_ref()
+                .map(|order| (resolve(&order.field), order.direction)),
+            filters,
+            params,
+            query,
+        })
+    }
+
+    /// 元のクエリ
+    pub fn query(&self) -> &QueryDescriptor {
+        &self.query
+    }
+
+    /// パラメータ名（`$` なし、出現順）
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    /// パラメータを束縛して実行
+    pub fn execute(
+        &self,
+        world: &World,
+        params: &HashMap<String, FilterValue>,
+    ) -> Result<QueryResult, QueryValidationError> {
+        let (entities, total_count) = self.run(world, params)?;
+        let rows = entities
+            .into_iter()
+            .map(|entity| {
+                let mut row = QueryResultRow::new(entity.to_u32());
+                if self.select.is_empty() {
+                    row.set_field("id", serde_json::json!(entity.to_u32()));
+                }
+                for (field, accessor) in &self.select {
+                    if let Some(value) = world.read_field(entity, accessor) {
+                        row.set_field(field.clone(), value);
+                    }
+                }
+                row
+            })
+            .collect();
+        Ok(QueryResult { rows, total_count })
+    }
+
+    /// パラメータを束縛して実行し、列指向の結果を返す
+    pub fn execute_columnar(
+        &self,
+        world: &World,
+        params: &HashMap<String, FilterValue>,
+    ) -> Result<ColumnarResult, QueryValidationError> {
+        let (entities, total_count) = self.run(world, params)?;
+        let select = self
+            .select
+            .iter()
+            .map(|(field, accessor)| (field.as_str(), Some(accessor)));
+        ColumnarResult::build(world, &entities, select, total_count)
+    }
+
+    /// パラメータを束縛し、フィルターごとの比較値を返す
+    fn bind(
+        &self,
+        params: &HashMap<String, FilterValue>,
+    ) -> Result<Vec<FilterValue>, QueryValidationError> {
+        let mut errors = Vec::new();
+        let mut values = Vec::with_capacity(self.filters.len());
+        for filter in &self.filters {
+            match &filter.operand {
+                Operand::Value(value) => values.push(value.clone()),
+                Operand::Param(name) => match params.get(name) {
+                    Some(value) => {
+                        errors.extend(check_comparison(
+                            &filter.field,
+                            filter.field_type,
+                            filter.op,
+                            value,
+                        ));
+                        values.push(value.clone());
+                    }
+                    None => {
+                        let error = QueryError::MissingParameter { name: name.clone() };
+                        if !errors.contains(&error) {
+                            errors.push(error);
+                        }
+                    }
+                },
+            }
+        }
+        if errors.is_empty() {
+            Ok(values)
+        } else {
+            Err(QueryValidationError { errors })
+        }
+    }
+
+    /// 条件に一致するEntityを並び替え・件数制限して返す（limit適用前の件数付き）
+    fn run(
+        &self,
+        world: &World,
+        params: &HashMap<String, FilterValue>,
+    ) -> Result<(Vec<EntityId>, usize), QueryValidationError> {
+        let values = self.bind(params)?;
+
+        // インデックスの選択には束縛後の条件を使う
+        let bound = QueryDescriptor {
+            with_components: self.query.with_components.clone(),
+            filters: self
+                .filters
+                .iter()
+                .zip(&values)
+                .map(|(f, v)| FilterExpr::new(f.field.clone(), f.op, v.clone()))
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut candidates = world.candidate_entities(&bound);
+        candidates.retain(|&entity| {
+            self.with
+                .iter()
+                .all(|t| t.is_some_and(|t| world.has_component_type(entity, t)))
+                && !self
+                    .without
+                    .iter()
+                    .any(|t| t.is_some_and(|t| world.has_component_type(entity, t)))
+                && self.filters.iter().zip(&values).all(|(filter, value)| {
+                    world
+                        .read_field(entity, &filter.accessor)
+                        .is_some_and(|v| filter.op.compare_values(&FilterValue::from_json(v), value))
+                })
+                && self
+                    .query
+                    .spatial
+                    .iter()
+                    .all(|s| world.evaluate_spatial(entity, s))
+                && self.query.relations.iter().all(|r| r.matches(world, entity))
+        });
+
+        let keyed: Vec<order::Keyed> = match &self.order_by {
+            Some((accessor, _)) => candidates
+                .into_iter()
+                .map(|entity| (entity, world.read_field(entity, accessor)))
+                .collect(),
+            None => candidates.into_iter().map(|entity| (entity, None)).collect(),
+        };
+        let direction = self.order_by.as_ref().map(|(_, direction)| *direction);
+        let (candidates, total_count) = order::order_and_limit(keyed, direction, self.query.limit);
+
+        Ok((candidates, total_count))
+    }
+}
+
+/// `$name` 形式のパラメータ名を取り出す
+fn param_name(value: &FilterValue) -> Option<&str> {
+    let name = value.as_str()?.strip_prefix('$')?;
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Name, Transform};
+    use crate::ecs::index::IndexKind;
+    use crate::ecs::query::OrderBy;
+
+    fn world() -> World {
+        let mut world = World::new();
+        for (name, y) in [("a", 1.0), ("b", 5.0), ("c", 3.0), ("d", -2.0)] {
+            let e = world.spawn();
+            world.insert(e, Name::new(name));
+            world.insert(e, Transform::from_position(Vec3::new(0.0, y, 0.0)));
+        }
+        world
+    }
+
+    fn query() -> QueryDescriptor {
+        QueryDescriptor::new()
+            .select(["name", "position.y"])
+            .with(["Transform"])
+            .filter(FilterExpr::gt("position.y", FilterValue::String("$minY".into())))
+            .filter(FilterExpr::ne("name", FilterValue::String("$exclude".into())))
+            .order_by(OrderBy::desc("position.y"))
+    }
+
+    fn params(min_y: f64, exclude: &str) -> HashMap<String, FilterValue> {
+        HashMap::from([
+            ("minY".to_string(), FilterValue::Number(min_y)),
+            ("exclude".to_string(), FilterValue::String(exclude.into())),
+        ])
+    }
+
+    #[test]
+    fn test_prepared_matches_execute_query() {
+        let mut world = world();
+        let prepared = PreparedQuery::prepare(&world, query()).unwrap();
+        assert_eq!(prepared.params(), ["minY", "exclude"]);
+
+        for (min_y, exclude) in [(0.0, "b"), (2.0, "x"), (10.0, "a")] {
+            let bound = QueryDescriptor::new()
+                .select(["name", "position.y"])
+                .with(["Transform"])
+                .filter(FilterExpr::gt("position.y", FilterValue::Number(min_y)))
+                .filter(FilterExpr::ne("name", FilterValue::String(exclude.into())))
+                .order_by(OrderBy::desc("position.y"));
+            let expected = world.execute_query(&bound);
+            assert_eq!(prepared.execute(&world, &params(min_y, exclude)), Ok(expected.clone()));
+
+            // インデックスがあっても同じ結果
+            world.create_index("position.y", IndexKind::BTree);
+            assert_eq!(prepared.execute(&world, &params(min_y, exclude)), Ok(expected));
+            world.drop_index("position.y");
+        }
+
+        // 列指向の結果も解決済みのアクセサから同じ値を読む
+        let columnar = prepared.execute_columnar(&world, &params(0.0, "b")).unwrap();
+        assert_eq!(columnar.len(), 2);
+        let bound = QueryDescriptor::new()
+            .select(["name", "position.y"])
+            .with(["Transform"])
+            .filter(FilterExpr::gt("position.y", FilterValue::Number(0.0)))
+            .filter(FilterExpr::ne("name", FilterValue::String("b".into())))
+            .order_by(OrderBy::desc("position.y"));
+        assert_eq!(Ok(columnar), world.execute_query_columnar(&bound));
+    }
+
+    #[test]
+    fn test_prepare_and_bind_errors() {
+        let world = world();
+        let invalid = query().filter(FilterExpr::lt("positon.y", FilterValue::String("$max".into())));
+        let errors = PreparedQuery::prepare(&world, invalid).unwrap_err().errors;
+        assert_eq!(
+            errors,
+            vec![QueryError::UnknownField {
+                field: "positon.y".into(),
+                clause: QueryClause::Filter
+            }]
+        );
+
+        let prepared = PreparedQuery::prepare(&world, query()).unwrap();
+        let errors = prepared
+            .execute(
+                &world,
+                &HashMap::from([("minY".to_string(), FilterValue::String("1".into()))]),
+            )
+            .unwrap_err()
+            .errors;
+        assert_eq!(
+            errors,
+            vec![
+                QueryError::TypeMismatch {
+                    field: "position.y".into(),
+                    expected: ValueType::Number,
+                    found: ValueType::String
+                },
+                QueryError::MissingParameter {
+                    name: "exclude".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_param_name() {
+        assert_eq!(param_name(&FilterValue::String("$minY".into())), Some("minY"));
+        assert_eq!(param_name(&FilterValue::String("$1".into())), None);
+        assert_eq!(param_name(&FilterValue::String("$".into())), None);
+        assert_eq!(param_name(&FilterValue::String("price $5".into())), None);
+        assert_eq!(param_name(&FilterValue::Number(1.0)), None);
+    }
+}
@@ -0,0 +1,367 @@
+//! クエリのプロファイル
+//!
+//! クエリの各段階（候補取得 → with → without → filters → spatial → relations
+//! → order_by → limit → select）について、段階後の候補数・所要時間・
+//! 取り出したフィールドを報告する。
+//!
+//! - `explain`: 実行せずに段階の一覧と使用するインデックスを返す（候補数は推定値のみ）
+//! - `profile`: `World::query_entities` に段階ごとのフックを渡して実行し、候補数と時間を計測する
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::ecs::index::{AccessPath, QueryPlan};
+use crate::ecs::world::World;
+
+use super::{QueryDescriptor, SortDirection};
+
+/// 段階の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileStage {
+    /// 候補Entityの取得（全件走査 or インデックス）
+    Scan,
+    With,
+    Without,
+    Filter,
+    Spatial,
+    Relation,
+    OrderBy,
+    Limit,
+    /// 結果行の構築
+    Select,
+}
+
+impl fmt::Display for ProfileStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Scan => "scan",
+            Self::With => "with",
+            Self::Without => "without",
+            Self::Filter => "filter",
+            Self::Spatial => "spatial",
+            Self::Relation => "relation",
+            Self::OrderBy => "order_by",
+            Self::Limit => "limit",
+            Self::Select => "select",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 1段階の報告
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StageReport {
+    pub stage: ProfileStage,
+    /// 段階の内容 (e.g., "Transform", "position.y > 0", "name DESC")
+    pub detail: String,
+    /// 段階後の候補数（explain では推定できない段階はNone）
+    pub rows: Option<usize>,
+    /// 所要時間（ミリ秒、profile のみ）
+    pub time_ms: Option<f64>,
+    /// この段階で取り出すフィールド
+    pub fields: Vec<String>,
+    /// フィールドの取り出し回数（profile のみ）
+    pub extracted: Option<usize>,
+}
+
+/// クエリのプロファイル結果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryProfile {
+    /// 候補の取得方法
+    pub plan: QueryPlan,
+    /// 使用するインデックス名 (e.g., ["btree(position.y)"])
+    pub indexes_used: Vec<String>,
+    pub stages: Vec<StageReport>,
+    /// 全体の所要時間（ミリ秒、profile のみ）
+    pub total_time_ms: Option<f64>,
+    /// limit適用前の件数（profile のみ）
+    pub total_count: Option<usize>,
+}
+
+impl QueryProfile {
+    /// 実行せずに段階の一覧を作成
+    pub fn explain(world: &World, query: &QueryDescriptor) -> Self {
+        let plan = world.explain_query(query);
+        let mut stages = vec![StageReport::new(
+            ProfileStage::Scan,
+            access_detail(&plan.access),
+            Vec::new(),
+        )];
+        stages[0].rows = Some(plan.estimated_candidates);
+
+        for_each_stage(query, |stage, detail, fields| {
+            stages.push(StageReport::new(stage, detail, fields));
+        });
+        if let Some(limit) = query.limit {
+            let limit_stage = stages.len() - 2;
+            stages[limit_stage].rows = Some(limit.min(plan.estimated_candidates));
+        }
+
+        Self {
+            indexes_used: indexes_used(&plan.access),
+            plan,
+            stages,
+            total_time_ms: None,
+            total_count: None,
+        }
+    }
+
+    /// 実行して段階ごとの候補数・時間を計測
+    ///
+    /// `now` は現在時刻（ミリ秒）を返す関数（wasm では `performance.now()` を使う）
+    pub fn profile(world: &World, query: &QueryDescriptor, now: impl FnMut() -> f64) -> Self {
+        let mut profile = Self::explain(world, query);
+        let mut recorder = Recorder {
+            stages: &mut profile.stages,
+            next: 0,
+            last: 0.0,
+            now,
+        };
+        let start = (recorder.now)();
+        recorder.last = start;
+
+        // 候補取得〜limit は World::query_entities そのものを計測する
+        let (entities, total_count) = world.query_entities_with(query, &mut recorder);
+        for &entity in &entities {
+            std::hint::black_box(world.build_row(entity, &query.select));
+        }
+        // build_row は選択したフィールドを1行につき1回ずつ取り出す
+        recorder.stage_done(ProfileStage::Select, entities.len(), entities.len() * query.select.len());
+
+        profile.total_time_ms = Some(recorder.last - start);
+        profile.total_count = Some(total_count);
+        profile
+    }
+
+    /// 全段階のフィールド取り出し回数の合計（profile のみ）
+    pub fn total_extracted(&self) -> Option<usize> {
+        self.stages.iter().map(|s| s.extracted).sum()
+    }
+}
+
+/// `World::query_entities_with` から段階の終了を受け取るフック
+pub(crate) trait StageHook {
+    /// 段階の終了（段階後の候補数と、段階内でのフィールドの取り出し回数）
+    fn stage_done(&mut self, stage: ProfileStage, rows: usize, extracted: usize);
+}
+
+/// 計測しない
+impl StageHook for () {
+    fn stage_done(&mut self, _stage: ProfileStage, _rows: usize, _extracted: usize) {}
+}
+
+/// 段階の終了ごとに報告を埋める
+struct Recorder<'a, F> {
+    stages: &'a mut [StageReport],
+    next: usize,
+    /// 前の段階の終了時刻
+    last: f64,
+    now: F,
+}
+
+impl<F: FnMut() -> f64> StageHook for Recorder<'_, F> {
+    fn stage_done(&mut self, stage: ProfileStage, rows: usize, extracted: usize) {
+        let end = (self.now)();
+        let report = &mut self.stages[self.next];
+        debug_assert_eq!(report.stage, stage);
+        report.rows = Some(rows);
+        report.time_ms = Some(end - self.last);
+        report.extracted = Some(extracted);
+        self.next += 1;
+        self.last = end;
+    }
+}
+
+impl StageReport {
+    fn new(stage: ProfileStage, detail: String, fields: Vec<String>) -> Self {
+        Self {
+            stage,
+            detail,
+            rows: None,
+            time_ms: None,
+            fields,
+            extracted: None,
+        }
+    }
+}
+
+/// 表形式で整形
+impl fmt::Display for QueryProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.plan)?;
+        for stage in &self.stages {
+            write!(f, "  {:<9} {:<32}", stage.stage.to_string(), stage.detail)?;
+            match stage.rows {
+                Some(rows) => write!(f, " rows={:<8}", rows)?,
+                None => write!(f, " rows={:<8}", "?")?,
+            }
+            if let Some(time) = stage.time_ms {
+                write!(f, " {:.3}ms", time)?;
+            }
+            if !stage.fields.is_empty() {
+                write!(f, " fields=[{}]", stage.fields.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        if let Some(total) = self.total_time_ms {
+            write!(f, "total {:.3}ms", total)?;
+        }
+        Ok(())
+    }
+}
+
+/// 候補取得以降の段階を実行順に列挙
+fn for_each_stage(query: &QueryDescriptor, mut visit: impl FnMut(ProfileStage, String, Vec<String>)) {
+    for component in &query.with_components {
+        visit(ProfileStage::With, component.clone(), Vec::new());
+    }
+    for component in &query.without_components {
+        visit(ProfileStage::Without, component.clone(), Vec::new());
+    }
+    for filter in &query.filters {
+        visit(ProfileStage::Filter, filter.to_string(), vec![filter.field.clone()]);
+    }
+    for spatial in &query.spatial {
+        visit(ProfileStage::Spatial, spatial_detail(spatial), Vec::new());
+    }
+    for relation in &query.relations {
+        visit(ProfileStage::Relation, relation_detail(relation), Vec::new());
+    }
+    if let Some(order) = &query.order_by {
+        let direction = match order.direction {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        };
+        visit(
+            ProfileStage::OrderBy,
+            format!("{} {}", order.field, direction),
+            vec![order.field.clone()],
+        );
+    }
+    if let Some(limit) = query.limit {
+        visit(ProfileStage::Limit, limit.to_string(), Vec::new());
+    }
+    let select = if query.select.is_empty() {
+        vec!["id".to_string()]
+    } else {
+        query.select.clone()
+    };
+    visit(ProfileStage::Select, select.join(", "), select);
+}
+
+fn spatial_detail(spatial: &super::SpatialFilter) -> String {
+    let args: Vec<String> = spatial.arguments().iter().map(|a| a.to_string()).collect();
+    format!("{}({})", spatial.function_name(), args.join(", "))
+}
+
+fn relation_detail(relation: &super::RelationFilter) -> String {
+    let args: Vec<String> = relation.arguments().iter().map(|a| a.to_string()).collect();
+    format!("{}({})", relation.function_name(), args.join(", "))
+}
+
+/// 取得方法の説明
+fn access_detail(access: &AccessPath) -> String {
+    match access {
+        AccessPath::FullScan => "full scan".to_string(),
+        AccessPath::FieldIndex { kind, field, .. } => format!("index {}({})", kind, field),
+        AccessPath::ComponentBitset { component } => format!("bitset component({})", component),
+    }
+}
+
+/// 使用するインデックス名（World::index_names と同じ形式）
+fn indexes_used(access: &AccessPath) -> Vec<String> {
+    match access {
+        AccessPath::FullScan => Vec::new(),
+        AccessPath::FieldIndex { kind, field, .. } => vec![format!("{}({})", kind, field)],
+        AccessPath::ComponentBitset { component } => vec![format!("component({})", component)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Name, Transform};
+    use crate::ecs::index::IndexKind;
+    use crate::ecs::query::{FilterExpr, FilterValue, OrderBy};
+    use glam::Vec3;
+
+    fn world() -> World {
+        let mut world = World::new();
+        for i in 0..20 {
+            let e = world.spawn();
+            world.insert(e, Name::new(format!("e{:02}", i)));
+            if i % 2 == 0 {
+                world.insert(e, Transform::from_position(Vec3::new(0.0, i as f32, 0.0)));
+            }
+        }
+        world
+    }
+
+    fn query() -> QueryDescriptor {
+        QueryDescriptor::new()
+            .select(["name", "position.y"])
+            .with(["Transform"])
+            .filter(FilterExpr::gt("position.y", FilterValue::Number(5.0)))
+            .order_by(OrderBy::desc("position.y"))
+            .limit(3)
+    }
+
+    fn stages(profile: &QueryProfile) -> Vec<(ProfileStage, Option<usize>)> {
+        profile.stages.iter().map(|s| (s.stage, s.rows)).collect()
+    }
+
+    #[test]
+    fn test_explain() {
+        let mut world = world();
+        world.create_index("position.y", IndexKind::BTree);
+        let explain = world.explain(&query());
+
+        assert_eq!(explain.indexes_used, vec!["btree(position.y)"]);
+        assert_eq!(
+            stages(&explain),
+            vec![
+                (ProfileStage::Scan, Some(7)),
+                (ProfileStage::With, None),
+                (ProfileStage::Filter, None),
+                (ProfileStage::OrderBy, None),
+                (ProfileStage::Limit, Some(3)),
+                (ProfileStage::Select, None),
+            ]
+        );
+        assert_eq!(explain.stages[2].detail, "position.y > 5");
+        assert_eq!(explain.stages[5].fields, vec!["name", "position.y"]);
+        assert_eq!(explain.total_time_ms, None);
+    }
+
+    #[test]
+    fn test_profile_counts() {
+        let world = world();
+        let mut clock = 0.0;
+        let profile = world.profile(&query(), || {
+            clock += 1.0;
+            clock
+        });
+
+        assert!(profile.indexes_used.is_empty());
+        assert_eq!(
+            stages(&profile),
+            vec![
+                (ProfileStage::Scan, Some(20)),
+                (ProfileStage::With, Some(10)),
+                (ProfileStage::Filter, Some(7)),
+                (ProfileStage::OrderBy, Some(7)),
+                (ProfileStage::Limit, Some(3)),
+                (ProfileStage::Select, Some(3)),
+            ]
+        );
+        assert_eq!(profile.total_count, Some(world.execute_query(&query()).total_count));
+        assert!(profile.stages.iter().all(|s| s.time_ms == Some(1.0)));
+        assert_eq!(profile.total_time_ms, Some(6.0));
+        // filter: 10回、order_by: 候補ごとに1回で7回、select: 3行 x 2フィールド
+        assert_eq!(profile.stages[3].extracted, Some(7));
+        assert_eq!(profile.total_extracted(), Some(23));
+        assert!(profile.to_string().contains("rows=7"));
+    }
+}
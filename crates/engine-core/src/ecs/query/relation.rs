@@ -0,0 +1,129 @@
+//! 親子関係フィルター
+//!
+//! `Parent` / `Children` による階層でEntityを絞り込む。
+//! Entityは JS と同じ u32 形式のIDで指定する。
+//!
+//! 階層に関するフィールドは通常のフィールドとして select / filters / order_by で使える:
+//! - `parent`: 親のID（ルートは null）
+//! - `children_count`: 直接の子の数
+//! - `depth`: ルートからの深さ（ルートは0）
+//! - `parent.<field>`: 親Entityのフィールド (e.g., `parent.name`)
+
+use serde::{Deserialize, Serialize};
+
+use crate::ecs::entity::EntityId;
+use crate::ecs::world::World;
+
+/// 親子関係フィルター
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationFilter {
+    /// 指定したEntityの直接の子
+    ChildOf { parent: u32 },
+
+    /// 指定したEntityの子孫（自身は含まない）
+    DescendantOf { ancestor: u32 },
+
+    /// 親を持たないEntity
+    Root,
+}
+
+impl RelationFilter {
+    /// 直接の子フィルター
+    pub fn child_of(parent: EntityId) -> Self {
+        Self::ChildOf {
+            parent: parent.to_u32(),
+        }
+    }
+
+    /// 子孫フィルター
+    pub fn descendant_of(ancestor: EntityId) -> Self {
+        Self::DescendantOf {
+            ancestor: ancestor.to_u32(),
+        }
+    }
+
+    /// テキストクエリでの関数名
+    pub fn function_name(&self) -> &'static str {
+        match self {
+            Self::ChildOf { .. } => "child_of",
+            Self::DescendantOf { .. } => "descendant_of",
+            Self::Root => "is_root",
+        }
+    }
+
+    /// テキストクエリでの引数列
+    pub fn arguments(&self) -> Vec<f64> {
+        match self {
+            Self::ChildOf { parent } => vec![*parent as f64],
+            Self::DescendantOf { ancestor } => vec![*ancestor as f64],
+            Self::Root => Vec::new(),
+        }
+    }
+
+    /// 関数名と引数列から作成（親子関係の関数でない場合はNone）
+    pub fn from_function(name: &str, args: &[f64]) -> Option<Result<Self, String>> {
+        let id = || match args {
+            [id] if (0.0..=u32::MAX as f64).contains(id) && id.fract() == 0.0 => Ok(*id as u32),
+            _ => Err(format!("{} expects 1 argument (entity id)", name)),
+        };
+        Some(match name {
+            "child_of" => id().map(|parent| Self::ChildOf { parent }),
+            "descendant_of" => id().map(|ancestor| Self::DescendantOf { ancestor }),
+            "is_root" if args.is_empty() => Ok(Self::Root),
+            "is_root" => Err("is_root expects no arguments".into()),
+            _ => return None,
+        })
+    }
+
+    /// Entityがフィルター条件を満たすか
+    pub fn matches(&self, world: &World, entity: EntityId) -> bool {
+        match self {
+            Self::ChildOf { parent } => world
+                .parent(entity)
+                .is_some_and(|p| p.to_u32() == *parent),
+            Self::DescendantOf { ancestor } => world
+                .ancestors(entity)
+                .any(|a| a.to_u32() == *ancestor),
+            Self::Root => world.is_alive(entity) && world.parent(entity).is_none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        let filters = vec![
+            RelationFilter::ChildOf { parent: 12 },
+            RelationFilter::DescendantOf { ancestor: 3 },
+            RelationFilter::Root,
+        ];
+        let json = serde_json::to_string(&filters).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"child_of":{"parent":12}},{"descendant_of":{"ancestor":3}},"root"]"#
+        );
+        let parsed: Vec<RelationFilter> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, filters);
+    }
+
+    #[test]
+    fn test_from_function() {
+        assert_eq!(
+            RelationFilter::from_function("child_of", &[12.0]),
+            Some(Ok(RelationFilter::ChildOf { parent: 12 }))
+        );
+        assert_eq!(
+            RelationFilter::from_function("is_root", &[]),
+            Some(Ok(RelationFilter::Root))
+        );
+        assert!(matches!(
+            RelationFilter::from_function("descendant_of", &[1.5]),
+            Some(Err(_))
+        ));
+        assert_eq!(RelationFilter::from_function("within_sphere", &[0.0]), None);
+    }
+}
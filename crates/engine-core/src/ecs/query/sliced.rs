@@ -0,0 +1,349 @@
+//! 分割実行クエリ
+//!
+//! 大規模なWorldでクエリを一度に実行するとメインスレッドを長時間ブロックするため、
+//! 候補Entityを一定数ずつ処理し、複数フレームに分けて実行する。
+//!
+//! 実行は Filtering → Sorting → Building の順に進む。
+//! 途中でWorldが変更された場合、各Entityは評価した時点の状態で判定される
+//! （実行開始後に生成されたEntityは、削除済みのスロットを再利用したものも含めて対象外。
+//! 削除されたEntityは結果から除外される）。
+
+use serde::Serialize;
+
+use crate::ecs::entity::EntityId;
+use crate::ecs::world::World;
+
+use super::order;
+use super::{QueryDescriptor, QueryResult, QueryResultRow};
+
+/// 実行段階
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryStage {
+    /// 条件の評価
+    Filtering,
+    /// 並び替え（予算によらず1回の `step` で全件を並び替える）
+    Sorting,
+    /// 結果行の構築
+    Building,
+    /// 完了
+    Done,
+}
+
+/// 進捗
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct QueryProgress {
+    pub stage: QueryStage,
+    /// 現在の段階で処理済みの件数
+    pub processed: usize,
+    /// 現在の段階の総件数
+    pub total: usize,
+}
+
+impl QueryProgress {
+    /// 全体の進捗率（0.0〜1.0、評価が大半を占めるため Filtering を 0.9 までとする）
+    pub fn fraction(&self) -> f32 {
+        let stage_fraction = if self.total == 0 {
+            1.0
+        } else {
+            self.processed as f32 / self.total as f32
+        };
+        match self.stage {
+            QueryStage::Filtering => stage_fraction * 0.9,
+            QueryStage::Sorting => 0.9,
+            QueryStage::Building => 0.95 + stage_fraction * 0.05,
+            QueryStage::Done => 1.0,
+        }
+    }
+}
+
+/// 候補Entityの列挙方法
+enum Candidates {
+    /// インデックスから取得済み
+    Indexed(Vec<EntityId>),
+    /// 全Entityのスロットを順に走査（開始時のスロット数まで）
+    Scan {
+        slots: u32,
+        /// 開始時の `World::spawn_count`（これ以降に生成されたEntityは除く）
+        spawn_count: u64,
+    },
+}
+
+/// 分割実行クエリ
+pub struct SlicedQuery {
+    query: QueryDescriptor,
+    candidates: Candidates,
+    stage: QueryStage,
+    /// 現在の段階で処理済みの件数
+    cursor: usize,
+    /// 条件を満たしたEntityとソートキー
+    matched: Vec<order::Keyed>,
+    rows: Vec<QueryResultRow>,
+    total_count: usize,
+}
+
+impl SlicedQuery {
+    /// 実行を開始（候補の取得のみ行い、評価は `step` で進める）
+    pub fn new(world: &World, query: QueryDescriptor) -> Self {
+        let candidates = match world.indexed_candidates(&query) {
+            Some(entities) => Candidates::Indexed(entities),
+            None => Candidates::Scan {
+                slots: world.entity_slots(),
+                spawn_count: world.spawn_count(),
+            },
+        };
+        Self {
+            query,
+            candidates,
+            stage: QueryStage::Filtering,
+            cursor: 0,
+            matched: Vec::new(),
+            rows: Vec::new(),
+            total_count: 0,
+        }
+    }
+
+    /// クエリ定義
+    pub fn query(&self) -> &QueryDescriptor {
+        &self.query
+    }
+
+    /// 最大 `budget` 件を処理して進める
+    ///
+    /// 並び替えは分割せず1件分として数える（キーは評価時に取り出し済みで、比較のみ行う）
+    pub fn step(&mut self, world: &World, budget: usize) -> QueryProgress {
+        let mut remaining = budget.max(1);
+        while remaining > 0 && self.stage != QueryStage::Done {
+            match self.stage {
+                QueryStage::Filtering => remaining -= self.filter(world, remaining),
+                QueryStage::Sorting => {
+                    self.sort(world);
+                    remaining -= 1;
+                }
+                QueryStage::Building => remaining -= self.build(world, remaining),
+                QueryStage::Done => {}
+            }
+        }
+        self.progress()
+    }
+
+    /// 完了するまで実行して結果を返す
+    pub fn run_to_end(mut self, world: &World) -> QueryResult {
+        while !self.is_done() {
+            self.step(world, usize::MAX);
+        }
+        self.into_result().unwrap_or_default()
+    }
+
+    /// 現在の進捗
+    pub fn progress(&self) -> QueryProgress {
+        let total = match self.stage {
+            QueryStage::Filtering => self.candidate_len(),
+            QueryStage::Sorting => 1,
+            QueryStage::Building => self.build_len(),
+            QueryStage::Done => 0,
+        };
+        QueryProgress {
+            stage: self.stage,
+            // 構築中は削除済みEntityを飛ばした分だけ cursor が先に進む
+            processed: self.cursor.min(total),
+            total,
+        }
+    }
+
+    /// 完了したか
+    pub fn is_done(&self) -> bool {
+        self.stage == QueryStage::Done
+    }
+
+    /// 結果を取得（未完了の場合None）
+    pub fn into_result(self) -> Option<QueryResult> {
+        let done = self.is_done();
+        done.then_some(QueryResult {
+            rows: self.rows,
+            total_count: self.total_count,
+        })
+    }
+
+    fn candidate_len(&self) -> usize {
+        match &self.candidates {
+            Candidates::Indexed(entities) => entities.len(),
+            Candidates::Scan { slots, .. } => *slots as usize,
+        }
+    }
+
+    fn build_len(&self) -> usize {
+        self.query
+            .limit
+            .map_or(self.matched.len(), |limit| limit.min(self.matched.len()))
+    }
+
+    /// 段階を進める
+    fn advance(&mut self, stage: QueryStage) {
+        self.stage = stage;
+        self.cursor = 0;
+    }
+
+    /// 候補を評価し、処理した件数を返す
+    fn filter(&mut self, world: &World, budget: usize) -> usize {
+        let end = self.candidate_len().min(self.cursor.saturating_add(budget));
+        for i in self.cursor..end {
+            let entity = match &self.candidates {
+                Candidates::Indexed(entities) => Some(entities[i]),
+                Candidates::Scan { spawn_count, .. } => {
+                    world.live_entity_spawned_before(i as u32, *spawn_count)
+                }
+            };
+            let Some(entity) = entity else {
+                continue;
+            };
+            if world.matches_query(entity, &self.query) {
+                let sort_key = self
+                    .query
+                    .order_by
+                    .as_ref()
+                    .and_then(|order| world.extract_field(entity, &order.field));
+                self.matched.push((entity, sort_key));
+            }
+        }
+        let processed = end - self.cursor;
+        self.cursor = end;
+        if self.cursor >= self.candidate_len() {
+            self.candidates = Candidates::Indexed(Vec::new());
+            self.advance(QueryStage::Sorting);
+        }
+        processed
+    }
+
+    /// 削除済みEntityを除いて並び替え
+    fn sort(&mut self, world: &World) {
+        self.matched.retain(|(entity, _)| world.is_alive(*entity));
+        self.total_count = self.matched.len();
+
+        if let Some(order) = &self.query.order_by {
+            order::sort_keyed(&mut self.matched, order.direction);
+        }
+        self.advance(QueryStage::Building);
+    }
+
+    /// 結果行を構築し、処理した件数を返す
+    ///
+    /// 並び替え後に削除されたEntityは飛ばし、件数からも除く
+    fn build(&mut self, world: &World, budget: usize) -> usize {
+        let limit = self.query.limit.unwrap_or(usize::MAX);
+        let end = self.matched.len().min(self.cursor.saturating_add(budget));
+        let start = self.cursor;
+        while self.cursor < end && self.rows.len() < limit {
+            let entity = self.matched[self.cursor].0;
+            self.cursor += 1;
+            if world.is_alive(entity) {
+                self.rows.push(world.build_row(entity, &self.query.select));
+            } else {
+                self.total_count -= 1;
+            }
+        }
+        let processed = self.cursor - start;
+        if self.rows.len() >= limit || self.cursor >= self.matched.len() {
+            // limit外の残りも削除済みなら件数から除く
+            let removed = self.matched[self.cursor..]
+                .iter()
+                .filter(|(entity, _)| !world.is_alive(*entity))
+                .count();
+            self.total_count -= removed;
+            self.matched = Vec::new();
+            self.advance(QueryStage::Done);
+        }
+        processed.max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Name, Transform};
+    use crate::ecs::index::IndexKind;
+    use crate::ecs::query::{FilterExpr, FilterValue, OrderBy};
+    use glam::Vec3;
+
+    fn world(count: usize) -> World {
+        let mut world = World::new();
+        for i in 0..count {
+            let e = world.spawn();
+            world.insert(e, Name::new(format!("e{}", i % 7)));
+            world.insert(e, Transform::from_position(Vec3::new(0.0, (i % 13) as f32, 0.0)));
+        }
+        world
+    }
+
+    fn query() -> QueryDescriptor {
+        QueryDescriptor::new()
+            .select(["name", "position.y"])
+            .filter(FilterExpr::gt("position.y", FilterValue::Number(4.0)))
+            .order_by(OrderBy::desc("name"))
+            .limit(20)
+    }
+
+    #[test]
+    fn test_matches_execute_query() {
+        let mut world = world(500);
+        let expected = world.execute_query(&query());
+
+        let mut sliced = SlicedQuery::new(&world, query());
+        let mut steps = 0;
+        let mut last_fraction = 0.0;
+        while !sliced.is_done() {
+            let progress = sliced.step(&world, 64);
+            assert!(progress.fraction() >= last_fraction);
+            last_fraction = progress.fraction();
+            steps += 1;
+        }
+        assert!(steps > 1);
+        assert_eq!(sliced.into_result(), Some(expected));
+
+        // インデックス使用時も同じ結果
+        world.create_index("position.y", IndexKind::BTree);
+        let expected = world.execute_query(&query());
+        assert_eq!(SlicedQuery::new(&world, query()).run_to_end(&world), expected);
+    }
+
+    #[test]
+    fn test_world_changes_between_steps() {
+        let mut world = world(100);
+        let mut sliced = SlicedQuery::new(&world, query());
+        sliced.step(&world, 10);
+
+        // 評価済みのEntityを削除、開始後に生成したEntityは対象外
+        let first = world.iter_entities().next().unwrap();
+        world.get_mut::<Transform>(first).unwrap().position.y = 10.0;
+        sliced.step(&world, 0);
+        world.despawn(first);
+        let late = world.spawn();
+        world.insert(late, Transform::from_position(Vec3::new(0.0, 100.0, 0.0)));
+
+        // 未評価のスロットを削除して再利用したEntityも対象外
+        let last = world.iter_entities().filter(|&e| e != late).last().unwrap();
+        world.despawn(last);
+        let reused = world.spawn();
+        assert_eq!(reused.index(), last.index());
+        world.insert(reused, Transform::from_position(Vec3::new(0.0, 100.0, 0.0)));
+
+        let result = sliced.run_to_end(&world);
+        assert!(result.iter().all(|row| ![first, late, reused].map(|e| e.to_u32()).contains(&row.id)));
+        let mut expected = query();
+        expected.filters.push(FilterExpr::lt("position.y", FilterValue::Number(100.0)));
+        assert_eq!(result.total_count, world.execute_query(&expected).total_count);
+
+        // 結果行の構築中に削除されたEntityも除外される（limit内・limit外とも）
+        let mut world = self::world(100);
+        let mut sliced = SlicedQuery::new(&world, query());
+        while sliced.progress().stage != QueryStage::Building {
+            sliced.step(&world, 1);
+        }
+        sliced.step(&world, 5);
+        assert_eq!(sliced.rows.len(), 5);
+        let (in_window, outside) = (sliced.matched[10].0, sliced.matched[30].0);
+        world.despawn(in_window);
+        world.despawn(outside);
+        assert_eq!(sliced.run_to_end(&world), world.execute_query(&query()));
+    }
+}
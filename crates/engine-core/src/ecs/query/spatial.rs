@@ -0,0 +1,324 @@
+//! 空間フィルター
+//!
+//! 球・AABB・視錐台・レイによるEntityの絞り込みと、
+//! `distance_to(x, y, z)` 計算フィールドを提供する。
+//!
+//! 判定は描画・Pickingと同じく、祖先のTransformを合成したワールド行列で行う。
+//!
+//! - `within_sphere` / `within_aabb` はEntityのワールド座標（点）で判定
+//! - `in_frustum` / `intersects_ray` はEntityのバウンディングボックスで判定
+//!   （単位キューブをワールド行列で変換したAABB）
+//! - `distance_to(x, y, z)` はワールド座標からの距離
+
+use glam::{Mat4, Vec3, Vec4};
+use serde::{Deserialize, Serialize};
+
+/// 空間フィルター
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpatialFilter {
+    /// 位置が球の内側にある
+    WithinSphere { center: [f32; 3], radius: f32 },
+
+    /// 位置がAABBの内側にある
+    WithinAabb { min: [f32; 3], max: [f32; 3] },
+
+    /// バウンディングボックスが視錐台と交差する（view_projは列優先）
+    InFrustum { view_proj: [[f32; 4]; 4] },
+
+    /// バウンディングボックスがレイと交差する
+    IntersectsRay {
+        origin: [f32; 3],
+        direction: [f32; 3],
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_distance: Option<f32>,
+    },
+}
+
+impl SpatialFilter {
+    /// 球フィルター
+    pub fn within_sphere(center: Vec3, radius: f32) -> Self {
+        Self::WithinSphere {
+            center: center.to_array(),
+            radius,
+        }
+    }
+
+    /// AABBフィルター
+    pub fn within_aabb(min: Vec3, max: Vec3) -> Self {
+        Self::WithinAabb {
+            min: min.to_array(),
+            max: max.to_array(),
+        }
+    }
+
+    /// 視錐台フィルター
+    pub fn in_frustum(view_proj: Mat4) -> Self {
+        Self::InFrustum {
+            view_proj: view_proj.to_cols_array_2d(),
+        }
+    }
+
+    /// レイフィルター
+    pub fn intersects_ray(origin: Vec3, direction: Vec3, max_distance: Option<f32>) -> Self {
+        Self::IntersectsRay {
+            origin: origin.to_array(),
+            direction: direction.to_array(),
+            max_distance,
+        }
+    }
+
+    /// テキストクエリでの関数名
+    pub fn function_name(&self) -> &'static str {
+        match self {
+            Self::WithinSphere { .. } => "within_sphere",
+            Self::WithinAabb { .. } => "within_aabb",
+            Self::InFrustum { .. } => "in_frustum",
+            Self::IntersectsRay { .. } => "intersects_ray",
+        }
+    }
+
+    /// テキストクエリでの引数列
+    pub fn arguments(&self) -> Vec<f32> {
+        match self {
+            Self::WithinSphere { center, radius } => {
+                let mut args = center.to_vec();
+                args.push(*radius);
+                args
+            }
+            Self::WithinAabb { min, max } => min.iter().chain(max.iter()).copied().collect(),
+            Self::InFrustum { view_proj } => view_proj.iter().flatten().copied().collect(),
+            Self::IntersectsRay {
+                origin,
+                direction,
+                max_distance,
+            } => origin
+                .iter()
+                .chain(direction.iter())
+                .chain(max_distance.iter())
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// 関数名と引数列から作成（引数の数が不正な場合はエラーメッセージ）
+    pub fn from_function(name: &str, args: &[f32]) -> Result<Self, String> {
+        let vec3 = |i: usize| [args[i], args[i + 1], args[i + 2]];
+        match (name, args.len()) {
+            ("within_sphere", 4) => Ok(Self::WithinSphere {
+                center: vec3(0),
+                radius: args[3],
+            }),
+            ("within_aabb", 6) => Ok(Self::WithinAabb {
+                min: vec3(0),
+                max: vec3(3),
+            }),
+            ("in_frustum", 16) => Ok(Self::InFrustum {
+                view_proj: Mat4::from_cols_slice(args).to_cols_array_2d(),
+            }),
+            ("intersects_ray", 6 | 7) => Ok(Self::IntersectsRay {
+                origin: vec3(0),
+                direction: vec3(3),
+                max_distance: args.get(6).copied(),
+            }),
+            ("within_sphere", _) => Err("within_sphere expects 4 arguments (x, y, z, radius)".into()),
+            ("within_aabb", _) => Err("within_aabb expects 6 arguments (min xyz, max xyz)".into()),
+            ("in_frustum", _) => Err("in_frustum expects 16 arguments (column-major matrix)".into()),
+            ("intersects_ray", _) => Err(
+                "intersects_ray expects 6 or 7 arguments (origin xyz, direction xyz, [max distance])"
+                    .into(),
+            ),
+            _ => Err(format!("unknown spatial function '{}'", name)),
+        }
+    }
+
+    /// ワールド行列のEntityがフィルター条件を満たすか
+    pub fn matches(&self, world_matrix: &Mat4) -> bool {
+        let position = world_matrix.w_axis.truncate();
+        match self {
+            Self::WithinSphere { center, radius } => {
+                position.distance_squared(Vec3::from_array(*center)) <= radius * radius
+            }
+            Self::WithinAabb { min, max } => {
+                let p = position;
+                p.cmpge(Vec3::from_array(*min)).all() && p.cmple(Vec3::from_array(*max)).all()
+            }
+            Self::InFrustum { view_proj } => {
+                let (min, max) = entity_bounds(world_matrix);
+                frustum_planes(&Mat4::from_cols_array_2d(view_proj))
+                    .iter()
+                    .all(|plane| {
+                        // 法線方向に最も進んだ頂点（positive vertex）が平面の裏なら完全に外側
+                        let normal = plane.truncate();
+                        let p = Vec3::select(normal.cmpge(Vec3::ZERO), max, min);
+                        normal.dot(p) + plane.w >= 0.0
+                    })
+            }
+            Self::IntersectsRay {
+                origin,
+                direction,
+                max_distance,
+            } => {
+                let (min, max) = entity_bounds(world_matrix);
+                match ray_aabb(Vec3::from_array(*origin), Vec3::from_array(*direction), min, max) {
+                    Some(t) => max_distance.is_none_or(|d| t <= d),
+                    None => false,
+                }
+            }
+        }
+    }
+}
+
+/// Entityのバウンディングボックス（単位キューブをワールド行列で変換したAABB）
+fn entity_bounds(world_matrix: &Mat4) -> (Vec3, Vec3) {
+    // 中心と、各軸の半分の長さの絶対値の和で広がりを求める
+    let center = world_matrix.w_axis.truncate();
+    let half = (world_matrix.x_axis.truncate().abs()
+        + world_matrix.y_axis.truncate().abs()
+        + world_matrix.z_axis.truncate().abs())
+        * 0.5;
+    (center - half, center + half)
+}
+
+/// View-Projection行列から視錐台の6平面を抽出（深度範囲 0〜1）
+/// 各平面は (normal, d) で、内側が正
+fn frustum_planes(view_proj: &Mat4) -> [Vec4; 6] {
+    let r0 = view_proj.row(0);
+    let r1 = view_proj.row(1);
+    let r2 = view_proj.row(2);
+    let r3 = view_proj.row(3);
+    [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2]
+}
+
+/// レイとAABBの交差判定（スラブ法）。交差時はレイ原点からの距離を返す
+fn ray_aabb(origin: Vec3, direction: Vec3, min: Vec3, max: Vec3) -> Option<f32> {
+    let direction = direction.try_normalize()?;
+    let inv_dir = direction.recip();
+    let t1 = (min - origin) * inv_dir;
+    let t2 = (max - origin) * inv_dir;
+
+    let tmin = t1.min(t2).max_element();
+    let tmax = t1.max(t2).min_element();
+
+    if tmax >= tmin && tmax >= 0.0 {
+        Some(tmin.max(0.0))
+    } else {
+        None
+    }
+}
+
+// ============================================================================
+// distance_to - 計算フィールド
+// ============================================================================
+
+/// `distance_to(x, y, z)` のフィールド名を作成
+pub fn distance_to_field(point: Vec3) -> String {
+    format!("distance_to({}, {}, {})", point.x, point.y, point.z)
+}
+
+/// `distance_to(x, y, z)` フィールド名から点を取り出す
+pub fn parse_distance_to(field: &str) -> Option<Vec3> {
+    let args = field.strip_prefix("distance_to(")?.strip_suffix(')')?;
+    let coords: Vec<f32> = args
+        .split(',')
+        .map(|s| s.trim().parse::<f32>().ok())
+        .collect::<Option<_>>()?;
+    match coords.as_slice() {
+        [x, y, z] => Some(Vec3::new(*x, *y, *z)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f32, y: f32, z: f32) -> Mat4 {
+        Mat4::from_translation(Vec3::new(x, y, z))
+    }
+
+    #[test]
+    fn test_within_sphere() {
+        let filter = SpatialFilter::within_sphere(Vec3::ZERO, 5.0);
+        assert!(filter.matches(&at(3.0, 4.0, 0.0)));
+        assert!(!filter.matches(&at(3.0, 4.0, 0.1)));
+    }
+
+    #[test]
+    fn test_within_aabb() {
+        let filter = SpatialFilter::within_aabb(Vec3::splat(-1.0), Vec3::new(1.0, 2.0, 1.0));
+        assert!(filter.matches(&at(0.5, 2.0, -1.0)));
+        assert!(!filter.matches(&at(0.5, 2.5, 0.0)));
+    }
+
+    #[test]
+    fn test_in_frustum() {
+        let view = Mat4::look_at_rh(Vec3::new(0.0, 0.0, 10.0), Vec3::ZERO, Vec3::Y);
+        let proj = Mat4::perspective_rh(45.0_f32.to_radians(), 1.0, 0.1, 100.0);
+        let filter = SpatialFilter::in_frustum(proj * view);
+
+        assert!(filter.matches(&at(0.0, 0.0, 0.0)));
+        // カメラの後ろ
+        assert!(!filter.matches(&at(0.0, 0.0, 20.0)));
+        // 視野外（横）
+        assert!(!filter.matches(&at(50.0, 0.0, 0.0)));
+        // far面より遠い
+        assert!(!filter.matches(&at(0.0, 0.0, -200.0)));
+        // 中心は視野外だがバウンディングボックスが掛かる
+        let edge = at(4.6, 0.0, 0.0) * Mat4::from_scale(Vec3::splat(2.0));
+        assert!(filter.matches(&edge));
+    }
+
+    #[test]
+    fn test_intersects_ray() {
+        let filter = SpatialFilter::intersects_ray(Vec3::new(0.0, 0.0, 10.0), -Vec3::Z, None);
+        assert!(filter.matches(&at(0.0, 0.0, 0.0)));
+        assert!(!filter.matches(&at(2.0, 0.0, 0.0)));
+
+        let short = SpatialFilter::intersects_ray(Vec3::new(0.0, 0.0, 10.0), -Vec3::Z, Some(5.0));
+        assert!(!short.matches(&at(0.0, 0.0, 0.0)));
+        assert!(short.matches(&at(0.0, 0.0, 6.0)));
+
+        // 回転したバウンディングボックス（X方向に長い箱をY軸周りに90度回すとZ方向に長い）
+        let long = at(0.0, 0.0, 0.0)
+            * Mat4::from_rotation_y(std::f32::consts::FRAC_PI_2)
+            * Mat4::from_scale(Vec3::new(4.0, 1.0, 1.0));
+        let across = SpatialFilter::intersects_ray(Vec3::new(-10.0, 0.0, 1.5), Vec3::X, None);
+        assert!(across.matches(&long));
+        assert!(!across.matches(&Mat4::from_scale(Vec3::new(4.0, 1.0, 1.0))));
+    }
+
+    #[test]
+    fn test_function_round_trip() {
+        let filters = [
+            SpatialFilter::within_sphere(Vec3::new(1.0, 2.0, 3.0), 4.0),
+            SpatialFilter::within_aabb(Vec3::ZERO, Vec3::ONE),
+            SpatialFilter::in_frustum(Mat4::from_translation(Vec3::X)),
+            SpatialFilter::intersects_ray(Vec3::ZERO, Vec3::Y, Some(3.0)),
+            SpatialFilter::intersects_ray(Vec3::ZERO, Vec3::Y, None),
+        ];
+        for filter in filters {
+            let rebuilt = SpatialFilter::from_function(filter.function_name(), &filter.arguments());
+            assert_eq!(rebuilt, Ok(filter));
+        }
+
+        assert!(SpatialFilter::from_function("within_sphere", &[1.0]).is_err());
+        assert!(SpatialFilter::from_function("within_cone", &[]).is_err());
+    }
+
+    #[test]
+    fn test_serialize() {
+        let filter = SpatialFilter::within_sphere(Vec3::ZERO, 5.0);
+        let json = serde_json::to_string(&filter).unwrap();
+        assert_eq!(json, r#"{"within_sphere":{"center":[0.0,0.0,0.0],"radius":5.0}}"#);
+    }
+
+    #[test]
+    fn test_distance_to_field() {
+        let field = distance_to_field(Vec3::new(1.0, -2.5, 0.0));
+        assert_eq!(field, "distance_to(1, -2.5, 0)");
+        assert_eq!(parse_distance_to(&field), Some(Vec3::new(1.0, -2.5, 0.0)));
+        assert_eq!(parse_distance_to("distance_to(1,2)"), None);
+        assert_eq!(parse_distance_to("position.x"), None);
+    }
+}
@@ -0,0 +1,437 @@
+//! クエリ検証
+//!
+//! 実行前にクエリを検査し、未知のフィールド・コンポーネント、
+//! 型の合わない比較、値の型で使えない演算子、並び替えできないフィールドをまとめて報告する。
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ecs::world::World;
+
+use super::{CompareOp, FilterValue, QueryDescriptor, MAX_LIMIT};
+
+/// フィールド・値の型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueType {
+    Number,
+    String,
+    Bool,
+    Null,
+    /// position / rotation などの複合値
+    Object,
+    /// EntityId（id・parent）。比較には数値を使う
+    #[serde(rename = "entity_id")]
+    EntityId,
+}
+
+impl ValueType {
+    /// フィルター値の型
+    pub fn of(value: &FilterValue) -> Self {
+        match value {
+            FilterValue::Number(_) => Self::Number,
+            FilterValue::String(_) => Self::String,
+            FilterValue::Bool(_) => Self::Bool,
+            FilterValue::Null => Self::Null,
+        }
+    }
+
+    /// 大小比較（< <= > >=）が可能か
+    fn is_ordered(self) -> bool {
+        matches!(self, Self::Number | Self::String)
+    }
+
+    /// order_by で並び替えできるか
+    fn is_sortable(self) -> bool {
+        self.is_ordered() || self == Self::EntityId
+    }
+
+    /// この型のフィールドと比較できる値の型か
+    fn accepts(self, value_type: ValueType) -> bool {
+        self == value_type || (self == Self::EntityId && value_type == Self::Number)
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Number => "number",
+            Self::String => "string",
+            Self::Bool => "bool",
+            Self::Null => "null",
+            Self::Object => "object",
+            Self::EntityId => "entity_id",
+        };
+        f.write_str(name)
+    }
+}
+
+/// フィールドが使われている句
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryClause {
+    Select,
+    Filter,
+    OrderBy,
+}
+
+impl fmt::Display for QueryClause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Select => "select",
+            Self::Filter => "filter",
+            Self::OrderBy => "order_by",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 個々の検証エラー
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QueryError {
+    /// 未知のフィールド
+    UnknownField { field: String, clause: QueryClause },
+
+    /// 未知のコンポーネント
+    UnknownComponent { component: String },
+
+    /// フィールドと比較値の型が合わない
+    TypeMismatch {
+        field: String,
+        expected: ValueType,
+        found: ValueType,
+    },
+
+    /// 値の型で使えない演算子
+    UnsupportedOperator {
+        field: String,
+        op: CompareOp,
+        value_type: ValueType,
+    },
+
+    /// 列指向の結果で、select の2つのフィールドが同じ名前の列を作る
+    /// （`field` の列 `column` が先の `other` の列と重なる）
+    DuplicateColumn {
+        column: String,
+        field: String,
+        other: String,
+    },
+
+    /// 比較値が NaN・無限大（テキストクエリに書き出せない）
+    NonFiniteNumber { field: String },
+
+    /// limit が上限（`MAX_LIMIT`）を超える
+    LimitOutOfRange { limit: usize },
+
+    /// 並び替えできない型（bool・object）のフィールドを order_by に指定
+    UnsortableField { field: String, value_type: ValueType },
+
+    /// 準備済みクエリの実行時にパラメータが指定されていない
+    MissingParameter { name: String },
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownField { field, clause } => {
+                write!(f, "unknown field '{}' in {}", field, clause)
+            }
+            Self::UnknownComponent { component } => {
+                write!(f, "unknown component '{}'", component)
+            }
+            Self::TypeMismatch {
+                field,
+                expected,
+                found,
+            } => write!(
+                f,
+                "cannot compare {} field '{}' with a {} value",
+                expected, field, found
+            ),
+            Self::UnsupportedOperator {
+                field,
+                op,
+                value_type,
+            } => write!(
+                f,
+                "operator '{}' is not supported for {} values (field '{}')",
+                op.symbol(),
+                value_type,
+                field
+            ),
+            Self::DuplicateColumn {
+                column,
+                field,
+                other,
+            } => write!(
+                f,
+                "select field '{}' produces column '{}', which '{}' already produces",
+                field, column, other
+            ),
+            Self::NonFiniteNumber { field } => {
+                write!(f, "comparison value for field '{}' is not a finite number", field)
+            }
+            Self::LimitOutOfRange { limit } => {
+                write!(f, "limit {} exceeds the maximum of {}", limit, MAX_LIMIT)
+            }
+            Self::UnsortableField { field, value_type } => {
+                write!(f, "cannot order by {} field '{}'", value_type, field)
+            }
+            Self::MissingParameter { name } => write!(f, "missing parameter '${}'", name),
+        }
+    }
+}
+
+/// クエリ検証エラー（検出したすべてのエラーを保持）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryValidationError {
+    pub errors: Vec<QueryError>,
+}
+
+impl fmt::Display for QueryValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid query: ")?;
+        for (i, error) in self.errors.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for QueryValidationError {}
+
+impl QueryDescriptor {
+    /// Worldに対してクエリを検証
+    pub fn validate(&self, world: &World) -> Result<(), QueryValidationError> {
+        let mut errors = Vec::new();
+
+        for component in self.with_components.iter().chain(&self.without_components) {
+            if !world.is_known_component(component) {
+                errors.push(QueryError::UnknownComponent {
+                    component: component.clone(),
+                });
+            }
+        }
+
+        for field in &self.select {
+            if world.field_type(field).is_none() {
+                errors.push(QueryError::UnknownField {
+                    field: field.clone(),
+                    clause: QueryClause::Select,
+                });
+            }
+        }
+
+        for filter in &self.filters {
+            let Some(field_type) = world.field_type(&filter.field) else {
+                errors.push(QueryError::UnknownField {
+                    field: filter.field.clone(),
+                    clause: QueryClause::Filter,
+                });
+                continue;
+            };
+            errors.extend(check_comparison(&filter.field, field_type, filter.op, &filter.value));
+        }
+
+        if let Some(order) = &self.order_by {
+            match world.field_type(&order.field) {
+                None => errors.push(QueryError::UnknownField {
+                    field: order.field.clone(),
+                    clause: QueryClause::OrderBy,
+                }),
+                Some(value_type) if !value_type.is_sortable() => {
+                    errors.push(QueryError::UnsortableField {
+                        field: order.field.clone(),
+                        value_type,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+
+        if let Some(limit) = self.limit
+            && limit as u64 > MAX_LIMIT
+        {
+            errors.push(QueryError::LimitOutOfRange { limit });
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(QueryValidationError { errors })
+        }
+    }
+}
+
+/// フィールドと値の比較が可能か検査
+pub(crate) fn check_comparison(
+    field: &str,
+    field_type: ValueType,
+    op: CompareOp,
+    value: &FilterValue,
+) -> Option<QueryError> {
+    let value_type = ValueType::of(value);
+    let is_equality = matches!(op, CompareOp::Eq | CompareOp::Ne);
+
+    if let FilterValue::Number(n) = value
+        && !n.is_finite()
+    {
+        Some(QueryError::NonFiniteNumber {
+            field: field.to_string(),
+        })
+    } else if value_type == ValueType::Null {
+        // null との比較は存在チェックとして == / != のみ許可
+        (!is_equality).then(|| QueryError::UnsupportedOperator {
+            field: field.to_string(),
+            op,
+            value_type,
+        })
+    } else if !field_type.accepts(value_type) {
+        Some(QueryError::TypeMismatch {
+            field: field.to_string(),
+            expected: field_type,
+            found: value_type,
+        })
+    } else if !is_equality && !value_type.is_ordered() {
+        Some(QueryError::UnsupportedOperator {
+            field: field.to_string(),
+            op,
+            value_type,
+        })
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::query::{FilterExpr, OrderBy};
+
+    #[test]
+    fn test_valid_query() {
+        let world = World::new();
+        let query = QueryDescriptor::new()
+            .select(["id", "name", "position", "distance_to(0, 0, 0)"])
+            .with(["Transform"])
+            .without(["Name"])
+            .filter(FilterExpr::gt("position.x", FilterValue::Number(0.0)))
+            .filter(FilterExpr::lt("name", FilterValue::String("m".into())))
+            .filter(FilterExpr::ne("name", FilterValue::Null))
+            .order_by(OrderBy::asc("scale.y"));
+        assert_eq!(query.validate(&world), Ok(()));
+    }
+
+    #[test]
+    fn test_collects_all_errors() {
+        let world = World::new();
+        let query = QueryDescriptor::new()
+            .select(["positon.x"])
+            .with(["Enemy"])
+            .filter(FilterExpr::eq("position.x", FilterValue::String("1".into())))
+            .filter(FilterExpr::lt("position.y", FilterValue::Null))
+            .filter(FilterExpr::eq("position", FilterValue::Number(1.0)))
+            .filter(FilterExpr::gt("hp", FilterValue::Number(1.0)))
+            .order_by(OrderBy::asc("nmae"));
+
+        let errors = query.validate(&world).unwrap_err().errors;
+        assert_eq!(
+            errors,
+            vec![
+                QueryError::UnknownComponent {
+                    component: "Enemy".into()
+                },
+                QueryError::UnknownField {
+                    field: "positon.x".into(),
+                    clause: QueryClause::Select
+                },
+                QueryError::TypeMismatch {
+                    field: "position.x".into(),
+                    expected: ValueType::Number,
+                    found: ValueType::String
+                },
+                QueryError::UnsupportedOperator {
+                    field: "position.y".into(),
+                    op: CompareOp::Lt,
+                    value_type: ValueType::Null
+                },
+                QueryError::TypeMismatch {
+                    field: "position".into(),
+                    expected: ValueType::Object,
+                    found: ValueType::Number
+                },
+                QueryError::UnknownField {
+                    field: "hp".into(),
+                    clause: QueryClause::Filter
+                },
+                QueryError::UnknownField {
+                    field: "nmae".into(),
+                    clause: QueryClause::OrderBy
+                },
+            ]
+        );
+
+        // 複合値・boolのフィールドでは並び替えできない（id・parent は可）
+        let order_errors = |field: &str| {
+            QueryDescriptor::new()
+                .order_by(OrderBy::asc(field))
+                .validate(&world)
+                .map_err(|e| e.errors)
+        };
+        assert_eq!(
+            order_errors("position"),
+            Err(vec![QueryError::UnsortableField {
+                field: "position".into(),
+                value_type: ValueType::Object
+            }])
+        );
+        assert_eq!(order_errors("parent"), Ok(()));
+    }
+
+    #[test]
+    fn test_non_finite_number_and_limit() {
+        let world = World::new();
+        for n in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let query = QueryDescriptor::new().filter(FilterExpr::gt("position.x", FilterValue::Number(n)));
+            assert_eq!(
+                query.validate(&world).map_err(|e| e.errors),
+                Err(vec![QueryError::NonFiniteNumber {
+                    field: "position.x".into()
+                }])
+            );
+        }
+
+        let limit = usize::MAX;
+        let query = QueryDescriptor::new().limit(limit);
+        let expected = if limit as u64 > MAX_LIMIT {
+            Err(vec![QueryError::LimitOutOfRange { limit }])
+        } else {
+            Ok(())
+        };
+        assert_eq!(query.validate(&world).map_err(|e| e.errors), expected);
+    }
+
+    #[test]
+    fn test_error_serialize_and_display() {
+        let error = QueryValidationError {
+            errors: vec![QueryError::UnknownField {
+                field: "positon.x".into(),
+                clause: QueryClause::Select,
+            }],
+        };
+        let json = serde_json::to_string(&error).unwrap();
+        assert_eq!(
+            json,
+            r#"{"errors":[{"kind":"unknown_field","field":"positon.x","clause":"select"}]}"#
+        );
+        assert_eq!(
+            error.to_string(),
+            "invalid query: unknown field 'positon.x' in select"
+        );
+    }
+}
@@ -4,8 +4,18 @@ use std::collections::HashMap;
 use super::entity::EntityId;
 use super::component::Component;
 use super::storage::ComponentStorage;
-use super::query::{QueryDescriptor, QueryResult, QueryResultRow, FilterExpr, FilterValue, SortDirection};
-use crate::components::{Transform, Name};
+use super::index::{IndexKind, IndexRegistry, QueryPlan};
+use super::change::ChangeSet;
+use super::query::{QueryDescriptor, QueryResult, QueryResultRow, FilterExpr, FilterValue, SpatialFilter};
+use super::query::spatial::parse_distance_to;
+use super::query::{QueryValidationError, ValueType};
+use super::query::computed::{ComputedField, FieldRegistry};
+use super::query::columnar::ColumnarResult;
+use super::query::prepared::FieldAccessor;
+use super::query::order;
+use super::query::profile::{ProfileStage, QueryProfile, StageHook};
+use crate::components::{Transform, Name, Parent, Children, MeshHandle, Material, DirectionalLight, PointLight, SpotLight, ShadowFlags};
+use crate::math::{Mat4, Vec3};
 
 /// Entity生存情報
 struct EntityMeta {
@@ -13,6 +23,8 @@ struct EntityMeta {
     generation: u32,
     /// 生存フラグ
     alive: bool,
+    /// 生成時の `World::spawn_count`
+    spawned_at: u64,
 }
 
 /// 型消去されたストレージのトレイト
@@ -20,6 +32,7 @@ trait AnyStorage: Send + Sync {
     fn as_any(&self) -> &dyn std::any::Any;
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any;
     fn remove(&mut self, entity: EntityId);
+    fn contains(&self, entity: EntityId) -> bool;
 }
 
 impl<T: Component> AnyStorage for ComponentStorage<T> {
@@ -32,6 +45,9 @@ impl<T: Component> AnyStorage for ComponentStorage<T> {
     fn remove(&mut self, entity: EntityId) {
         ComponentStorage::remove(self, entity);
     }
+    fn contains(&self, entity: EntityId) -> bool {
+        ComponentStorage::contains(self, entity)
+    }
 }
 
 /// ECSのメインコンテナ
@@ -41,8 +57,18 @@ pub struct World {
     entities: Vec<EntityMeta>,
     /// 再利用可能なインデックス
     free_list: Vec<u32>,
+    /// 生成したEntityの累計
+    spawn_count: u64,
     /// 型ごとのコンポーネントストレージ
     storages: HashMap<TypeId, Box<dyn AnyStorage>>,
+    /// クエリ用セカンダリインデックス
+    indexes: IndexRegistry,
+    /// 変更履歴（変更追跡が無効の場合None）
+    changes: Option<ChangeSet>,
+    /// クエリの計算フィールド
+    fields: FieldRegistry,
+    /// distance_to_camera で使用するカメラ位置
+    camera_position: Option<Vec3>,
 }
 
 impl World {
@@ -51,17 +77,23 @@ impl World {
         Self {
             entities: Vec::new(),
             free_list: Vec::new(),
+            spawn_count: 0,
             storages: HashMap::new(),
+            indexes: IndexRegistry::default(),
+            changes: None,
+            fields: FieldRegistry::default(),
+            camera_position: None,
         }
     }
 
     /// 新規Entityを生成
     pub fn spawn(&mut self) -> EntityId {
-        if let Some(index) = self.free_list.pop() {
+        let entity = if let Some(index) = self.free_list.pop() {
             // 再利用: 世代番号をインクリメント
             let meta = &mut self.entities[index as usize];
             meta.generation += 1;
             meta.alive = true;
+            meta.spawned_at = self.spawn_count;
             EntityId::new(index, meta.generation)
         } else {
             // 新規割り当て
@@ -69,28 +101,42 @@ impl World {
             self.entities.push(EntityMeta {
                 generation: 1,
                 alive: true,
+                spawned_at: self.spawn_count,
             });
             EntityId::new(index, 1)
+        };
+        self.spawn_count += 1;
+
+        if let Some(changes) = &mut self.changes {
+            changes.record_spawn(entity);
         }
+
+        entity
     }
 
     /// Entityを削除
     /// 成功時true、既に削除済みまたは無効なEntityの場合false
+    /// 子Entityは削除されず、親子関係を解除してルートになる
     pub fn despawn(&mut self, entity: EntityId) -> bool {
-        let index = entity.index() as usize;
-
-        if index >= self.entities.len() {
+        if !self.is_alive(entity) {
             return false;
         }
 
-        let meta = &mut self.entities[index];
-        if !meta.alive || meta.generation != entity.generation() {
-            return false;
+        // 親子関係を解除
+        self.set_parent(entity, None);
+        for child in self.children(entity).to_vec() {
+            self.set_parent(child, None);
         }
 
-        meta.alive = false;
+        self.entities[entity.index() as usize].alive = false;
         self.free_list.push(entity.index());
 
+        self.flush_indexes();
+        self.indexes.on_despawn(entity);
+        if let Some(changes) = &mut self.changes {
+            changes.record_despawn(entity);
+        }
+
         // 全ストレージからコンポーネントを削除
         for storage in self.storages.values_mut() {
             storage.remove(entity);
@@ -113,6 +159,12 @@ impl World {
             return;
         }
 
+        self.flush_indexes();
+        self.indexes.on_added(TypeId::of::<T>(), entity);
+        if let Some(changes) = &mut self.changes {
+            changes.record_component(entity, TypeId::of::<T>());
+        }
+
         let storage = self.get_or_create_storage::<T>();
         storage.insert(entity, component);
     }
@@ -132,6 +184,17 @@ impl World {
             return None;
         }
 
+        // 参照経由で値が変わる可能性があるため、変更として扱う
+        if self.get_storage::<T>()?.contains(entity) {
+            if !self.indexes.is_empty() {
+                self.flush_indexes();
+                self.indexes.on_changed(TypeId::of::<T>(), entity);
+            }
+            if let Some(changes) = &mut self.changes {
+                changes.record_component(entity, TypeId::of::<T>());
+            }
+        }
+
         self.get_storage_mut::<T>()?.get_mut(entity)
     }
 
@@ -141,7 +204,15 @@ impl World {
             return None;
         }
 
-        self.get_storage_mut::<T>()?.remove(entity)
+        let removed = self.get_storage_mut::<T>()?.remove(entity);
+        if removed.is_some() {
+            self.flush_indexes();
+            self.indexes.on_removed(TypeId::of::<T>(), entity);
+            if let Some(changes) = &mut self.changes {
+                changes.record_component(entity, TypeId::of::<T>());
+            }
+        }
+        removed
     }
 
     /// 全Entityをイテレート
@@ -153,6 +224,34 @@ impl World {
             .map(|(index, meta)| EntityId::new(index as u32, meta.generation))
     }
 
+    /// indexから生存EntityのEntityIdを取得
+    pub(crate) fn live_entity(&self, index: u32) -> Option<EntityId> {
+        self.entities
+            .get(index as usize)
+            .filter(|meta| meta.alive)
+            .map(|meta| EntityId::new(index, meta.generation))
+    }
+
+    /// indexの生存Entityのうち、生成数が `spawn_count` の時点で存在したもの
+    ///
+    /// 削除されたスロットを後から再利用したEntityを除くために使う
+    pub(crate) fn live_entity_spawned_before(&self, index: u32, spawn_count: u64) -> Option<EntityId> {
+        self.entities
+            .get(index as usize)
+            .filter(|meta| meta.alive && meta.spawned_at < spawn_count)
+            .map(|meta| EntityId::new(index, meta.generation))
+    }
+
+    /// 生成したEntityの累計
+    pub(crate) fn spawn_count(&self) -> u64 {
+        self.spawn_count
+    }
+
+    /// Entityのindexの上限（削除済みを含む割り当て済みのスロット数）
+    pub(crate) fn entity_slots(&self) -> u32 {
+        self.entities.len() as u32
+    }
+
     /// 生存Entity数を取得
     pub fn entity_count(&self) -> usize {
         self.entities.iter().filter(|meta| meta.alive).count()
@@ -165,6 +264,43 @@ impl World {
         })
     }
 
+    // ========================================================================
+    // Change Tracking
+    // ========================================================================
+
+    /// 変更追跡の有効/無効を設定（無効化すると記録済みの変更は破棄）
+    pub fn set_change_tracking(&mut self, enabled: bool) {
+        match (enabled, self.changes.is_some()) {
+            (true, false) => self.changes = Some(ChangeSet::new()),
+            (false, true) => self.changes = None,
+            _ => {}
+        }
+    }
+
+    /// 変更追跡が有効か
+    pub fn is_change_tracking(&self) -> bool {
+        self.changes.is_some()
+    }
+
+    /// 前回の取得以降の変更を取り出す（変更追跡が無効の場合は空）
+    pub fn take_changes(&mut self) -> ChangeSet {
+        match &mut self.changes {
+            Some(changes) => std::mem::take(changes),
+            None => ChangeSet::new(),
+        }
+    }
+
+    /// Entityが持つコンポーネント数
+    pub fn component_count(&self, entity: EntityId) -> usize {
+        if !self.is_alive(entity) {
+            return 0;
+        }
+        self.storages
+            .values()
+            .filter(|storage| storage.contains(entity))
+            .count()
+    }
+
     /// 型に対応するストレージを取得または作成
     fn get_or_create_storage<T: Component>(&mut self) -> &mut ComponentStorage<T> {
         let type_id = TypeId::of::<T>();
@@ -195,98 +331,460 @@ impl World {
             .downcast_mut::<ComponentStorage<T>>()
     }
 
+    // ========================================================================
+    // Hierarchy
+    // ========================================================================
+
+    /// 親を設定（Noneで親子関係を解除）
+    /// 無効なEntity、または自身・子孫を親にしようとした場合false
+    pub fn set_parent(&mut self, child: EntityId, parent: Option<EntityId>) -> bool {
+        if !self.is_alive(child) {
+            return false;
+        }
+        if let Some(parent) = parent
+            && (!self.is_alive(parent) || parent == child || self.ancestors(parent).any(|a| a == child))
+        {
+            return false;
+        }
+
+        let old = self.parent(child);
+        if old == parent {
+            return true;
+        }
+
+        if let Some(old) = old {
+            let now_empty = self.get_mut::<Children>(old).is_some_and(|children| {
+                children.remove(child);
+                children.is_empty()
+            });
+            if now_empty {
+                self.remove::<Children>(old);
+            }
+        }
+
+        match parent {
+            Some(parent) => {
+                self.insert(child, Parent::new(parent));
+                match self.get_mut::<Children>(parent) {
+                    Some(children) => children.push(child),
+                    None => {
+                        let mut children = Children::default();
+                        children.push(child);
+                        self.insert(parent, children);
+                    }
+                }
+            }
+            None => {
+                self.remove::<Parent>(child);
+            }
+        }
+
+        // 子孫の depth などが変わるため、子孫も変更として扱う
+        if let Some(changes) = &mut self.changes {
+            for descendant in Self::collect_descendants(&self.storages, child) {
+                changes.record_component(descendant, TypeId::of::<Parent>());
+            }
+        }
+        true
+    }
+
+    /// 親Entity
+    pub fn parent(&self, entity: EntityId) -> Option<EntityId> {
+        self.get::<Parent>(entity).map(Parent::get)
+    }
+
+    /// 直接の子Entity（追加順）
+    pub fn children(&self, entity: EntityId) -> &[EntityId] {
+        self.get::<Children>(entity).map_or(&[], Children::as_slice)
+    }
+
+    /// 親から順にルートまでの祖先をイテレート
+    pub fn ancestors(&self, entity: EntityId) -> impl Iterator<Item = EntityId> + '_ {
+        std::iter::successors(self.parent(entity), |&parent| self.parent(parent))
+    }
+
+    /// ルートからの深さ（ルートは0）
+    pub fn depth(&self, entity: EntityId) -> usize {
+        self.ancestors(entity).count()
+    }
+
+    /// 全子孫（深さ優先、自身は含まない）
+    pub fn descendants(&self, entity: EntityId) -> Vec<EntityId> {
+        if !self.is_alive(entity) {
+            return Vec::new();
+        }
+        Self::collect_descendants(&self.storages, entity)
+    }
+
+    /// 祖先のTransformを合成したワールド行列（Transformなしの祖先は単位行列として扱う）
+    pub fn world_matrix(&self, entity: EntityId) -> Option<Mat4> {
+        let local = self.get::<Transform>(entity)?.to_matrix();
+        Some(self.ancestors(entity).fold(local, |matrix, ancestor| {
+            match self.get::<Transform>(ancestor) {
+                Some(transform) => transform.to_matrix() * matrix,
+                None => matrix,
+            }
+        }))
+    }
+
+    /// 祖先のTransformを合成したワールド座標
+    pub fn world_position(&self, entity: EntityId) -> Option<Vec3> {
+        self.world_matrix(entity).map(|matrix| matrix.w_axis.truncate())
+    }
+
+    /// 子孫を収集（変更記録中にも使えるようストレージのみを参照）
+    fn collect_descendants(
+        storages: &HashMap<TypeId, Box<dyn AnyStorage>>,
+        entity: EntityId,
+    ) -> Vec<EntityId> {
+        let children = |entity: EntityId| {
+            storages
+                .get(&TypeId::of::<Children>())
+                .and_then(|s| s.as_any().downcast_ref::<ComponentStorage<Children>>())
+                .and_then(|s| s.get(entity))
+                .map_or(&[][..], Children::as_slice)
+        };
+
+        let mut descendants = Vec::new();
+        let mut stack: Vec<EntityId> = children(entity).iter().rev().copied().collect();
+        while let Some(next) = stack.pop() {
+            descendants.push(next);
+            stack.extend(children(next).iter().rev());
+        }
+        descendants
+    }
+
+    // ========================================================================
+    // Computed Fields
+    // ========================================================================
+
+    /// 計算フィールドを登録（同名の計算フィールドは置き換え）
+    ///
+    /// `name` や `position.x`、`parent.*` などの組み込みフィールドと同名の場合は
+    /// 組み込みフィールドが優先されるためfalse
+    pub fn register_field(&mut self, name: impl Into<String>, field: ComputedField) -> bool {
+        let name = name.into();
+        if Self::stored_field_source(&name).is_some()
+            || Self::hierarchy_field_source(&name).is_some()
+            || name == "id"
+            || name.starts_with("parent.")
+        {
+            return false;
+        }
+        self.fields.register(name, field);
+        true
+    }
+
+    /// 登録済みの計算フィールド名
+    pub fn computed_field_names(&self) -> Vec<String> {
+        self.fields.names()
+    }
+
+    /// 計算フィールドの定義を取得
+    pub(crate) fn computed_field(&self, name: &str) -> Option<&ComputedField> {
+        self.fields.get(name)
+    }
+
+    /// カメラ位置を設定（distance_to_camera で使用）
+    pub fn set_camera_position(&mut self, position: Vec3) {
+        if self.camera_position == Some(position) {
+            return;
+        }
+        self.camera_position = Some(position);
+        if let Some(changes) = &mut self.changes {
+            changes.record_camera_moved();
+        }
+    }
+
+    /// カメラ位置
+    pub fn camera_position(&self) -> Option<Vec3> {
+        self.camera_position
+    }
+
+    // ========================================================================
+    // Index
+    // ========================================================================
+
+    /// フィールドインデックスを作成（"name" には Hash、"position.x" などには BTree を推奨）
+    /// 未知のフィールド、または同じフィールドに既にインデックスがある場合はfalse
+    pub fn create_index(&mut self, field: &str, kind: IndexKind) -> bool {
+        let Some(source) = self.field_source(field) else {
+            return false;
+        };
+        if self.indexes.has_field_index(field) {
+            return false;
+        }
+
+        let entities: Vec<EntityId> = self.iter_entities().collect();
+        self.indexes.add_field_index(field, kind, source, entities.into_iter());
+        self.flush_indexes();
+        true
+    }
+
+    /// コンポーネントのビットセットインデックスを作成
+    /// 未知のコンポーネント、または既にインデックスがある場合はfalse
+    pub fn create_component_index(&mut self, component: &str) -> bool {
+        let Some(source) = Self::component_type_id(component) else {
+            return false;
+        };
+        if self.indexes.has_component_index(component) {
+            return false;
+        }
+
+        let entities: Vec<EntityId> = self
+            .iter_entities()
+            .filter(|&entity| self.has_component(entity, component))
+            .collect();
+        self.indexes.add_component_index(component, source, entities.into_iter());
+        true
+    }
+
+    /// インデックスを削除（フィールド名またはコンポーネント名）
+    pub fn drop_index(&mut self, name: &str) -> bool {
+        self.indexes.drop_index(name)
+    }
+
+    /// 作成済みインデックスの一覧 (e.g., ["hash(name)", "component(Transform)"])
+    pub fn index_names(&self) -> Vec<String> {
+        self.indexes.names()
+    }
+
+    /// クエリの実行計画を取得
+    pub fn explain_query(&self, query: &QueryDescriptor) -> QueryPlan {
+        self.indexes.plan(query, self.entity_count()).0
+    }
+
+    /// クエリの段階ごとの計画（候補取得方法・使用インデックス・取り出すフィールド）を取得
+    pub fn explain(&self, query: &QueryDescriptor) -> QueryProfile {
+        QueryProfile::explain(self, query)
+    }
+
+    /// クエリを実行し、段階ごとの候補数・時間・フィールド取り出し回数を計測
+    /// `now` は現在時刻をミリ秒で返す関数
+    pub fn profile(&self, query: &QueryDescriptor, now: impl FnMut() -> f64) -> QueryProfile {
+        QueryProfile::profile(self, query, now)
+    }
+
+    /// 保留中のEntityを最新値でインデックスに再登録
+    fn flush_indexes(&mut self) {
+        if !self.indexes.has_pending() {
+            return;
+        }
+        let mut indexes = std::mem::take(&mut self.indexes);
+        indexes.flush(|entity, field| {
+            self.extract_field(entity, field).map(FilterValue::from_json)
+        });
+        self.indexes = indexes;
+    }
+
     // ========================================================================
     // Query System
     // ========================================================================
 
     /// クエリを実行
     pub fn execute_query(&self, query: &QueryDescriptor) -> QueryResult {
-        // 1. 全生存Entityを取得
-        let mut candidates: Vec<EntityId> = self.iter_entities().collect();
+        let (entities, total_count) = self.query_entities(query);
+
+        // select でフィールド抽出して結果を構築
+        let rows: Vec<QueryResultRow> = entities
+            .into_iter()
+            .map(|entity| self.build_row(entity, &query.select))
+            .collect();
+
+        QueryResult { rows, total_count }
+    }
+
+    /// クエリを実行し、列指向の結果を返す（列名が重なる場合はエラー）
+    pub fn execute_query_columnar(
+        &self,
+        query: &QueryDescriptor,
+    ) -> Result<ColumnarResult, QueryValidationError> {
+        let (entities, total_count) = self.query_entities(query);
+        let select: Vec<(&str, Option<FieldAccessor>)> = query
+            .select
+            .iter()
+            .map(|field| (field.as_str(), self.resolve_field(field)))
+            .collect();
+        let select = select.iter().map(|(field, accessor)| (*field, accessor.as_ref()));
+        ColumnarResult::build(self, &entities, select, total_count)
+    }
+
+    /// 条件に一致するEntityを並び替え・件数制限して返す（limit適用前の件数付き）
+    fn query_entities(&self, query: &QueryDescriptor) -> (Vec<EntityId>, usize) {
+        self.query_entities_with(query, &mut ())
+    }
+
+    /// `query_entities` の各段階の後に `hook` を呼ぶ（プロファイル用）
+    ///
+    /// 段階の順序は `QueryProfile` の段階一覧と同じ
+    pub(crate) fn query_entities_with(
+        &self,
+        query: &QueryDescriptor,
+        hook: &mut impl StageHook,
+    ) -> (Vec<EntityId>, usize) {
+        // 1. 候補Entityを取得
+        let mut candidates = self.candidate_entities(query);
+        hook.stage_done(ProfileStage::Scan, candidates.len(), 0);
 
         // 2. with_components でフィルタリング
         for component_name in &query.with_components {
             candidates.retain(|&entity| self.has_component(entity, component_name));
+            hook.stage_done(ProfileStage::With, candidates.len(), 0);
         }
 
         // 3. without_components で除外
         for component_name in &query.without_components {
             candidates.retain(|&entity| !self.has_component(entity, component_name));
+            hook.stage_done(ProfileStage::Without, candidates.len(), 0);
         }
 
-        // 4. filters で条件フィルタ
+        // 4. filters で条件フィルタ（1件につきフィールドを1回取り出す）
         for filter in &query.filters {
+            let extracted = candidates.len();
             candidates.retain(|&entity| self.evaluate_filter(entity, filter));
+            hook.stage_done(ProfileStage::Filter, candidates.len(), extracted);
+        }
+
+        // 4.5. spatial で空間フィルタ
+        for spatial in &query.spatial {
+            candidates.retain(|&entity| self.evaluate_spatial(entity, spatial));
+            hook.stage_done(ProfileStage::Spatial, candidates.len(), 0);
         }
 
-        let total_count = candidates.len();
+        // 4.6. relations で親子関係フィルタ
+        for relation in &query.relations {
+            candidates.retain(|&entity| relation.matches(self, entity));
+            hook.stage_done(ProfileStage::Relation, candidates.len(), 0);
+        }
 
-        // 5. order_by でソート（Task 05で詳細実装）
+        // 5. order_by でソート（ソートキーは候補ごとに1回だけ取り出す）
+        let mut keyed: Vec<order::Keyed> = match query.order_by {
+            Some(ref order) => candidates
+                .iter()
+                .map(|&entity| (entity, self.extract_field(entity, &order.field)))
+                .collect(),
+            None => candidates.iter().map(|&entity| (entity, None)).collect(),
+        };
         if let Some(ref order) = query.order_by {
-            candidates.sort_by(|&a, &b| {
-                let val_a = self.extract_field(a, &order.field);
-                let val_b = self.extract_field(b, &order.field);
-                let cmp = Self::compare_json_values(&val_a, &val_b);
-                match order.direction {
-                    SortDirection::Asc => cmp,
-                    SortDirection::Desc => cmp.reverse(),
-                }
-            });
+            order::sort_keyed(&mut keyed, order.direction);
+            hook.stage_done(ProfileStage::OrderBy, keyed.len(), keyed.len());
         }
 
         // 6. limit で件数制限
-        if let Some(limit) = query.limit {
-            candidates.truncate(limit);
+        let (candidates, total_count) = order::order_and_limit(keyed, None, query.limit);
+        if query.limit.is_some() {
+            hook.stage_done(ProfileStage::Limit, candidates.len(), 0);
         }
 
-        // 7. select でフィールド抽出して結果を構築
-        let rows: Vec<QueryResultRow> = candidates
-            .into_iter()
-            .map(|entity| {
-                let mut row = QueryResultRow::new(entity.to_u32());
-
-                // selectが空の場合はidのみ返す
-                if query.select.is_empty() {
-                    row.set_field("id", serde_json::json!(entity.to_u32()));
-                } else {
-                    for field in &query.select {
-                        if let Some(value) = self.extract_field(entity, field) {
-                            row.set_field(field.clone(), value);
-                        }
-                    }
-                }
+        (candidates, total_count)
+    }
 
-                row
-            })
-            .collect();
+    /// 候補Entityを取得（インデックスがあれば使用、なければ全生存Entity）
+    pub(crate) fn candidate_entities(&self, query: &QueryDescriptor) -> Vec<EntityId> {
+        self.indexed_candidates(query)
+            .unwrap_or_else(|| self.iter_entities().collect())
+    }
 
-        QueryResult { rows, total_count }
+    /// インデックスから候補Entityを取得（使えるインデックスがなければNone）
+    pub(crate) fn indexed_candidates(&self, query: &QueryDescriptor) -> Option<Vec<EntityId>> {
+        if self.indexes.is_empty() {
+            return None;
+        }
+        let mut indices = self.indexes.plan(query, self.entity_count()).1?;
+        // 全件スキャンと同じ順序にする
+        indices.sort_unstable();
+        indices.dedup();
+        Some(
+            indices
+                .into_iter()
+                .filter_map(|index| self.live_entity(index))
+                .collect(),
+        )
+    }
+
+    /// 単一Entityがクエリの条件（with/without/filters/spatial/relations）を満たすか
+    pub(crate) fn matches_query(&self, entity: EntityId, query: &QueryDescriptor) -> bool {
+        self.is_alive(entity)
+            && query
+                .with_components
+                .iter()
+                .all(|c| self.has_component(entity, c))
+            && !query
+                .without_components
+                .iter()
+                .any(|c| self.has_component(entity, c))
+            && query.filters.iter().all(|f| self.evaluate_filter(entity, f))
+            && query.spatial.iter().all(|s| self.evaluate_spatial(entity, s))
+            && query.relations.iter().all(|r| r.matches(self, entity))
+    }
+
+    /// select に従って結果行を構築
+    pub(crate) fn build_row(&self, entity: EntityId, select: &[String]) -> QueryResultRow {
+        let mut row = QueryResultRow::new(entity.to_u32());
+
+        // selectが空の場合はidのみ返す
+        if select.is_empty() {
+            row.set_field("id", serde_json::json!(entity.to_u32()));
+        } else {
+            for field in select {
+                if let Some(value) = self.extract_field(entity, field) {
+                    row.set_field(field.clone(), value);
+                }
+            }
+        }
+
+        row
     }
 
     /// Entityから指定フィールドの値を取得
-    fn extract_field(&self, entity: EntityId, field: &str) -> Option<serde_json::Value> {
-        match field {
-            "id" => Some(serde_json::json!(entity.to_u32())),
-            "name" => self
+    pub(crate) fn extract_field(&self, entity: EntityId, field: &str) -> Option<serde_json::Value> {
+        if let Some(inner) = field.strip_prefix("parent.") {
+            return self.extract_field(self.parent(entity)?, inner);
+        }
+        if let Some(accessor) = FieldAccessor::stored(field) {
+            return self.read_field(entity, &accessor);
+        }
+        if let Some(computed) = self.fields.get(field) {
+            return computed.compute(self, entity);
+        }
+        // 計算フィールド: distance_to(x, y, z)
+        let point = parse_distance_to(field)?;
+        self.read_field(entity, &FieldAccessor::DistanceTo(point))
+    }
+
+    /// フィールド名を解決済みのアクセサに変換（未知のフィールドはNone）
+    pub(crate) fn resolve_field(&self, field: &str) -> Option<FieldAccessor> {
+        if let Some(inner) = field.strip_prefix("parent.") {
+            return self
+                .resolve_field(inner)
+                .map(|accessor| FieldAccessor::ParentField(Box::new(accessor)));
+        }
+        if let Some(accessor) = FieldAccessor::stored(field) {
+            return Some(accessor);
+        }
+        if self.fields.get(field).is_some() {
+            return Some(FieldAccessor::Computed(field.to_string()));
+        }
+        parse_distance_to(field).map(FieldAccessor::DistanceTo)
+    }
+
+    /// 解決済みのアクセサで値を取得
+    pub(crate) fn read_field(
+        &self,
+        entity: EntityId,
+        accessor: &FieldAccessor,
+    ) -> Option<serde_json::Value> {
+        let vec3 = |v: Vec3| serde_json::json!({ "x": v.x, "y": v.y, "z": v.z });
+        match accessor {
+            FieldAccessor::Id => Some(serde_json::json!(entity.to_u32())),
+            FieldAccessor::Name => self
                 .get::<Name>(entity)
                 .map(|n| serde_json::json!(n.as_str())),
-            "position" => self.get::<Transform>(entity).map(|t| {
-                serde_json::json!({
-                    "x": t.position.x,
-                    "y": t.position.y,
-                    "z": t.position.z,
-                })
-            }),
-            "position.x" => self
-                .get::<Transform>(entity)
-                .map(|t| serde_json::json!(t.position.x)),
-            "position.y" => self
-                .get::<Transform>(entity)
-                .map(|t| serde_json::json!(t.position.y)),
-            "position.z" => self
+            FieldAccessor::Position => self.get::<Transform>(entity).map(|t| vec3(t.position)),
+            FieldAccessor::PositionAxis(axis) => self
                 .get::<Transform>(entity)
-                .map(|t| serde_json::json!(t.position.z)),
-            "rotation" => self.get::<Transform>(entity).map(|t| {
+                .map(|t| serde_json::json!(t.position[*axis])),
+            FieldAccessor::Rotation => self.get::<Transform>(entity).map(|t| {
                 serde_json::json!({
                     "x": t.rotation.x,
                     "y": t.rotation.y,
@@ -294,38 +792,123 @@ impl World {
                     "w": t.rotation.w,
                 })
             }),
-            "scale" => self.get::<Transform>(entity).map(|t| {
-                serde_json::json!({
-                    "x": t.scale.x,
-                    "y": t.scale.y,
-                    "z": t.scale.z,
-                })
-            }),
-            "scale.x" => self
-                .get::<Transform>(entity)
-                .map(|t| serde_json::json!(t.scale.x)),
-            "scale.y" => self
-                .get::<Transform>(entity)
-                .map(|t| serde_json::json!(t.scale.y)),
-            "scale.z" => self
+            FieldAccessor::Scale => self.get::<Transform>(entity).map(|t| vec3(t.scale)),
+            FieldAccessor::ScaleAxis(axis) => self
                 .get::<Transform>(entity)
-                .map(|t| serde_json::json!(t.scale.z)),
+                .map(|t| serde_json::json!(t.scale[*axis])),
+            FieldAccessor::DistanceTo(point) => self
+                .world_position(entity)
+                .map(|p| serde_json::json!(p.distance(*point))),
+            FieldAccessor::Parent => self.is_alive(entity).then(|| {
+                self.parent(entity)
+                    .map_or(serde_json::Value::Null, |p| serde_json::json!(p.to_u32()))
+            }),
+            FieldAccessor::ChildrenCount => self
+                .is_alive(entity)
+                .then(|| serde_json::json!(self.children(entity).len())),
+            FieldAccessor::Depth => self
+                .is_alive(entity)
+                .then(|| serde_json::json!(self.depth(entity))),
+            FieldAccessor::ParentField(inner) => self.read_field(self.parent(entity)?, inner),
+            FieldAccessor::Computed(name) => self.fields.get(name)?.compute(self, entity),
+        }
+    }
+
+    /// フィールド値の取得元コンポーネント
+    /// （計算フィールドは単一のコンポーネントのみを参照する場合のみ。
+    /// `distance_to(...)` は祖先のTransformにも依存するためNone）
+    pub(crate) fn field_source(&self, field: &str) -> Option<TypeId> {
+        if parse_distance_to(field).is_some() {
+            return None;
+        }
+        if let Some(source) = Self::stored_field_source(field) {
+            return Some(source);
+        }
+        let computed = self.fields.get(field)?;
+        match computed.components()? {
+            [source] if !computed.uses_camera() && !computed.uses_hierarchy() => Some(*source),
             _ => None,
         }
     }
 
-    /// コンポーネントの存在チェック
-    fn has_component(&self, entity: EntityId, component_name: &str) -> bool {
+    /// 組み込みフィールドの取得元コンポーネント
+    pub(crate) fn stored_field_source(field: &str) -> Option<TypeId> {
+        match field {
+            "name" => Some(TypeId::of::<Name>()),
+            "position" | "position.x" | "position.y" | "position.z" | "rotation" | "scale"
+            | "scale.x" | "scale.y" | "scale.z" => Some(TypeId::of::<Transform>()),
+            _ => parse_distance_to(field).map(|_| TypeId::of::<Transform>()),
+        }
+    }
+
+    /// 親子関係フィールドの取得元コンポーネント
+    /// （値は祖先やコンポーネントの有無で決まるため、インデックスには使えない）
+    pub(crate) fn hierarchy_field_source(field: &str) -> Option<TypeId> {
+        match field {
+            "parent" | "depth" => Some(TypeId::of::<Parent>()),
+            "children_count" => Some(TypeId::of::<Children>()),
+            _ => None,
+        }
+    }
+
+    /// フィールドの値の型（未知のフィールドはNone）
+    pub(crate) fn field_type(&self, field: &str) -> Option<ValueType> {
+        if let Some(inner) = field.strip_prefix("parent.") {
+            return self.field_type(inner);
+        }
+        match field {
+            "id" | "parent" => Some(ValueType::EntityId),
+            "children_count" | "depth" => Some(ValueType::Number),
+            "name" => Some(ValueType::String),
+            "position" | "rotation" | "scale" => Some(ValueType::Object),
+            "position.x" | "position.y" | "position.z" | "scale.x" | "scale.y" | "scale.z" => {
+                Some(ValueType::Number)
+            }
+            _ => match self.fields.get(field) {
+                Some(computed) => Some(computed.value_type()),
+                None => parse_distance_to(field).map(|_| ValueType::Number),
+            },
+        }
+    }
+
+    /// クエリで使用可能なコンポーネント名か
+    pub(crate) fn is_known_component(&self, component_name: &str) -> bool {
+        Self::component_type_id(component_name).is_some()
+    }
+
+    /// コンポーネント名から型IDを取得
+    pub(crate) fn component_type_id(component_name: &str) -> Option<TypeId> {
         match component_name {
-            "Transform" => self.get::<Transform>(entity).is_some(),
-            "Name" => self.get::<Name>(entity).is_some(),
-            // カスタムコンポーネントは動的登録が必要（Phase 4以降）
-            _ => false,
+            "Transform" => Some(TypeId::of::<Transform>()),
+            "Name" => Some(TypeId::of::<Name>()),
+            "Parent" => Some(TypeId::of::<Parent>()),
+            "Children" => Some(TypeId::of::<Children>()),
+            "MeshHandle" => Some(TypeId::of::<MeshHandle>()),
+            "Material" => Some(TypeId::of::<Material>()),
+            "DirectionalLight" => Some(TypeId::of::<DirectionalLight>()),
+            "PointLight" => Some(TypeId::of::<PointLight>()),
+            "SpotLight" => Some(TypeId::of::<SpotLight>()),
+            "ShadowFlags" => Some(TypeId::of::<ShadowFlags>()),
+            _ => None,
         }
     }
 
+    /// 型IDによるコンポーネントの存在チェック
+    pub(crate) fn has_component_type(&self, entity: EntityId, type_id: TypeId) -> bool {
+        self.storages
+            .get(&type_id)
+            .is_some_and(|storage| storage.contains(entity))
+    }
+
+    /// コンポーネントの存在チェック
+    pub(crate) fn has_component(&self, entity: EntityId, component_name: &str) -> bool {
+        // カスタムコンポーネントは動的登録が必要（Phase 4以降）
+        Self::component_type_id(component_name)
+            .is_some_and(|type_id| self.has_component_type(entity, type_id))
+    }
+
     /// フィルター条件を評価
-    fn evaluate_filter(&self, entity: EntityId, filter: &FilterExpr) -> bool {
+    pub(crate) fn evaluate_filter(&self, entity: EntityId, filter: &FilterExpr) -> bool {
         let field_value = match self.extract_field(entity, &filter.field) {
             Some(v) => FilterValue::from_json(v),
             None => return false, // フィールドなし = マッチしない
@@ -334,8 +917,14 @@ impl World {
         filter.op.compare_values(&field_value, &filter.value)
     }
 
+    /// 空間フィルターをワールド行列で評価（Transformなし = マッチしない）
+    pub(crate) fn evaluate_spatial(&self, entity: EntityId, filter: &SpatialFilter) -> bool {
+        self.world_matrix(entity)
+            .is_some_and(|matrix| filter.matches(&matrix))
+    }
+
     /// JSON値の比較（ソート用）
-    fn compare_json_values(
+    pub(crate) fn compare_json_values(
         a: &Option<serde_json::Value>,
         b: &Option<serde_json::Value>,
     ) -> std::cmp::Ordering {
@@ -521,7 +1110,9 @@ mod tests {
     // ========================================================================
 
     use crate::components::{Transform as RealTransform, Name as RealName};
+    use crate::ecs::query::RelationFilter;
     use crate::ecs::query::{QueryDescriptor, FilterExpr, FilterValue, OrderBy};
+    use crate::ecs::index::AccessPath;
     use glam::Vec3;
 
     #[test]
@@ -651,4 +1242,330 @@ mod tests {
         assert_eq!(result.len(), 3);
         assert_eq!(result.total_count, 10); // limit前の総数
     }
+
+    #[test]
+    fn test_index_plan_and_results() {
+        let mut world = World::new();
+        for i in 0..20 {
+            let e = world.spawn();
+            world.insert(e, RealName::new(format!("Entity{}", i % 5)));
+            world.insert(e, RealTransform::from_position(Vec3::new(i as f32, 0.0, 0.0)));
+        }
+
+        let by_x = QueryDescriptor::new()
+            .select(["name"])
+            .filter(FilterExpr::gt("position.x", FilterValue::Number(15.0)));
+        let by_name = QueryDescriptor::new()
+            .select(["position.x"])
+            .filter(FilterExpr::eq("name", FilterValue::String("Entity2".into())));
+
+        let scan_x = world.execute_query(&by_x);
+        let scan_name = world.execute_query(&by_name);
+        assert_eq!(world.explain_query(&by_x).access, AccessPath::FullScan);
+
+        assert!(world.create_index("position.x", IndexKind::BTree));
+        assert!(world.create_index("name", IndexKind::Hash));
+        assert!(!world.create_index("name", IndexKind::BTree));
+        assert!(!world.create_index("unknown", IndexKind::Hash));
+
+        let plan = world.explain_query(&by_x);
+        assert!(matches!(plan.access, AccessPath::FieldIndex { kind: IndexKind::BTree, .. }));
+        assert_eq!(plan.estimated_candidates, 4);
+        assert_eq!(plan.total_entities, 20);
+        assert_eq!(world.explain_query(&by_name).estimated_candidates, 4);
+
+        // インデックス使用時も全件スキャンと同じ結果
+        let indexed_x = world.execute_query(&by_x);
+        let indexed_name = world.execute_query(&by_name);
+        assert_eq!(
+            serde_json::to_value(&indexed_x).unwrap(),
+            serde_json::to_value(&scan_x).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(&indexed_name).unwrap(),
+            serde_json::to_value(&scan_name).unwrap()
+        );
+
+        assert!(world.drop_index("position.x"));
+        assert_eq!(world.index_names(), vec!["hash(name)"]);
+    }
+
+    #[test]
+    fn test_index_maintained_on_mutation() {
+        let mut world = World::new();
+        let a = world.spawn();
+        world.insert(a, RealTransform::from_position(Vec3::new(-1.0, 0.0, 0.0)));
+        let b = world.spawn();
+        world.insert(b, RealTransform::from_position(Vec3::new(5.0, 0.0, 0.0)));
+        let c = world.spawn();
+        world.insert(c, RealName::new("NoTransform"));
+
+        assert!(world.create_index("position.x", IndexKind::BTree));
+        assert!(world.create_component_index("Transform"));
+
+        let positive = QueryDescriptor::new()
+            .filter(FilterExpr::gt("position.x", FilterValue::Number(0.0)));
+        let ids = |result: QueryResult| result.rows.iter().map(|r| r.id).collect::<Vec<_>>();
+
+        assert_eq!(ids(world.execute_query(&positive)), vec![b.to_u32()]);
+
+        // get_mut経由の変更（flush前でも反映される）
+        world.get_mut::<RealTransform>(a).unwrap().position.x = 3.0;
+        assert_eq!(ids(world.execute_query(&positive)), vec![a.to_u32(), b.to_u32()]);
+
+        // 次の可変操作でflushされる
+        world.get_mut::<RealTransform>(b).unwrap().position.x = -3.0;
+        assert_eq!(ids(world.execute_query(&positive)), vec![a.to_u32()]);
+
+        // コンポーネント追加・削除・despawn
+        world.insert(c, RealTransform::from_position(Vec3::new(9.0, 0.0, 0.0)));
+        assert_eq!(ids(world.execute_query(&positive)), vec![a.to_u32(), c.to_u32()]);
+        world.remove::<RealTransform>(a);
+        world.despawn(c);
+        let d = world.spawn();
+        assert_eq!(d.index(), c.index());
+        assert!(world.execute_query(&positive).is_empty());
+
+        let with_transform = QueryDescriptor::new().with(["Transform"]);
+        let plan = world.explain_query(&with_transform);
+        assert_eq!(plan.access, AccessPath::ComponentBitset { component: "Transform".into() });
+        assert_eq!(ids(world.execute_query(&with_transform)), vec![b.to_u32()]);
+    }
+
+    #[test]
+    fn test_change_tracking() {
+        use std::any::TypeId;
+
+        let mut world = World::new();
+        let before = world.spawn();
+        world.insert(before, Position { x: 0.0, y: 0.0 });
+        assert!(world.take_changes().is_empty());
+
+        world.set_change_tracking(true);
+        let e = world.spawn();
+        world.insert(e, Position { x: 1.0, y: 2.0 });
+        world.get_mut::<Position>(before).unwrap().x = 5.0;
+        // 存在しないコンポーネントへのget_mutは変更扱いにしない
+        assert!(world.get_mut::<Velocity>(before).is_none());
+
+        let changes = world.take_changes();
+        assert_eq!(changes.len(), 2);
+        assert!(changes.get(e).unwrap().spawned);
+        assert!(changes.get(e).unwrap().touches(TypeId::of::<Position>()));
+        assert!(!changes.get(before).unwrap().spawned);
+        assert_eq!(changes.get(before).unwrap().components.len(), 1);
+
+        world.remove::<Position>(e);
+        world.despawn(before);
+        let changes = world.take_changes();
+        assert!(changes.get(e).unwrap().touches(TypeId::of::<Position>()));
+        assert!(changes.get(before).unwrap().despawned);
+        assert!(world.take_changes().is_empty());
+    }
+
+    #[test]
+    fn test_computed_fields() {
+        use crate::ecs::query::{ComputedField, ValueType};
+
+        let mut world = World::new();
+        for (name, x) in [("a", 3.0), ("b", -1.0), ("c", 2.0)] {
+            let e = world.spawn();
+            world.insert(e, RealName::new(name));
+            world.insert(e, RealTransform::from_position(Vec3::new(x, 0.0, 0.0)));
+        }
+
+        // 組み込みフィールドと同名は登録不可
+        assert!(!world.register_field("name", ComputedField::new(ValueType::String, |_, _| None)));
+        assert!(world.register_field(
+            "name_length",
+            ComputedField::new(ValueType::Number, |world, entity| {
+                world
+                    .get::<RealName>(entity)
+                    .map(|n| serde_json::json!(n.as_str().len()))
+            })
+            .reads::<RealName>(),
+        ));
+        assert!(world.computed_field_names().contains(&"name_length".to_string()));
+
+        world.set_camera_position(Vec3::new(2.0, 0.0, 0.0));
+        let query = QueryDescriptor::new()
+            .select(["name", "name_length", "component_count"])
+            .filter(FilterExpr::eq("name_length", FilterValue::Number(1.0)))
+            .filter(FilterExpr::lt("distance_to_camera", FilterValue::Number(2.5)))
+            .order_by(OrderBy::desc("world_position.x"));
+        assert_eq!(query.validate(&world), Ok(()));
+
+        let result = world.execute_query(&query);
+        let names: Vec<_> = result.iter().map(|r| r.get_field("name").unwrap().clone()).collect();
+        assert_eq!(names, vec![serde_json::json!("a"), serde_json::json!("c")]);
+        assert_eq!(result.rows[0].get_field("component_count"), Some(&serde_json::json!(2)));
+
+        // 単一コンポーネントを参照する計算フィールドにはインデックスを作成可能
+        assert!(world.create_index("name_length", IndexKind::Hash));
+        assert!(!world.create_index("distance_to_camera", IndexKind::BTree));
+        assert_eq!(world.execute_query(&query), result);
+    }
+
+    #[test]
+    fn test_execute_query_spatial() {
+        let mut world = World::new();
+
+        for (name, x) in [("Near", 1.0), ("Mid", 3.0), ("Far", 10.0)] {
+            let e = world.spawn();
+            world.insert(e, RealName::new(name));
+            world.insert(e, RealTransform::from_position(Vec3::new(x, 0.0, 0.0)));
+        }
+        let no_transform = world.spawn();
+        world.insert(no_transform, RealName::new("NoTransform"));
+
+        // 原点から半径5以内を、(4, 0, 0)からの距離順に取得
+        let distance_field = "distance_to(4, 0, 0)";
+        let query = QueryDescriptor::new()
+            .select(["name", distance_field])
+            .spatial(SpatialFilter::within_sphere(Vec3::ZERO, 5.0))
+            .order_by(OrderBy::asc(distance_field));
+
+        let result = world.execute_query(&query);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result.rows[0].get_field("name"), Some(&serde_json::json!("Mid")));
+        assert_eq!(result.rows[0].get_field(distance_field), Some(&serde_json::json!(1.0)));
+        assert_eq!(result.rows[1].get_field("name"), Some(&serde_json::json!("Near")));
+    }
+
+    #[test]
+    fn test_spatial_queries_use_world_position() {
+        let mut world = World::new();
+        let parent = world.spawn();
+        world.insert(parent, RealName::new("Parent"));
+        world.insert(parent, RealTransform::from_position(Vec3::new(10.0, 0.0, 0.0)));
+        let child = world.spawn();
+        world.insert(child, RealName::new("Child"));
+        world.insert(child, RealTransform::from_position(Vec3::new(1.0, 0.0, 0.0)));
+        world.set_parent(child, Some(parent));
+
+        let names = |query: QueryDescriptor| -> Vec<serde_json::Value> {
+            world
+                .execute_query(&query.select(["name"]))
+                .iter()
+                .map(|r| r.get_field("name").unwrap().clone())
+                .collect()
+        };
+        let child_only = vec![serde_json::json!("Child")];
+
+        // 子のワールド座標は (11, 0, 0)（ローカル座標の (1, 0, 0) ではない）
+        let sphere = QueryDescriptor::new().spatial(SpatialFilter::within_sphere(Vec3::new(11.0, 0.0, 0.0), 0.5));
+        assert_eq!(names(sphere), child_only);
+        let local = QueryDescriptor::new().spatial(SpatialFilter::within_sphere(Vec3::X, 0.5));
+        assert!(names(local).is_empty());
+        let aabb = QueryDescriptor::new().spatial(SpatialFilter::within_aabb(
+            Vec3::new(10.5, -1.0, -1.0),
+            Vec3::new(11.5, 1.0, 1.0),
+        ));
+        assert_eq!(names(aabb), child_only);
+        let ray = QueryDescriptor::new().spatial(SpatialFilter::intersects_ray(
+            Vec3::new(11.0, 0.0, 10.0),
+            -Vec3::Z,
+            None,
+        ));
+        assert_eq!(names(ray), child_only);
+        let view = Mat4::look_at_rh(Vec3::new(11.0, 0.0, 3.0), Vec3::new(11.0, 0.0, 0.0), Vec3::Y);
+        let proj = Mat4::perspective_rh(10.0_f32.to_radians(), 1.0, 0.1, 100.0);
+        let frustum = QueryDescriptor::new().spatial(SpatialFilter::in_frustum(proj * view));
+        assert_eq!(names(frustum), child_only);
+
+        world.set_camera_position(Vec3::new(11.0, 0.0, 5.0));
+        let result = world.execute_query(
+            &QueryDescriptor::new()
+                .select(["distance_to(10, 0, 0)", "distance_to_camera"])
+                .relation(RelationFilter::child_of(parent)),
+        );
+        assert_eq!(result.rows[0].get_field("distance_to(10, 0, 0)"), Some(&serde_json::json!(1.0)));
+        assert_eq!(result.rows[0].get_field("distance_to_camera"), Some(&serde_json::json!(5.0)));
+
+        // 祖先に依存するため distance_to にはインデックスを作らない
+        assert!(!world.create_index("distance_to(10, 0, 0)", IndexKind::BTree));
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let mut world = World::new();
+        let root = world.spawn();
+        let a = world.spawn();
+        let b = world.spawn();
+        let c = world.spawn();
+
+        assert!(world.set_parent(a, Some(root)));
+        assert!(world.set_parent(b, Some(root)));
+        assert!(world.set_parent(c, Some(a)));
+        assert_eq!(world.children(root), &[a, b]);
+        assert_eq!(world.depth(c), 2);
+        assert_eq!(world.ancestors(c).collect::<Vec<_>>(), vec![a, root]);
+        assert_eq!(world.descendants(root), vec![a, c, b]);
+
+        // 循環は作れない
+        assert!(!world.set_parent(root, Some(c)));
+        assert!(!world.set_parent(a, Some(a)));
+
+        // 付け替え
+        assert!(world.set_parent(a, Some(b)));
+        assert_eq!(world.children(root), &[b]);
+        assert_eq!(world.depth(c), 3);
+
+        // 削除すると子はルートになる
+        assert!(world.despawn(b));
+        assert_eq!(world.parent(a), None);
+        assert_eq!(world.depth(c), 1);
+        assert!(world.get::<Children>(root).is_none());
+    }
+
+    #[test]
+    fn test_execute_query_hierarchy() {
+        let mut world = World::new();
+        let lights = world.spawn();
+        world.insert(lights, RealName::new("Lights"));
+        let key = world.spawn();
+        world.insert(key, RealName::new("key"));
+        world.set_parent(key, Some(lights));
+        let fill = world.spawn();
+        world.insert(fill, RealName::new("fill"));
+        world.set_parent(fill, Some(lights));
+        let bulb = world.spawn();
+        world.set_parent(bulb, Some(key));
+
+        let ids = |result: QueryResult| result.iter().map(|r| r.id).collect::<Vec<_>>();
+
+        // 親の名前で絞り込み
+        let query = QueryDescriptor::new()
+            .select(["name", "parent", "depth"])
+            .filter(FilterExpr::eq("parent.name", FilterValue::String("Lights".into())));
+        assert_eq!(query.validate(&world), Ok(()));
+        let result = world.execute_query(&query);
+        assert_eq!(ids(result.clone()), vec![key.to_u32(), fill.to_u32()]);
+        assert_eq!(result.rows[0].get_field("parent"), Some(&serde_json::json!(lights.to_u32())));
+        assert_eq!(result.rows[0].get_field("depth"), Some(&serde_json::json!(1)));
+
+        // 直接の子・子孫・ルート
+        let children = QueryDescriptor::new().relation(RelationFilter::child_of(lights));
+        assert_eq!(ids(world.execute_query(&children)), vec![key.to_u32(), fill.to_u32()]);
+        let descendants = QueryDescriptor::new()
+            .relation(RelationFilter::descendant_of(lights))
+            .filter(FilterExpr::le("depth", FilterValue::Number(1.0)));
+        assert_eq!(ids(world.execute_query(&descendants)), vec![key.to_u32(), fill.to_u32()]);
+        let roots = QueryDescriptor::new()
+            .select(["children_count"])
+            .relation(RelationFilter::Root);
+        let result = world.execute_query(&roots);
+        assert_eq!(ids(result.clone()), vec![lights.to_u32()]);
+        assert_eq!(result.rows[0].get_field("children_count"), Some(&serde_json::json!(2)));
+
+        // ルートの parent は null
+        let query = QueryDescriptor::new().filter(FilterExpr::eq("parent", FilterValue::Null));
+        assert_eq!(ids(world.execute_query(&query)), vec![lights.to_u32()]);
+
+        // 親子関係のフィールドにはインデックスを作成できない
+        assert!(!world.create_index("depth", IndexKind::BTree));
+        assert!(!world.create_index("parent.name", IndexKind::Hash));
+        assert!(!world.create_index("world_position.x", IndexKind::BTree));
+        assert!(!world.register_field("parent.hp", ComputedField::new(ValueType::Number, |_, _| None)));
+    }
 }
@@ -3,5 +3,5 @@ pub mod math;
 pub mod components;
 
 // Re-exports
-pub use ecs::{EntityId, World, QueryDescriptor, QueryResult};
-pub use components::{Transform, ModelUniform, Name};
+pub use ecs::{EntityId, World, QueryDescriptor, QueryResult, ParseError, parse_query, IndexKind, QueryPlan, ChangeSet, LiveQuery, QueryDelta, QueryValidationError, ComputedField, ValueType, Column, ColumnarResult, PreparedQuery, FilterValue, RelationFilter, SlicedQuery, QueryProgress, QueryProfile};
+pub use components::{Transform, ModelUniform, Name, Parent, Children, MeshHandle, Material, TextureHandle, DirectionalLight, PointLight, SpotLight, ShadowFlags, ShadowSettings};
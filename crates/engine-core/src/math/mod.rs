@@ -1,2 +1,2 @@
 // glamの型を再エクスポート
-pub use glam::{Mat4, Quat, Vec3};
+pub use glam::{Mat4, Quat, Vec3, Vec4};
@@ -0,0 +1,144 @@
+//! アセットストアモジュール
+//!
+//! GPUリソースなどのアセットをハンドルIDで管理し、参照カウントで解放する
+
+use std::collections::HashMap;
+
+/// 参照カウント付きエントリ
+#[derive(Debug)]
+struct Entry<T> {
+    asset: T,
+    ref_count: u32,
+}
+
+/// 参照カウント付きアセットストア
+///
+/// `insert` で参照カウント1のアセットを登録し、`retain`/`release` で増減する。
+/// カウントが0になったアセットはストアから取り除かれる
+#[derive(Debug)]
+pub struct AssetStore<T> {
+    entries: HashMap<u32, Entry<T>>,
+    next_id: u32,
+}
+
+impl<T> AssetStore<T> {
+    /// 空のストアを作成（IDは0から払い出す）
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// アセットを登録し、ハンドルIDを返す（参照カウント1）
+    pub fn insert(&mut self, asset: T) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.insert(id, Entry { asset, ref_count: 1 });
+        id
+    }
+
+    /// 参照を追加（存在しないIDならfalse）
+    pub fn retain(&mut self, id: u32) -> bool {
+        match self.entries.get_mut(&id) {
+            Some(entry) => {
+                entry.ref_count += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 参照を解放し、カウントが0になったらアセットを取り出して返す
+    pub fn release(&mut self, id: u32) -> Option<T> {
+        let entry = self.entries.get_mut(&id)?;
+        entry.ref_count -= 1;
+        if entry.ref_count == 0 {
+            self.entries.remove(&id).map(|entry| entry.asset)
+        } else {
+            None
+        }
+    }
+
+    /// アセットを取得
+    pub fn get(&self, id: u32) -> Option<&T> {
+        self.entries.get(&id).map(|entry| &entry.asset)
+    }
+
+    /// アセットを可変で取得
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut T> {
+        self.entries.get_mut(&id).map(|entry| &mut entry.asset)
+    }
+
+    /// 参照カウントを取得
+    pub fn ref_count(&self, id: u32) -> Option<u32> {
+        self.entries.get(&id).map(|entry| entry.ref_count)
+    }
+
+    /// IDが登録されているか
+    pub fn contains(&self, id: u32) -> bool {
+        self.entries.contains_key(&id)
+    }
+
+    /// 登録済みアセット数
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// ストアが空か
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<T> Default for AssetStore<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_assigns_sequential_ids() {
+        let mut store = AssetStore::new();
+        assert_eq!(store.insert("a"), 0);
+        assert_eq!(store.insert("b"), 1);
+        assert_eq!(store.get(1), Some(&"b"));
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn test_ref_counting() {
+        let mut store = AssetStore::new();
+        let id = store.insert(String::from("mesh"));
+        assert!(store.retain(id));
+        assert_eq!(store.ref_count(id), Some(2));
+
+        // 参照が残っている間は解放されない
+        assert_eq!(store.release(id), None);
+        assert!(store.contains(id));
+
+        assert_eq!(store.release(id).as_deref(), Some("mesh"));
+        assert!(!store.contains(id));
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_id() {
+        let mut store: AssetStore<u8> = AssetStore::new();
+        assert!(!store.retain(3));
+        assert_eq!(store.release(3), None);
+        assert_eq!(store.ref_count(3), None);
+    }
+
+    #[test]
+    fn test_ids_are_not_reused() {
+        let mut store = AssetStore::new();
+        let first = store.insert(1);
+        store.release(first);
+        assert_ne!(store.insert(2), first);
+    }
+}
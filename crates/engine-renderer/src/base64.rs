@@ -0,0 +1,72 @@
+//! Base64エンコード/デコード
+//!
+//! glTFのdata URI（埋め込みバッファ）用の最小実装
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// 標準Base64（パディングあり）にエンコード
+#[cfg(test)]
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - i * 6)) as usize & 0x3f] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// 標準Base64をデコード（パディング省略可、空白は無視）
+pub(crate) fn decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    let mut padding = false;
+
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        if c == b'=' {
+            padding = true;
+            continue;
+        }
+        // パディングの後にデータが続くのは不正
+        if padding {
+            return None;
+        }
+        let value = ALPHABET.iter().position(|&a| a == c)? as u32;
+        buffer = buffer << 6 | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for text in ["", "f", "fo", "foo", "foob", "fooba", "foobar"] {
+            let encoded = encode(text.as_bytes());
+            assert_eq!(decode(&encoded).unwrap(), text.as_bytes());
+        }
+        assert_eq!(encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode(b"fo"), "Zm8=");
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert!(decode("Zm9v!").is_none());
+        assert!(decode("Zm8=Zm8=").is_none());
+        assert_eq!(decode("Zm8").unwrap(), b"fo");
+    }
+}
@@ -2,20 +2,29 @@ use bytemuck::{Pod, Zeroable};
 use glam::{Mat4, Vec3};
 
 /// GPU用カメラUniform
-/// View-Projection行列を列優先形式で格納
+/// View-Projection行列を列優先形式で格納し、ライティング用にカメラ位置を持つ
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Pod, Zeroable)]
 pub struct CameraUniform {
     pub view_proj: [[f32; 4]; 4],
+    /// ワールド空間のカメラ位置（wは1.0）
+    pub position: [f32; 4],
 }
 
 impl CameraUniform {
-    /// Mat4からCameraUniformを作成
+    /// Mat4からCameraUniformを作成（カメラ位置は原点）
     pub fn from_mat4(mat: Mat4) -> Self {
         Self {
             view_proj: mat.to_cols_array_2d(),
+            position: [0.0, 0.0, 0.0, 1.0],
         }
     }
+
+    /// カメラ位置を設定
+    pub fn with_position(mut self, position: Vec3) -> Self {
+        self.position = position.extend(1.0).to_array();
+        self
+    }
 }
 
 impl Default for CameraUniform {
@@ -141,7 +150,7 @@ impl Camera {
 
     /// GPU用のCameraUniformを取得
     pub fn uniform(&self) -> CameraUniform {
-        CameraUniform::from_mat4(self.build_view_projection_matrix())
+        CameraUniform::from_mat4(self.build_view_projection_matrix()).with_position(self.position)
     }
 
     /// カメラ位置を取得
@@ -196,6 +205,38 @@ impl Camera {
         Mat4::perspective_rh(self.fov, self.aspect, self.near, self.far)
     }
 
+    /// ニアクリップ距離
+    pub fn near(&self) -> f32 {
+        self.near
+    }
+
+    /// ファークリップ距離
+    pub fn far(&self) -> f32 {
+        self.far
+    }
+
+    /// 視線方向（単位ベクトル）
+    pub fn forward(&self) -> Vec3 {
+        (self.target - self.position).normalize_or(Vec3::NEG_Z)
+    }
+
+    /// 視線方向の距離 `near`〜`far` の視錐台の8頂点（ワールド空間）
+    ///
+    /// 近い面の4頂点、遠い面の4頂点の順
+    pub fn frustum_corners(&self, near: f32, far: f32) -> [Vec3; 8] {
+        let forward = self.forward();
+        let right = forward.cross(self.up).normalize_or(Vec3::X);
+        let up = right.cross(forward);
+        let tan_y = (self.fov * 0.5).tan();
+        let tan_x = tan_y * self.aspect;
+        std::array::from_fn(|i| {
+            let depth = if i < 4 { near } else { far };
+            let sx = if i & 1 == 0 { -1.0 } else { 1.0 };
+            let sy = if i & 2 == 0 { -1.0 } else { 1.0 };
+            self.position + forward * depth + right * (sx * tan_x * depth) + up * (sy * tan_y * depth)
+        })
+    }
+
     /// スクリーン座標（0〜1）からワールド空間のレイを生成
     ///
     /// # Arguments
@@ -266,8 +307,21 @@ mod tests {
 
     #[test]
     fn test_camera_uniform_size() {
-        // 4x4 floats * 4 bytes = 64 bytes
-        assert_eq!(std::mem::size_of::<CameraUniform>(), 64);
+        // 4x4 floats * 4 bytes + position(vec4) = 80 bytes
+        assert_eq!(std::mem::size_of::<CameraUniform>(), 80);
+    }
+
+    #[test]
+    fn test_frustum_corners_project_to_clip_edges() {
+        let camera = Camera::new(1.5);
+        let view_proj = camera.build_view_projection_matrix();
+        for (i, corner) in camera.frustum_corners(1.0, 10.0).into_iter().enumerate() {
+            let ndc = view_proj.project_point3(corner);
+            assert!((ndc.x.abs() - 1.0).abs() < 1e-4, "corner {}: {:?}", i, ndc);
+            assert!((ndc.y.abs() - 1.0).abs() < 1e-4, "corner {}: {:?}", i, ndc);
+        }
+        let depth = (camera.frustum_corners(2.0, 3.0)[5] - camera.position()).dot(camera.forward());
+        assert!((depth - 3.0).abs() < 1e-4);
     }
 
     #[test]
@@ -0,0 +1,389 @@
+//! glTF 2.0（GLB）エクスポート
+//!
+//! Entityの `Name`・`Transform` 階層をノードに、`MeshHandle` のメッシュを
+//! POSITION / NORMAL / COLOR_0（UVがあればTEXCOORD_0）とインデックスを持つglTFメッシュとして書き出す。
+//! `Material` はメタリック・ラフネスのglTFマテリアル（unlitは `KHR_materials_unlit`）になる。
+//! 同じメッシュ・マテリアルの組を参照するEntityは1つのglTFメッシュを共有する。
+//! glTFのマテリアルはメッシュのプリミティブに属するため、メッシュを持たないEntity
+//! （`MeshHandle::NONE` など）の `Material` は書き出されない
+
+use std::collections::HashMap;
+
+use glam::{Quat, Vec3};
+use serde_json::{json, Map, Value};
+
+use engine_core::{EntityId, Material, MeshHandle, Name, Transform, World};
+
+use crate::mesh::Mesh;
+
+/// GLBヘッダのマジック（"glTF"）
+const GLB_MAGIC: u32 = 0x4654_6C67;
+/// GLBチャンク種別
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+/// アクセサの成分型
+const COMPONENT_FLOAT: u32 = 5126;
+const COMPONENT_UNSIGNED_INT: u32 = 5125;
+/// バッファビューの用途
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// WorldをGLBとして書き出す
+///
+/// `mesh_source` はハンドルからメッシュの頂点データを引く（GPU側のストアなど）。
+/// MeshHandleを持たずTransformを持つEntityは描画時と同じく組み込みのCubeとして扱う。
+/// メッシュを書き出さないEntityの `Material` は失われる（描画にも使われない）
+pub fn export_glb<'a>(world: &World, mesh_source: impl Fn(MeshHandle) -> Option<&'a Mesh>) -> Vec<u8> {
+    let mut exporter = Exporter {
+        world,
+        mesh_source,
+        nodes: Vec::new(),
+        meshes: Vec::new(),
+        mesh_indices: HashMap::new(),
+        materials: Vec::new(),
+        accessors: Vec::new(),
+        buffer_views: Vec::new(),
+        bin: Vec::new(),
+    };
+
+    let mut roots: Vec<EntityId> = world
+        .iter_entities()
+        .filter(|&entity| world.parent(entity).is_none())
+        .collect();
+    roots.sort_by_key(|entity| entity.to_u32());
+    let root_nodes: Vec<usize> = roots.into_iter().map(|entity| exporter.node(entity)).collect();
+
+    let mut document = json!({
+        "asset": { "version": "2.0", "generator": "wasm_engine" },
+        "scene": 0,
+        "scenes": [{ "nodes": root_nodes }],
+        "nodes": exporter.nodes,
+    });
+    if !exporter.materials.is_empty() {
+        if exporter.materials.iter().any(|m| m.unlit) {
+            document["extensionsUsed"] = json!(["KHR_materials_unlit"]);
+        }
+        document["materials"] = exporter.materials.iter().map(material_json).collect();
+    }
+    if !exporter.meshes.is_empty() {
+        document["meshes"] = Value::Array(exporter.meshes);
+        document["accessors"] = Value::Array(exporter.accessors);
+        document["bufferViews"] = Value::Array(exporter.buffer_views);
+        document["buffers"] = json!([{ "byteLength": exporter.bin.len() }]);
+    }
+
+    write_glb(&document, &exporter.bin)
+}
+
+struct Exporter<'w, F> {
+    world: &'w World,
+    mesh_source: F,
+    nodes: Vec<Value>,
+    meshes: Vec<Value>,
+    /// 書き出し済みメッシュ（(ハンドル, マテリアル番号)→glTFメッシュ番号）
+    mesh_indices: HashMap<(MeshHandle, Option<usize>), Option<usize>>,
+    /// 書き出し済みマテリアル（番号順）
+    materials: Vec<Material>,
+    accessors: Vec<Value>,
+    buffer_views: Vec<Value>,
+    bin: Vec<u8>,
+}
+
+impl<'a, F> Exporter<'_, F>
+where
+    F: Fn(MeshHandle) -> Option<&'a Mesh>,
+{
+    /// Entityとその子孫をノードとして追加し、ノード番号を返す
+    fn node(&mut self, entity: EntityId) -> usize {
+        let index = self.nodes.len();
+        self.nodes.push(Value::Null);
+
+        let mut node = Map::new();
+        if let Some(name) = self.world.get::<Name>(entity) {
+            node.insert("name".into(), json!(name.as_str()));
+        }
+        let transform = self.world.get::<Transform>(entity);
+        if let Some(t) = transform {
+            if t.position != Vec3::ZERO {
+                node.insert("translation".into(), json!(t.position.to_array()));
+            }
+            if t.rotation != Quat::IDENTITY {
+                node.insert("rotation".into(), json!(t.rotation.to_array()));
+            }
+            if t.scale != Vec3::ONE {
+                node.insert("scale".into(), json!(t.scale.to_array()));
+            }
+        }
+
+        // 描画と同じ規則: Transformを持つEntityのみ描画され、MeshHandleがなければCube
+        let handle = match self.world.get::<MeshHandle>(entity) {
+            Some(handle) => Some(*handle),
+            None => transform.map(|_| MeshHandle::CUBE),
+        };
+        // 書き出すメッシュがなければマテリアルも登録しない
+        if let Some(handle) = handle.filter(|&handle| self.source_mesh(handle).is_some()) {
+            let material = self.world.get::<Material>(entity).map(|m| self.material(m));
+            if let Some(mesh) = self.mesh(handle, material) {
+                node.insert("mesh".into(), json!(mesh));
+            }
+        }
+
+        let children: Vec<usize> = self
+            .world
+            .children(entity)
+            .to_vec()
+            .into_iter()
+            .map(|child| self.node(child))
+            .collect();
+        if !children.is_empty() {
+            node.insert("children".into(), json!(children));
+        }
+
+        self.nodes[index] = Value::Object(node);
+        index
+    }
+
+    /// マテリアルを登録して番号を返す（同じ値なら再利用）
+    fn material(&mut self, material: &Material) -> usize {
+        match self.materials.iter().position(|m| m == material) {
+            Some(index) => index,
+            None => {
+                self.materials.push(material.clone());
+                self.materials.len() - 1
+            }
+        }
+    }
+
+    /// メッシュを書き出してglTFメッシュ番号を返す（書き出し済みなら再利用）
+    fn mesh(&mut self, handle: MeshHandle, material: Option<usize>) -> Option<usize> {
+        if let Some(&index) = self.mesh_indices.get(&(handle, material)) {
+            return index;
+        }
+        let index = self
+            .source_mesh(handle)
+            .map(|mesh| self.write_mesh(handle, mesh, material));
+        self.mesh_indices.insert((handle, material), index);
+        index
+    }
+
+    /// 書き出せるメッシュ（NONEや三角形のないメッシュはNone）
+    fn source_mesh(&self, handle: MeshHandle) -> Option<&'a Mesh> {
+        (self.mesh_source)(handle).filter(|mesh| !handle.is_none() && !mesh.indices.is_empty())
+    }
+
+    fn write_mesh(&mut self, handle: MeshHandle, mesh: &Mesh, material: Option<usize>) -> usize {
+        let positions: Vec<[f32; 3]> = mesh.vertices.iter().map(|v| v.position).collect();
+        let normals: Vec<[f32; 3]> = mesh.vertices.iter().map(|v| v.normal).collect();
+        let colors: Vec<[f32; 3]> = mesh.vertices.iter().map(|v| v.color).collect();
+
+        // POSITIONはmin/maxが必須
+        let (min, max) = positions.iter().fold(
+            ([f32::MAX; 3], [f32::MIN; 3]),
+            |(min, max), p| {
+                (
+                    [0, 1, 2].map(|i| min[i].min(p[i])),
+                    [0, 1, 2].map(|i| max[i].max(p[i])),
+                )
+            },
+        );
+        let position = self.accessor(
+            bytemuck::cast_slice(&positions),
+            TARGET_ARRAY_BUFFER,
+            json!({ "componentType": COMPONENT_FLOAT, "count": positions.len(), "type": "VEC3", "min": min, "max": max }),
+        );
+        let normal = self.accessor(
+            bytemuck::cast_slice(&normals),
+            TARGET_ARRAY_BUFFER,
+            json!({ "componentType": COMPONENT_FLOAT, "count": normals.len(), "type": "VEC3" }),
+        );
+        let color = self.accessor(
+            bytemuck::cast_slice(&colors),
+            TARGET_ARRAY_BUFFER,
+            json!({ "componentType": COMPONENT_FLOAT, "count": colors.len(), "type": "VEC3" }),
+        );
+        let indices = self.accessor(
+            bytemuck::cast_slice(&mesh.indices),
+            TARGET_ELEMENT_ARRAY_BUFFER,
+            json!({ "componentType": COMPONENT_UNSIGNED_INT, "count": mesh.indices.len(), "type": "SCALAR" }),
+        );
+
+        let name = if handle == MeshHandle::CUBE {
+            "Cube".to_string()
+        } else {
+            format!("Mesh {}", handle.id())
+        };
+        let mut primitive = json!({
+            "attributes": { "POSITION": position, "NORMAL": normal, "COLOR_0": color },
+            "indices": indices,
+        });
+        if mesh.has_uvs() {
+            primitive["attributes"]["TEXCOORD_0"] = json!(self.accessor(
+                bytemuck::cast_slice(&mesh.uvs),
+                TARGET_ARRAY_BUFFER,
+                json!({ "componentType": COMPONENT_FLOAT, "count": mesh.uvs.len(), "type": "VEC2" }),
+            ));
+        }
+        if let Some(material) = material {
+            primitive["material"] = json!(material);
+        }
+        self.meshes.push(json!({ "name": name, "primitives": [primitive] }));
+        self.meshes.len() - 1
+    }
+
+    /// バイナリにデータを追加し、バッファビューとアクセサを作ってアクセサ番号を返す
+    fn accessor(&mut self, data: &[u8], target: u32, mut accessor: Value) -> usize {
+        // 全ての成分は4バイトなので、4バイト境界に揃えておけば良い
+        self.bin.resize(self.bin.len().next_multiple_of(4), 0);
+        self.buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": self.bin.len(),
+            "byteLength": data.len(),
+            "target": target,
+        }));
+        self.bin.extend_from_slice(data);
+
+        accessor["bufferView"] = json!(self.buffer_views.len() - 1);
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+}
+
+/// MaterialをglTFマテリアルに変換
+///
+/// 発光色は1を超えてもそのまま書き出す（読み戻したときに値が変わらないように）
+fn material_json(material: &Material) -> Value {
+    let mut value = json!({
+        "pbrMetallicRoughness": {
+            "baseColorFactor": material.base_color.to_array(),
+            "metallicFactor": material.metallic,
+            "roughnessFactor": material.roughness,
+        },
+    });
+    if material.emissive != Vec3::ZERO {
+        value["emissiveFactor"] = json!(material.emissive.to_array());
+    }
+    if material.unlit {
+        value["extensions"] = json!({ "KHR_materials_unlit": {} });
+    }
+    value
+}
+
+/// JSONとバイナリをGLBコンテナにまとめる
+fn write_glb(document: &Value, bin: &[u8]) -> Vec<u8> {
+    // チャンクは4バイト境界に揃える（JSONは空白、BINは0で埋める）
+    let mut json = document.to_string().into_bytes();
+    json.resize(json.len().next_multiple_of(4), b' ');
+    let bin_len = bin.len().next_multiple_of(4);
+
+    let mut total = 12 + 8 + json.len();
+    if !bin.is_empty() {
+        total += 8 + bin_len;
+    }
+
+    let mut bytes = Vec::with_capacity(total);
+    bytes.extend_from_slice(&GLB_MAGIC.to_le_bytes());
+    bytes.extend_from_slice(&2u32.to_le_bytes());
+    bytes.extend_from_slice(&(total as u32).to_le_bytes());
+    bytes.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&CHUNK_JSON.to_le_bytes());
+    bytes.extend_from_slice(&json);
+    if !bin.is_empty() {
+        bytes.extend_from_slice(&(bin_len as u32).to_le_bytes());
+        bytes.extend_from_slice(&CHUNK_BIN.to_le_bytes());
+        bytes.extend_from_slice(bin);
+        bytes.resize(total, 0);
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::parse_gltf;
+
+    fn spawn(world: &mut World, name: &str, transform: Transform) -> EntityId {
+        let entity = world.spawn();
+        world.insert(entity, Name::new(name));
+        world.insert(entity, transform);
+        entity
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut sphere = Mesh::uv_sphere(0.5, 8, 4);
+        sphere.vertices[0].color = [0.25, 0.5, 0.75];
+        let cube = Mesh::cube();
+        let source = |handle: MeshHandle| match handle.id() {
+            0 => Some(&cube),
+            5 => Some(&sphere),
+            _ => None,
+        };
+
+        let mut world = World::new();
+        let root = spawn(
+            &mut world,
+            "Root",
+            Transform::new(Vec3::new(1.0, 2.0, 3.0), Quat::from_rotation_y(0.7), Vec3::ONE),
+        );
+        world.insert(root, MeshHandle::NONE);
+        // メッシュのないEntityのマテリアルは書き出されない
+        world.insert(root, Material::from_color(glam::Vec4::ONE));
+        let ball = spawn(&mut world, "Ball", Transform::new(Vec3::X, Quat::IDENTITY, Vec3::splat(2.0)));
+        world.insert(ball, MeshHandle::new(5));
+        world.set_parent(ball, Some(root));
+        let glowing = Material {
+            emissive: Vec3::new(2.0, 0.5, 0.0),
+            metallic: 1.0,
+            unlit: true,
+            ..Material::from_color(glam::Vec4::new(0.2, 0.4, 0.6, 0.8))
+        };
+        world.insert(ball, glowing.clone());
+        let other = spawn(&mut world, "Other Ball", Transform::default());
+        world.insert(other, MeshHandle::new(5));
+        world.set_parent(other, Some(root));
+        // MeshHandleのないEntityはCube
+        spawn(&mut world, "Box", Transform::default());
+
+        let scene = parse_gltf(&export_glb(&world, source)).unwrap();
+        assert!(scene.warnings.is_empty());
+        assert_eq!(scene.roots.len(), 2);
+        // マテリアルの異なるボール2つとCubeで3メッシュ
+        assert_eq!(scene.meshes.len(), 3);
+
+        let root_node = &scene.nodes[scene.roots[0]];
+        assert_eq!(root_node.name, "Root");
+        assert_eq!(root_node.transform, *world.get::<Transform>(root).unwrap());
+        assert_eq!(root_node.mesh, None);
+        assert_eq!(root_node.children.len(), 2);
+
+        let ball_node = &scene.nodes[root_node.children[0]];
+        assert_eq!(ball_node.name, "Ball");
+        assert_eq!(ball_node.transform, *world.get::<Transform>(ball).unwrap());
+        let mesh = &scene.meshes[ball_node.mesh.unwrap()].mesh;
+        assert_eq!(mesh.vertices, sphere.vertices);
+        assert_eq!(mesh.indices, sphere.indices);
+        assert_eq!(mesh.uvs, sphere.uvs);
+        let material = scene.meshes[ball_node.mesh.unwrap()].material.unwrap();
+        assert_eq!(scene.materials[material].to_material(), glowing);
+
+        let other_node = &scene.nodes[root_node.children[1]];
+        assert_eq!(scene.meshes[other_node.mesh.unwrap()].material, None);
+        assert_eq!(scene.meshes[other_node.mesh.unwrap()].mesh.vertices, sphere.vertices);
+
+        let box_node = &scene.nodes[scene.roots[1]];
+        assert_eq!(scene.meshes[box_node.mesh.unwrap()].mesh.vertices, cube.vertices);
+        assert_eq!(scene.materials.len(), 1);
+
+        let mut imported = World::new();
+        let roots = scene.spawn(&mut imported, &[None; 3]);
+        assert!(imported.get::<Material>(roots[0]).is_none());
+    }
+
+    #[test]
+    fn test_empty_world() {
+        let scene = parse_gltf(&export_glb(&World::new(), |_| None)).unwrap();
+        assert!(scene.nodes.is_empty());
+        assert!(scene.meshes.is_empty());
+    }
+}
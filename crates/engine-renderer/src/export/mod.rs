@@ -0,0 +1,7 @@
+//! シーンエクスポートモジュール
+//!
+//! WorldのEntity階層とメッシュを外部ファイル形式に書き出す
+
+pub mod gltf;
+
+pub use gltf::export_glb;
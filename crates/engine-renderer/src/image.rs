@@ -0,0 +1,588 @@
+//! 画像デコードモジュール
+//!
+//! PNG・JPEG・Radiance HDRのファイルをデコードしてテクスチャデータを作成する。
+//! 色空間はファイルの情報ではなく呼び出し側の指定に従う（HDRは常にリニア）
+
+use std::fmt;
+use std::io::Cursor;
+
+use crate::texture::{linear_to_srgb, srgb_to_linear, ColorSpace, TextureData, TextureError};
+
+/// デコードできる画像のピクセル数の上限
+pub const MAX_IMAGE_PIXELS: u64 = 1 << 25;
+/// HDR（RGBA f32、1ピクセル16バイト）のピクセル数の上限
+pub const MAX_HDR_PIXELS: u64 = 1 << 23;
+
+/// 画像ファイルの形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    /// Radiance HDR（RGBE）
+    Hdr,
+}
+
+impl ImageFormat {
+    /// 先頭のシグネチャから形式を判定
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if bytes.starts_with(&[0xff, 0xd8]) {
+            Some(Self::Jpeg)
+        } else if bytes.starts_with(b"#?RADIANCE") || bytes.starts_with(b"#?RGBE") {
+            Some(Self::Hdr)
+        } else {
+            None
+        }
+    }
+}
+
+/// 画像デコードエラー
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageError {
+    /// PNG・JPEG・HDRのいずれでもない
+    UnknownFormat,
+    /// PNGのデコードに失敗
+    Png(String),
+    /// JPEGのデコードに失敗
+    Jpeg(String),
+    /// HDRの解析に失敗
+    Hdr(String),
+    /// ピクセル数が上限（`MAX_IMAGE_PIXELS`、HDRは `MAX_HDR_PIXELS`）を超える
+    TooLarge { width: u32, height: u32, limit: u64 },
+    /// テクスチャデータの作成に失敗
+    Texture(TextureError),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFormat => write!(f, "unknown image format (expected PNG, JPEG or HDR)"),
+            Self::Png(message) => write!(f, "PNG: {}", message),
+            Self::Jpeg(message) => write!(f, "JPEG: {}", message),
+            Self::Hdr(message) => write!(f, "HDR: {}", message),
+            Self::TooLarge { width, height, limit } => {
+                write!(f, "image size {}x{} exceeds {} pixels", width, height, limit)
+            }
+            Self::Texture(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<TextureError> for ImageError {
+    fn from(e: TextureError) -> Self {
+        Self::Texture(e)
+    }
+}
+
+/// デコードしたピクセル列（行は上から下、RGBAの順）
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImagePixels {
+    /// 8bit（PNG・JPEG）
+    Rgba8(Vec<u8>),
+    /// リニアな浮動小数点（HDR）
+    RgbaF32(Vec<f32>),
+}
+
+/// デコードした画像
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: ImagePixels,
+}
+
+/// テクスチャへのデコード設定
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecodeOptions {
+    /// 8bit画像の色空間（HDRでは無視される）
+    pub color_space: ColorSpace,
+    /// 色にアルファを乗算しておく
+    pub premultiply_alpha: bool,
+    pub generate_mipmaps: bool,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self {
+            color_space: ColorSpace::Srgb,
+            premultiply_alpha: false,
+            generate_mipmaps: true,
+        }
+    }
+}
+
+/// 画像ファイルをデコードしてテクスチャデータを作成
+pub fn decode_texture(bytes: &[u8], options: &DecodeOptions) -> Result<TextureData, ImageError> {
+    let image = decode_image(bytes)?;
+    let data = match image.pixels {
+        ImagePixels::Rgba8(mut rgba) => {
+            if options.premultiply_alpha {
+                premultiply_alpha(&mut rgba, options.color_space);
+            }
+            TextureData::from_rgba8(&rgba, image.width, image.height, options.color_space, options.generate_mipmaps)?
+        }
+        // HDRは不透明なので乗算しても変わらない
+        ImagePixels::RgbaF32(rgba) => {
+            TextureData::from_rgba_f32(&rgba, image.width, image.height, options.generate_mipmaps)?
+        }
+    };
+    Ok(data)
+}
+
+/// 画像ファイルを形式を判定してデコード
+pub fn decode_image(bytes: &[u8]) -> Result<Image, ImageError> {
+    match ImageFormat::detect(bytes) {
+        Some(ImageFormat::Png) => decode_png(bytes),
+        Some(ImageFormat::Jpeg) => decode_jpeg(bytes),
+        Some(ImageFormat::Hdr) => decode_hdr(bytes),
+        None => Err(ImageError::UnknownFormat),
+    }
+}
+
+/// RGBA8の色にアルファを乗算する
+///
+/// sRGBの色はリニアに戻してから乗算する
+pub fn premultiply_alpha(rgba: &mut [u8], color_space: ColorSpace) {
+    for texel in rgba.chunks_exact_mut(4) {
+        let alpha = texel[3] as f32 / 255.0;
+        for c in &mut texel[..3] {
+            let v = match color_space {
+                ColorSpace::Srgb => linear_to_srgb(srgb_to_linear(*c) * alpha),
+                ColorSpace::Linear => *c as f32 / 255.0 * alpha,
+            };
+            *c = (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+    }
+}
+
+fn check_pixels(width: u32, height: u32, limit: u64) -> Result<(), ImageError> {
+    if width as u64 * height as u64 > limit {
+        return Err(ImageError::TooLarge { width, height, limit });
+    }
+    Ok(())
+}
+
+// ============================================================
+// PNG
+// ============================================================
+
+/// PNGをデコード（パレット・16bit・グレースケールはRGBA8に展開する）
+fn decode_png(bytes: &[u8]) -> Result<Image, ImageError> {
+    let png_error = |e: png::DecodingError| ImageError::Png(e.to_string());
+
+    let mut decoder = png::Decoder::new(Cursor::new(bytes));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(png_error)?;
+    let (width, height) = reader.info().size();
+    check_pixels(width, height, MAX_IMAGE_PIXELS)?;
+
+    let size = reader
+        .output_buffer_size()
+        .ok_or_else(|| ImageError::Png("image buffer size overflows".to_string()))?;
+    let mut buffer = vec![0; size];
+    let info = reader.next_frame(&mut buffer).map_err(png_error)?;
+    buffer.truncate(info.buffer_size());
+
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => buffer,
+        png::ColorType::Rgb => buffer.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => buffer.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => buffer.iter().flat_map(|&l| [l, l, l, 255]).collect(),
+        png::ColorType::Indexed => {
+            return Err(ImageError::Png("indexed color was not expanded".to_string()));
+        }
+    };
+    Ok(Image {
+        width,
+        height,
+        pixels: ImagePixels::Rgba8(rgba),
+    })
+}
+
+// ============================================================
+// JPEG
+// ============================================================
+
+/// JPEGをデコード（グレースケール・CMYKはRGBA8に変換する）
+fn decode_jpeg(bytes: &[u8]) -> Result<Image, ImageError> {
+    let mut decoder = jpeg_decoder::Decoder::new(bytes);
+    decoder.read_info().map_err(|e| ImageError::Jpeg(e.to_string()))?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| ImageError::Jpeg("missing frame header".to_string()))?;
+    let (width, height) = (info.width as u32, info.height as u32);
+    check_pixels(width, height, MAX_IMAGE_PIXELS)?;
+
+    let pixels = decoder.decode().map_err(|e| ImageError::Jpeg(e.to_string()))?;
+    let rgba: Vec<u8> = match info.pixel_format {
+        jpeg_decoder::PixelFormat::L8 => pixels.iter().flat_map(|&l| [l, l, l, 255]).collect(),
+        jpeg_decoder::PixelFormat::L16 => pixels
+            .chunks_exact(2)
+            .flat_map(|p| {
+                let l = (u16::from_ne_bytes([p[0], p[1]]) >> 8) as u8;
+                [l, l, l, 255]
+            })
+            .collect(),
+        jpeg_decoder::PixelFormat::RGB24 => pixels.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        jpeg_decoder::PixelFormat::CMYK32 => pixels
+            .chunks_exact(4)
+            .flat_map(|p| {
+                let k = 255 - p[3] as u32;
+                let channel = |c: u8| ((255 - c as u32) * k / 255) as u8;
+                [channel(p[0]), channel(p[1]), channel(p[2]), 255]
+            })
+            .collect(),
+    };
+    if rgba.len() != width as usize * height as usize * 4 {
+        return Err(ImageError::Jpeg("decoded data does not match image size".to_string()));
+    }
+    Ok(Image {
+        width,
+        height,
+        pixels: ImagePixels::Rgba8(rgba),
+    })
+}
+
+// ============================================================
+// Radiance HDR
+// ============================================================
+
+/// Radiance HDR（32-bit_rle_rgbe）をデコード
+///
+/// 解像度行は `-Y h +X w`（上から下）と `+Y h +X w`（下から上）に対応する
+fn decode_hdr(bytes: &[u8]) -> Result<Image, ImageError> {
+    let hdr_error = |message: &str| ImageError::Hdr(message.to_string());
+    let mut cursor = 0;
+    let mut next_line = || -> Result<&str, ImageError> {
+        let rest = &bytes[cursor..];
+        let end = rest
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| hdr_error("unexpected end of header"))?;
+        cursor += end + 1;
+        std::str::from_utf8(&rest[..end]).map_err(|_| hdr_error("header is not valid text"))
+    };
+
+    // ヘッダ（空行まで）
+    next_line()?;
+    loop {
+        let line = next_line()?.trim();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(format) = line.strip_prefix("FORMAT=")
+            && format != "32-bit_rle_rgbe"
+        {
+            return Err(ImageError::Hdr(format!("unsupported format '{}'", format)));
+        }
+    }
+
+    let resolution = next_line()?;
+    let fields: Vec<&str> = resolution.split_whitespace().collect();
+    let (flip_y, height, width) = match fields.as_slice() {
+        [y_axis, h, "+X", w] if *y_axis == "-Y" || *y_axis == "+Y" => {
+            let parse = |s: &str| s.parse::<u32>().ok().filter(|&n| n > 0);
+            match (parse(h), parse(w)) {
+                (Some(h), Some(w)) => (*y_axis == "+Y", h, w),
+                _ => return Err(ImageError::Hdr(format!("invalid resolution '{}'", resolution))),
+            }
+        }
+        _ => return Err(ImageError::Hdr(format!("unsupported resolution '{}'", resolution))),
+    };
+    check_pixels(width, height, MAX_HDR_PIXELS)?;
+
+    // ヘッダの解像度で一括確保せず、読めた行だけ伸ばす（切れたデータで巨大な確保をしない）
+    let row_len = width as usize * 4;
+    let mut data = &bytes[cursor..];
+    let mut rgba = Vec::new();
+    let mut scanline = vec![[0u8; 4]; width as usize];
+    for _ in 0..height {
+        data = read_scanline(data, &mut scanline)?;
+        rgba.extend(scanline.iter().flat_map(|&rgbe| rgbe_to_rgba(rgbe)));
+    }
+    // +Yは下から上の順なので行を入れ替える
+    if flip_y {
+        let rows = height as usize;
+        for y in 0..rows / 2 {
+            let (top, bottom) = rgba.split_at_mut((rows - 1 - y) * row_len);
+            top[y * row_len..(y + 1) * row_len].swap_with_slice(&mut bottom[..row_len]);
+        }
+    }
+
+    Ok(Image {
+        width,
+        height,
+        pixels: ImagePixels::RgbaF32(rgba),
+    })
+}
+
+/// 1行分のRGBEを読み、残りのデータを返す
+///
+/// 新形式のRLE（先頭が 2, 2, 幅の上位, 幅の下位）と非圧縮に対応する
+fn read_scanline<'a>(data: &'a [u8], scanline: &mut [[u8; 4]]) -> Result<&'a [u8], ImageError> {
+    let truncated = || ImageError::Hdr("scanline data is truncated".to_string());
+    let width = scanline.len();
+
+    let is_rle = (8..0x8000).contains(&width)
+        && data.len() >= 4
+        && data[0] == 2
+        && data[1] == 2
+        && ((data[2] as usize) << 8 | data[3] as usize) == width;
+    if !is_rle {
+        let (flat, rest) = data.split_at_checked(width * 4).ok_or_else(truncated)?;
+        for (texel, rgbe) in scanline.iter_mut().zip(flat.chunks_exact(4)) {
+            texel.copy_from_slice(rgbe);
+        }
+        return Ok(rest);
+    }
+
+    let mut data = &data[4..];
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let (&count, rest) = data.split_first().ok_or_else(truncated)?;
+            let (run, count) = if count > 128 {
+                (true, (count - 128) as usize)
+            } else {
+                (false, count as usize)
+            };
+            if count == 0 || x + count > width {
+                return Err(ImageError::Hdr("invalid run length in scanline".to_string()));
+            }
+            let used = if run { 1 } else { count };
+            let (values, rest) = rest.split_at_checked(used).ok_or_else(truncated)?;
+            for (i, texel) in scanline[x..x + count].iter_mut().enumerate() {
+                texel[channel] = if run { values[0] } else { values[i] };
+            }
+            x += count;
+            data = rest;
+        }
+    }
+    Ok(data)
+}
+
+/// RGBE（共有指数）をリニアなRGBAに変換
+fn rgbe_to_rgba([r, g, b, e]: [u8; 4]) -> [f32; 4] {
+    if e == 0 {
+        return [0.0, 0.0, 0.0, 1.0];
+    }
+    let scale = 2f32.powi(e as i32 - 136);
+    [r as f32 * scale, g as f32 * scale, b as f32 * scale, 1.0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::{f16_to_f32, TexelFormat};
+
+    fn encode_png(rgba: &[u8], width: u32, height: u32, color: png::ColorType) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, width, height);
+        encoder.set_color(color);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(rgba).unwrap();
+        writer.finish().unwrap();
+        bytes
+    }
+
+    /// 8x8のグレースケール（全画素128）のベースラインJPEG
+    ///
+    /// DC・ACともに差分0のみを符号化するハフマン表で、各ブロックは1bit（DC=0, EOB）
+    fn gray_jpeg() -> Vec<u8> {
+        let mut bytes = vec![0xff, 0xd8];
+        // DQT: 量子化表0（全て1）
+        bytes.extend_from_slice(&[0xff, 0xdb, 0x00, 0x43, 0x00]);
+        bytes.extend_from_slice(&[1; 64]);
+        // SOF0: 8bit, 8x8, 1成分
+        bytes.extend_from_slice(&[0xff, 0xc0, 0x00, 0x0b, 0x08, 0x00, 0x08, 0x00, 0x08, 0x01, 0x01, 0x11, 0x00]);
+        // DHT: DC表0・AC表0（長さ1の符号1つでシンボル0）
+        for class in [0x00, 0x10] {
+            bytes.extend_from_slice(&[0xff, 0xc4, 0x00, 0x14, class, 1]);
+            bytes.extend_from_slice(&[0; 15]);
+            bytes.push(0x00);
+        }
+        // SOS
+        bytes.extend_from_slice(&[0xff, 0xda, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3f, 0x00]);
+        bytes.push(0x3f);
+        bytes.extend_from_slice(&[0xff, 0xd9]);
+        bytes
+    }
+
+    fn hdr(resolution: &str, scanlines: &[u8]) -> Vec<u8> {
+        let mut bytes = format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\nEXPOSURE=1.0\n\n{}\n", resolution).into_bytes();
+        bytes.extend_from_slice(scanlines);
+        bytes
+    }
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(ImageFormat::detect(&encode_png(&[0; 4], 1, 1, png::ColorType::Rgba)), Some(ImageFormat::Png));
+        assert_eq!(ImageFormat::detect(&gray_jpeg()), Some(ImageFormat::Jpeg));
+        assert_eq!(ImageFormat::detect(b"#?RGBE\n"), Some(ImageFormat::Hdr));
+        assert_eq!(ImageFormat::detect(b"GIF89a"), None);
+        assert_eq!(decode_image(b"GIF89a").unwrap_err(), ImageError::UnknownFormat);
+        assert_eq!(decode_image(&[]).unwrap_err(), ImageError::UnknownFormat);
+    }
+
+    #[test]
+    fn test_decode_png() {
+        let rgba = [255, 0, 0, 255, 0, 255, 0, 128, 0, 0, 255, 0, 10, 20, 30, 40];
+        let image = decode_image(&encode_png(&rgba, 2, 2, png::ColorType::Rgba)).unwrap();
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(image.pixels, ImagePixels::Rgba8(rgba.to_vec()));
+
+        // RGB・グレースケールは不透明なRGBAに展開される
+        let image = decode_image(&encode_png(&[1, 2, 3, 4, 5, 6], 2, 1, png::ColorType::Rgb)).unwrap();
+        assert_eq!(image.pixels, ImagePixels::Rgba8(vec![1, 2, 3, 255, 4, 5, 6, 255]));
+        let image = decode_image(&encode_png(&[7, 200], 1, 1, png::ColorType::GrayscaleAlpha)).unwrap();
+        assert_eq!(image.pixels, ImagePixels::Rgba8(vec![7, 7, 7, 200]));
+    }
+
+    #[test]
+    fn test_decode_jpeg() {
+        let image = decode_image(&gray_jpeg()).unwrap();
+        assert_eq!((image.width, image.height), (8, 8));
+        let ImagePixels::Rgba8(rgba) = image.pixels else {
+            panic!("expected 8-bit pixels");
+        };
+        assert_eq!(rgba.len(), 8 * 8 * 4);
+        assert!(rgba.chunks_exact(4).all(|p| p == [128, 128, 128, 255]));
+    }
+
+    #[test]
+    fn test_decode_hdr() {
+        // 非圧縮の2x1（1.0と0.5・0・約2.0）、+Yは下から上の順
+        let flat = [128, 128, 128, 129, 64, 0, 255, 129];
+        let image = decode_image(&hdr("-Y 1 +X 2", &flat)).unwrap();
+        assert_eq!(
+            image.pixels,
+            ImagePixels::RgbaF32(vec![1.0, 1.0, 1.0, 1.0, 0.5, 0.0, 1.9921875, 1.0])
+        );
+        let image = decode_image(&hdr("+Y 2 +X 1", &flat)).unwrap();
+        assert_eq!(
+            image.pixels,
+            ImagePixels::RgbaF32(vec![0.5, 0.0, 1.9921875, 1.0, 1.0, 1.0, 1.0, 1.0])
+        );
+
+        // RLE（幅8）: Rは連続、Gはリテラル、B・Eは連続
+        let mut rle = vec![2, 2, 0, 8];
+        rle.extend_from_slice(&[128 + 8, 128]);
+        rle.extend_from_slice(&[8, 0, 32, 64, 96, 128, 160, 192, 224]);
+        rle.extend_from_slice(&[128 + 8, 0]);
+        rle.extend_from_slice(&[128 + 4, 129, 128 + 4, 0]);
+        let image = decode_image(&hdr("-Y 1 +X 8", &rle)).unwrap();
+        let ImagePixels::RgbaF32(rgba) = image.pixels else {
+            panic!("expected float pixels");
+        };
+        assert_eq!(&rgba[..4], &[1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(&rgba[12..16], &[1.0, 0.75, 0.0, 1.0]);
+        assert_eq!(&rgba[16..20], &[0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_decode_texture() {
+        let png = encode_png(&[255, 128, 0, 128], 1, 1, png::ColorType::Rgba);
+        let options = DecodeOptions {
+            generate_mipmaps: false,
+            ..Default::default()
+        };
+        let texture = decode_texture(&png, &options).unwrap();
+        assert_eq!(texture.format(), wgpu::TextureFormat::Rgba8UnormSrgb);
+        assert_eq!(texture.mips[0].data, vec![255, 128, 0, 128]);
+
+        let linear = DecodeOptions {
+            color_space: ColorSpace::Linear,
+            premultiply_alpha: true,
+            ..options
+        };
+        let texture = decode_texture(&png, &linear).unwrap();
+        assert_eq!(texture.format(), wgpu::TextureFormat::Rgba8Unorm);
+        assert_eq!(texture.mips[0].data, vec![128, 64, 0, 128]);
+
+        // sRGBはリニア空間で乗算する（リニア0.5倍は約188）
+        let srgb = DecodeOptions {
+            premultiply_alpha: true,
+            ..options
+        };
+        let texture = decode_texture(&png, &srgb).unwrap();
+        assert_eq!(texture.mips[0].data[0], 188);
+        assert_eq!(texture.mips[0].data[2], 0);
+        assert_eq!(texture.mips[0].data[3], 128);
+
+        // HDRは色空間の指定によらずリニアな半精度
+        let texture = decode_texture(&hdr("-Y 1 +X 1", &[128, 64, 0, 130]), &srgb).unwrap();
+        assert_eq!(texture.texel_format, TexelFormat::Rgba16Float);
+        assert_eq!(texture.color_space, ColorSpace::Linear);
+        let half = |i: usize| f16_to_f32(u16::from_le_bytes([texture.mips[0].data[i * 2], texture.mips[0].data[i * 2 + 1]]));
+        assert_eq!([half(0), half(1), half(2), half(3)], [2.0, 1.0, 0.0, 1.0]);
+
+        // ミップマップ
+        let png = encode_png(&[255; 4 * 4 * 4], 4, 4, png::ColorType::Rgba);
+        assert_eq!(decode_texture(&png, &DecodeOptions::default()).unwrap().mip_level_count(), 3);
+    }
+
+    #[test]
+    fn test_corrupt_png() {
+        let png = encode_png(&[10, 20, 30, 255, 40, 50, 60, 255], 2, 1, png::ColorType::Rgba);
+
+        // シグネチャのみ・途中で切れたデータ
+        assert!(matches!(decode_image(&png[..8]), Err(ImageError::Png(_))));
+        assert!(matches!(decode_image(&png[..png.len() - 20]), Err(ImageError::Png(_))));
+
+        // IHDRのCRC不一致
+        let mut bad_crc = png.clone();
+        bad_crc[29] ^= 0xff;
+        assert!(matches!(decode_image(&bad_crc), Err(ImageError::Png(_))));
+
+        // 幅0のIHDR
+        let mut zero_width = png.clone();
+        zero_width[16..20].copy_from_slice(&[0; 4]);
+        assert!(matches!(decode_image(&zero_width), Err(ImageError::Png(_))));
+    }
+
+    #[test]
+    fn test_corrupt_jpeg() {
+        let jpeg = gray_jpeg();
+        assert!(matches!(decode_image(&jpeg[..2]), Err(ImageError::Jpeg(_))));
+        // フレームヘッダの途中で切れている
+        assert!(matches!(decode_image(&jpeg[..80]), Err(ImageError::Jpeg(_))));
+        // SOS以前で終わり、画像データがない
+        let mut no_scan = jpeg[..jpeg.len() - 13].to_vec();
+        no_scan.extend_from_slice(&[0xff, 0xd9]);
+        assert!(matches!(decode_image(&no_scan), Err(ImageError::Jpeg(_))));
+    }
+
+    #[test]
+    fn test_corrupt_hdr() {
+        let err = |bytes: &[u8]| match decode_image(bytes) {
+            Err(ImageError::Hdr(message)) => message,
+            other => panic!("expected HDR error, got {:?}", other),
+        };
+
+        assert!(err(b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n").contains("end of header"));
+        assert!(err(b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n\0\0\0\0").contains("xyze"));
+        assert!(err(&hdr("+X 1 -Y 1", &[0; 4])).contains("unsupported resolution"));
+        assert!(err(&hdr("-Y 0 +X 1", &[])).contains("invalid resolution"));
+        assert!(err(&hdr("-Y one +X 1", &[])).contains("invalid resolution"));
+
+        // 非圧縮の行が足りない
+        assert!(err(&hdr("-Y 2 +X 1", &[128, 128, 128, 129])).contains("truncated"));
+        // RLEの行が途中で切れている・連続長が幅を超える
+        assert!(err(&hdr("-Y 1 +X 8", &[2, 2, 0, 8, 128 + 8, 1])).contains("truncated"));
+        assert!(err(&hdr("-Y 1 +X 8", &[2, 2, 0, 8, 128 + 9, 1])).contains("run length"));
+        assert!(err(&hdr("-Y 1 +X 8", &[2, 2, 0, 8, 0])).contains("run length"));
+
+        // HDRはf32で展開するので8bitの画像より上限が低い
+        assert_eq!(
+            decode_image(&hdr("-Y 4096 +X 4096", &[])).unwrap_err(),
+            ImageError::TooLarge { width: 4096, height: 4096, limit: MAX_HDR_PIXELS }
+        );
+        // 上限内でも、データが全行分に満たなければ展開前に失敗する
+        assert!(err(&hdr("-Y 2048 +X 4096", &[2, 2, 16, 0])).contains("truncated"));
+        assert!(err(&hdr("-Y 4096 +X 4", &[0; 4 * 4 * 4095])).contains("truncated"));
+    }
+}
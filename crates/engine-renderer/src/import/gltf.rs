@@ -0,0 +1,1213 @@
+//! glTF 2.0インポート
+//!
+//! `.gltf`（data URIで埋め込まれたバッファ）と `.glb` を読み込み、
+//! メッシュ・マテリアル・ノード階層を取り出す。
+//! 未対応の機能はエラーではなく警告として `GltfScene::warnings` に記録する
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use glam::{Mat4, Quat, Vec3, Vec4};
+use serde::Deserialize;
+use serde_json::Value;
+
+use engine_core::{EntityId, Material, MeshHandle, Name, Transform, World};
+
+use super::weighted_corner_normals;
+use crate::base64;
+use crate::mesh::{Mesh, Vertex};
+
+/// GLBヘッダのマジック（"glTF"）
+const GLB_MAGIC: u32 = 0x4654_6C67;
+/// GLBチャンク種別
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+/// 対応している拡張
+const SUPPORTED_EXTENSIONS: &[&str] = &["KHR_materials_unlit"];
+
+/// プリミティブのトポロジー
+const MODE_TRIANGLES: u32 = 4;
+const MODE_TRIANGLE_STRIP: u32 = 5;
+const MODE_TRIANGLE_FAN: u32 = 6;
+
+/// bufferViewのないアクセサ（全て0）の成分数の上限
+const MAX_ZERO_ACCESSOR_VALUES: usize = 1 << 24;
+
+/// glTFインポートエラー
+#[derive(Debug, Clone, PartialEq)]
+pub enum GltfError {
+    /// GLBのヘッダ・チャンクが不正
+    InvalidGlb(String),
+    /// JSONとして解釈できない
+    InvalidJson(String),
+    /// glTF 2.0以外
+    UnsupportedVersion(String),
+    /// 外部ファイルを参照するURI（埋め込みのみ対応）
+    ExternalUri(String),
+    /// data URIのBase64が不正
+    InvalidDataUri { buffer: usize },
+    /// 存在しない要素への参照
+    InvalidReference { kind: &'static str, index: usize },
+    /// アクセサの内容が不正
+    InvalidAccessor { index: usize, reason: String },
+    /// ノードが複数の親を持つ、または循環している
+    InvalidHierarchy { node: usize },
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidGlb(reason) => write!(f, "invalid GLB: {}", reason),
+            Self::InvalidJson(reason) => write!(f, "invalid glTF JSON: {}", reason),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported glTF version '{}' (2.x is required)", version)
+            }
+            Self::ExternalUri(uri) => write!(
+                f,
+                "external resource '{}' is not supported; embed buffers or use GLB",
+                uri
+            ),
+            Self::InvalidDataUri { buffer } => {
+                write!(f, "buffer {} has an invalid base64 data URI", buffer)
+            }
+            Self::InvalidReference { kind, index } => {
+                write!(f, "reference to missing {} {}", kind, index)
+            }
+            Self::InvalidAccessor { index, reason } => {
+                write!(f, "accessor {}: {}", index, reason)
+            }
+            Self::InvalidHierarchy { node } => {
+                write!(f, "node {} has multiple parents or is part of a cycle", node)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GltfError {}
+
+/// PBRマテリアルの係数
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfMaterial {
+    pub name: Option<String>,
+    /// ベースカラー（RGBA、リニア）
+    pub base_color: [f32; 4],
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emissive: [f32; 3],
+    /// KHR_materials_unlit
+    pub unlit: bool,
+}
+
+impl GltfMaterial {
+    /// Materialコンポーネントに変換（テクスチャは参照しない）
+    pub fn to_material(&self) -> Material {
+        Material {
+            base_color: Vec4::from_array(self.base_color),
+            metallic: self.metallic,
+            roughness: self.roughness,
+            emissive: Vec3::from_array(self.emissive),
+            unlit: self.unlit,
+            ..Material::default()
+        }
+    }
+}
+
+impl Default for GltfMaterial {
+    fn default() -> Self {
+        Self {
+            name: None,
+            base_color: [1.0; 4],
+            metallic: 1.0,
+            roughness: 1.0,
+            emissive: [0.0; 3],
+            unlit: false,
+        }
+    }
+}
+
+/// glTFのメッシュ（全プリミティブを1つのMeshに結合したもの）
+#[derive(Debug, Clone)]
+pub struct GltfMesh {
+    pub name: Option<String>,
+    pub mesh: Mesh,
+    /// 全プリミティブ共通のマテリアル（異なる場合は頂点色に焼き込まれNone）
+    pub material: Option<usize>,
+}
+
+/// ノード
+#[derive(Debug, Clone)]
+pub struct GltfNode {
+    pub name: String,
+    pub transform: Transform,
+    pub mesh: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+/// 読み込んだシーン
+#[derive(Debug, Clone, Default)]
+pub struct GltfScene {
+    pub nodes: Vec<GltfNode>,
+    /// シーンのルートノード
+    pub roots: Vec<usize>,
+    /// glTFのメッシュ番号順
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<GltfMaterial>,
+    /// 未対応機能などの警告
+    pub warnings: Vec<String>,
+}
+
+impl GltfScene {
+    /// ノード階層をEntityとして生成し、ルートEntityを返す
+    ///
+    /// `mesh_handles` はglTFのメッシュ番号に対応するハンドル（Noneならメッシュなし）。
+    /// 各EntityにはName・Transform・MeshHandleが付く（メッシュのないノードは `MeshHandle::NONE`）。
+    /// メッシュにマテリアルがあればMaterialも付く
+    pub fn spawn(&self, world: &mut World, mesh_handles: &[Option<MeshHandle>]) -> Vec<EntityId> {
+        self.roots
+            .iter()
+            .map(|&root| self.spawn_node(world, root, None, mesh_handles))
+            .collect()
+    }
+
+    fn spawn_node(
+        &self,
+        world: &mut World,
+        index: usize,
+        parent: Option<EntityId>,
+        mesh_handles: &[Option<MeshHandle>],
+    ) -> EntityId {
+        let node = &self.nodes[index];
+        let entity = world.spawn();
+        world.insert(entity, Name::new(node.name.as_str()));
+        world.insert(entity, node.transform.clone());
+        // メッシュのないノードは既定のCubeで描画されないようNONEにする
+        let handle = node.mesh.and_then(|m| mesh_handles.get(m).copied().flatten());
+        world.insert(entity, handle.unwrap_or(MeshHandle::NONE));
+        if let Some(material) = node
+            .mesh
+            .and_then(|m| self.meshes.get(m))
+            .and_then(|mesh| mesh.material)
+            .and_then(|m| self.materials.get(m))
+        {
+            world.insert(entity, material.to_material());
+        }
+        if parent.is_some() {
+            world.set_parent(entity, parent);
+        }
+        for &child in &node.children {
+            self.spawn_node(world, child, Some(entity), mesh_handles);
+        }
+        entity
+    }
+}
+
+/// `.gltf`（JSON）または `.glb` のバイト列を読み込む
+pub fn parse_gltf(bytes: &[u8]) -> Result<GltfScene, GltfError> {
+    let (json, bin) = if bytes.len() >= 4 && read_u32(bytes, 0) == GLB_MAGIC {
+        split_glb(bytes)?
+    } else {
+        (bytes, None)
+    };
+
+    let document: Document =
+        serde_json::from_slice(json).map_err(|e| GltfError::InvalidJson(e.to_string()))?;
+    if !document.asset.version.starts_with("2.") {
+        return Err(GltfError::UnsupportedVersion(document.asset.version));
+    }
+
+    Importer::new(&document, bin)?.import()
+}
+
+// ============================================================================
+// GLB
+// ============================================================================
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+/// GLBをJSONチャンクとBINチャンクに分ける
+fn split_glb(bytes: &[u8]) -> Result<(&[u8], Option<&[u8]>), GltfError> {
+    if bytes.len() < 20 {
+        return Err(GltfError::InvalidGlb("file is shorter than the header".into()));
+    }
+    let version = read_u32(bytes, 4);
+    if version != 2 {
+        return Err(GltfError::InvalidGlb(format!("container version {} is not supported", version)));
+    }
+    let length = (read_u32(bytes, 8) as usize).min(bytes.len());
+
+    let mut offset = 12;
+    let mut json = None;
+    let mut bin = None;
+    while offset + 8 <= length {
+        let chunk_length = read_u32(bytes, offset) as usize;
+        let chunk_type = read_u32(bytes, offset + 4);
+        let start = offset + 8;
+        let end = start
+            .checked_add(chunk_length)
+            .filter(|&end| end <= length)
+            .ok_or_else(|| GltfError::InvalidGlb(format!("chunk at byte {} overruns the file", offset)))?;
+        match chunk_type {
+            CHUNK_JSON if json.is_none() => json = Some(&bytes[start..end]),
+            CHUNK_BIN if bin.is_none() => bin = Some(&bytes[start..end]),
+            _ => {}
+        }
+        // チャンクは4バイト境界に揃えられている
+        offset = end.next_multiple_of(4);
+    }
+
+    let json = json.ok_or_else(|| GltfError::InvalidGlb("missing JSON chunk".into()))?;
+    Ok((json, bin))
+}
+
+// ============================================================================
+// JSONドキュメント（使用する部分のみ）
+// ============================================================================
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Document {
+    asset: AssetDef,
+    scene: Option<usize>,
+    scenes: Vec<SceneDef>,
+    nodes: Vec<NodeDef>,
+    meshes: Vec<MeshDef>,
+    accessors: Vec<AccessorDef>,
+    buffer_views: Vec<BufferViewDef>,
+    buffers: Vec<BufferDef>,
+    materials: Vec<MaterialDef>,
+    skins: Vec<Value>,
+    animations: Vec<Value>,
+    extensions_used: Vec<String>,
+    extensions_required: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AssetDef {
+    version: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SceneDef {
+    nodes: Vec<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct NodeDef {
+    name: Option<String>,
+    children: Vec<usize>,
+    mesh: Option<usize>,
+    matrix: Option<[f32; 16]>,
+    translation: Option<[f32; 3]>,
+    rotation: Option<[f32; 4]>,
+    scale: Option<[f32; 3]>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MeshDef {
+    name: Option<String>,
+    primitives: Vec<PrimitiveDef>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PrimitiveDef {
+    attributes: HashMap<String, usize>,
+    indices: Option<usize>,
+    material: Option<usize>,
+    mode: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct AccessorDef {
+    buffer_view: Option<usize>,
+    byte_offset: usize,
+    component_type: u32,
+    normalized: bool,
+    count: usize,
+    #[serde(rename = "type")]
+    kind: String,
+    sparse: Option<Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct BufferViewDef {
+    buffer: usize,
+    byte_offset: usize,
+    byte_length: usize,
+    byte_stride: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct BufferDef {
+    uri: Option<String>,
+    byte_length: usize,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct MaterialDef {
+    name: Option<String>,
+    pbr_metallic_roughness: Option<PbrDef>,
+    emissive_factor: Option<[f32; 3]>,
+    normal_texture: Option<Value>,
+    occlusion_texture: Option<Value>,
+    emissive_texture: Option<Value>,
+    extensions: HashMap<String, Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct PbrDef {
+    base_color_factor: Option<[f32; 4]>,
+    metallic_factor: Option<f32>,
+    roughness_factor: Option<f32>,
+    base_color_texture: Option<Value>,
+    metallic_roughness_texture: Option<Value>,
+}
+
+// ============================================================================
+// 変換
+// ============================================================================
+
+struct Importer<'a> {
+    document: &'a Document,
+    buffers: Vec<Vec<u8>>,
+    warnings: Vec<String>,
+}
+
+impl<'a> Importer<'a> {
+    fn new(document: &'a Document, bin: Option<&[u8]>) -> Result<Self, GltfError> {
+        let buffers = document
+            .buffers
+            .iter()
+            .enumerate()
+            .map(|(index, buffer)| load_buffer(index, buffer, bin))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            document,
+            buffers,
+            warnings: Vec::new(),
+        })
+    }
+
+    fn import(mut self) -> Result<GltfScene, GltfError> {
+        self.check_extensions();
+        if !self.document.skins.is_empty() {
+            self.warn("skins are not supported; meshes are imported in bind pose".into());
+        }
+        if !self.document.animations.is_empty() {
+            self.warn("animations are not supported and were skipped".into());
+        }
+
+        let materials = self.materials();
+        let meshes = (0..self.document.meshes.len())
+            .map(|index| self.mesh(index, &materials))
+            .collect::<Result<Vec<_>, _>>()?;
+        let nodes = self.nodes()?;
+        let roots = self.roots(&nodes)?;
+
+        Ok(GltfScene {
+            nodes,
+            roots,
+            meshes,
+            materials,
+            warnings: self.warnings,
+        })
+    }
+
+    fn warn(&mut self, message: String) {
+        if !self.warnings.contains(&message) {
+            self.warnings.push(message);
+        }
+    }
+
+    fn check_extensions(&mut self) {
+        for name in &self.document.extensions_required {
+            if !SUPPORTED_EXTENSIONS.contains(&name.as_str()) {
+                self.warn(format!(
+                    "required extension '{}' is not supported; the result may be incorrect",
+                    name
+                ));
+            }
+        }
+        for name in &self.document.extensions_used {
+            if !SUPPORTED_EXTENSIONS.contains(&name.as_str())
+                && !self.document.extensions_required.contains(name)
+            {
+                self.warn(format!("extension '{}' is not supported and was ignored", name));
+            }
+        }
+    }
+
+    fn materials(&mut self) -> Vec<GltfMaterial> {
+        let document = self.document;
+        document
+            .materials
+            .iter()
+            .map(|def| {
+                let mut material = GltfMaterial {
+                    name: def.name.clone(),
+                    emissive: def.emissive_factor.unwrap_or([0.0; 3]),
+                    unlit: def.extensions.contains_key("KHR_materials_unlit"),
+                    ..GltfMaterial::default()
+                };
+                let mut textured = def.normal_texture.is_some()
+                    || def.occlusion_texture.is_some()
+                    || def.emissive_texture.is_some();
+                if let Some(pbr) = &def.pbr_metallic_roughness {
+                    material.base_color = pbr.base_color_factor.unwrap_or([1.0; 4]);
+                    material.metallic = pbr.metallic_factor.unwrap_or(1.0);
+                    material.roughness = pbr.roughness_factor.unwrap_or(1.0);
+                    textured |= pbr.base_color_texture.is_some()
+                        || pbr.metallic_roughness_texture.is_some();
+                }
+                if textured {
+                    self.warn("textures are not supported; only material factors were imported".into());
+                }
+                material
+            })
+            .collect()
+    }
+
+    /// メッシュの全プリミティブを1つのMeshに結合
+    ///
+    /// 全プリミティブが同じマテリアルならそれをメッシュのマテリアルにする。
+    /// 異なる場合は各プリミティブのベースカラー係数を頂点色に乗算する
+    fn mesh(&mut self, index: usize, materials: &[GltfMaterial]) -> Result<GltfMesh, GltfError> {
+        let def = &self.document.meshes[index];
+        let mut mesh = Mesh::new();
+
+        if let Some(&m) = def
+            .primitives
+            .iter()
+            .filter_map(|p| p.material.as_ref())
+            .find(|&&m| m >= materials.len())
+        {
+            return Err(GltfError::InvalidReference { kind: "material", index: m });
+        }
+        let shared_material = def
+            .primitives
+            .iter()
+            .all(|p| p.material == def.primitives.first().and_then(|p| p.material));
+        if !shared_material {
+            self.warn(format!(
+                "mesh {}: primitives use different materials; base colors are baked into vertex colors",
+                index
+            ));
+        }
+
+        let mut textured = false;
+        for (primitive_index, primitive) in def.primitives.iter().enumerate() {
+            let label = format!("mesh {} primitive {}", index, primitive_index);
+            let mode = primitive.mode.unwrap_or(MODE_TRIANGLES);
+            if !matches!(mode, MODE_TRIANGLES | MODE_TRIANGLE_STRIP | MODE_TRIANGLE_FAN) {
+                self.warn(format!("{}: points and lines are not supported and were skipped", label));
+                continue;
+            }
+            let Some(&position_accessor) = primitive.attributes.get("POSITION") else {
+                self.warn(format!("{}: no POSITION attribute, skipped", label));
+                continue;
+            };
+
+            let positions = self.read_vec3(position_accessor)?;
+            let normals = match primitive.attributes.get("NORMAL") {
+                Some(&accessor) => Some(self.read_vec3(accessor)?),
+                None => None,
+            };
+            let colors = match primitive.attributes.get("COLOR_0") {
+                Some(&accessor) => Some(self.read_color(accessor)?),
+                None => None,
+            };
+            let uvs = match primitive.attributes.get("TEXCOORD_0") {
+                Some(&accessor) => Some(self.read_vec2(accessor)?),
+                None => None,
+            };
+            let lengths = [
+                ("NORMAL", normals.as_ref().map(Vec::len)),
+                ("COLOR_0", colors.as_ref().map(Vec::len)),
+                ("TEXCOORD_0", uvs.as_ref().map(Vec::len)),
+            ];
+            for (name, len) in lengths {
+                if let Some(len) = len
+                    && len != positions.len()
+                {
+                    return Err(GltfError::InvalidAccessor {
+                        index: primitive.attributes[name],
+                        reason: format!(
+                            "{} has {} elements but POSITION has {}",
+                            name,
+                            len,
+                            positions.len()
+                        ),
+                    });
+                }
+            }
+
+            let indices = match primitive.indices {
+                Some(accessor) => self.read_indices(accessor, positions.len())?,
+                None => (0..positions.len() as u32).collect(),
+            };
+            let triangles = match mode {
+                MODE_TRIANGLE_STRIP => strip_to_list(&indices),
+                MODE_TRIANGLE_FAN => fan_to_list(&indices),
+                _ => indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect(),
+            };
+
+            let base_color = match primitive.material {
+                Some(m) if !shared_material => materials[m].base_color,
+                _ => [1.0; 4],
+            };
+            let color_at = |i: usize| {
+                let c = colors.as_ref().map_or([1.0; 3], |c| c[i]);
+                [c[0] * base_color[0], c[1] * base_color[1], c[2] * base_color[2]]
+            };
+            // UVのないプリミティブは他のプリミティブに合わせて0で埋める
+            textured |= uvs.is_some();
+            let uv_at = |i: usize| uvs.as_ref().map_or([0.0; 2], |uv| uv[i]);
+
+            let base = mesh.vertices.len() as u32;
+            match &normals {
+                Some(normals) => {
+                    for (i, position) in positions.iter().enumerate() {
+                        mesh.vertices.push(Vertex::new(
+                            position.to_array(),
+                            normalized(normals[i]).to_array(),
+                            color_at(i),
+                        ));
+                        mesh.uvs.push(uv_at(i));
+                    }
+                    for [a, b, c] in triangles {
+                        mesh.indices.extend_from_slice(&[base + a, base + b, base + c]);
+                    }
+                }
+                // 法線がない場合、仕様に従いフラット法線を生成する
+                None => {
+                    for [a, b, c] in triangles {
+                        let points = [a, b, c].map(|i| positions[i as usize]);
+                        let normal = weighted_corner_normals(points[0], points[1], points[2])[0]
+                            .normalize_or_zero();
+                        for (i, point) in [a, b, c].into_iter().zip(points) {
+                            mesh.indices.push(mesh.vertices.len() as u32);
+                            mesh.vertices.push(Vertex::new(
+                                point.to_array(),
+                                normal.to_array(),
+                                color_at(i as usize),
+                            ));
+                            mesh.uvs.push(uv_at(i as usize));
+                        }
+                    }
+                }
+            }
+        }
+
+        if !textured {
+            mesh.uvs.clear();
+        }
+
+        Ok(GltfMesh {
+            name: def.name.clone(),
+            mesh,
+            material: def.primitives.first().and_then(|p| p.material).filter(|_| shared_material),
+        })
+    }
+
+    fn nodes(&self) -> Result<Vec<GltfNode>, GltfError> {
+        let document = self.document;
+        document
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, def)| {
+                if let Some(mesh) = def.mesh.filter(|&m| m >= document.meshes.len()) {
+                    return Err(GltfError::InvalidReference { kind: "mesh", index: mesh });
+                }
+                if let Some(&child) = def.children.iter().find(|&&c| c >= document.nodes.len()) {
+                    return Err(GltfError::InvalidReference { kind: "node", index: child });
+                }
+
+                let transform = match def.matrix {
+                    Some(matrix) => {
+                        let (scale, rotation, position) =
+                            Mat4::from_cols_array(&matrix).to_scale_rotation_translation();
+                        Transform::new(position, rotation, scale)
+                    }
+                    None => Transform::new(
+                        def.translation.map_or(Vec3::ZERO, Vec3::from_array),
+                        def.rotation.map_or(Quat::IDENTITY, |r| normalized_rotation(Quat::from_array(r))),
+                        def.scale.map_or(Vec3::ONE, Vec3::from_array),
+                    ),
+                };
+                let name = def
+                    .name
+                    .clone()
+                    .or_else(|| def.mesh.and_then(|m| document.meshes[m].name.clone()))
+                    .unwrap_or_else(|| format!("Node {}", index));
+
+                Ok(GltfNode {
+                    name,
+                    transform,
+                    mesh: def.mesh,
+                    children: def.children.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// シーンのルートノードを決め、階層が木になっているか検証する
+    fn roots(&self, nodes: &[GltfNode]) -> Result<Vec<usize>, GltfError> {
+        let mut parent_count = vec![0usize; nodes.len()];
+        for node in nodes {
+            for &child in &node.children {
+                parent_count[child] += 1;
+                if parent_count[child] > 1 {
+                    return Err(GltfError::InvalidHierarchy { node: child });
+                }
+            }
+        }
+
+        let scene = self
+            .document
+            .scene
+            .or((!self.document.scenes.is_empty()).then_some(0));
+        let roots: Vec<usize> = match scene {
+            Some(index) => {
+                let scene = self.document.scenes.get(index).ok_or(GltfError::InvalidReference {
+                    kind: "scene",
+                    index,
+                })?;
+                if let Some(&node) = scene.nodes.iter().find(|&&n| n >= nodes.len()) {
+                    return Err(GltfError::InvalidReference { kind: "node", index: node });
+                }
+                scene.nodes.clone()
+            }
+            // シーン定義がない場合は親を持たない全ノード
+            None => (0..nodes.len()).filter(|&i| parent_count[i] == 0).collect(),
+        };
+
+        // ルートから辿って循環がないか確認
+        let mut visited = HashSet::new();
+        let mut stack = roots.clone();
+        while let Some(node) = stack.pop() {
+            if !visited.insert(node) || (parent_count[node] > 0 && roots.contains(&node)) {
+                return Err(GltfError::InvalidHierarchy { node });
+            }
+            stack.extend(&nodes[node].children);
+        }
+        Ok(roots)
+    }
+
+    // ------------------------------------------------------------------------
+    // アクセサ
+    // ------------------------------------------------------------------------
+
+    /// アクセサの各要素を成分ごとのf32として読む
+    fn read_accessor(&mut self, index: usize) -> Result<(Vec<f32>, usize), GltfError> {
+        self.read_components(index, 0.0, read_component)
+    }
+
+    /// アクセサの各成分のバイト列を `read` で変換して読む（bufferViewがなければ `zero`）
+    fn read_components<T: Copy>(
+        &mut self,
+        index: usize,
+        zero: T,
+        read: impl Fn(&[u8], u32, bool) -> T,
+    ) -> Result<(Vec<T>, usize), GltfError> {
+        let accessor = self
+            .document
+            .accessors
+            .get(index)
+            .ok_or(GltfError::InvalidReference { kind: "accessor", index })?;
+        let invalid = |reason: String| GltfError::InvalidAccessor { index, reason };
+
+        let components = match accessor.kind.as_str() {
+            "SCALAR" => 1,
+            "VEC2" => 2,
+            "VEC3" => 3,
+            "VEC4" => 4,
+            "MAT2" => 4,
+            "MAT3" => 9,
+            "MAT4" => 16,
+            other => return Err(invalid(format!("unknown type '{}'", other))),
+        };
+        let component_size = match accessor.component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            other => return Err(invalid(format!("unknown componentType {}", other))),
+        };
+        if accessor.sparse.is_some() {
+            self.warn(format!("accessor {}: sparse accessors are not supported; base values were used", index));
+        }
+
+        let count = accessor.count;
+        let overflow = || invalid(format!("count {} is too large", count));
+        let value_count = count.checked_mul(components).ok_or_else(overflow)?;
+        let Some(view_index) = accessor.buffer_view else {
+            // bufferViewのないアクセサは全て0（参照するデータがないため数を制限する）
+            if value_count > MAX_ZERO_ACCESSOR_VALUES {
+                return Err(overflow());
+            }
+            return Ok((vec![zero; value_count], components));
+        };
+        let view = self
+            .document
+            .buffer_views
+            .get(view_index)
+            .ok_or(GltfError::InvalidReference { kind: "bufferView", index: view_index })?;
+        let buffer = self
+            .buffers
+            .get(view.buffer)
+            .ok_or(GltfError::InvalidReference { kind: "buffer", index: view.buffer })?;
+
+        let element_size = components * component_size;
+        // 0は詰めて並んでいるものとして扱う。要素が重なるストライドは不正
+        let stride = match view.byte_stride {
+            None | Some(0) => element_size,
+            Some(stride) if stride < element_size => {
+                return Err(invalid(format!("byteStride {} is smaller than the element size {}", stride, element_size)));
+            }
+            Some(stride) => stride,
+        };
+        let out_of_range = || invalid("byte range overflows".to_string());
+        let start = view.byte_offset.checked_add(accessor.byte_offset).ok_or_else(out_of_range)?;
+        let view_end = view.byte_offset.checked_add(view.byte_length).ok_or_else(out_of_range)?;
+        if count > 0 {
+            let end = stride
+                .checked_mul(count - 1)
+                .and_then(|n| n.checked_add(start))
+                .and_then(|n| n.checked_add(element_size))
+                .ok_or_else(out_of_range)?;
+            if end > view_end || view_end > buffer.len() {
+                return Err(invalid(format!(
+                    "reads bytes {}..{} but the buffer view ends at {} (buffer length {})",
+                    start,
+                    end,
+                    view_end,
+                    buffer.len()
+                )));
+            }
+        }
+
+        let normalized = accessor.normalized;
+        let component_type = accessor.component_type;
+        // 範囲は検証済みのため、要素数はバッファの長さで抑えられる
+        let mut values = Vec::with_capacity(value_count);
+        for element in 0..count {
+            let offset = start + element * stride;
+            for c in 0..components {
+                let at = offset + c * component_size;
+                let bytes = &buffer[at..at + component_size];
+                values.push(read(bytes, component_type, normalized));
+            }
+        }
+        Ok((values, components))
+    }
+
+    fn read_vec3(&mut self, index: usize) -> Result<Vec<Vec3>, GltfError> {
+        let (values, components) = self.read_accessor(index)?;
+        if components != 3 {
+            return Err(GltfError::InvalidAccessor {
+                index,
+                reason: format!("expected VEC3, found {} components", components),
+            });
+        }
+        Ok(values.chunks_exact(3).map(|v| Vec3::new(v[0], v[1], v[2])).collect())
+    }
+
+    /// TEXCOORD_0（VEC2、原点は画像の左上）
+    fn read_vec2(&mut self, index: usize) -> Result<Vec<[f32; 2]>, GltfError> {
+        let (values, components) = self.read_accessor(index)?;
+        if components != 2 {
+            return Err(GltfError::InvalidAccessor {
+                index,
+                reason: format!("expected VEC2, found {} components", components),
+            });
+        }
+        Ok(values.chunks_exact(2).map(|v| [v[0], v[1]]).collect())
+    }
+
+    /// COLOR_0（VEC3またはVEC4、アルファは使用しない）
+    fn read_color(&mut self, index: usize) -> Result<Vec<[f32; 3]>, GltfError> {
+        let (values, components) = self.read_accessor(index)?;
+        if components != 3 && components != 4 {
+            return Err(GltfError::InvalidAccessor {
+                index,
+                reason: format!("COLOR_0 must be VEC3 or VEC4, found {} components", components),
+            });
+        }
+        Ok(values
+            .chunks_exact(components)
+            .map(|v| [v[0], v[1], v[2]])
+            .collect())
+    }
+
+    /// インデックスは整数のまま読む（f32を経由すると2^24を超える値が丸められる）
+    fn read_indices(&mut self, index: usize, vertex_count: usize) -> Result<Vec<u32>, GltfError> {
+        let component_type = self
+            .document
+            .accessors
+            .get(index)
+            .ok_or(GltfError::InvalidReference { kind: "accessor", index })?
+            .component_type;
+        if !matches!(component_type, 5121 | 5123 | 5125) {
+            return Err(GltfError::InvalidAccessor {
+                index,
+                reason: format!(
+                    "indices must be UNSIGNED_BYTE, UNSIGNED_SHORT or UNSIGNED_INT, found componentType {}",
+                    component_type
+                ),
+            });
+        }
+        let (values, components) = self.read_components(index, 0, read_index)?;
+        if components != 1 {
+            return Err(GltfError::InvalidAccessor {
+                index,
+                reason: "indices must be SCALAR".into(),
+            });
+        }
+        values
+            .into_iter()
+            .map(|i| {
+                if (i as usize) < vertex_count {
+                    Ok(i)
+                } else {
+                    Err(GltfError::InvalidAccessor {
+                        index,
+                        reason: format!("index {} is out of range for {} vertices", i, vertex_count),
+                    })
+                }
+            })
+            .collect()
+    }
+}
+
+/// バッファを読み込む（GLBのBINチャンクまたはdata URI）
+fn load_buffer(index: usize, buffer: &BufferDef, bin: Option<&[u8]>) -> Result<Vec<u8>, GltfError> {
+    let data = match &buffer.uri {
+        None => bin
+            .ok_or_else(|| GltfError::InvalidGlb(format!("buffer {} refers to a missing BIN chunk", index)))?
+            .to_vec(),
+        Some(uri) if uri.starts_with("data:") => {
+            let (_, payload) = uri
+                .split_once(";base64,")
+                .ok_or(GltfError::InvalidDataUri { buffer: index })?;
+            base64::decode(payload).ok_or(GltfError::InvalidDataUri { buffer: index })?
+        }
+        Some(uri) => return Err(GltfError::ExternalUri(uri.clone())),
+    };
+    if data.len() < buffer.byte_length {
+        return Err(GltfError::InvalidGlb(format!(
+            "buffer {} has {} bytes but byteLength is {}",
+            index,
+            data.len(),
+            buffer.byte_length
+        )));
+    }
+    Ok(data)
+}
+
+/// 成分を1つ読み、正規化指定があれば [0,1] / [-1,1] に変換する
+fn read_component(bytes: &[u8], component_type: u32, normalized: bool) -> f32 {
+    match component_type {
+        5120 => {
+            let v = bytes[0] as i8 as f32;
+            if normalized { (v / 127.0).max(-1.0) } else { v }
+        }
+        5121 => {
+            let v = bytes[0] as f32;
+            if normalized { v / 255.0 } else { v }
+        }
+        5122 => {
+            let v = i16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+            if normalized { (v / 32767.0).max(-1.0) } else { v }
+        }
+        5123 => {
+            let v = u16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+            if normalized { v / 65535.0 } else { v }
+        }
+        5125 => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32,
+        _ => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    }
+}
+
+/// 符号なし整数のインデックスを1つ読む（成分の種類は検証済み）
+fn read_index(bytes: &[u8], component_type: u32, _normalized: bool) -> u32 {
+    match component_type {
+        5121 => bytes[0] as u32,
+        5123 => u16::from_le_bytes([bytes[0], bytes[1]]) as u32,
+        _ => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    }
+}
+
+/// 正規化済みでない場合のみ正規化する（書き出した値をそのまま読み戻せるように）
+fn normalized(v: Vec3) -> Vec3 {
+    if v.is_normalized() { v } else { v.normalize_or_zero() }
+}
+
+fn normalized_rotation(q: Quat) -> Quat {
+    if q.is_normalized() { q } else { q.normalize() }
+}
+
+fn strip_to_list(indices: &[u32]) -> Vec<[u32; 3]> {
+    (0..indices.len().saturating_sub(2))
+        .map(|i| {
+            if i.is_multiple_of(2) {
+                [indices[i], indices[i + 1], indices[i + 2]]
+            } else {
+                [indices[i + 1], indices[i], indices[i + 2]]
+            }
+        })
+        .collect()
+}
+
+fn fan_to_list(indices: &[u32]) -> Vec<[u32; 3]> {
+    (1..indices.len().saturating_sub(1))
+        .map(|i| [indices[0], indices[i], indices[i + 1]])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 三角形1枚（位置3頂点 + インデックス3つ）のバイナリ
+    fn triangle_buffer() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for v in [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] {
+            for c in v {
+                bytes.extend_from_slice(&c.to_le_bytes());
+            }
+        }
+        for i in [0u16, 1, 2] {
+            bytes.extend_from_slice(&i.to_le_bytes());
+        }
+        bytes.extend_from_slice(&[0, 0]);
+        bytes
+    }
+
+    fn document(buffer_uri: Option<String>, extra: &str) -> String {
+        let uri = buffer_uri.map_or(String::new(), |uri| format!(r#""uri": "{}","#, uri));
+        format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "scene": 0,
+                "scenes": [{{ "nodes": [0] }}],
+                "nodes": [
+                    {{ "name": "Root", "translation": [1, 2, 3], "children": [1] }},
+                    {{ "mesh": 0, "scale": [2, 2, 2] }}
+                ],
+                "meshes": [{{ "name": "Tri", "primitives": [{{
+                    "attributes": {{ "POSITION": 0 }}, "indices": 1, "material": 0
+                }}] }}],
+                "materials": [{{ "pbrMetallicRoughness": {{
+                    "baseColorFactor": [1, 0.5, 0.25, 1], "metallicFactor": 0.0, "roughnessFactor": 0.5
+                }} }}],
+                "accessors": [
+                    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }},
+                    {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+                ],
+                "bufferViews": [
+                    {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+                    {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }}
+                ],
+                "buffers": [{{ {} "byteLength": 44 }}]
+                {}
+            }}"#,
+            uri, extra
+        )
+    }
+
+    fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+        let mut json = json.as_bytes().to_vec();
+        while !json.len().is_multiple_of(4) {
+            json.push(b' ');
+        }
+        let total = 12 + 8 + json.len() + 8 + bin.len();
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&GLB_MAGIC.to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&(total as u32).to_le_bytes());
+        bytes.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&CHUNK_JSON.to_le_bytes());
+        bytes.extend_from_slice(&json);
+        bytes.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&CHUNK_BIN.to_le_bytes());
+        bytes.extend_from_slice(bin);
+        bytes
+    }
+
+    #[test]
+    fn test_parse_embedded_gltf() {
+        let uri = format!("data:application/octet-stream;base64,{}", base64::encode(&triangle_buffer()));
+        let scene = parse_gltf(document(Some(uri), "").as_bytes()).unwrap();
+
+        assert_eq!(scene.roots, vec![0]);
+        assert_eq!(scene.nodes[0].name, "Root");
+        assert_eq!(scene.nodes[0].transform.position, Vec3::new(1.0, 2.0, 3.0));
+        // 名前のないノードはメッシュ名を使う
+        assert_eq!(scene.nodes[1].name, "Tri");
+        assert_eq!(scene.nodes[1].transform.scale, Vec3::splat(2.0));
+
+        let mesh = &scene.meshes[0].mesh;
+        assert_eq!(mesh.indices.len(), 3);
+        assert!(mesh.validate().is_ok());
+        // 法線がないためフラット法線（+Z）が生成される
+        assert_eq!(mesh.vertices[0].normal, [0.0, 0.0, 1.0]);
+        // マテリアルが1つなのでベースカラーは頂点色に焼き込まれない
+        assert_eq!(mesh.vertices[0].color, [1.0, 1.0, 1.0]);
+        assert_eq!(scene.meshes[0].material, Some(0));
+
+        let material = &scene.materials[0];
+        assert_eq!(material.base_color, [1.0, 0.5, 0.25, 1.0]);
+        assert_eq!(material.metallic, 0.0);
+        assert_eq!(material.roughness, 0.5);
+        assert!(scene.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_parse_glb() {
+        let bytes = glb(&document(None, ""), &triangle_buffer());
+        let scene = parse_gltf(&bytes).unwrap();
+        assert_eq!(scene.meshes[0].mesh.vertices.len(), 3);
+        assert_eq!(scene.meshes[0].material, Some(0));
+    }
+
+    #[test]
+    fn test_spawn_preserves_hierarchy() {
+        let bytes = glb(&document(None, ""), &triangle_buffer());
+        let scene = parse_gltf(&bytes).unwrap();
+
+        let mut world = World::new();
+        let roots = scene.spawn(&mut world, &[Some(MeshHandle::new(7))]);
+        assert_eq!(roots.len(), 1);
+
+        let root = roots[0];
+        assert_eq!(world.get::<Name>(root).map(|n| n.as_str()), Some("Root"));
+        assert_eq!(world.get::<MeshHandle>(root), Some(&MeshHandle::NONE));
+
+        let children = world.children(root).to_vec();
+        assert_eq!(children.len(), 1);
+        assert_eq!(world.get::<MeshHandle>(children[0]), Some(&MeshHandle::new(7)));
+        let material = world.get::<Material>(children[0]).unwrap();
+        assert_eq!(material.base_color, Vec4::new(1.0, 0.5, 0.25, 1.0));
+        assert_eq!(material.roughness, 0.5);
+        assert!(world.get::<Material>(root).is_none());
+        let matrix = world.world_matrix(children[0]).unwrap();
+        assert!(matrix.w_axis.truncate().abs_diff_eq(Vec3::new(1.0, 2.0, 3.0), 1e-6));
+    }
+
+    #[test]
+    fn test_unsupported_extension_warns() {
+        let extra = r#", "extensionsUsed": ["KHR_draco_mesh_compression", "KHR_materials_unlit"]"#;
+        let bytes = glb(&document(None, extra), &triangle_buffer());
+        let scene = parse_gltf(&bytes).unwrap();
+        assert_eq!(scene.warnings.len(), 1);
+        assert!(scene.warnings[0].contains("KHR_draco_mesh_compression"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(parse_gltf(b"glTF\x01\0\0\0"), Err(GltfError::InvalidGlb(_))));
+        assert!(matches!(parse_gltf(b"{ not json"), Err(GltfError::InvalidJson(_))));
+
+        let external = document(Some("model.bin".into()), "");
+        assert!(matches!(parse_gltf(external.as_bytes()), Err(GltfError::ExternalUri(_))));
+
+        // バッファが短すぎる
+        let bytes = glb(&document(None, ""), &triangle_buffer()[..20]);
+        assert!(parse_gltf(&bytes).is_err());
+    }
+
+    /// 最初のアクセサ・bufferViewを書き換えたGLBを読む
+    fn parse_patched(from: &str, to: &str) -> Result<GltfScene, GltfError> {
+        let json = document(None, "");
+        assert!(json.contains(from));
+        parse_gltf(&glb(&json.replace(from, to), &triangle_buffer()))
+    }
+
+    #[test]
+    fn test_accessor_count_overflow() {
+        // bufferViewなしで count × 成分数 が桁あふれする
+        let result = parse_patched(
+            r#"{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }"#,
+            r#"{ "componentType": 5126, "count": 9223372036854775807, "type": "VEC3" }"#,
+        );
+        assert!(matches!(result, Err(GltfError::InvalidAccessor { index: 0, .. })));
+
+        // 桁あふれしなくても全て0のアクセサは上限を超えれば確保しない
+        let result = parse_patched(
+            r#"{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }"#,
+            r#"{ "componentType": 5126, "count": 1000000000, "type": "VEC3" }"#,
+        );
+        assert!(matches!(result, Err(GltfError::InvalidAccessor { index: 0, .. })));
+    }
+
+    #[test]
+    fn test_buffer_view_offset_overflow() {
+        let result = parse_patched(
+            r#"{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }"#,
+            r#"{ "buffer": 0, "byteOffset": 18446744073709551615, "byteLength": 36 }"#,
+        );
+        assert!(matches!(result, Err(GltfError::InvalidAccessor { index: 0, .. })));
+    }
+
+    #[test]
+    fn test_buffer_view_stride_overflow() {
+        let result = parse_patched(
+            r#"{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }"#,
+            r#"{ "buffer": 0, "byteOffset": 0, "byteLength": 36, "byteStride": 9223372036854775807 }"#,
+        );
+        assert!(matches!(result, Err(GltfError::InvalidAccessor { index: 0, .. })));
+
+        // 要素が重なるストライドも不正
+        let result = parse_patched(
+            r#"{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }"#,
+            r#"{ "buffer": 0, "byteOffset": 0, "byteLength": 36, "byteStride": 4 }"#,
+        );
+        assert!(matches!(result, Err(GltfError::InvalidAccessor { index: 0, .. })));
+    }
+
+    #[test]
+    fn test_index_component_types() {
+        let indices = r#"{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }"#;
+        let view = r#"{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }"#;
+
+        // 浮動小数点のインデックスは不正
+        let result = parse_patched(indices, &indices.replace("5123", "5126"));
+        assert!(matches!(result, Err(GltfError::InvalidAccessor { index: 1, .. })));
+
+        // UNSIGNED_INTはf32を経由せず読むため、2^24を超える値も丸められない
+        let mut buffer = triangle_buffer()[..36].to_vec();
+        for i in [0u32, 1, (1 << 24) + 1] {
+            buffer.extend_from_slice(&i.to_le_bytes());
+        }
+        let json = document(None, "")
+            .replace(indices, &indices.replace("5123", "5125"))
+            .replace(view, r#"{ "buffer": 0, "byteOffset": 36, "byteLength": 12 }"#)
+            .replace(r#""byteLength": 44"#, r#""byteLength": 48"#);
+        match parse_gltf(&glb(&json, &buffer)) {
+            Err(GltfError::InvalidAccessor { index: 1, reason }) => assert!(reason.contains("16777217"), "{}", reason),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+        buffer[44..48].copy_from_slice(&2u32.to_le_bytes());
+        let scene = parse_gltf(&glb(&json, &buffer)).unwrap();
+        assert_eq!(scene.meshes[0].mesh.indices, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_strip_and_fan() {
+        assert_eq!(strip_to_list(&[0, 1, 2, 3]), vec![[0, 1, 2], [2, 1, 3]]);
+        assert_eq!(fan_to_list(&[0, 1, 2, 3]), vec![[0, 1, 2], [0, 2, 3]]);
+    }
+}
@@ -0,0 +1,220 @@
+//! メッシュインポートモジュール
+//!
+//! 外部ファイル形式からMeshを生成する
+
+pub mod gltf;
+pub mod obj;
+pub mod ply;
+pub mod stl;
+
+pub use gltf::{parse_gltf, GltfError, GltfMaterial, GltfMesh, GltfNode, GltfScene};
+pub use obj::{parse_mtl, parse_obj, ObjError, ObjErrorKind, ObjMaterial, ObjObject};
+pub use ply::{parse_ply, PlyError};
+pub use stl::{parse_stl, StlError};
+
+use glam::Vec3;
+
+use crate::mesh::{Mesh, Vertex};
+
+/// 色の指定がない頂点の色
+const DEFAULT_COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+
+/// 法線がファイルに含まれない場合の生成方法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NormalMode {
+    /// 面ごとの法線（頂点は面ごとに分かれる）
+    Flat,
+    /// 同じ位置を共有する面の法線を頂点の角度で重み付け平均
+    #[default]
+    Smooth,
+}
+
+/// 三角形の法線（長さは面積の2倍、正規化しない）
+pub(crate) fn triangle_normal(a: Vec3, b: Vec3, c: Vec3) -> Vec3 {
+    (b - a).cross(c - a)
+}
+
+/// 三角形の各頂点における、角度で重み付けした単位法線
+///
+/// スムース法線の累積に使う。分割の仕方に依存しないため四角形の角でも偏らない
+pub(crate) fn weighted_corner_normals(a: Vec3, b: Vec3, c: Vec3) -> [Vec3; 3] {
+    let normal = triangle_normal(a, b, c).normalize_or_zero();
+    let angle = |p: Vec3, q: Vec3, r: Vec3| (q - p).angle_between(r - p);
+    let angles = [angle(a, b, c), angle(b, c, a), angle(c, a, b)];
+    angles.map(|w| if w.is_finite() { normal * w } else { Vec3::ZERO })
+}
+
+/// 多角形をCCWの三角形に分割し、頂点番号（0始まり）の三つ組を返す
+///
+/// 凹多角形にも対応するため、Newell法で求めた法線の平面に投影して耳切りを行う。
+/// 分割できない場合（自己交差など）は扇形分割にフォールバックする
+pub(crate) fn triangulate(points: &[Vec3]) -> Vec<[usize; 3]> {
+    let n = points.len();
+    if n < 3 {
+        return Vec::new();
+    }
+    if n == 3 {
+        return vec![[0, 1, 2]];
+    }
+
+    let normal = polygon_normal(points);
+    let fan = || (1..n - 1).map(|i| [0, i, i + 1]).collect();
+    if normal.length_squared() <= f32::EPSILON {
+        return fan();
+    }
+
+    // 法線の最大成分の軸を落として2Dに投影（向きを保つよう符号を考慮）
+    let axis = normal.abs().max_position();
+    let flip = normal[axis] < 0.0;
+    let project = |p: Vec3| -> (f32, f32) {
+        let (u, v) = match axis {
+            0 => (p.y, p.z),
+            1 => (p.z, p.x),
+            _ => (p.x, p.y),
+        };
+        if flip { (v, u) } else { (u, v) }
+    };
+    let projected: Vec<(f32, f32)> = points.iter().map(|p| project(*p)).collect();
+
+    let cross = |o: (f32, f32), a: (f32, f32), b: (f32, f32)| {
+        (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+    };
+    let inside = |p: (f32, f32), a: (f32, f32), b: (f32, f32), c: (f32, f32)| {
+        cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0
+    };
+
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut triangles = Vec::with_capacity(n - 2);
+
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let ear = (0..m).find(|&i| {
+            let (ia, ib, ic) = (remaining[(i + m - 1) % m], remaining[i], remaining[(i + 1) % m]);
+            let (a, b, c) = (projected[ia], projected[ib], projected[ic]);
+            // 凸頂点で、他の頂点を内側に含まない
+            cross(a, b, c) > 0.0
+                && remaining
+                    .iter()
+                    .filter(|&&j| j != ia && j != ib && j != ic)
+                    .all(|&j| !inside(projected[j], a, b, c))
+        });
+
+        let Some(i) = ear else {
+            return fan();
+        };
+        triangles.push([remaining[(i + m - 1) % m], remaining[i], remaining[(i + 1) % m]]);
+        remaining.remove(i);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+/// 頂点を共有する三角形リストからMeshを組み立てる
+///
+/// `normals` がない場合は `normal_mode` に従って生成する。
+/// Flatでは三角形ごとに頂点を分け、Smoothでは共有頂点に角度重み付きの法線を付ける
+pub(crate) fn build_mesh(
+    positions: &[Vec3],
+    normals: Option<&[Vec3]>,
+    colors: Option<&[[f32; 3]]>,
+    uvs: Option<&[[f32; 2]]>,
+    triangles: &[[u32; 3]],
+    normal_mode: NormalMode,
+) -> Mesh {
+    let color = |i: u32| colors.map_or(DEFAULT_COLOR, |c| c[i as usize]);
+    let mut mesh = Mesh::new();
+
+    if normals.is_none() && normal_mode == NormalMode::Flat {
+        for &[a, b, c] in triangles {
+            let [pa, pb, pc] = [a, b, c].map(|i| positions[i as usize]);
+            let normal = triangle_normal(pa, pb, pc).normalize_or_zero().to_array();
+            for (i, p) in [(a, pa), (b, pb), (c, pc)] {
+                mesh.indices.push(mesh.vertices.len() as u32);
+                mesh.vertices.push(Vertex::new(p.to_array(), normal, color(i)));
+                if let Some(uvs) = uvs {
+                    mesh.uvs.push(uvs[i as usize]);
+                }
+            }
+        }
+        return mesh;
+    }
+
+    let normals = match normals {
+        Some(normals) => normals.iter().map(|n| n.normalize_or_zero()).collect(),
+        None => {
+            let mut accumulated = vec![Vec3::ZERO; positions.len()];
+            for &[a, b, c] in triangles {
+                let corners = [a, b, c].map(|i| positions[i as usize]);
+                let weighted = weighted_corner_normals(corners[0], corners[1], corners[2]);
+                for (i, n) in [a, b, c].into_iter().zip(weighted) {
+                    accumulated[i as usize] += n;
+                }
+            }
+            accumulated.iter().map(|n| n.normalize_or_zero()).collect::<Vec<_>>()
+        }
+    };
+    mesh.vertices = positions
+        .iter()
+        .zip(&normals)
+        .enumerate()
+        .map(|(i, (p, n))| Vertex::new(p.to_array(), n.to_array(), color(i as u32)))
+        .collect();
+    mesh.uvs = uvs.map(<[_]>::to_vec).unwrap_or_default();
+    mesh.indices = triangles.iter().flatten().copied().collect();
+    mesh
+}
+
+/// Newell法による多角形の法線（正規化しない）
+fn polygon_normal(points: &[Vec3]) -> Vec3 {
+    let mut normal = Vec3::ZERO;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        normal.x += (a.y - b.y) * (a.z + b.z);
+        normal.y += (a.z - b.z) * (a.x + b.x);
+        normal.z += (a.x - b.x) * (a.y + b.y);
+    }
+    normal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triangulate_convex() {
+        let square = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ];
+        let triangles = triangulate(&square);
+        assert_eq!(triangles.len(), 2);
+        for [a, b, c] in triangles {
+            assert!(triangle_normal(square[a], square[b], square[c]).z > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_triangulate_concave() {
+        // L字型（頂点3が凹）を-Z向きに並べる
+        let l_shape = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            Vec3::new(1.0, 2.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(2.0, 1.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+        ];
+        let triangles = triangulate(&l_shape);
+        assert_eq!(triangles.len(), 4);
+
+        let mut area = 0.0;
+        for [a, b, c] in triangles {
+            let n = triangle_normal(l_shape[a], l_shape[b], l_shape[c]);
+            assert!(n.z < 0.0, "triangle winding must follow the polygon");
+            area += n.length() * 0.5;
+        }
+        assert!((area - 3.0).abs() < 1e-5);
+    }
+}
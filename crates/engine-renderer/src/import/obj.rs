@@ -0,0 +1,650 @@
+//! OBJ/MTLインポート
+//!
+//! Wavefront OBJ をオブジェクト（`o`）・グループ（`g`）単位のMeshに変換する。
+//! MTLの拡散色（`Kd`）は `Vertex::color` に反映される
+
+use std::collections::HashMap;
+use std::fmt;
+
+use glam::Vec3;
+
+use super::{triangle_normal, triangulate, weighted_corner_normals, NormalMode};
+use crate::mesh::{Mesh, Vertex};
+
+/// マテリアル未指定の面の色
+const DEFAULT_COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+
+/// 名前のない先頭オブジェクトの名前
+const DEFAULT_OBJECT_NAME: &str = "default";
+
+/// エラーが発生したファイル
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjFile {
+    Obj,
+    Mtl,
+}
+
+/// OBJ/MTL解析エラーの種類
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjErrorKind {
+    /// 数値として解釈できない
+    InvalidNumber { token: String },
+    /// 必要な値が足りない
+    MissingValues {
+        statement: String,
+        expected: usize,
+        found: usize,
+    },
+    /// 面の頂点指定が不正（`v/vt/vn` 形式でない、0を指定など）
+    InvalidIndex { token: String },
+    /// 定義されていない頂点・法線を参照している
+    IndexOutOfRange {
+        element: &'static str,
+        index: i64,
+        count: usize,
+    },
+    /// 頂点が3つ未満の面
+    DegenerateFace { count: usize },
+    /// `newmtl` より前のマテリアル定義
+    NoActiveMaterial { statement: String },
+}
+
+/// OBJ/MTL解析エラー（行番号は1始まり）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjError {
+    pub file: ObjFile,
+    pub line: usize,
+    pub kind: ObjErrorKind,
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let file = match self.file {
+            ObjFile::Obj => "OBJ",
+            ObjFile::Mtl => "MTL",
+        };
+        write!(f, "{} line {}: ", file, self.line)?;
+        match &self.kind {
+            ObjErrorKind::InvalidNumber { token } => write!(f, "invalid number '{}'", token),
+            ObjErrorKind::MissingValues {
+                statement,
+                expected,
+                found,
+            } => write!(
+                f,
+                "'{}' needs at least {} values, found {}",
+                statement, expected, found
+            ),
+            ObjErrorKind::InvalidIndex { token } => {
+                write!(f, "invalid face vertex '{}'", token)
+            }
+            ObjErrorKind::IndexOutOfRange {
+                element,
+                index,
+                count,
+            } => write!(
+                f,
+                "{} index {} is out of range ({} defined so far)",
+                element, index, count
+            ),
+            ObjErrorKind::DegenerateFace { count } => {
+                write!(f, "face has {} vertices, at least 3 are required", count)
+            }
+            ObjErrorKind::NoActiveMaterial { statement } => {
+                write!(f, "'{}' appears before any 'newmtl'", statement)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+/// MTLのマテリアル
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjMaterial {
+    pub name: String,
+    /// 拡散色（Kd）
+    pub diffuse: [f32; 3],
+}
+
+/// インポートされたオブジェクト
+#[derive(Debug, Clone)]
+pub struct ObjObject {
+    /// `o` または `g` で指定された名前
+    pub name: String,
+    pub mesh: Mesh,
+}
+
+/// MTLを解析してマテリアル名→マテリアルのマップを返す
+pub fn parse_mtl(source: &str) -> Result<HashMap<String, ObjMaterial>, ObjError> {
+    let mut materials = HashMap::new();
+    let mut current: Option<ObjMaterial> = None;
+
+    for (number, line) in source.lines().enumerate() {
+        let error = |kind| ObjError {
+            file: ObjFile::Mtl,
+            line: number + 1,
+            kind,
+        };
+        let mut tokens = statement_tokens(line);
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "newmtl" => {
+                if let Some(material) = current.take() {
+                    materials.insert(material.name.clone(), material);
+                }
+                let name = args.join(" ");
+                if name.is_empty() {
+                    return Err(error(ObjErrorKind::MissingValues {
+                        statement: keyword.to_string(),
+                        expected: 1,
+                        found: 0,
+                    }));
+                }
+                current = Some(ObjMaterial {
+                    name,
+                    diffuse: DEFAULT_COLOR,
+                });
+            }
+            "Kd" => {
+                let Some(material) = current.as_mut() else {
+                    return Err(error(ObjErrorKind::NoActiveMaterial {
+                        statement: keyword.to_string(),
+                    }));
+                };
+                let values = parse_floats(keyword, &args, 3).map_err(error)?;
+                material.diffuse = [values[0], values[1], values[2]];
+            }
+            // 他の属性（Ka, Ks, Ns, d, map_* など）は現状使用しない
+            _ => {}
+        }
+    }
+
+    if let Some(material) = current {
+        materials.insert(material.name.clone(), material);
+    }
+    Ok(materials)
+}
+
+/// OBJを解析してオブジェクトごとのMeshを返す
+///
+/// `materials` は `parse_mtl` の結果。見つからないマテリアルは白として扱う。
+/// 法線のない頂点は `normals` に従って生成する
+pub fn parse_obj(
+    source: &str,
+    materials: &HashMap<String, ObjMaterial>,
+    normals: NormalMode,
+) -> Result<Vec<ObjObject>, ObjError> {
+    let mut parser = ObjParser::new(materials, normals);
+    for (number, line) in source.lines().enumerate() {
+        parser.statement(line).map_err(|kind| ObjError {
+            file: ObjFile::Obj,
+            line: number + 1,
+            kind,
+        })?;
+    }
+    Ok(parser.finish())
+}
+
+/// 出力頂点の重複判定キー（位置, UV, 法線, 色）
+type VertexKey = (usize, Option<usize>, Option<usize>, [u32; 3]);
+
+/// 構築中のオブジェクト
+struct ObjectBuilder {
+    name: String,
+    mesh: Mesh,
+    lookup: HashMap<VertexKey, u32>,
+    /// UVを指定した頂点があるか（なければUVなしのメッシュになる）
+    textured: bool,
+    /// スムース法線を後から設定する頂点（出力頂点, 位置）
+    pending_smooth: Vec<(u32, usize)>,
+}
+
+impl ObjectBuilder {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            mesh: Mesh::new(),
+            lookup: HashMap::new(),
+            textured: false,
+            pending_smooth: Vec::new(),
+        }
+    }
+}
+
+/// 面の頂点指定（0始まりに解決済み）
+#[derive(Debug, Clone, Copy)]
+struct Corner {
+    position: usize,
+    texcoord: Option<usize>,
+    normal: Option<usize>,
+}
+
+struct ObjParser<'a> {
+    materials: &'a HashMap<String, ObjMaterial>,
+    normal_mode: NormalMode,
+    positions: Vec<Vec3>,
+    /// `v x y z r g b` 形式の頂点色
+    vertex_colors: Vec<Option<[f32; 3]>>,
+    normals: Vec<Vec3>,
+    /// 画像の左上を原点とするUV（OBJの左下原点からVを反転済み）
+    texcoords: Vec<[f32; 2]>,
+    /// 位置ごとの面法線の累積（スムース法線用）
+    smooth_normals: Vec<Vec3>,
+    color: [f32; 3],
+    current: ObjectBuilder,
+    objects: Vec<ObjectBuilder>,
+}
+
+impl<'a> ObjParser<'a> {
+    fn new(materials: &'a HashMap<String, ObjMaterial>, normal_mode: NormalMode) -> Self {
+        Self {
+            materials,
+            normal_mode,
+            positions: Vec::new(),
+            vertex_colors: Vec::new(),
+            normals: Vec::new(),
+            texcoords: Vec::new(),
+            smooth_normals: Vec::new(),
+            color: DEFAULT_COLOR,
+            current: ObjectBuilder::new(DEFAULT_OBJECT_NAME.to_string()),
+            objects: Vec::new(),
+        }
+    }
+
+    fn statement(&mut self, line: &str) -> Result<(), ObjErrorKind> {
+        let mut tokens = statement_tokens(line);
+        let Some(keyword) = tokens.next() else {
+            return Ok(());
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                let values = parse_floats(keyword, &args, 3)?;
+                self.positions.push(Vec3::new(values[0], values[1], values[2]));
+                self.vertex_colors
+                    .push((values.len() >= 6).then(|| [values[3], values[4], values[5]]));
+                self.smooth_normals.push(Vec3::ZERO);
+            }
+            "vn" => {
+                let values = parse_floats(keyword, &args, 3)?;
+                self.normals
+                    .push(Vec3::new(values[0], values[1], values[2]).normalize_or_zero());
+            }
+            "vt" => {
+                let values = parse_floats(keyword, &args, 1)?;
+                let v = values.get(1).copied().unwrap_or(0.0);
+                self.texcoords.push([values[0], 1.0 - v]);
+            }
+            "f" => self.face(&args)?,
+            "o" | "g" => {
+                let name = args.join(" ");
+                let name = if name.is_empty() {
+                    DEFAULT_OBJECT_NAME.to_string()
+                } else {
+                    name
+                };
+                let previous = std::mem::replace(&mut self.current, ObjectBuilder::new(name));
+                self.objects.push(previous);
+            }
+            "usemtl" => {
+                let name = args.join(" ");
+                self.color = self
+                    .materials
+                    .get(&name)
+                    .map_or(DEFAULT_COLOR, |material| material.diffuse);
+            }
+            // s, mtllib, l, p などは現状使用しない
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn face(&mut self, args: &[&str]) -> Result<(), ObjErrorKind> {
+        if args.len() < 3 {
+            return Err(ObjErrorKind::DegenerateFace { count: args.len() });
+        }
+        let corners = args
+            .iter()
+            .map(|token| self.corner(token))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let points: Vec<Vec3> = corners.iter().map(|c| self.positions[c.position]).collect();
+        let triangles = triangulate(&points);
+
+        // スムース法線用に、位置ごとに面法線を累積
+        for [a, b, c] in &triangles {
+            let weighted = weighted_corner_normals(points[*a], points[*b], points[*c]);
+            for (i, normal) in [a, b, c].into_iter().zip(weighted) {
+                self.smooth_normals[corners[*i].position] += normal;
+            }
+        }
+
+        for [a, b, c] in triangles {
+            let flat = triangle_normal(points[a], points[b], points[c]).normalize_or_zero();
+            for i in [a, b, c] {
+                let index = self.emit_vertex(corners[i], flat);
+                self.current.mesh.indices.push(index);
+            }
+        }
+        Ok(())
+    }
+
+    /// 面の頂点を出力し、その番号を返す
+    fn emit_vertex(&mut self, corner: Corner, flat_normal: Vec3) -> u32 {
+        let position = self.positions[corner.position];
+        let color = self.vertex_colors[corner.position].unwrap_or(self.color);
+        let color_key = color.map(f32::to_bits);
+        let uv = corner.texcoord.map_or([0.0; 2], |t| self.texcoords[t]);
+        let builder = &mut self.current;
+        builder.textured |= corner.texcoord.is_some();
+
+        let normal = match (corner.normal, self.normal_mode) {
+            (Some(n), _) => self.normals[n],
+            // フラット法線の頂点は面ごとに分ける
+            (None, NormalMode::Flat) => {
+                let index = builder.mesh.vertices.len() as u32;
+                builder
+                    .mesh
+                    .vertices
+                    .push(Vertex::new(position.to_array(), flat_normal.to_array(), color));
+                builder.mesh.uvs.push(uv);
+                return index;
+            }
+            // スムース法線は全ての面を読み終えてから設定する
+            (None, NormalMode::Smooth) => Vec3::ZERO,
+        };
+
+        let key = (corner.position, corner.texcoord, corner.normal, color_key);
+        if let Some(&index) = builder.lookup.get(&key) {
+            return index;
+        }
+        let index = builder.mesh.vertices.len() as u32;
+        builder
+            .mesh
+            .vertices
+            .push(Vertex::new(position.to_array(), normal.to_array(), color));
+        builder.mesh.uvs.push(uv);
+        builder.lookup.insert(key, index);
+        if corner.normal.is_none() {
+            builder.pending_smooth.push((index, corner.position));
+        }
+        index
+    }
+
+    /// `v`, `v/vt`, `v//vn`, `v/vt/vn` 形式の頂点指定を解決
+    fn corner(&self, token: &str) -> Result<Corner, ObjErrorKind> {
+        let invalid = || ObjErrorKind::InvalidIndex {
+            token: token.to_string(),
+        };
+        let mut parts = token.split('/');
+        let position = parts.next().filter(|s| !s.is_empty()).ok_or_else(invalid)?;
+        let texcoord = parts.next().filter(|s| !s.is_empty());
+        let normal = parts.next().filter(|s| !s.is_empty());
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        let position = resolve_index(position, self.positions.len(), "vertex").map_err(|e| {
+            e.unwrap_or_else(invalid)
+        })?;
+        let texcoord = texcoord
+            .map(|t| resolve_index(t, self.texcoords.len(), "texture coordinate"))
+            .transpose()
+            .map_err(|e| e.unwrap_or_else(invalid))?;
+        let normal = normal
+            .map(|n| resolve_index(n, self.normals.len(), "normal"))
+            .transpose()
+            .map_err(|e| e.unwrap_or_else(invalid))?;
+        Ok(Corner {
+            position,
+            texcoord,
+            normal,
+        })
+    }
+
+    fn finish(mut self) -> Vec<ObjObject> {
+        let last = std::mem::replace(&mut self.current, ObjectBuilder::new(String::new()));
+        self.objects.push(last);
+
+        let smooth_normals: Vec<Vec3> = self
+            .smooth_normals
+            .iter()
+            .map(|n| n.normalize_or_zero())
+            .collect();
+
+        self.objects
+            .into_iter()
+            .filter(|object| !object.mesh.indices.is_empty())
+            .map(|mut object| {
+                for (index, position) in &object.pending_smooth {
+                    object.mesh.vertices[*index as usize].normal =
+                        smooth_normals[*position].to_array();
+                }
+                if !object.textured {
+                    object.mesh.uvs.clear();
+                }
+                ObjObject {
+                    name: object.name,
+                    mesh: object.mesh,
+                }
+            })
+            .collect()
+    }
+}
+
+/// コメントを除いた文のトークン
+fn statement_tokens(line: &str) -> std::str::SplitWhitespace<'_> {
+    let line = line.split_once('#').map_or(line, |(code, _)| code);
+    line.split_whitespace()
+}
+
+/// 少なくとも `expected` 個の数値を読む
+fn parse_floats(statement: &str, args: &[&str], expected: usize) -> Result<Vec<f32>, ObjErrorKind> {
+    if args.len() < expected {
+        return Err(ObjErrorKind::MissingValues {
+            statement: statement.to_string(),
+            expected,
+            found: args.len(),
+        });
+    }
+    args.iter()
+        .map(|token| {
+            token.parse::<f32>().map_err(|_| ObjErrorKind::InvalidNumber {
+                token: token.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// 1始まり（負数は末尾からの相対）のインデックスを0始まりに解決
+///
+/// 数値でない場合は `Err(None)` を返す
+fn resolve_index(
+    token: &str,
+    count: usize,
+    element: &'static str,
+) -> Result<usize, Option<ObjErrorKind>> {
+    let index: i64 = token.parse().map_err(|_| None)?;
+    let resolved = if index > 0 {
+        index - 1
+    } else {
+        count as i64 + index
+    };
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(Some(ObjErrorKind::IndexOutOfRange {
+            element,
+            index,
+            count,
+        }));
+    }
+    Ok(resolved as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CUBE: &str = "
+# unit cube without normals
+mtllib cube.mtl
+o Cube
+v -0.5 -0.5  0.5
+v  0.5 -0.5  0.5
+v  0.5  0.5  0.5
+v -0.5  0.5  0.5
+v -0.5 -0.5 -0.5
+v  0.5 -0.5 -0.5
+v  0.5  0.5 -0.5
+v -0.5  0.5 -0.5
+usemtl Red
+f 1 2 3 4
+f 6 5 8 7
+f 5 1 4 8
+f 2 6 7 3
+f 4 3 7 8
+f 5 6 2 1
+";
+
+    const MTL: &str = "
+newmtl Red
+Ka 0 0 0
+Kd 1.0 0.0 0.0
+newmtl Blue Paint
+Kd 0 0 1
+";
+
+    fn no_materials() -> HashMap<String, ObjMaterial> {
+        HashMap::new()
+    }
+
+    #[test]
+    fn test_parse_mtl() {
+        let materials = parse_mtl(MTL).unwrap();
+        assert_eq!(materials.len(), 2);
+        assert_eq!(materials["Red"].diffuse, [1.0, 0.0, 0.0]);
+        assert_eq!(materials["Blue Paint"].diffuse, [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_quads_are_triangulated_with_smooth_normals() {
+        let materials = parse_mtl(MTL).unwrap();
+        let objects = parse_obj(CUBE, &materials, NormalMode::Smooth).unwrap();
+        assert_eq!(objects.len(), 1);
+
+        let cube = &objects[0];
+        assert_eq!(cube.name, "Cube");
+        assert_eq!(cube.mesh.vertex_count(), 8);
+        assert_eq!(cube.mesh.index_count(), 36);
+        cube.mesh.validate().unwrap();
+
+        for v in &cube.mesh.vertices {
+            assert_eq!(v.color, [1.0, 0.0, 0.0]);
+            // 角の法線は外向きの対角方向
+            let normal = Vec3::from(v.normal);
+            let expected = Vec3::from(v.position).normalize();
+            assert!(normal.dot(expected) > 0.99, "{:?}", normal);
+        }
+    }
+
+    #[test]
+    fn test_flat_normals() {
+        let objects = parse_obj(CUBE, &no_materials(), NormalMode::Flat).unwrap();
+        let cube = &objects[0].mesh;
+        assert_eq!(cube.vertex_count(), 36);
+
+        for tri in cube.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| cube.vertices[tri[i] as usize]);
+            let face = triangle_normal(a.position.into(), b.position.into(), c.position.into());
+            assert!(face.normalize().dot(Vec3::from(a.normal)) > 0.999);
+            // 面は外向き
+            assert!(face.dot(Vec3::from(a.position)) > 0.0);
+            // マテリアルが見つからない場合は白
+            assert_eq!(a.color, DEFAULT_COLOR);
+        }
+    }
+
+    #[test]
+    fn test_explicit_normals_and_groups() {
+        let source = "
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vn 0 0 1
+vt 0.25 0
+f 1 2 3
+g Second
+f 1//1 3//1 4//1
+g Empty
+g Third
+f -4/1/1 -3/1/1 -1/1/1
+";
+        let objects = parse_obj(source, &no_materials(), NormalMode::Smooth).unwrap();
+        let names: Vec<&str> = objects.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, ["default", "Second", "Third"]);
+
+        assert_eq!(objects[1].mesh.vertex_count(), 3);
+        for v in &objects[1].mesh.vertices {
+            assert_eq!(v.normal, [0.0, 0.0, 1.0]);
+        }
+        // 負のインデックスは末尾からの相対
+        assert_eq!(objects[2].mesh.vertices[2].position, [0.0, 1.0, 0.0]);
+
+        // UVは画像の左上原点に変換され、vtを使わないオブジェクトはUVを持たない
+        assert!(!objects[0].mesh.has_uvs());
+        assert_eq!(objects[2].mesh.uvs, vec![[0.25, 1.0]; 3]);
+    }
+
+    #[test]
+    fn test_vertex_colors() {
+        let source = "v 0 0 0 1 0 0\nv 1 0 0 0 1 0\nv 0 1 0 0 0 1\nf 1 2 3\n";
+        let objects = parse_obj(source, &no_materials(), NormalMode::Smooth).unwrap();
+        let colors: Vec<[f32; 3]> = objects[0].mesh.vertices.iter().map(|v| v.color).collect();
+        assert_eq!(colors, [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let parse = |source: &str| parse_obj(source, &no_materials(), NormalMode::Smooth);
+
+        let error = parse("v 0 0 0\nv 1 x 0\n").unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(
+            error.kind,
+            ObjErrorKind::InvalidNumber {
+                token: "x".to_string()
+            }
+        );
+        assert_eq!(error.to_string(), "OBJ line 2: invalid number 'x'");
+
+        let error = parse("v 0 0\n").unwrap_err();
+        assert!(matches!(error.kind, ObjErrorKind::MissingValues { found: 2, .. }));
+
+        let error = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "OBJ line 4: vertex index 4 is out of range (3 defined so far)"
+        );
+
+        let error = parse("v 0 0 0\nf 1 1\n").unwrap_err();
+        assert_eq!(error.kind, ObjErrorKind::DegenerateFace { count: 2 });
+
+        let error = parse("v 0 0 0\nf a 1 1\n").unwrap_err();
+        assert!(matches!(error.kind, ObjErrorKind::InvalidIndex { .. }));
+
+        let error = parse("v 0 0 0\nf 1//2 1 1\n").unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ObjErrorKind::IndexOutOfRange { element: "normal", .. }
+        ));
+
+        let error = parse_mtl("Kd 1 1 1\n").unwrap_err();
+        assert_eq!(error.to_string(), "MTL line 1: 'Kd' appears before any 'newmtl'");
+    }
+}
@@ -0,0 +1,525 @@
+//! PLYインポート
+//!
+//! ASCII・バイナリ（リトル/ビッグエンディアン）のPLYを読み込む。
+//! `vertex` 要素の位置・法線・色と `face` 要素の頂点リストを使い、それ以外の要素は読み飛ばす
+
+use std::fmt;
+
+use glam::Vec3;
+
+use super::{build_mesh, triangulate, NormalMode};
+use crate::mesh::Mesh;
+
+/// PLY解析エラー
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlyError {
+    /// ヘッダが不正（行番号は1始まり）
+    InvalidHeader { line: usize, message: String },
+    /// 本体の値が読めない（データ不足・数値不正）
+    InvalidData {
+        element: String,
+        index: usize,
+        message: String,
+    },
+    /// 面が存在しない頂点を参照している
+    IndexOutOfRange { face: usize, index: i64, count: usize },
+}
+
+impl fmt::Display for PlyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader { line, message } => {
+                write!(f, "PLY header line {}: {}", line, message)
+            }
+            Self::InvalidData {
+                element,
+                index,
+                message,
+            } => write!(f, "PLY {} {}: {}", element, index, message),
+            Self::IndexOutOfRange { face, index, count } => write!(
+                f,
+                "PLY face {} refers to vertex {} but only {} vertices exist",
+                face, index, count
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PlyError {}
+
+/// PLYを読み込んで1つのMeshにする
+///
+/// 法線がファイルにあればそれを使い、なければ `normal_mode` で生成する。
+/// 頂点色（`red`/`green`/`blue`）がなければ白になる。
+/// UV（`u`/`v` または `s`/`t`）は左下原点とみなし、Vを反転して読み込む
+pub fn parse_ply(bytes: &[u8], normal_mode: NormalMode) -> Result<Mesh, PlyError> {
+    let (header, body_start) = parse_header(bytes)?;
+    let mut reader = match header.format {
+        Format::Ascii => Reader::Ascii(
+            String::from_utf8_lossy(&bytes[body_start..])
+                .split_whitespace()
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+                .into_iter(),
+        ),
+        Format::BinaryLittleEndian => Reader::Binary {
+            bytes: &bytes[body_start..],
+            offset: 0,
+            big_endian: false,
+        },
+        Format::BinaryBigEndian => Reader::Binary {
+            bytes: &bytes[body_start..],
+            offset: 0,
+            big_endian: true,
+        },
+    };
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut colors = Vec::new();
+    let mut uvs = Vec::new();
+    let mut faces: Vec<Vec<i64>> = Vec::new();
+
+    for element in &header.elements {
+        let layout = VertexLayout::new(element);
+        for index in 0..element.count {
+            let invalid = |message: String| PlyError::InvalidData {
+                element: element.name.clone(),
+                index,
+                message,
+            };
+            let mut values = vec![0.0f64; element.properties.len()];
+            let mut list = None;
+            for (i, property) in element.properties.iter().enumerate() {
+                match &property.kind {
+                    PropertyKind::Scalar(scalar) => {
+                        values[i] = reader.read(*scalar).map_err(invalid)?;
+                    }
+                    PropertyKind::List { count, item } => {
+                        let len = reader.read(*count).map_err(invalid)?;
+                        if len < 0.0 {
+                            return Err(invalid(format!("negative list length {}", len)));
+                        }
+                        let items = (0..len as usize)
+                            .map(|_| reader.read(*item).map(|v| v as i64))
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err(invalid)?;
+                        if element.name == "face"
+                            && matches!(property.name.as_str(), "vertex_indices" | "vertex_index")
+                        {
+                            list = Some(items);
+                        }
+                    }
+                }
+            }
+
+            match element.name.as_str() {
+                "vertex" => {
+                    let get = |i: Option<usize>| i.map(|i| values[i] as f32);
+                    positions.push(Vec3::new(
+                        get(layout.position[0]).unwrap_or(0.0),
+                        get(layout.position[1]).unwrap_or(0.0),
+                        get(layout.position[2]).unwrap_or(0.0),
+                    ));
+                    if let [Some(x), Some(y), Some(z)] = layout.normal.map(get) {
+                        normals.push(Vec3::new(x, y, z));
+                    }
+                    if let Some(color) = layout.color {
+                        colors.push(color.map(|(i, scale)| (values[i] * scale) as f32));
+                    }
+                    if let [Some(u), Some(v)] = layout.uv.map(get) {
+                        uvs.push([u, 1.0 - v]);
+                    }
+                }
+                "face" => faces.push(list.ok_or_else(|| invalid("missing vertex_indices".into()))?),
+                _ => {}
+            }
+        }
+    }
+
+    let mut triangles = Vec::new();
+    for (face, corners) in faces.iter().enumerate() {
+        let mut points = Vec::with_capacity(corners.len());
+        for &index in corners {
+            if index < 0 || index as usize >= positions.len() {
+                return Err(PlyError::IndexOutOfRange {
+                    face,
+                    index,
+                    count: positions.len(),
+                });
+            }
+            points.push(positions[index as usize]);
+        }
+        for [a, b, c] in triangulate(&points) {
+            triangles.push([corners[a], corners[b], corners[c]].map(|i| i as u32));
+        }
+    }
+
+    let normals = (normals.len() == positions.len() && !normals.is_empty()).then_some(normals.as_slice());
+    let colors = (colors.len() == positions.len() && !colors.is_empty()).then_some(colors.as_slice());
+    let uvs = (uvs.len() == positions.len() && !uvs.is_empty()).then_some(uvs.as_slice());
+    Ok(build_mesh(&positions, normals, colors, uvs, &triangles, normal_mode))
+}
+
+// ============================================================================
+// ヘッダ
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "char" | "int8" => Self::I8,
+            "uchar" | "uint8" => Self::U8,
+            "short" | "int16" => Self::I16,
+            "ushort" | "uint16" => Self::U16,
+            "int" | "int32" => Self::I32,
+            "uint" | "uint32" => Self::U32,
+            "float" | "float32" => Self::F32,
+            "double" | "float64" => Self::F64,
+            _ => return None,
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    /// 色を [0,1] に変換する係数（整数型は最大値で割る）
+    fn color_scale(self) -> f64 {
+        match self {
+            Self::U8 | Self::I8 => 1.0 / 255.0,
+            Self::U16 | Self::I16 => 1.0 / 65535.0,
+            Self::U32 | Self::I32 => 1.0 / u32::MAX as f64,
+            Self::F32 | Self::F64 => 1.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum PropertyKind {
+    Scalar(Scalar),
+    List { count: Scalar, item: Scalar },
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    kind: PropertyKind,
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+#[derive(Debug)]
+struct Header {
+    format: Format,
+    elements: Vec<Element>,
+}
+
+/// ヘッダを解析し、本体の開始位置を返す
+fn parse_header(bytes: &[u8]) -> Result<(Header, usize), PlyError> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut offset = 0;
+    let mut number = 0;
+
+    loop {
+        number += 1;
+        let error = |message: String| PlyError::InvalidHeader {
+            line: number,
+            message,
+        };
+        let Some(end) = bytes[offset..].iter().position(|&b| b == b'\n') else {
+            return Err(error("missing 'end_header'".into()));
+        };
+        let line = String::from_utf8_lossy(&bytes[offset..offset + end]);
+        offset += end + 1;
+
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["ply"] if number == 1 => {}
+            _ if number == 1 => return Err(error("not a PLY file (missing 'ply' magic)".into())),
+            ["format", kind, version] => {
+                if !version.starts_with('1') {
+                    return Err(error(format!("unsupported version '{}'", version)));
+                }
+                format = Some(match *kind {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    other => return Err(error(format!("unknown format '{}'", other))),
+                });
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| error(format!("invalid element count '{}'", count)))?,
+                properties: Vec::new(),
+            }),
+            ["property", rest @ ..] => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| error("'property' before any 'element'".into()))?;
+                let scalar = |name: &str| {
+                    Scalar::parse(name).ok_or_else(|| error(format!("unknown property type '{}'", name)))
+                };
+                let property = match rest {
+                    ["list", count, item, name] => Property {
+                        name: name.to_string(),
+                        kind: PropertyKind::List {
+                            count: scalar(count)?,
+                            item: scalar(item)?,
+                        },
+                    },
+                    [kind, name] => Property {
+                        name: name.to_string(),
+                        kind: PropertyKind::Scalar(scalar(kind)?),
+                    },
+                    _ => return Err(error(format!("malformed property '{}'", line.trim()))),
+                };
+                element.properties.push(property);
+            }
+            ["end_header"] => break,
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => return Err(error(format!("unexpected header line '{}'", line.trim()))),
+        }
+    }
+
+    let format = format.ok_or_else(|| PlyError::InvalidHeader {
+        line: number,
+        message: "missing 'format' line".into(),
+    })?;
+    Ok((Header { format, elements }, offset))
+}
+
+/// `vertex` 要素のプロパティ位置
+struct VertexLayout {
+    position: [Option<usize>; 3],
+    normal: [Option<usize>; 3],
+    /// (プロパティ位置, [0,1]への係数)
+    color: Option<[(usize, f64); 3]>,
+    uv: [Option<usize>; 2],
+}
+
+impl VertexLayout {
+    fn new(element: &Element) -> Self {
+        let find = |names: &[&str]| {
+            element
+                .properties
+                .iter()
+                .position(|p| names.contains(&p.name.as_str()) && matches!(p.kind, PropertyKind::Scalar(_)))
+        };
+        let color_channel = |names: &[&str]| {
+            let i = find(names)?;
+            match element.properties[i].kind {
+                PropertyKind::Scalar(scalar) => Some((i, scalar.color_scale())),
+                PropertyKind::List { .. } => None,
+            }
+        };
+        let color = match (
+            color_channel(&["red", "r", "diffuse_red"]),
+            color_channel(&["green", "g", "diffuse_green"]),
+            color_channel(&["blue", "b", "diffuse_blue"]),
+        ) {
+            (Some(r), Some(g), Some(b)) => Some([r, g, b]),
+            _ => None,
+        };
+
+        Self {
+            position: [find(&["x"]), find(&["y"]), find(&["z"])],
+            normal: [find(&["nx"]), find(&["ny"]), find(&["nz"])],
+            color,
+            uv: [
+                find(&["u", "s", "texture_u", "texture_s"]),
+                find(&["v", "t", "texture_v", "texture_t"]),
+            ],
+        }
+    }
+}
+
+// ============================================================================
+// 本体
+// ============================================================================
+
+enum Reader<'a> {
+    Ascii(std::vec::IntoIter<String>),
+    Binary {
+        bytes: &'a [u8],
+        offset: usize,
+        big_endian: bool,
+    },
+}
+
+impl Reader<'_> {
+    fn read(&mut self, scalar: Scalar) -> Result<f64, String> {
+        match self {
+            Self::Ascii(tokens) => {
+                let token = tokens.next().ok_or_else(|| "unexpected end of data".to_string())?;
+                token
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid number '{}'", token))
+            }
+            Self::Binary {
+                bytes,
+                offset,
+                big_endian,
+            } => {
+                let size = scalar.size();
+                let data = bytes
+                    .get(*offset..*offset + size)
+                    .ok_or_else(|| "unexpected end of data".to_string())?;
+                *offset += size;
+
+                let mut raw = [0u8; 8];
+                raw[..size].copy_from_slice(data);
+                if *big_endian {
+                    raw[..size].reverse();
+                }
+                Ok(match scalar {
+                    Scalar::I8 => raw[0] as i8 as f64,
+                    Scalar::U8 => raw[0] as f64,
+                    Scalar::I16 => i16::from_le_bytes([raw[0], raw[1]]) as f64,
+                    Scalar::U16 => u16::from_le_bytes([raw[0], raw[1]]) as f64,
+                    Scalar::I32 => i32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64,
+                    Scalar::U32 => u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64,
+                    Scalar::F32 => f32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64,
+                    Scalar::F64 => f64::from_le_bytes(raw),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASCII_QUAD: &str = "ply
+format ascii 1.0
+comment unit quad with colors
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+property float s
+property float t
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0 0 0
+1 0 0 0 255 0 1 0
+1 1 0 0 0 255 1 1
+0 1 0 255 255 255 0 1
+4 0 1 2 3
+";
+
+    #[test]
+    fn test_ascii_with_colors() {
+        let mesh = parse_ply(ASCII_QUAD.as_bytes(), NormalMode::Smooth).unwrap();
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.indices.len(), 6);
+        assert_eq!(mesh.vertices[0].color, [1.0, 0.0, 0.0]);
+        assert_eq!(mesh.vertices[2].color, [0.0, 0.0, 1.0]);
+        assert_eq!(mesh.uvs, vec![[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]]);
+        for vertex in &mesh.vertices {
+            assert_eq!(vertex.normal, [0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn test_binary_big_endian_with_normals() {
+        let mut bytes = b"ply
+format binary_big_endian 1.0
+element vertex 3
+property float x
+property float y
+property float z
+property float nx
+property float ny
+property float nz
+element edge 1
+property int vertex1
+property int vertex2
+element face 1
+property list uchar uint vertex_indices
+end_header
+"
+        .to_vec();
+        for v in [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] {
+            for c in v.into_iter().chain([0.0, 1.0, 0.0]) {
+                bytes.extend_from_slice(&c.to_be_bytes());
+            }
+        }
+        // edge要素は読み飛ばされる
+        bytes.extend_from_slice(&0i32.to_be_bytes());
+        bytes.extend_from_slice(&1i32.to_be_bytes());
+        bytes.push(3);
+        for i in [0u32, 1, 2] {
+            bytes.extend_from_slice(&i.to_be_bytes());
+        }
+
+        let mesh = parse_ply(&bytes, NormalMode::Smooth).unwrap();
+        assert_eq!(mesh.indices, vec![0, 1, 2]);
+        assert_eq!(mesh.vertices[1].position, [1.0, 0.0, 0.0]);
+        // ファイルの法線が使われる
+        assert_eq!(mesh.vertices[0].normal, [0.0, 1.0, 0.0]);
+        assert_eq!(mesh.vertices[0].color, [1.0, 1.0, 1.0]);
+        assert!(!mesh.has_uvs());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            parse_ply(b"obj\n", NormalMode::Smooth),
+            Err(PlyError::InvalidHeader { line: 1, .. })
+        ));
+        assert!(matches!(
+            parse_ply(b"ply\nformat ascii 1.0\nelement vertex 1\n", NormalMode::Smooth),
+            Err(PlyError::InvalidHeader { .. })
+        ));
+
+        let truncated = ASCII_QUAD.replace("4 0 1 2 3\n", "4 0 1 2\n");
+        assert!(matches!(
+            parse_ply(truncated.as_bytes(), NormalMode::Smooth),
+            Err(PlyError::InvalidData { index: 0, .. })
+        ));
+
+        let out_of_range = ASCII_QUAD.replace("4 0 1 2 3\n", "4 0 1 2 9\n");
+        assert!(matches!(
+            parse_ply(out_of_range.as_bytes(), NormalMode::Smooth),
+            Err(PlyError::IndexOutOfRange { index: 9, count: 4, .. })
+        ));
+    }
+}
@@ -0,0 +1,264 @@
+//! STLインポート
+//!
+//! バイナリ・ASCIIのSTLを読み込む。STLは三角形ごとに頂点を持つため、
+//! 同じ位置の頂点を溶接してから法線を計算する（ファイル内の面法線は使わない）
+
+use std::collections::HashMap;
+use std::fmt;
+
+use glam::Vec3;
+
+use super::{build_mesh, NormalMode};
+use crate::mesh::Mesh;
+
+/// バイナリSTLのヘッダ長（80バイトのヘッダ + 三角形数）
+const BINARY_HEADER_LEN: usize = 84;
+/// バイナリSTLの三角形1つ分の長さ（法線・3頂点・属性）
+const BINARY_TRIANGLE_LEN: usize = 50;
+
+/// STL解析エラー
+#[derive(Debug, Clone, PartialEq)]
+pub enum StlError {
+    /// バイナリSTLの長さが三角形数と合わない
+    Truncated { triangles: u32, len: usize },
+    /// ASCII STLの構文エラー（行番号は1始まり）
+    Syntax { line: usize, message: String },
+}
+
+impl fmt::Display for StlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated { triangles, len } => write!(
+                f,
+                "binary STL declares {} triangles ({} bytes) but has {} bytes",
+                triangles,
+                BINARY_HEADER_LEN + *triangles as usize * BINARY_TRIANGLE_LEN,
+                len
+            ),
+            Self::Syntax { line, message } => write!(f, "STL line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for StlError {}
+
+/// STL（バイナリまたはASCII）を読み込んで1つのMeshにする
+///
+/// 複数の `solid` を含むASCII STLは結合される
+pub fn parse_stl(bytes: &[u8], normal_mode: NormalMode) -> Result<Mesh, StlError> {
+    let triangles = if is_ascii(bytes) {
+        parse_ascii(bytes)?
+    } else {
+        parse_binary(bytes)?
+    };
+
+    let mut welder = Welder::default();
+    let indices: Vec<[u32; 3]> = triangles
+        .iter()
+        .map(|triangle| triangle.map(|p| welder.index(p)))
+        // 溶接で潰れた三角形は捨てる
+        .filter(|[a, b, c]| a != b && b != c && c != a)
+        .collect();
+
+    Ok(build_mesh(&welder.positions, None, None, None, &indices, normal_mode))
+}
+
+/// ASCII STLか判定
+///
+/// バイナリSTLのヘッダも "solid" で始まることがあるため、長さが三角形数と一致する場合や
+/// NULバイトを含む場合はバイナリとみなす
+fn is_ascii(bytes: &[u8]) -> bool {
+    if bytes.len() >= BINARY_HEADER_LEN {
+        let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+        if count.checked_mul(BINARY_TRIANGLE_LEN).map(|n| n + BINARY_HEADER_LEN) == Some(bytes.len()) {
+            return false;
+        }
+    }
+    let start = bytes.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(bytes.len());
+    bytes[start..].starts_with(b"solid") && !bytes.contains(&0)
+}
+
+fn parse_binary(bytes: &[u8]) -> Result<Vec<[Vec3; 3]>, StlError> {
+    if bytes.len() < BINARY_HEADER_LEN {
+        return Err(StlError::Truncated {
+            triangles: 0,
+            len: bytes.len(),
+        });
+    }
+    let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]);
+    let required = (count as usize)
+        .checked_mul(BINARY_TRIANGLE_LEN)
+        .and_then(|n| n.checked_add(BINARY_HEADER_LEN));
+    if required.is_none_or(|required| bytes.len() < required) {
+        return Err(StlError::Truncated {
+            triangles: count,
+            len: bytes.len(),
+        });
+    }
+
+    let read_vec3 = |offset: usize| {
+        let f = |i: usize| {
+            let at = offset + i * 4;
+            f32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+        };
+        Vec3::new(f(0), f(1), f(2))
+    };
+    Ok((0..count as usize)
+        .map(|i| {
+            // 先頭12バイトの面法線は読み飛ばす
+            let offset = BINARY_HEADER_LEN + i * BINARY_TRIANGLE_LEN + 12;
+            [read_vec3(offset), read_vec3(offset + 12), read_vec3(offset + 24)]
+        })
+        .collect())
+}
+
+fn parse_ascii(bytes: &[u8]) -> Result<Vec<[Vec3; 3]>, StlError> {
+    let source = String::from_utf8_lossy(bytes);
+    let mut triangles = Vec::new();
+    let mut corners: Vec<Vec3> = Vec::with_capacity(3);
+
+    for (number, line) in source.lines().enumerate() {
+        let syntax = |message: String| StlError::Syntax {
+            line: number + 1,
+            message,
+        };
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("vertex") => {
+                let values: Vec<&str> = tokens.collect();
+                if values.len() != 3 {
+                    return Err(syntax(format!("'vertex' needs 3 values, found {}", values.len())));
+                }
+                let mut point = [0.0; 3];
+                for (value, token) in point.iter_mut().zip(values) {
+                    *value = token
+                        .parse()
+                        .map_err(|_| syntax(format!("invalid number '{}'", token)))?;
+                }
+                corners.push(Vec3::from_array(point));
+            }
+            Some("outer") => corners.clear(),
+            Some("endloop") => {
+                if corners.len() != 3 {
+                    return Err(syntax(format!(
+                        "facet has {} vertices, exactly 3 are required",
+                        corners.len()
+                    )));
+                }
+                triangles.push([corners[0], corners[1], corners[2]]);
+                corners.clear();
+            }
+            // solid / facet / endfacet / endsolid は内容を持たない
+            _ => {}
+        }
+    }
+    Ok(triangles)
+}
+
+/// 位置が完全に一致する頂点を1つにまとめる
+#[derive(Default)]
+struct Welder {
+    positions: Vec<Vec3>,
+    lookup: HashMap<[u32; 3], u32>,
+}
+
+impl Welder {
+    fn index(&mut self, position: Vec3) -> u32 {
+        // -0.0 と 0.0 を同一視する
+        let key = (position + Vec3::ZERO).to_array().map(f32::to_bits);
+        *self.lookup.entry(key).or_insert_with(|| {
+            self.positions.push(position);
+            self.positions.len() as u32 - 1
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 四面体（4頂点・4面、全て外向き）
+    const TETRAHEDRON: [[[f32; 3]; 3]; 4] = [
+        [[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]],
+        [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
+        [[0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]],
+        [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+    ];
+
+    fn binary(triangles: &[[[f32; 3]; 3]]) -> Vec<u8> {
+        // バイナリでもヘッダが "solid" で始まることがある
+        let mut bytes = b"solid binary".to_vec();
+        bytes.resize(80, 0);
+        bytes.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+        for triangle in triangles {
+            bytes.extend_from_slice(&[0; 12]);
+            for value in triangle.iter().flatten() {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            bytes.extend_from_slice(&[0; 2]);
+        }
+        bytes
+    }
+
+    fn ascii(triangles: &[[[f32; 3]; 3]]) -> String {
+        let mut source = String::from("solid test\n");
+        for triangle in triangles {
+            source.push_str("  facet normal 0 0 0\n    outer loop\n");
+            for [x, y, z] in triangle {
+                source.push_str(&format!("      vertex {} {} {}\n", x, y, z));
+            }
+            source.push_str("    endloop\n  endfacet\n");
+        }
+        source.push_str("endsolid test\n");
+        source
+    }
+
+    #[test]
+    fn test_binary_welds_vertices() {
+        let mesh = parse_stl(&binary(&TETRAHEDRON), NormalMode::Smooth).unwrap();
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.indices.len(), 12);
+
+        // 原点の法線は3面の平均で、外側（-1,-1,-1方向）を向く
+        let origin = mesh.vertices.iter().find(|v| v.position == [0.0; 3]).unwrap();
+        let normal = Vec3::from_array(origin.normal);
+        assert!(normal.dot(Vec3::splat(-1.0).normalize()) > 0.99);
+    }
+
+    #[test]
+    fn test_ascii_matches_binary() {
+        let from_ascii = parse_stl(ascii(&TETRAHEDRON).as_bytes(), NormalMode::Smooth).unwrap();
+        let from_binary = parse_stl(&binary(&TETRAHEDRON), NormalMode::Smooth).unwrap();
+        assert_eq!(from_ascii.vertices, from_binary.vertices);
+        assert_eq!(from_ascii.indices, from_binary.indices);
+    }
+
+    #[test]
+    fn test_flat_normals() {
+        let mesh = parse_stl(&binary(&TETRAHEDRON), NormalMode::Flat).unwrap();
+        assert_eq!(mesh.vertices.len(), 12);
+        assert_eq!(mesh.vertices[0].normal, [0.0, 0.0, -1.0]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut truncated = binary(&TETRAHEDRON);
+        truncated.truncate(120);
+        assert!(matches!(
+            parse_stl(&truncated, NormalMode::Smooth),
+            Err(StlError::Truncated { triangles: 4, .. })
+        ));
+
+        let source = "solid x\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nendloop\n";
+        assert!(matches!(
+            parse_stl(source.as_bytes(), NormalMode::Smooth),
+            Err(StlError::Syntax { line: 6, .. })
+        ));
+
+        let source = "solid x\nouter loop\nvertex 0 zero 0\n";
+        assert!(matches!(
+            parse_stl(source.as_bytes(), NormalMode::Smooth),
+            Err(StlError::Syntax { line: 3, .. })
+        ));
+    }
+}
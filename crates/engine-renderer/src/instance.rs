@@ -0,0 +1,188 @@
+//! インスタンス描画モジュール
+//!
+//! Entityごとのモデル行列をインスタンスバッファにまとめ、
+//! 同じメッシュ+マテリアルを1回のドローで描画する
+
+use std::ops::Range;
+
+use bytemuck::{Pod, Zeroable};
+use glam::Mat4;
+use wgpu::{BufferAddress, VertexAttribute, VertexBufferLayout, VertexStepMode};
+
+/// インスタンスごとのGPUデータ
+/// モデル行列を4つの列ベクトルとして頂点属性に渡す（WebGL2互換）
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct InstanceData {
+    pub model: [[f32; 4]; 4],
+    /// マテリアルテーブルの番号（`MaterialTable::insert` の戻り値）
+    pub material: u32,
+}
+
+impl InstanceData {
+    // 0〜2は頂点属性（position, normal, color）が使用
+    const ATTRIBUTES: [VertexAttribute; 5] = wgpu::vertex_attr_array![
+        3 => Float32x4,
+        4 => Float32x4,
+        5 => Float32x4,
+        6 => Float32x4,
+        7 => Uint32,
+    ];
+
+    /// 行列からインスタンスデータを作成（既定のマテリアル）
+    pub fn from_matrix(matrix: Mat4) -> Self {
+        Self {
+            model: matrix.to_cols_array_2d(),
+            material: 0,
+        }
+    }
+
+    /// マテリアル番号を設定
+    pub fn with_material(mut self, material: u32) -> Self {
+        self.material = material;
+        self
+    }
+
+    /// 単位行列のインスタンスデータを作成
+    pub fn identity() -> Self {
+        Self::from_matrix(Mat4::IDENTITY)
+    }
+
+    /// インスタンスバッファレイアウトを取得
+    pub fn desc() -> VertexBufferLayout<'static> {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<InstanceData>() as BufferAddress,
+            step_mode: VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+impl Default for InstanceData {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// 1回のインスタンス描画
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawBatch<K> {
+    /// 描画キー（メッシュ+マテリアル）
+    pub key: K,
+    /// インスタンスバッファ内の範囲
+    pub instances: Range<u32>,
+}
+
+impl<K> DrawBatch<K> {
+    /// インスタンス数
+    pub fn instance_count(&self) -> u32 {
+        self.instances.end - self.instances.start
+    }
+}
+
+/// 描画キーごとにまとめたインスタンス列
+///
+/// `instances` はキー順に連続して並び、各バッチはその部分範囲を指す。
+/// 1回のバッファ書き込みで全インスタンスをアップロードできる
+#[derive(Debug, Clone)]
+pub struct InstanceBatches<K> {
+    instances: Vec<InstanceData>,
+    batches: Vec<DrawBatch<K>>,
+}
+
+impl<K: Ord + Clone> InstanceBatches<K> {
+    /// (キー, インスタンス) の列からバッチを構築
+    ///
+    /// 同じキー内では入力順を保持する
+    pub fn build(items: impl IntoIterator<Item = (K, InstanceData)>) -> Self {
+        let mut items: Vec<(K, InstanceData)> = items.into_iter().collect();
+        items.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut instances = Vec::with_capacity(items.len());
+        let mut batches: Vec<DrawBatch<K>> = Vec::new();
+
+        for (key, instance) in items {
+            let index = instances.len() as u32;
+            instances.push(instance);
+            match batches.last_mut() {
+                Some(batch) if batch.key == key => batch.instances.end = index + 1,
+                _ => batches.push(DrawBatch {
+                    key,
+                    instances: index..index + 1,
+                }),
+            }
+        }
+
+        Self { instances, batches }
+    }
+}
+
+impl<K> InstanceBatches<K> {
+    /// 全インスタンス（GPUへそのまま書き込める）
+    pub fn instances(&self) -> &[InstanceData] {
+        &self.instances
+    }
+
+    /// バッチ一覧
+    pub fn batches(&self) -> &[DrawBatch<K>] {
+        &self.batches
+    }
+
+    /// インスタンス総数
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    /// インスタンスが空か
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec3;
+
+    fn at(x: f32) -> InstanceData {
+        InstanceData::from_matrix(Mat4::from_translation(Vec3::new(x, 0.0, 0.0)))
+    }
+
+    #[test]
+    fn test_instance_size() {
+        // 行列64バイト + マテリアル番号4バイト
+        assert_eq!(std::mem::size_of::<InstanceData>(), 68);
+        assert_eq!(InstanceData::desc().array_stride, 68);
+        assert_eq!(InstanceData::desc().step_mode, VertexStepMode::Instance);
+    }
+
+    #[test]
+    fn test_build_groups_by_key() {
+        let batches = InstanceBatches::build(vec![
+            (1, at(0.0)),
+            (0, at(1.0)),
+            (1, at(2.0)),
+            (0, at(3.0)),
+            (2, at(4.0)),
+        ]);
+
+        assert_eq!(batches.len(), 5);
+        assert_eq!(batches.batches().len(), 3);
+        assert_eq!(batches.batches()[0].key, 0);
+        assert_eq!(batches.batches()[0].instances, 0..2);
+        assert_eq!(batches.batches()[1].instances, 2..4);
+        assert_eq!(batches.batches()[2].instance_count(), 1);
+
+        // 同じキー内の順序は入力順
+        assert_eq!(batches.instances()[0], at(1.0));
+        assert_eq!(batches.instances()[1], at(3.0));
+        assert_eq!(batches.instances()[2], at(0.0));
+    }
+
+    #[test]
+    fn test_build_empty() {
+        let batches = InstanceBatches::<u32>::build(Vec::new());
+        assert!(batches.is_empty());
+        assert!(batches.batches().is_empty());
+    }
+}
@@ -1,11 +1,34 @@
 pub mod mesh;
+pub mod primitives;
 pub mod camera;
 pub mod picking;
 pub mod gizmo;
+pub mod instance;
+pub mod assets;
+pub mod material;
+pub mod light;
+pub mod shadow;
+pub mod texture;
+pub mod image;
+pub mod import;
+pub mod export;
+mod base64;
 
-pub use mesh::{Mesh, Vertex};
+pub use mesh::{Mesh, MeshError, Vertex, UV_LOCATION};
+pub use primitives::{MAX_SEGMENTS, MAX_SUBDIVISIONS};
 pub use camera::{Camera, CameraUniform};
 pub use picking::{Ray, AABB};
+pub use instance::{InstanceData, InstanceBatches, DrawBatch};
+pub use assets::AssetStore;
+pub use material::{MaterialTable, MaterialUniform, MAX_MATERIALS};
+pub use light::{AmbientLight, LightHeader, LightSet, LightUniform, MAX_LIGHTS, MAX_UNIFORM_LIGHTS, NO_SHADOW};
+pub use shadow::{
+    ShadowFrame, ShadowMapUniform, ShadowUniform,
+    CASCADE_COUNT, MAX_SHADOW_MAPS, MAX_SPOT_SHADOWS, SHADOW_MAP_SIZE,
+};
+pub use texture::{ColorSpace, SamplerDesc, TextureData, TextureError, MAX_TEXTURE_SIZE};
+pub use image::{decode_image, decode_texture, premultiply_alpha, DecodeOptions, ImageError, ImageFormat};
+pub use import::NormalMode;
 pub use gizmo::{
     GizmoMode, GizmoAxis, GizmoState, GizmoVertex,
     create_arrow_vertices, create_plane_vertices, create_circle_vertices,
@@ -0,0 +1,364 @@
+//! ライトモジュール
+//!
+//! ライトコンポーネントをフレームごとに集めてGPU用のライトバッファにまとめる。
+//! シェーダーはヘッダ（環境光・ライト数）に続くライト配列をループで評価する。
+//! WebGPUではストレージバッファ、ストレージバッファのないWebGL2では
+//! Uniformバッファに格納するため、上限のライト数が異なる。
+//! 影を落とすライトにはここでシャドウマップの番号を割り当てる
+
+use bytemuck::{Pod, Zeroable};
+use engine_core::{DirectionalLight, EntityId, PointLight, ShadowSettings, SpotLight, World};
+use glam::{Mat4, Vec3};
+
+use crate::shadow::{CASCADE_COUNT, MAX_SPOT_SHADOWS};
+
+/// ストレージバッファ使用時のライト数の上限
+pub const MAX_LIGHTS: usize = 256;
+
+/// Uniformバッファ使用時（WebGL2）のライト数の上限
+///
+/// 32 + 80バイト × 64 ≒ 5KiB（WebGL2のUniformバッファ上限16KiBに収まる）
+pub const MAX_UNIFORM_LIGHTS: usize = 64;
+
+/// ライトの種類（シェーダーの `LIGHT_*` 定数と同じ値）
+pub const LIGHT_DIRECTIONAL: u32 = 0;
+pub const LIGHT_POINT: u32 = 1;
+pub const LIGHT_SPOT: u32 = 2;
+
+/// 影のないライトの `shadow_map`
+pub const NO_SHADOW: i32 = -1;
+
+/// ライトが1つもないシーンで使う平行光源の向き（光の進む方向）
+const DEFAULT_LIGHT_DIRECTION: Vec3 = Vec3::new(-1.0, -1.0, -1.0);
+
+/// 環境光の設定
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmbientLight {
+    /// 色（リニアRGB）
+    pub color: Vec3,
+    /// 強さ（色と乗算される）
+    pub intensity: f32,
+}
+
+impl Default for AmbientLight {
+    fn default() -> Self {
+        Self {
+            color: Vec3::ONE,
+            intensity: 0.3,
+        }
+    }
+}
+
+/// ライトバッファのヘッダ（std140互換、32バイト）
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct LightHeader {
+    /// 環境光（色 × 強さ、wは未使用）
+    pub ambient: [f32; 4],
+    /// 有効なライト数
+    pub count: u32,
+    _padding: [u32; 3],
+}
+
+/// GPU用ライトUniform（std140互換、80バイト）
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct LightUniform {
+    /// ワールド空間の位置（平行光源では未使用）
+    pub position: [f32; 3],
+    /// 光が届く距離（平行光源では未使用）
+    pub range: f32,
+    /// 光の進む方向（単位ベクトル、点光源では未使用）
+    pub direction: [f32; 3],
+    /// `LIGHT_DIRECTIONAL` / `LIGHT_POINT` / `LIGHT_SPOT`
+    pub kind: u32,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    /// 内側・外側の角度の余弦（スポットライトのみ）
+    pub inner_cos: f32,
+    pub outer_cos: f32,
+    /// 影のバイアス（シャドウマップのテクセル単位）
+    pub shadow_bias: f32,
+    pub shadow_normal_bias: f32,
+    /// シャドウマップの番号（平行光源は最初のカスケード）、影がなければ `NO_SHADOW`
+    pub shadow_map: i32,
+    _padding: [u32; 3],
+}
+
+impl LightUniform {
+    /// 平行光源から作成（`direction` は光の進む方向）
+    pub fn directional(light: &DirectionalLight, direction: Vec3) -> Self {
+        Self {
+            direction: direction.normalize_or(Vec3::NEG_Z).to_array(),
+            kind: LIGHT_DIRECTIONAL,
+            color: light.color.to_array(),
+            intensity: light.intensity.max(0.0),
+            ..Self::zeroed()
+        }
+        .with_shadow_bias(&light.shadow)
+    }
+
+    /// 点光源から作成
+    pub fn point(light: &PointLight, position: Vec3) -> Self {
+        Self {
+            position: position.to_array(),
+            range: valid_range(light.range),
+            kind: LIGHT_POINT,
+            color: light.color.to_array(),
+            intensity: light.intensity.max(0.0),
+            shadow_map: NO_SHADOW,
+            ..Self::zeroed()
+        }
+    }
+
+    /// スポットライトから作成
+    ///
+    /// 外側の角度は (0, π/2]、内側の角度は外側よりわずかに小さい値までに丸める
+    pub fn spot(light: &SpotLight, position: Vec3, direction: Vec3) -> Self {
+        let outer = light.outer_cone_angle.clamp(1e-3, std::f32::consts::FRAC_PI_2);
+        let inner = light.inner_cone_angle.clamp(0.0, outer - 1e-3);
+        Self {
+            position: position.to_array(),
+            range: valid_range(light.range),
+            direction: direction.normalize_or(Vec3::NEG_Z).to_array(),
+            kind: LIGHT_SPOT,
+            color: light.color.to_array(),
+            intensity: light.intensity.max(0.0),
+            inner_cos: inner.cos(),
+            outer_cos: outer.cos(),
+            ..Self::zeroed()
+        }
+        .with_shadow_bias(&light.shadow)
+    }
+
+    /// バイアスを設定（シャドウマップは未割り当て）
+    fn with_shadow_bias(mut self, shadow: &ShadowSettings) -> Self {
+        self.shadow_bias = shadow.bias.max(0.0);
+        self.shadow_normal_bias = shadow.normal_bias.max(0.0);
+        self.shadow_map = NO_SHADOW;
+        self
+    }
+
+    /// 影があるか
+    pub fn has_shadow(&self) -> bool {
+        self.shadow_map != NO_SHADOW
+    }
+}
+
+fn valid_range(range: f32) -> f32 {
+    if range.is_finite() { range.max(1e-3) } else { f32::MAX }
+}
+
+/// 1フレーム分のライト
+///
+/// 上限を超える場合は平行光源を優先し、残りはカメラに近い順に残す。
+/// シャドウマップは影を落とす最初の平行光源（カスケード）と、
+/// カメラに近い順に `MAX_SPOT_SHADOWS` 個までのスポットライトに割り当てる
+#[derive(Debug, Clone)]
+pub struct LightSet {
+    header: LightHeader,
+    lights: Vec<LightUniform>,
+}
+
+impl LightSet {
+    /// Worldのライトコンポーネントを集める
+    ///
+    /// ライトの位置・向きはEntityのワールド行列（Transformがなければ原点・-Z向き）で決まる。
+    /// ライトが1つもない場合は既定の平行光源を1つ置く
+    pub fn gather(world: &World, ambient: AmbientLight, camera_position: Vec3, capacity: usize) -> Self {
+        let matrix = |entity: EntityId| world.world_matrix(entity).unwrap_or(Mat4::IDENTITY);
+        let position = |m: Mat4| m.w_axis.truncate();
+        let forward = |m: Mat4| m.transform_vector3(Vec3::NEG_Z);
+
+        // (ライト, 影を落とすか)
+        let mut lights: Vec<(LightUniform, bool)> = world
+            .iter_with::<DirectionalLight>()
+            .map(|(entity, light)| {
+                (LightUniform::directional(light, forward(matrix(entity))), light.shadow.enabled)
+            })
+            .collect();
+        let has_local = world.iter_with::<PointLight>().next().is_some() || world.iter_with::<SpotLight>().next().is_some();
+        if lights.is_empty() && !has_local {
+            let light = DirectionalLight::default();
+            lights.push((LightUniform::directional(&light, DEFAULT_LIGHT_DIRECTION), light.shadow.enabled));
+        }
+
+        let mut local: Vec<(LightUniform, bool)> = world
+            .iter_with::<PointLight>()
+            .map(|(entity, light)| (LightUniform::point(light, position(matrix(entity))), false))
+            .chain(world.iter_with::<SpotLight>().map(|(entity, light)| {
+                let m = matrix(entity);
+                (LightUniform::spot(light, position(m), forward(m)), light.shadow.enabled)
+            }))
+            .collect();
+        let distance = |light: &LightUniform| Vec3::from_array(light.position).distance_squared(camera_position);
+        local.sort_by(|a, b| distance(&a.0).total_cmp(&distance(&b.0)));
+        lights.extend(local);
+        lights.truncate(capacity);
+
+        // シャドウマップの割り当て（0〜CASCADE_COUNT-1 がカスケード、以降がスポットライト）
+        let mut cascades_used = false;
+        let mut spot_shadows = 0;
+        let lights: Vec<LightUniform> = lights
+            .into_iter()
+            .map(|(mut light, casts)| {
+                if casts && light.kind == LIGHT_DIRECTIONAL && !cascades_used {
+                    light.shadow_map = 0;
+                    cascades_used = true;
+                } else if casts && light.kind == LIGHT_SPOT && spot_shadows < MAX_SPOT_SHADOWS {
+                    light.shadow_map = (CASCADE_COUNT + spot_shadows) as i32;
+                    spot_shadows += 1;
+                }
+                light
+            })
+            .collect();
+
+        Self {
+            header: LightHeader {
+                ambient: (ambient.color * ambient.intensity.max(0.0)).extend(1.0).to_array(),
+                count: lights.len() as u32,
+                _padding: [0; 3],
+            },
+            lights,
+        }
+    }
+
+    /// バッファ先頭のヘッダ
+    pub fn header(&self) -> &LightHeader {
+        &self.header
+    }
+
+    /// ヘッダに続くライト配列
+    pub fn lights(&self) -> &[LightUniform] {
+        &self.lights
+    }
+}
+
+/// ライトバッファのバイト数
+pub fn light_buffer_size(capacity: usize) -> usize {
+    std::mem::size_of::<LightHeader>() + capacity * std::mem::size_of::<LightUniform>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engine_core::Transform;
+    use glam::Quat;
+
+    #[test]
+    fn test_uniform_layout() {
+        assert_eq!(std::mem::size_of::<LightHeader>(), 32);
+        assert_eq!(std::mem::size_of::<LightUniform>(), 80);
+        assert!(light_buffer_size(MAX_UNIFORM_LIGHTS) <= 16 * 1024);
+    }
+
+    #[test]
+    fn test_gather_uses_world_transform() {
+        let mut world = World::new();
+        let sun = world.spawn();
+        world.insert(sun, DirectionalLight::default());
+        // -Z を下向きに回転
+        world.insert(
+            sun,
+            Transform::new(Vec3::ZERO, Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2), Vec3::ONE),
+        );
+        let lamp = world.spawn();
+        world.insert(lamp, PointLight { range: 5.0, ..Default::default() });
+        world.insert(lamp, Transform::from_position(Vec3::new(1.0, 2.0, 3.0)));
+
+        let set = LightSet::gather(&world, AmbientLight::default(), Vec3::ZERO, MAX_LIGHTS);
+        assert_eq!(set.header().count, 2);
+        assert!((set.header().ambient[0] - 0.3).abs() < 1e-6);
+        let [sun, lamp] = set.lights() else { panic!("expected 2 lights") };
+        assert_eq!(sun.kind, LIGHT_DIRECTIONAL);
+        assert!(Vec3::from_array(sun.direction).abs_diff_eq(Vec3::NEG_Y, 1e-5));
+        assert_eq!(lamp.kind, LIGHT_POINT);
+        assert_eq!(lamp.position, [1.0, 2.0, 3.0]);
+        assert_eq!(lamp.range, 5.0);
+    }
+
+    #[test]
+    fn test_default_light_when_empty() {
+        let set = LightSet::gather(&World::new(), AmbientLight::default(), Vec3::ZERO, MAX_LIGHTS);
+        assert_eq!(set.lights().len(), 1);
+        assert_eq!(set.lights()[0].kind, LIGHT_DIRECTIONAL);
+
+        // 点光源だけのシーンには既定の平行光源を追加しない
+        let mut world = World::new();
+        let lamp = world.spawn();
+        world.insert(lamp, PointLight::default());
+        let set = LightSet::gather(&world, AmbientLight::default(), Vec3::ZERO, MAX_LIGHTS);
+        assert_eq!(set.lights().len(), 1);
+        assert_eq!(set.lights()[0].kind, LIGHT_POINT);
+    }
+
+    #[test]
+    fn test_cap_keeps_directional_and_nearest() {
+        let mut world = World::new();
+        for i in 0..4 {
+            let lamp = world.spawn();
+            world.insert(lamp, PointLight::default());
+            world.insert(lamp, Transform::from_position(Vec3::new(10.0 - i as f32, 0.0, 0.0)));
+        }
+        let sun = world.spawn();
+        world.insert(sun, DirectionalLight::default());
+
+        let set = LightSet::gather(&world, AmbientLight::default(), Vec3::ZERO, 3);
+        assert_eq!(set.header().count, 3);
+        assert_eq!(set.lights()[0].kind, LIGHT_DIRECTIONAL);
+        assert_eq!(set.lights()[1].position[0], 7.0);
+        assert_eq!(set.lights()[2].position[0], 8.0);
+    }
+
+    #[test]
+    fn test_shadow_assignment() {
+        let mut world = World::new();
+        for _ in 0..2 {
+            let sun = world.spawn();
+            world.insert(sun, DirectionalLight::default());
+        }
+        for i in 0..MAX_SPOT_SHADOWS + 1 {
+            let spot = world.spawn();
+            world.insert(spot, SpotLight::default());
+            world.insert(spot, Transform::from_position(Vec3::new(i as f32, 0.0, 0.0)));
+        }
+        let lamp = world.spawn();
+        world.insert(lamp, PointLight::default());
+        let unshadowed = world.spawn();
+        world.insert(
+            unshadowed,
+            SpotLight {
+                shadow: ShadowSettings { enabled: false, ..Default::default() },
+                ..Default::default()
+            },
+        );
+
+        let set = LightSet::gather(&world, AmbientLight::default(), Vec3::ZERO, MAX_LIGHTS);
+        let maps: Vec<i32> = set.lights().iter().map(|l| l.shadow_map).collect();
+        // 最初の平行光源のみカスケードを使い、スポットライトは近い順
+        assert_eq!(maps[..2], [0, NO_SHADOW]);
+        let spots: Vec<i32> = set
+            .lights()
+            .iter()
+            .filter(|l| l.kind == LIGHT_SPOT && l.has_shadow())
+            .map(|l| l.shadow_map)
+            .collect();
+        assert_eq!(spots, (0..MAX_SPOT_SHADOWS).map(|i| (CASCADE_COUNT + i) as i32).collect::<Vec<_>>());
+        assert!(set.lights().iter().filter(|l| l.kind == LIGHT_POINT).all(|l| !l.has_shadow()));
+        assert_eq!(set.lights().iter().filter(|l| l.has_shadow()).count(), 1 + MAX_SPOT_SHADOWS);
+        assert_eq!(set.lights()[0].shadow_bias, ShadowSettings::default().bias);
+    }
+
+    #[test]
+    fn test_spot_cone_is_clamped() {
+        let spot = SpotLight {
+            inner_cone_angle: 2.0,
+            outer_cone_angle: 3.0,
+            ..Default::default()
+        };
+        let uniform = LightUniform::spot(&spot, Vec3::ZERO, Vec3::new(0.0, 0.0, -2.0));
+        assert_eq!(uniform.direction, [0.0, 0.0, -1.0]);
+        assert!(uniform.outer_cos.abs() < 1e-6);
+        assert!(uniform.inner_cos > uniform.outer_cos);
+    }
+}
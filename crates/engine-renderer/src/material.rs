@@ -0,0 +1,176 @@
+//! マテリアルモジュール
+//!
+//! `Material` コンポーネントをGPU用のUniformに変換し、フレームごとに重複を除いてテーブルにまとめる。
+//! シェーダーはインスタンスごとのマテリアル番号でUniform配列を参照する。
+//! テクスチャはUniformに含めず、描画キーごとのバインドグループで渡す
+
+use std::collections::HashMap;
+
+use bytemuck::{Pod, Zeroable};
+use engine_core::Material;
+
+/// 1フレームで使えるマテリアル数の上限
+///
+/// 48バイト × 256 = 12KiB（WebGL2のUniformバッファ上限16KiBに収まる）。
+/// 影を受けるかどうかもUniformに含むため、同じMaterialでも `ShadowFlags` の違いで2つ使う
+pub const MAX_MATERIALS: usize = 256;
+
+/// GPU用マテリアルUniform（std140互換、48バイト）
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct MaterialUniform {
+    pub base_color: [f32; 4],
+    pub emissive: [f32; 3],
+    pub metallic: f32,
+    pub roughness: f32,
+    /// 1.0ならライティングなし
+    pub unlit: f32,
+    /// 1.0なら法線マップを使う
+    pub normal_map: f32,
+    /// 0.0なら影を受けない
+    pub receives_shadows: f32,
+}
+
+impl MaterialUniform {
+    /// Materialから作成（金属度・粗さは0〜1に丸める）
+    pub fn from_material(material: &Material) -> Self {
+        Self {
+            base_color: material.base_color.to_array(),
+            emissive: material.emissive.to_array(),
+            metallic: material.metallic.clamp(0.0, 1.0),
+            roughness: material.roughness.clamp(0.0, 1.0),
+            unlit: if material.unlit { 1.0 } else { 0.0 },
+            normal_map: if material.normal_texture.is_some() { 1.0 } else { 0.0 },
+            receives_shadows: 1.0,
+        }
+    }
+
+    /// 影を受けるかを設定
+    pub fn with_receives_shadows(mut self, receives: bool) -> Self {
+        self.receives_shadows = if receives { 1.0 } else { 0.0 };
+        self
+    }
+}
+
+impl Default for MaterialUniform {
+    fn default() -> Self {
+        Self::from_material(&Material::default())
+    }
+}
+
+/// 1フレーム分のマテリアルテーブル
+///
+/// 番号0は既定のマテリアル。同じ値のマテリアルは同じ番号を共有する。
+/// 上限を超えた分は既定のマテリアル（番号0）で描画され、`overflow` で数を確認できる
+#[derive(Debug, Clone)]
+pub struct MaterialTable {
+    uniforms: Vec<MaterialUniform>,
+    lookup: HashMap<[u32; 12], u32>,
+    overflow: usize,
+}
+
+impl MaterialTable {
+    /// 既定のマテリアルのみを持つテーブルを作成
+    pub fn new() -> Self {
+        let mut table = Self {
+            uniforms: Vec::new(),
+            lookup: HashMap::new(),
+            overflow: 0,
+        };
+        table.insert(&Material::default());
+        table
+    }
+
+    /// マテリアルを登録して番号を返す
+    pub fn insert(&mut self, material: &Material) -> u32 {
+        self.insert_uniform(MaterialUniform::from_material(material))
+    }
+
+    /// 変換済みのUniformを登録して番号を返す
+    pub fn insert_uniform(&mut self, uniform: MaterialUniform) -> u32 {
+        let key: [u32; 12] = bytemuck::cast(uniform);
+        if let Some(&index) = self.lookup.get(&key) {
+            return index;
+        }
+        if self.uniforms.len() >= MAX_MATERIALS {
+            self.overflow += 1;
+            return 0;
+        }
+        let index = self.uniforms.len() as u32;
+        self.uniforms.push(uniform);
+        self.lookup.insert(key, index);
+        index
+    }
+
+    /// 登録済みのUniform（番号順）
+    pub fn uniforms(&self) -> &[MaterialUniform] {
+        &self.uniforms
+    }
+
+    /// 登録済みマテリアル数（既定のマテリアルを含む）
+    pub fn len(&self) -> usize {
+        self.uniforms.len()
+    }
+
+    /// 上限を超えて既定のマテリアルで代用した登録の数
+    pub fn overflow(&self) -> usize {
+        self.overflow
+    }
+
+    /// 常にfalse（既定のマテリアルが必ず存在する）
+    pub fn is_empty(&self) -> bool {
+        self.uniforms.is_empty()
+    }
+}
+
+impl Default for MaterialTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec4;
+
+    #[test]
+    fn test_uniform_layout() {
+        assert_eq!(std::mem::size_of::<MaterialUniform>(), 48);
+        assert_eq!(std::mem::size_of::<MaterialUniform>() % 16, 0);
+    }
+
+    #[test]
+    fn test_table_deduplicates() {
+        let mut table = MaterialTable::new();
+        let red = Material::from_color(Vec4::new(1.0, 0.0, 0.0, 1.0));
+        assert_eq!(table.insert(&Material::default()), 0);
+        assert_eq!(table.insert(&red), 1);
+        assert_eq!(table.insert(&red.clone()), 1);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.uniforms()[1].base_color, [1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_receives_shadows_is_part_of_key() {
+        let mut table = MaterialTable::new();
+        assert_eq!(MaterialUniform::default().receives_shadows, 1.0);
+        let unshadowed = MaterialUniform::default().with_receives_shadows(false);
+        assert_eq!(table.insert_uniform(MaterialUniform::default()), 0);
+        assert_eq!(table.insert_uniform(unshadowed), 1);
+        assert_eq!(table.insert_uniform(unshadowed), 1);
+    }
+
+    #[test]
+    fn test_table_overflow_uses_default() {
+        let mut table = MaterialTable::new();
+        for i in 1..MAX_MATERIALS {
+            let material = Material::from_color(Vec4::splat(i as f32 + 1.0));
+            assert_eq!(table.insert(&material), i as u32);
+        }
+        assert_eq!(table.overflow(), 0);
+        assert_eq!(table.insert(&Material::from_color(Vec4::splat(-1.0))), 0);
+        assert_eq!(table.len(), MAX_MATERIALS);
+        assert_eq!(table.overflow(), 1);
+    }
+}
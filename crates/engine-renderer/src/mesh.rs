@@ -1,10 +1,15 @@
+use std::fmt;
+
 use bytemuck::{Pod, Zeroable};
+use glam::Vec3;
 use wgpu::{VertexBufferLayout, VertexAttribute, VertexFormat, VertexStepMode, BufferAddress};
 
+use crate::picking::AABB;
+
 /// 頂点構造体
 /// 位置、法線、色を含む
 #[repr(C)]
-#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
 pub struct Vertex {
     pub position: [f32; 3],
     pub normal: [f32; 3],
@@ -12,6 +17,9 @@ pub struct Vertex {
 }
 
 impl Vertex {
+    /// 1頂点あたりのf32数（position, normal, color）
+    pub const FLOATS: usize = 9;
+
     /// 新しい頂点を作成
     pub fn new(position: [f32; 3], normal: [f32; 3], color: [f32; 3]) -> Self {
         Self { position, normal, color }
@@ -46,12 +54,63 @@ impl Vertex {
     }
 }
 
+/// メッシュデータのエラー
+#[derive(Debug, Clone, PartialEq)]
+pub enum MeshError {
+    /// 頂点配列の長さが1頂点のf32数の倍数でない
+    InvalidVertexData { len: usize },
+    /// インデックス数が3の倍数でない
+    InvalidIndexCount { count: usize },
+    /// 頂点数を超えるインデックス
+    IndexOutOfRange { index: u32, vertex_count: usize },
+    /// UV数が頂点数と一致しない
+    UvCountMismatch { uvs: usize, vertex_count: usize },
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidVertexData { len } => write!(
+                f,
+                "vertex data length {} is not a multiple of {} floats",
+                len,
+                Vertex::FLOATS
+            ),
+            Self::InvalidIndexCount { count } => {
+                write!(f, "index count {} is not a multiple of 3", count)
+            }
+            Self::IndexOutOfRange {
+                index,
+                vertex_count,
+            } => write!(
+                f,
+                "index {} is out of range for {} vertices",
+                index, vertex_count
+            ),
+            Self::UvCountMismatch { uvs, vertex_count } => write!(
+                f,
+                "{} uvs do not match {} vertices",
+                uvs, vertex_count
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+/// UVストリームのシェーダーロケーション（0〜2は頂点、3〜7はインスタンス属性）
+pub const UV_LOCATION: u32 = 8;
+
 /// メッシュ構造体
 /// 頂点とインデックスを含む
+///
+/// UVは頂点とは別のストリームで持つ（空ならUVなし、GPUには0が渡る）。
+/// UVの原点は画像の左上（glTFと同じ向き）
 #[derive(Debug, Clone)]
 pub struct Mesh {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
+    pub uvs: Vec<[f32; 2]>,
 }
 
 impl Mesh {
@@ -60,6 +119,7 @@ impl Mesh {
         Self {
             vertices: Vec::new(),
             indices: Vec::new(),
+            uvs: Vec::new(),
         }
     }
 
@@ -138,7 +198,107 @@ impl Mesh {
         ]);
         indices.extend_from_slice(&[base, base+1, base+2, base, base+2, base+3]);
 
-        Self { vertices, indices }
+        // 各面を外側から見て、上方向をY（上下面は奥行き方向）とした平面投影
+        let uvs = vertices
+            .iter()
+            .map(|v| {
+                let normal = Vec3::from(v.normal);
+                let up = if normal.y.abs() > 0.5 { Vec3::new(0.0, 0.0, -normal.y) } else { Vec3::Y };
+                let right = up.cross(normal);
+                let position = Vec3::from(v.position);
+                [position.dot(right) + 0.5, 0.5 - position.dot(up)]
+            })
+            .collect();
+
+        Self { vertices, indices, uvs }
+    }
+
+    /// f32配列（1頂点あたり position, normal, color の9要素）から作成
+    pub fn from_flat(vertices: &[f32], indices: Vec<u32>) -> Result<Self, MeshError> {
+        if !vertices.len().is_multiple_of(Vertex::FLOATS) {
+            return Err(MeshError::InvalidVertexData {
+                len: vertices.len(),
+            });
+        }
+
+        let vertices = vertices
+            .chunks_exact(Vertex::FLOATS)
+            .map(|v| Vertex::new([v[0], v[1], v[2]], [v[3], v[4], v[5]], [v[6], v[7], v[8]]))
+            .collect();
+
+        let mesh = Self { vertices, indices, uvs: Vec::new() };
+        mesh.validate()?;
+        Ok(mesh)
+    }
+
+    /// UVを設定（1頂点あたり u, v の2要素、空ならUVなし）
+    pub fn with_uvs(mut self, uvs: &[f32]) -> Result<Self, MeshError> {
+        if uvs.len() != self.vertices.len() * 2 {
+            return Err(MeshError::UvCountMismatch {
+                uvs: uvs.len() / 2,
+                vertex_count: self.vertices.len(),
+            });
+        }
+        self.uvs = uvs.chunks_exact(2).map(|uv| [uv[0], uv[1]]).collect();
+        Ok(self)
+    }
+
+    /// UVを持つか
+    pub fn has_uvs(&self) -> bool {
+        !self.uvs.is_empty()
+    }
+
+    /// GPUに渡すUVストリーム（UVがなければ頂点数分の0）
+    pub fn uv_stream(&self) -> Vec<[f32; 2]> {
+        if self.has_uvs() {
+            self.uvs.clone()
+        } else {
+            vec![[0.0; 2]; self.vertices.len()]
+        }
+    }
+
+    /// UVストリームの頂点バッファレイアウト
+    pub fn uv_desc() -> VertexBufferLayout<'static> {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<[f32; 2]>() as BufferAddress,
+            step_mode: VertexStepMode::Vertex,
+            attributes: &[VertexAttribute {
+                offset: 0,
+                shader_location: UV_LOCATION,
+                format: VertexFormat::Float32x2,
+            }],
+        }
+    }
+
+    /// インデックスが三角形リストとして有効か、UV数が頂点数と一致するか検証
+    pub fn validate(&self) -> Result<(), MeshError> {
+        if self.has_uvs() && self.uvs.len() != self.vertices.len() {
+            return Err(MeshError::UvCountMismatch {
+                uvs: self.uvs.len(),
+                vertex_count: self.vertices.len(),
+            });
+        }
+        if !self.indices.len().is_multiple_of(3) {
+            return Err(MeshError::InvalidIndexCount {
+                count: self.indices.len(),
+            });
+        }
+        if let Some(&index) = self
+            .indices
+            .iter()
+            .find(|&&i| i as usize >= self.vertices.len())
+        {
+            return Err(MeshError::IndexOutOfRange {
+                index,
+                vertex_count: self.vertices.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// ローカル空間のバウンディングボックス（頂点がなければNone）
+    pub fn bounds(&self) -> Option<AABB> {
+        AABB::from_points(self.vertices.iter().map(|v| Vec3::from(v.position)))
     }
 
     /// 頂点数を取得
@@ -150,6 +310,11 @@ impl Mesh {
     pub fn index_count(&self) -> usize {
         self.indices.len()
     }
+
+    /// 描画する三角形がないか（GPUに空のバッファを作らないよう、アップロード前に確認する）
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
 }
 
 impl Default for Mesh {
@@ -190,10 +355,80 @@ mod tests {
         assert_eq!(cube.index_count(), 36);
     }
 
+    #[test]
+    fn test_from_flat() {
+        let vertices = [
+            0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, //
+            1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, //
+            0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0,
+        ];
+        let mesh = Mesh::from_flat(&vertices, vec![0, 1, 2]).unwrap();
+        assert_eq!(mesh.vertex_count(), 3);
+        assert_eq!(mesh.vertices[1].position, [1.0, 0.0, 0.0]);
+
+        let bounds = mesh.bounds().unwrap();
+        assert_eq!(bounds.min, Vec3::ZERO);
+        assert_eq!(bounds.max, Vec3::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_from_flat_errors() {
+        assert_eq!(
+            Mesh::from_flat(&[0.0; 10], vec![]).unwrap_err(),
+            MeshError::InvalidVertexData { len: 10 }
+        );
+        assert_eq!(
+            Mesh::from_flat(&[0.0; 18], vec![0, 1]).unwrap_err(),
+            MeshError::InvalidIndexCount { count: 2 }
+        );
+        assert_eq!(
+            Mesh::from_flat(&[0.0; 18], vec![0, 1, 2]).unwrap_err(),
+            MeshError::IndexOutOfRange {
+                index: 2,
+                vertex_count: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_cube_uvs() {
+        let cube = Mesh::cube();
+        assert_eq!(cube.uvs.len(), cube.vertex_count());
+        // 各面が0〜1のUV範囲を覆う
+        for face in cube.uvs.chunks_exact(4) {
+            let mut corners: Vec<[u32; 2]> = face.iter().map(|uv| uv.map(|c| c as u32)).collect();
+            corners.sort();
+            assert_eq!(corners, vec![[0, 0], [0, 1], [1, 0], [1, 1]]);
+        }
+        // +Z面の左下頂点は画像の左下（v=1）
+        assert_eq!(cube.uvs[16], [0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_with_uvs() {
+        let mesh = Mesh::from_flat(&[0.0; 18], vec![]).unwrap();
+        assert!(!mesh.has_uvs());
+        assert_eq!(mesh.uv_stream(), vec![[0.0; 2]; 2]);
+
+        let mesh = mesh.with_uvs(&[0.0, 0.0, 1.0, 0.5]).unwrap();
+        assert_eq!(mesh.uvs[1], [1.0, 0.5]);
+        assert!(mesh.validate().is_ok());
+
+        assert_eq!(
+            Mesh::from_flat(&[0.0; 18], vec![]).unwrap().with_uvs(&[0.0; 2]).unwrap_err(),
+            MeshError::UvCountMismatch { uvs: 1, vertex_count: 2 }
+        );
+    }
+
     #[test]
     fn test_empty_mesh() {
         let mesh = Mesh::new();
         assert_eq!(mesh.vertex_count(), 0);
         assert_eq!(mesh.index_count(), 0);
+        assert!(mesh.is_empty());
+
+        // 頂点だけでインデックスのないメッシュも空として扱う
+        assert!(Mesh::from_flat(&[0.0; 18], vec![]).unwrap().is_empty());
+        assert!(!Mesh::from_flat(&[0.0; 27], vec![0, 1, 2]).unwrap().is_empty());
     }
 }
@@ -2,7 +2,7 @@
 //!
 //! Provides CPU-based entity picking using raycasting.
 
-use glam::Vec3;
+use glam::{Mat4, Vec3};
 
 /// Ray for raycasting
 #[derive(Debug, Clone, Copy)]
@@ -85,6 +85,35 @@ impl AABB {
         }
     }
 
+    /// Create the smallest AABB containing all points (None if empty)
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let (min, max) = points.fold((first, first), |(min, max), p| (min.min(p), max.max(p)));
+        Some(Self { min, max })
+    }
+
+    /// Transform the box by a matrix and return the enclosing AABB
+    pub fn transformed(&self, matrix: Mat4) -> Self {
+        let corners = (0..8).map(|i| {
+            matrix.transform_point3(Vec3::new(
+                if i & 1 == 0 { self.min.x } else { self.max.x },
+                if i & 2 == 0 { self.min.y } else { self.max.y },
+                if i & 4 == 0 { self.min.z } else { self.max.z },
+            ))
+        });
+        // 8頂点あるため必ずSome
+        Self::from_points(corners).unwrap_or(*self)
+    }
+
+    /// Smallest AABB containing both boxes
+    pub fn union(&self, other: &AABB) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
     /// Check if a point is inside the AABB
     pub fn contains(&self, point: Vec3) -> bool {
         point.x >= self.min.x
@@ -146,4 +175,25 @@ mod tests {
         assert_eq!(aabb.min, Vec3::new(0.0, 0.0, 0.0));
         assert_eq!(aabb.max, Vec3::new(2.0, 4.0, 6.0));
     }
+
+    #[test]
+    fn test_from_points() {
+        let aabb = AABB::from_points([Vec3::new(1.0, -2.0, 0.0), Vec3::new(-1.0, 3.0, 0.5)]).unwrap();
+        assert_eq!(aabb.min, Vec3::new(-1.0, -2.0, 0.0));
+        assert_eq!(aabb.max, Vec3::new(1.0, 3.0, 0.5));
+        assert!(AABB::from_points([]).is_none());
+    }
+
+    #[test]
+    fn test_transformed() {
+        let aabb = AABB::unit_cube(Vec3::ZERO, Vec3::ONE);
+        let matrix = Mat4::from_scale_rotation_translation(
+            Vec3::splat(2.0),
+            glam::Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+            Vec3::new(10.0, 0.0, 0.0),
+        );
+        let moved = aabb.transformed(matrix);
+        assert!((moved.min - Vec3::new(9.0, -1.0, -1.0)).length() < 1e-5);
+        assert!((moved.max - Vec3::new(11.0, 1.0, 1.0)).length() < 1e-5);
+    }
 }
@@ -0,0 +1,515 @@
+//! プリミティブ生成モジュール
+//!
+//! パラメータ指定で基本形状のメッシュを生成する。
+//! すべてY軸を上、原点中心とし、外側から見て反時計回り（CCW）が表面。
+//! UVは回転体なら経度方向がu・断面の上から下がv、平面形状なら平面投影。
+//! 分割数は形状ごとの下限と `MAX_SEGMENTS`・`MAX_SUBDIVISIONS` の間に丸める
+
+use std::collections::HashMap;
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use glam::Vec3;
+
+use crate::mesh::{Mesh, Vertex};
+
+/// segments・rings などの分割数の上限（1024 x 1024 で約100万頂点）
+pub const MAX_SEGMENTS: u32 = 1024;
+/// Icosphereの分割回数の上限（6回で約4万頂点）
+pub const MAX_SUBDIVISIONS: u32 = 6;
+
+/// プリミティブの頂点色
+const DEFAULT_COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+
+/// 回転体の断面上の点
+#[derive(Debug, Clone, Copy)]
+struct ProfilePoint {
+    /// 回転軸からの距離（0なら極）
+    radius: f32,
+    y: f32,
+    /// 断面内の法線 (半径方向, Y方向)
+    normal: (f32, f32),
+}
+
+impl ProfilePoint {
+    fn new(radius: f32, y: f32, normal: (f32, f32)) -> Self {
+        Self { radius, y, normal }
+    }
+}
+
+/// メッシュ構築用ヘルパー
+struct MeshBuilder {
+    mesh: Mesh,
+}
+
+impl MeshBuilder {
+    fn new() -> Self {
+        Self { mesh: Mesh::new() }
+    }
+
+    fn vertex(&mut self, position: Vec3, normal: Vec3, uv: [f32; 2]) -> u32 {
+        let index = self.mesh.vertices.len() as u32;
+        self.mesh.vertices.push(Vertex::new(
+            position.to_array(),
+            normal.normalize_or_zero().to_array(),
+            DEFAULT_COLOR,
+        ));
+        self.mesh.uvs.push(uv);
+        index
+    }
+
+    fn triangle(&mut self, a: u32, b: u32, c: u32) {
+        self.mesh.indices.extend_from_slice(&[a, b, c]);
+    }
+
+    /// 断面を上から下へ並べた点列をY軸周りに回転させる
+    ///
+    /// 半径0の点は極として扱い、三角形1枚で閉じる。
+    /// 極の法線は各セグメントの中央角で計算する
+    fn revolve(&mut self, profile: &[ProfilePoint], segments: u32) {
+        let base = self.mesh.vertices.len() as u32;
+        let row = segments + 1;
+
+        let last = (profile.len() - 1).max(1) as f32;
+        for (i, point) in profile.iter().enumerate() {
+            let pole = point.radius == 0.0;
+            let v = i as f32 / last;
+            for s in 0..=segments {
+                let mut u = s as f32 / segments as f32;
+                if pole {
+                    u += 0.5 / segments as f32;
+                }
+                let (sin, cos) = (u * TAU).sin_cos();
+                let position = Vec3::new(point.radius * cos, point.y, -point.radius * sin);
+                let normal = Vec3::new(point.normal.0 * cos, point.normal.1, -point.normal.0 * sin);
+                // 極の最後の頂点は三角形から参照されない
+                self.vertex(position, normal, [u.min(1.0), v]);
+            }
+        }
+
+        for i in 0..profile.len() as u32 - 1 {
+            let upper_pole = profile[i as usize].radius == 0.0;
+            let lower_pole = profile[i as usize + 1].radius == 0.0;
+            for s in 0..segments {
+                let a = base + i * row + s;
+                let b = a + 1;
+                let c = a + row;
+                let d = c + 1;
+                if upper_pole {
+                    self.triangle(a, c, d);
+                } else if lower_pole {
+                    self.triangle(a, c, b);
+                } else {
+                    self.triangle(a, c, b);
+                    self.triangle(b, c, d);
+                }
+            }
+        }
+    }
+
+    /// Y軸に垂直な円盤（up=trueで+Y向き）
+    ///
+    /// UVは外側から見て円が0〜1に収まる平面投影
+    fn cap(&mut self, y: f32, radius: f32, segments: u32, up: bool) {
+        let normal = if up { Vec3::Y } else { Vec3::NEG_Y };
+        let flip = if up { 1.0 } else { -1.0 };
+        let center = self.vertex(Vec3::new(0.0, y, 0.0), normal, [0.5, 0.5]);
+        for s in 0..=segments {
+            let (sin, cos) = (s as f32 / segments as f32 * TAU).sin_cos();
+            let uv = [0.5 + 0.5 * cos, 0.5 - 0.5 * sin * flip];
+            self.vertex(Vec3::new(radius * cos, y, -radius * sin), normal, uv);
+        }
+        for s in 0..segments {
+            let rim = center + 1 + s;
+            if up {
+                self.triangle(center, rim, rim + 1);
+            } else {
+                self.triangle(center, rim + 1, rim);
+            }
+        }
+    }
+
+    fn build(self) -> Mesh {
+        self.mesh
+    }
+}
+
+impl Mesh {
+    /// UV球（segments: 経度方向の分割数、rings: 緯度方向の分割数）
+    pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Self {
+        let segments = segments.clamp(3, MAX_SEGMENTS);
+        let rings = rings.clamp(2, MAX_SEGMENTS);
+
+        let profile: Vec<ProfilePoint> = (0..=rings)
+            .map(|r| {
+                let theta = r as f32 / rings as f32 * PI;
+                let (sin, cos) = theta.sin_cos();
+                // 極は半径を厳密に0にする
+                let sin = if r == 0 || r == rings { 0.0 } else { sin };
+                ProfilePoint::new(radius * sin, radius * cos, (sin, cos))
+            })
+            .collect();
+
+        let mut builder = MeshBuilder::new();
+        builder.revolve(&profile, segments);
+        builder.build()
+    }
+
+    /// 正二十面体を再分割した球（subdivisions: 分割回数、1回ごとに面数4倍）
+    pub fn icosphere(radius: f32, subdivisions: u32) -> Self {
+        let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+        let mut positions: Vec<Vec3> = [
+            [-1.0, t, 0.0],
+            [1.0, t, 0.0],
+            [-1.0, -t, 0.0],
+            [1.0, -t, 0.0],
+            [0.0, -1.0, t],
+            [0.0, 1.0, t],
+            [0.0, -1.0, -t],
+            [0.0, 1.0, -t],
+            [t, 0.0, -1.0],
+            [t, 0.0, 1.0],
+            [-t, 0.0, -1.0],
+            [-t, 0.0, 1.0],
+        ]
+        .iter()
+        .map(|p| Vec3::from_array(*p).normalize())
+        .collect();
+
+        let mut faces: Vec<[u32; 3]> = vec![
+            [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+            [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+            [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+            [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions.min(MAX_SUBDIVISIONS) {
+            // 辺の中点を共有して頂点の重複を防ぐ
+            let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+            let mut midpoint = |a: u32, b: u32, positions: &mut Vec<Vec3>| {
+                let key = (a.min(b), a.max(b));
+                *midpoints.entry(key).or_insert_with(|| {
+                    let p = (positions[a as usize] + positions[b as usize]).normalize();
+                    positions.push(p);
+                    positions.len() as u32 - 1
+                })
+            };
+
+            let mut next = Vec::with_capacity(faces.len() * 4);
+            for [a, b, c] in faces {
+                let ab = midpoint(a, b, &mut positions);
+                let bc = midpoint(b, c, &mut positions);
+                let ca = midpoint(c, a, &mut positions);
+                next.extend_from_slice(&[[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
+            }
+            faces = next;
+        }
+
+        // 頂点を共有するため経度の継ぎ目ではUVが折り返す
+        let mut builder = MeshBuilder::new();
+        for p in &positions {
+            let u = (-p.z).atan2(p.x).rem_euclid(TAU) / TAU;
+            let v = p.y.clamp(-1.0, 1.0).acos() / PI;
+            builder.vertex(*p * radius, *p, [u, v]);
+        }
+        for [a, b, c] in faces {
+            builder.triangle(a, b, c);
+        }
+        builder.build()
+    }
+
+    /// XZ平面上の+Y向き平面（segments_x/segments_z: 各方向の分割数）
+    pub fn plane(width: f32, depth: f32, segments_x: u32, segments_z: u32) -> Self {
+        let segments_x = segments_x.clamp(1, MAX_SEGMENTS);
+        let segments_z = segments_z.clamp(1, MAX_SEGMENTS);
+
+        let mut builder = MeshBuilder::new();
+        for z in 0..=segments_z {
+            for x in 0..=segments_x {
+                let u = x as f32 / segments_x as f32;
+                let v = z as f32 / segments_z as f32;
+                let position = Vec3::new((u - 0.5) * width, 0.0, (v - 0.5) * depth);
+                builder.vertex(position, Vec3::Y, [u, v]);
+            }
+        }
+
+        let row = segments_x + 1;
+        for z in 0..segments_z {
+            for x in 0..segments_x {
+                let a = z * row + x;
+                let b = a + 1;
+                let c = a + row;
+                let d = c + 1;
+                builder.triangle(a, c, b);
+                builder.triangle(b, c, d);
+            }
+        }
+        builder.build()
+    }
+
+    /// XY平面上の+Z向き四角形
+    pub fn quad(width: f32, height: f32) -> Self {
+        let (w, h) = (width * 0.5, height * 0.5);
+        let mut builder = MeshBuilder::new();
+        let a = builder.vertex(Vec3::new(-w, -h, 0.0), Vec3::Z, [0.0, 1.0]);
+        let b = builder.vertex(Vec3::new(w, -h, 0.0), Vec3::Z, [1.0, 1.0]);
+        let c = builder.vertex(Vec3::new(w, h, 0.0), Vec3::Z, [1.0, 0.0]);
+        let d = builder.vertex(Vec3::new(-w, h, 0.0), Vec3::Z, [0.0, 0.0]);
+        builder.triangle(a, b, c);
+        builder.triangle(a, c, d);
+        builder.build()
+    }
+
+    /// XZ平面上の+Y向き円盤
+    pub fn disc(radius: f32, segments: u32) -> Self {
+        let mut builder = MeshBuilder::new();
+        builder.cap(0.0, radius, segments.clamp(3, MAX_SEGMENTS), true);
+        builder.build()
+    }
+
+    /// 上下に蓋のある円柱
+    pub fn cylinder(radius: f32, height: f32, segments: u32) -> Self {
+        let segments = segments.clamp(3, MAX_SEGMENTS);
+        let half = height * 0.5;
+
+        let mut builder = MeshBuilder::new();
+        builder.revolve(
+            &[
+                ProfilePoint::new(radius, half, (1.0, 0.0)),
+                ProfilePoint::new(radius, -half, (1.0, 0.0)),
+            ],
+            segments,
+        );
+        builder.cap(half, radius, segments, true);
+        builder.cap(-half, radius, segments, false);
+        builder.build()
+    }
+
+    /// 底面に蓋のある円錐（頂点が+Y側）
+    pub fn cone(radius: f32, height: f32, segments: u32) -> Self {
+        let segments = segments.clamp(3, MAX_SEGMENTS);
+        let half = height * 0.5;
+        // 側面の法線は斜面に垂直
+        let slant = Vec3::new(height, radius, 0.0).normalize_or_zero();
+        let normal = (slant.x, slant.y);
+
+        let mut builder = MeshBuilder::new();
+        builder.revolve(
+            &[
+                ProfilePoint::new(0.0, half, normal),
+                ProfilePoint::new(radius, -half, normal),
+            ],
+            segments,
+        );
+        builder.cap(-half, radius, segments, false);
+        builder.build()
+    }
+
+    /// Y軸周りのトーラス
+    /// （major: 中心から管の中心までの半径、minor: 管の半径）
+    pub fn torus(
+        major_radius: f32,
+        minor_radius: f32,
+        major_segments: u32,
+        minor_segments: u32,
+    ) -> Self {
+        let major_segments = major_segments.clamp(3, MAX_SEGMENTS);
+        let minor_segments = minor_segments.clamp(3, MAX_SEGMENTS);
+
+        // 管の断面を外側から上→内側→下の順に一周する
+        let profile: Vec<ProfilePoint> = (0..=minor_segments)
+            .map(|i| {
+                let alpha = FRAC_PI_2 - i as f32 / minor_segments as f32 * TAU;
+                let (sin, cos) = alpha.sin_cos();
+                ProfilePoint::new(major_radius + minor_radius * cos, minor_radius * sin, (cos, sin))
+            })
+            .collect();
+
+        let mut builder = MeshBuilder::new();
+        builder.revolve(&profile, major_segments);
+        builder.build()
+    }
+
+    /// カプセル（height: 円柱部分の長さ、rings: 半球1つあたりの緯度分割数）
+    pub fn capsule(radius: f32, height: f32, segments: u32, rings: u32) -> Self {
+        let segments = segments.clamp(3, MAX_SEGMENTS);
+        let rings = rings.clamp(1, MAX_SEGMENTS);
+        let half = height * 0.5;
+
+        let hemisphere = |r: u32, offset: f32, upper: bool| {
+            let t = r as f32 / rings as f32 * FRAC_PI_2;
+            let theta = if upper { t } else { FRAC_PI_2 + t };
+            let (sin, cos) = theta.sin_cos();
+            let pole = (upper && r == 0) || (!upper && r == rings);
+            let sin = if pole { 0.0 } else { sin };
+            ProfilePoint::new(radius * sin, radius * cos + offset, (sin, cos))
+        };
+
+        let profile: Vec<ProfilePoint> = (0..=rings)
+            .map(|r| hemisphere(r, half, true))
+            .chain((0..=rings).map(|r| hemisphere(r, -half, false)))
+            .collect();
+
+        let mut builder = MeshBuilder::new();
+        builder.revolve(&profile, segments);
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 位置で頂点を統合した上で、全ての有向辺に逆向きの辺が1本ずつあるか
+    fn is_watertight(mesh: &Mesh) -> bool {
+        let key = |v: &Vertex| v.position.map(|c| (c * 10_000.0).round() as i64);
+        let mut welded: HashMap<[i64; 3], u32> = HashMap::new();
+        let ids: Vec<u32> = mesh
+            .vertices
+            .iter()
+            .map(|v| {
+                let next = welded.len() as u32;
+                *welded.entry(key(v)).or_insert(next)
+            })
+            .collect();
+
+        let mut edges: HashMap<(u32, u32), i32> = HashMap::new();
+        for tri in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [ids[tri[0] as usize], ids[tri[1] as usize], ids[tri[2] as usize]];
+            if a == b || b == c || c == a {
+                return false;
+            }
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                *edges.entry((u, v)).or_default() += 1;
+            }
+        }
+        edges
+            .iter()
+            .all(|(&(u, v), &count)| count == 1 && edges.get(&(v, u)) == Some(&1))
+    }
+
+    /// 面の向き（CCW）が頂点法線と一致しているか
+    fn assert_winding(mesh: &Mesh) {
+        for tri in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[tri[i] as usize]);
+            let face = (Vec3::from(b.position) - Vec3::from(a.position))
+                .cross(Vec3::from(c.position) - Vec3::from(a.position));
+            let normal = Vec3::from(a.normal) + Vec3::from(b.normal) + Vec3::from(c.normal);
+            assert!(face.dot(normal) > 0.0, "triangle {:?} faces inward", tri);
+        }
+    }
+
+    fn assert_unit_normals(mesh: &Mesh) {
+        for v in &mesh.vertices {
+            let length = Vec3::from(v.normal).length();
+            assert!((length - 1.0).abs() < 1e-4, "normal length {}", length);
+        }
+    }
+
+    fn assert_valid(mesh: &Mesh, closed: bool) {
+        mesh.validate().unwrap();
+        assert_unit_normals(mesh);
+        assert_winding(mesh);
+        assert_eq!(mesh.uvs.len(), mesh.vertex_count());
+        assert!(mesh.uvs.iter().flatten().all(|c| (0.0..=1.0).contains(c)));
+        assert_eq!(is_watertight(mesh), closed);
+    }
+
+    #[test]
+    fn test_uv_sphere() {
+        let mesh = Mesh::uv_sphere(1.0, 16, 8);
+        assert_eq!(mesh.vertex_count(), 9 * 17);
+        assert_eq!(mesh.index_count(), 6 * 16 * 7);
+        assert_valid(&mesh, true);
+
+        for v in &mesh.vertices {
+            assert!((Vec3::from(v.position).length() - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_icosphere() {
+        let mesh = Mesh::icosphere(2.0, 0);
+        assert_eq!(mesh.vertex_count(), 12);
+        assert_eq!(mesh.index_count(), 60);
+        assert_valid(&mesh, true);
+
+        let mesh = Mesh::icosphere(2.0, 2);
+        assert_eq!(mesh.vertex_count(), 10 * 16 + 2);
+        assert_eq!(mesh.index_count(), 60 * 16);
+        assert_valid(&mesh, true);
+    }
+
+    #[test]
+    fn test_plane() {
+        let mesh = Mesh::plane(2.0, 4.0, 3, 2);
+        assert_eq!(mesh.vertex_count(), 4 * 3);
+        assert_eq!(mesh.index_count(), 6 * 3 * 2);
+        assert_valid(&mesh, false);
+
+        let bounds = mesh.bounds().unwrap();
+        assert_eq!(bounds.min, Vec3::new(-1.0, 0.0, -2.0));
+        assert_eq!(bounds.max, Vec3::new(1.0, 0.0, 2.0));
+    }
+
+    #[test]
+    fn test_quad_and_disc() {
+        let quad = Mesh::quad(1.0, 1.0);
+        assert_eq!(quad.vertex_count(), 4);
+        assert_eq!(quad.index_count(), 6);
+        assert_valid(&quad, false);
+
+        let disc = Mesh::disc(1.0, 12);
+        assert_eq!(disc.vertex_count(), 12 + 2);
+        assert_eq!(disc.index_count(), 3 * 12);
+        assert_valid(&disc, false);
+    }
+
+    #[test]
+    fn test_cylinder() {
+        let mesh = Mesh::cylinder(0.5, 2.0, 12);
+        assert_eq!(mesh.vertex_count(), 4 * 12 + 6);
+        assert_eq!(mesh.index_count(), 12 * 12);
+        assert_valid(&mesh, true);
+    }
+
+    #[test]
+    fn test_cone() {
+        let mesh = Mesh::cone(0.5, 1.0, 12);
+        assert_eq!(mesh.vertex_count(), 3 * 12 + 4);
+        assert_eq!(mesh.index_count(), 6 * 12);
+        assert_valid(&mesh, true);
+    }
+
+    #[test]
+    fn test_torus() {
+        let mesh = Mesh::torus(1.0, 0.25, 24, 12);
+        assert_eq!(mesh.vertex_count(), 25 * 13);
+        assert_eq!(mesh.index_count(), 6 * 24 * 12);
+        assert_valid(&mesh, true);
+    }
+
+    #[test]
+    fn test_capsule() {
+        let mesh = Mesh::capsule(0.5, 1.0, 16, 4);
+        assert_eq!(mesh.vertex_count(), 2 * 5 * 17);
+        assert_eq!(mesh.index_count(), 12 * 16 * 4);
+        assert_valid(&mesh, true);
+
+        let bounds = mesh.bounds().unwrap();
+        assert!((bounds.max.y - 1.0).abs() < 1e-5);
+        assert!((bounds.min.y + 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_parameters_are_clamped() {
+        let mesh = Mesh::uv_sphere(1.0, 0, 0);
+        assert_eq!(mesh.vertex_count(), 3 * 4);
+        assert_valid(&mesh, true);
+
+        // 上限を超える分割数は上限に丸める（segments + 1 が桁あふれしない）
+        let mesh = Mesh::plane(1.0, 1.0, u32::MAX, 1);
+        assert_eq!(mesh.vertex_count(), (MAX_SEGMENTS as usize + 1) * 2);
+        let mesh = Mesh::icosphere(1.0, u32::MAX);
+        assert_eq!(mesh.vertex_count(), Mesh::icosphere(1.0, MAX_SUBDIVISIONS).vertex_count());
+    }
+}
@@ -0,0 +1,280 @@
+//! シャドウマップモジュール
+//!
+//! 平行光源はカメラの視錐台を距離で分割したカスケードシャドウマップ、
+//! スポットライトは透視投影のシャドウマップで影を描く。
+//! シャドウマップは1枚の深度テクスチャ配列にまとめ、レイヤー番号で参照する
+//! （0〜CASCADE_COUNT-1 がカスケード、以降がスポットライト）
+
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Vec3};
+
+use crate::camera::Camera;
+use crate::light::{LightUniform, LIGHT_DIRECTIONAL, LIGHT_SPOT};
+use crate::picking::AABB;
+
+/// シャドウマップ1枚の解像度
+pub const SHADOW_MAP_SIZE: u32 = 1024;
+/// 平行光源のカスケード数
+pub const CASCADE_COUNT: usize = 3;
+/// 影を落とすスポットライトの最大数
+pub const MAX_SPOT_SHADOWS: usize = 4;
+/// シャドウマップの総数（深度テクスチャ配列のレイヤー数）
+pub const MAX_SHADOW_MAPS: usize = CASCADE_COUNT + MAX_SPOT_SHADOWS;
+/// カスケードが覆うカメラからの最大距離
+pub const MAX_SHADOW_DISTANCE: f32 = 60.0;
+/// カスケード分割の対数分割の割合（0 = 均等、1 = 対数）
+const CASCADE_SPLIT_LAMBDA: f32 = 0.6;
+/// スポットライトのシャドウマップのニアクリップ
+const SPOT_SHADOW_NEAR: f32 = 0.05;
+/// スポットライトの最大視野角（透視投影が破綻しないように制限する）
+const MAX_SPOT_SHADOW_FOV: f32 = 170.0 * std::f32::consts::PI / 180.0;
+
+/// シャドウマップ1枚分のUniform（80バイト）
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct ShadowMapUniform {
+    /// ワールド座標からシャドウマップのクリップ座標への変換
+    pub view_proj: [[f32; 4]; 4],
+    /// テクセルのワールド空間での大きさ（透視投影では距離1あたり）
+    pub texel_size: f32,
+    /// 透視投影なら1
+    pub perspective: f32,
+    _padding: [f32; 2],
+}
+
+/// シャドウ全体のUniform
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct ShadowUniform {
+    pub maps: [ShadowMapUniform; MAX_SHADOW_MAPS],
+    /// 各カスケードの遠端（カメラの視線方向の距離）
+    pub cascade_splits: [f32; 4],
+    /// カメラの視線方向（xyz）とカスケード数（w、カスケードがなければ0）
+    pub camera_forward: [f32; 4],
+}
+
+/// 1フレーム分のシャドウマップ
+///
+/// `LightSet` で割り当てたシャドウマップの変換行列を計算する
+#[derive(Debug, Clone)]
+pub struct ShadowFrame {
+    uniform: ShadowUniform,
+    /// 描画するレイヤー
+    layers: Vec<usize>,
+}
+
+impl ShadowFrame {
+    /// ライトとカメラからシャドウマップを計算
+    ///
+    /// `casters` は影を落とすオブジェクト全体の境界で、カスケードの深度範囲を
+    /// 視錐台の外にある遮蔽物まで広げるために使う
+    pub fn build(lights: &[LightUniform], camera: &Camera, casters: Option<&AABB>) -> Self {
+        let mut uniform = ShadowUniform::zeroed();
+        let mut layers = Vec::new();
+        uniform.camera_forward = camera.forward().extend(0.0).to_array();
+
+        for light in lights.iter().filter(|l| l.has_shadow()) {
+            let first = light.shadow_map as usize;
+            match light.kind {
+                LIGHT_DIRECTIONAL if first == 0 => {
+                    let direction = Vec3::from_array(light.direction);
+                    let splits = cascade_splits(camera.near(), camera.far().min(MAX_SHADOW_DISTANCE));
+                    let mut near = camera.near();
+                    for (cascade, &far) in splits.iter().enumerate() {
+                        uniform.maps[cascade] = cascade_map(camera, near, far, direction, casters);
+                        uniform.cascade_splits[cascade] = far;
+                        layers.push(cascade);
+                        near = far;
+                    }
+                    uniform.camera_forward[3] = CASCADE_COUNT as f32;
+                }
+                LIGHT_SPOT if (CASCADE_COUNT..MAX_SHADOW_MAPS).contains(&first) => {
+                    uniform.maps[first] = spot_map(light);
+                    layers.push(first);
+                }
+                _ => {}
+            }
+        }
+        Self { uniform, layers }
+    }
+
+    pub fn uniform(&self) -> &ShadowUniform {
+        &self.uniform
+    }
+
+    /// 描画するシャドウマップの (レイヤー, 変換行列)
+    pub fn passes(&self) -> impl Iterator<Item = (usize, Mat4)> + '_ {
+        self.layers
+            .iter()
+            .map(|&layer| (layer, Mat4::from_cols_array_2d(&self.uniform.maps[layer].view_proj)))
+    }
+}
+
+/// カスケードの遠端の距離（均等分割と対数分割の混合）
+pub fn cascade_splits(near: f32, far: f32) -> [f32; CASCADE_COUNT] {
+    let near = near.max(1e-3);
+    let far = far.max(near * 2.0);
+    std::array::from_fn(|i| {
+        let t = (i + 1) as f32 / CASCADE_COUNT as f32;
+        let uniform = near + (far - near) * t;
+        let log = near * (far / near).powf(t);
+        uniform + (log - uniform) * CASCADE_SPLIT_LAMBDA
+    })
+}
+
+/// 視錐台の一部を覆う平行投影のシャドウマップ
+///
+/// 視錐台の境界球を覆うことでカメラの回転で大きさが変わらないようにし、
+/// 中心をテクセル単位に揃えてカメラの移動による影のちらつきを抑える
+fn cascade_map(camera: &Camera, near: f32, far: f32, direction: Vec3, casters: Option<&AABB>) -> ShadowMapUniform {
+    let corners = camera.frustum_corners(near, far);
+    let center = corners.iter().copied().sum::<Vec3>() / corners.len() as f32;
+    let radius = corners.iter().map(|c| c.distance(center)).fold(0.0, f32::max);
+    let radius = (radius * 16.0).ceil() / 16.0;
+
+    let direction = direction.normalize_or(Vec3::NEG_Y);
+    let up = if direction.y.abs() > 0.99 { Vec3::Z } else { Vec3::Y };
+    let view = Mat4::look_to_rh(Vec3::ZERO, direction, up);
+
+    let texel = 2.0 * radius / SHADOW_MAP_SIZE as f32;
+    let mut center = view.transform_point3(center);
+    center.x = (center.x / texel).round() * texel;
+    center.y = (center.y / texel).round() * texel;
+
+    // ライト空間では -Z が光の進む方向。光源側の遮蔽物まで深度範囲を広げる
+    let mut max_z = center.z + radius;
+    if let Some(casters) = casters {
+        max_z = max_z.max(casters.transformed(view).max.z);
+    }
+    let min_z = center.z - radius;
+    let projection = Mat4::orthographic_rh(
+        center.x - radius,
+        center.x + radius,
+        center.y - radius,
+        center.y + radius,
+        -max_z,
+        -min_z,
+    );
+
+    ShadowMapUniform {
+        view_proj: (projection * view).to_cols_array_2d(),
+        texel_size: texel,
+        perspective: 0.0,
+        _padding: [0.0; 2],
+    }
+}
+
+/// スポットライトの円錐を覆う透視投影のシャドウマップ
+fn spot_map(light: &LightUniform) -> ShadowMapUniform {
+    let position = Vec3::from_array(light.position);
+    let direction = Vec3::from_array(light.direction).normalize_or(Vec3::NEG_Z);
+    let up = if direction.y.abs() > 0.99 { Vec3::Z } else { Vec3::Y };
+    let fov = (2.0 * light.outer_cos.clamp(-1.0, 1.0).acos()).clamp(1e-3, MAX_SPOT_SHADOW_FOV);
+    let far = light.range.max(SPOT_SHADOW_NEAR * 2.0);
+
+    let view = Mat4::look_to_rh(position, direction, up);
+    let projection = Mat4::perspective_rh(fov, 1.0, SPOT_SHADOW_NEAR, far);
+    ShadowMapUniform {
+        view_proj: (projection * view).to_cols_array_2d(),
+        texel_size: 2.0 * (fov * 0.5).tan() / SHADOW_MAP_SIZE as f32,
+        perspective: 1.0,
+        _padding: [0.0; 2],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::light::{AmbientLight, LightSet, MAX_LIGHTS};
+    use engine_core::{DirectionalLight, ShadowSettings, SpotLight, Transform, World};
+    use glam::{Quat, Vec4Swizzles};
+
+    fn project(map: &ShadowMapUniform, point: Vec3) -> Vec3 {
+        let clip = Mat4::from_cols_array_2d(&map.view_proj) * point.extend(1.0);
+        clip.xyz() / clip.w
+    }
+
+    fn inside(ndc: Vec3) -> bool {
+        ndc.x.abs() <= 1.0 + 1e-4 && ndc.y.abs() <= 1.0 + 1e-4 && (-1e-4..=1.0 + 1e-4).contains(&ndc.z)
+    }
+
+    #[test]
+    fn test_uniform_layout() {
+        assert_eq!(std::mem::size_of::<ShadowMapUniform>(), 80);
+        assert_eq!(std::mem::size_of::<ShadowUniform>(), 80 * MAX_SHADOW_MAPS + 32);
+    }
+
+    #[test]
+    fn test_cascade_splits_increase_to_far() {
+        let splits = cascade_splits(0.1, 50.0);
+        assert!(splits.windows(2).all(|w| w[0] < w[1]));
+        assert!(splits[0] > 0.1);
+        assert!((splits[CASCADE_COUNT - 1] - 50.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_cascades_cover_frustum_slices() {
+        let mut world = World::new();
+        let sun = world.spawn();
+        world.insert(sun, DirectionalLight::default());
+        world.insert(sun, Transform::new(Vec3::ZERO, Quat::from_rotation_x(-1.0), Vec3::ONE));
+        let camera = Camera::new(16.0 / 9.0);
+        let set = LightSet::gather(&world, AmbientLight::default(), camera.position(), MAX_LIGHTS);
+
+        // 遮蔽物が光源側の視錐台の外にあっても深度範囲に入る
+        let caster = AABB::new(Vec3::new(-1.0, 40.0, -1.0), Vec3::new(1.0, 41.0, 1.0));
+        let frame = ShadowFrame::build(set.lights(), &camera, Some(&caster));
+        assert_eq!(frame.uniform().camera_forward[3], CASCADE_COUNT as f32);
+        assert_eq!(frame.passes().count(), CASCADE_COUNT);
+
+        let far = camera.far().min(MAX_SHADOW_DISTANCE);
+        let mut near = camera.near();
+        for cascade in 0..CASCADE_COUNT {
+            let split = frame.uniform().cascade_splits[cascade];
+            let map = &frame.uniform().maps[cascade];
+            for corner in camera.frustum_corners(near, split) {
+                assert!(inside(project(map, corner)), "cascade {} misses {:?}", cascade, corner);
+            }
+            near = split;
+        }
+        assert!((near - far).abs() < 1e-3);
+
+        // 遮蔽物は最初のカスケードで受け手より手前（深度が小さい）
+        let map = &frame.uniform().maps[0];
+        let ground = project(map, camera.target());
+        let top = project(map, Vec3::new(0.0, 40.5, 0.0));
+        assert!((0.0..=1.0).contains(&top.z) && top.z < ground.z);
+    }
+
+    #[test]
+    fn test_spot_map_covers_cone() {
+        let mut world = World::new();
+        let spot = world.spawn();
+        world.insert(spot, SpotLight::default());
+        world.insert(spot, Transform::from_position(Vec3::new(0.0, 2.0, 0.0)));
+        let plain = world.spawn();
+        world.insert(
+            plain,
+            SpotLight {
+                shadow: ShadowSettings { enabled: false, ..Default::default() },
+                ..Default::default()
+            },
+        );
+
+        let camera = Camera::new(1.0);
+        let set = LightSet::gather(&world, AmbientLight::default(), camera.position(), MAX_LIGHTS);
+        let frame = ShadowFrame::build(set.lights(), &camera, None);
+        let passes: Vec<usize> = frame.passes().map(|(layer, _)| layer).collect();
+        assert_eq!(passes, vec![CASCADE_COUNT]);
+        assert_eq!(frame.uniform().camera_forward[3], 0.0);
+
+        let map = &frame.uniform().maps[CASCADE_COUNT];
+        assert_eq!(map.perspective, 1.0);
+        // 光は -Z 方向に進む。円錐の内側はマップ内、背後はマップ外
+        let ahead = project(map, Vec3::new(0.5, 2.0, -5.0));
+        assert!(inside(ahead));
+        assert!(!inside(project(map, Vec3::new(0.0, 2.0, 5.0))));
+        let nearer = project(map, Vec3::new(0.0, 2.0, -2.0));
+        assert!(nearer.z < ahead.z);
+    }
+}
@@ -0,0 +1,552 @@
+//! テクスチャモジュール
+//!
+//! GPUにアップロードする前のテクスチャデータとサンプラー設定を扱う。
+//! ミップマップはCPUでボックスフィルタにより生成する（WebGL2でも同じ結果になる）。
+//! 8bitの色画像はRGBA8、HDR画像は半精度浮動小数点のRGBA16Floatで保持する
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// 半精度浮動小数点の最大値
+const F16_MAX: f32 = 65504.0;
+
+/// テクスチャの幅・高さの上限（WebGL2の既定の制限）
+///
+/// 超える場合は上限に収まるまで縮小してからアップロードする
+pub const MAX_TEXTURE_SIZE: u32 = 2048;
+
+/// テクスチャデータのエラー
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextureError {
+    /// 幅か高さが0
+    EmptySize { width: u32, height: u32 },
+    /// データ長が幅×高さ×4チャンネルと一致しない
+    InvalidDataLength { expected: usize, len: usize },
+    /// 幅×高さ×4がアドレス可能なサイズを超える
+    TooLarge { width: u32, height: u32 },
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptySize { width, height } => {
+                write!(f, "texture size {}x{} is empty", width, height)
+            }
+            Self::InvalidDataLength { expected, len } => write!(
+                f,
+                "texture data has {} values, expected {} (width * height * 4)",
+                len, expected
+            ),
+            Self::TooLarge { width, height } => {
+                write!(f, "texture size {}x{} is too large", width, height)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
+
+/// テクセルの色空間
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorSpace {
+    /// sRGB（ベースカラーなどの色画像）
+    #[default]
+    Srgb,
+    /// リニア（法線マップ・粗さなどのデータ画像）
+    Linear,
+}
+
+/// テクセルの格納形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TexelFormat {
+    /// 8bit × 4チャンネル
+    #[default]
+    Rgba8,
+    /// 半精度浮動小数点 × 4チャンネル（HDR、常にリニア）
+    Rgba16Float,
+}
+
+impl TexelFormat {
+    /// 1テクセルのバイト数
+    pub fn bytes_per_texel(self) -> usize {
+        match self {
+            Self::Rgba8 => 4,
+            Self::Rgba16Float => 8,
+        }
+    }
+}
+
+/// サンプリング時のフィルタ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterMode {
+    Nearest,
+    #[default]
+    Linear,
+}
+
+impl FilterMode {
+    fn to_wgpu(self) -> wgpu::FilterMode {
+        match self {
+            Self::Nearest => wgpu::FilterMode::Nearest,
+            Self::Linear => wgpu::FilterMode::Linear,
+        }
+    }
+}
+
+/// UVが0〜1の外に出たときの扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressMode {
+    #[default]
+    Repeat,
+    MirrorRepeat,
+    ClampToEdge,
+}
+
+impl AddressMode {
+    fn to_wgpu(self) -> wgpu::AddressMode {
+        match self {
+            Self::Repeat => wgpu::AddressMode::Repeat,
+            Self::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+            Self::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+        }
+    }
+}
+
+/// サンプラー設定
+///
+/// 例: `{ "address_mode_u": "clamp_to_edge", "mag_filter": "nearest" }`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SamplerDesc {
+    pub address_mode_u: AddressMode,
+    pub address_mode_v: AddressMode,
+    pub mag_filter: FilterMode,
+    pub min_filter: FilterMode,
+    pub mipmap_filter: FilterMode,
+}
+
+impl SamplerDesc {
+    /// wgpuのサンプラー記述子に変換
+    pub fn to_wgpu(&self) -> wgpu::SamplerDescriptor<'static> {
+        wgpu::SamplerDescriptor {
+            label: Some("Texture Sampler"),
+            address_mode_u: self.address_mode_u.to_wgpu(),
+            address_mode_v: self.address_mode_v.to_wgpu(),
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: self.mag_filter.to_wgpu(),
+            min_filter: self.min_filter.to_wgpu(),
+            mipmap_filter: self.mipmap_filter.to_wgpu(),
+            ..Default::default()
+        }
+    }
+}
+
+/// 1つのミップレベル
+#[derive(Debug, Clone, PartialEq)]
+pub struct MipLevel {
+    pub width: u32,
+    pub height: u32,
+    /// テクセルのバイト列（行は上から下、f16はリトルエンディアン）
+    pub data: Vec<u8>,
+}
+
+/// GPUにアップロードできるテクスチャ
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureData {
+    pub color_space: ColorSpace,
+    pub texel_format: TexelFormat,
+    /// ミップレベル（0が最大）
+    pub mips: Vec<MipLevel>,
+}
+
+impl TextureData {
+    /// RGBA8のピクセル列から作成
+    ///
+    /// `generate_mipmaps` がtrueなら1x1までのミップチェーンを生成する。
+    /// `MAX_TEXTURE_SIZE` を超える画像は縮小される
+    pub fn from_rgba8(
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        color_space: ColorSpace,
+        generate_mipmaps: bool,
+    ) -> Result<Self, TextureError> {
+        check_size(rgba.len(), width, height)?;
+        let base = MipLevel {
+            width,
+            height,
+            data: rgba.to_vec(),
+        };
+        Ok(Self::build(base, color_space, TexelFormat::Rgba8, generate_mipmaps))
+    }
+
+    /// リニアなRGBA（f32）のピクセル列からHDRテクスチャを作成
+    ///
+    /// 半精度の範囲（0〜65504）に丸め、NaNは0になる
+    pub fn from_rgba_f32(
+        rgba: &[f32],
+        width: u32,
+        height: u32,
+        generate_mipmaps: bool,
+    ) -> Result<Self, TextureError> {
+        check_size(rgba.len(), width, height)?;
+        let base = MipLevel {
+            width,
+            height,
+            data: rgba.iter().flat_map(|&v| f32_to_f16(clamp_hdr(v)).to_le_bytes()).collect(),
+        };
+        Ok(Self::build(base, ColorSpace::Linear, TexelFormat::Rgba16Float, generate_mipmaps))
+    }
+
+    /// 上限サイズへの縮小とミップチェーンの生成
+    fn build(mut base: MipLevel, color_space: ColorSpace, texel_format: TexelFormat, generate_mipmaps: bool) -> Self {
+        let codec = Codec { color_space, texel_format };
+        while base.width > MAX_TEXTURE_SIZE || base.height > MAX_TEXTURE_SIZE {
+            base = downsample(&base, codec);
+        }
+
+        let mut mips = vec![base];
+        if generate_mipmaps {
+            while let Some(last) = mips.last().filter(|m| m.width > 1 || m.height > 1) {
+                let next = downsample(last, codec);
+                mips.push(next);
+            }
+        }
+        Self {
+            color_space,
+            texel_format,
+            mips,
+        }
+    }
+
+    /// 1x1の単色テクスチャ
+    pub fn solid(rgba: [u8; 4], color_space: ColorSpace) -> Self {
+        Self {
+            color_space,
+            texel_format: TexelFormat::Rgba8,
+            mips: vec![MipLevel {
+                width: 1,
+                height: 1,
+                data: rgba.to_vec(),
+            }],
+        }
+    }
+
+    /// レベル0の幅
+    pub fn width(&self) -> u32 {
+        self.mips[0].width
+    }
+
+    /// レベル0の高さ
+    pub fn height(&self) -> u32 {
+        self.mips[0].height
+    }
+
+    /// ミップレベル数
+    pub fn mip_level_count(&self) -> u32 {
+        self.mips.len() as u32
+    }
+
+    /// 1行のバイト数（ミップレベル指定）
+    pub fn bytes_per_row(&self, level: usize) -> u32 {
+        self.mips[level].width * self.texel_format.bytes_per_texel() as u32
+    }
+
+    /// GPU上のフォーマット（sRGBならサンプリング時にリニアへ変換される）
+    pub fn format(&self) -> wgpu::TextureFormat {
+        match (self.texel_format, self.color_space) {
+            (TexelFormat::Rgba16Float, _) => wgpu::TextureFormat::Rgba16Float,
+            (TexelFormat::Rgba8, ColorSpace::Srgb) => wgpu::TextureFormat::Rgba8UnormSrgb,
+            (TexelFormat::Rgba8, ColorSpace::Linear) => wgpu::TextureFormat::Rgba8Unorm,
+        }
+    }
+}
+
+/// 値の数が幅×高さ×4か検証
+fn check_size(len: usize, width: u32, height: u32) -> Result<(), TextureError> {
+    if width == 0 || height == 0 {
+        return Err(TextureError::EmptySize { width, height });
+    }
+    // wasm32ではusizeが32bitなので、u64で計算してから変換する
+    let expected = (width as u64)
+        .checked_mul(height as u64)
+        .and_then(|n| n.checked_mul(4))
+        .and_then(|n| usize::try_from(n).ok())
+        .ok_or(TextureError::TooLarge { width, height })?;
+    if len != expected {
+        return Err(TextureError::InvalidDataLength { expected, len });
+    }
+    Ok(())
+}
+
+fn clamp_hdr(v: f32) -> f32 {
+    if v.is_nan() { 0.0 } else { v.clamp(0.0, F16_MAX) }
+}
+
+/// テクセルのバイト列とリニア値の相互変換
+#[derive(Debug, Clone, Copy)]
+struct Codec {
+    color_space: ColorSpace,
+    texel_format: TexelFormat,
+}
+
+impl Codec {
+    /// テクセル番号・チャンネルの値をリニアで読む
+    fn load(self, data: &[u8], texel: usize, channel: usize) -> f32 {
+        match self.texel_format {
+            TexelFormat::Rgba8 => {
+                let c = data[texel * 4 + channel];
+                match self.color_space {
+                    ColorSpace::Srgb if channel < 3 => srgb_to_linear(c),
+                    _ => c as f32 / 255.0,
+                }
+            }
+            TexelFormat::Rgba16Float => {
+                let at = (texel * 4 + channel) * 2;
+                f16_to_f32(u16::from_le_bytes([data[at], data[at + 1]]))
+            }
+        }
+    }
+
+    /// リニア値を書き込む
+    fn store(self, data: &mut Vec<u8>, v: f32, channel: usize) {
+        match self.texel_format {
+            TexelFormat::Rgba8 => {
+                let v = match self.color_space {
+                    ColorSpace::Srgb if channel < 3 => linear_to_srgb(v),
+                    _ => v,
+                };
+                data.push((v.clamp(0.0, 1.0) * 255.0).round() as u8);
+            }
+            TexelFormat::Rgba16Float => {
+                data.extend_from_slice(&f32_to_f16(clamp_hdr(v)).to_le_bytes());
+            }
+        }
+    }
+}
+
+/// 幅・高さを半分（最小1）に縮小する
+///
+/// 各テクセルは対応する元画像の範囲の平均で、奇数サイズでは3テクセル幅を平均する。
+/// sRGBの色はリニア空間で平均する（アルファは常にリニア）
+fn downsample(level: &MipLevel, codec: Codec) -> MipLevel {
+    let (src_w, src_h) = (level.width as usize, level.height as usize);
+    let width = (level.width / 2).max(1);
+    let height = (level.height / 2).max(1);
+    let (dst_w, dst_h) = (width as usize, height as usize);
+
+    let mut data = Vec::with_capacity(dst_w * dst_h * codec.texel_format.bytes_per_texel());
+    for y in 0..dst_h {
+        let (y0, y1) = (y * src_h / dst_h, ((y + 1) * src_h).div_ceil(dst_h));
+        for x in 0..dst_w {
+            let (x0, x1) = (x * src_w / dst_w, ((x + 1) * src_w).div_ceil(dst_w));
+            let mut sum = [0.0f32; 4];
+            for sy in y0..y1 {
+                for sx in x0..x1 {
+                    for (channel, total) in sum.iter_mut().enumerate() {
+                        *total += codec.load(&level.data, sy * src_w + sx, channel);
+                    }
+                }
+            }
+            let count = ((y1 - y0) * (x1 - x0)) as f32;
+            for (channel, total) in sum.iter().enumerate() {
+                codec.store(&mut data, total / count, channel);
+            }
+        }
+    }
+
+    MipLevel { width, height, data }
+}
+
+/// sRGBの8bit値をリニアに変換
+pub fn srgb_to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// リニア値をsRGB（0〜1）に変換
+pub fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// f32を半精度（IEEE 754 binary16）に変換（最近接偶数丸め）
+pub fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+    let half_exponent = exponent - 112;
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if half_exponent <= 0 {
+        // 非正規化数（小さすぎる値は0）
+        if half_exponent < -10 {
+            return sign;
+        }
+        let full = mantissa | 0x80_0000;
+        let shift = (14 - half_exponent) as u32;
+        let rounded = (full + (1 << (shift - 1)) - 1 + ((full >> shift) & 1)) >> shift;
+        return sign | rounded as u16;
+    }
+
+    let combined = ((half_exponent as u32) << 10) | (mantissa >> 13);
+    let rest = mantissa & 0x1fff;
+    // 繰り上がりで指数が増え、最大値を超えると無限大になる
+    let rounded = if rest > 0x1000 || (rest == 0x1000 && combined & 1 == 1) {
+        combined + 1
+    } else {
+        combined
+    };
+    sign | rounded as u16
+}
+
+/// 半精度（IEEE 754 binary16）をf32に変換
+pub fn f16_to_f32(half: u16) -> f32 {
+    let sign = ((half & 0x8000) as u32) << 16;
+    let exponent = ((half >> 10) & 0x1f) as u32;
+    let mantissa = (half & 0x3ff) as u32;
+    let bits = match exponent {
+        0 if mantissa == 0 => sign,
+        0 => {
+            let value = mantissa as f32 / (1 << 24) as f32;
+            return if sign != 0 { -value } else { value };
+        }
+        0x1f => sign | 0x7f80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 112) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checker(size: u32) -> Vec<u8> {
+        (0..size * size)
+            .flat_map(|i| {
+                let white = (i % size + i / size).is_multiple_of(2);
+                let c = if white { 255 } else { 0 };
+                [c, c, c, 255]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_mip_chain() {
+        let texture = TextureData::from_rgba8(&checker(4), 4, 4, ColorSpace::Linear, true).unwrap();
+        let sizes: Vec<(u32, u32)> = texture.mips.iter().map(|m| (m.width, m.height)).collect();
+        assert_eq!(sizes, [(4, 4), (2, 2), (1, 1)]);
+        // リニアでは白黒の平均は中間値
+        assert_eq!(texture.mips[2].data, [128, 128, 128, 255]);
+        assert_eq!(texture.format(), wgpu::TextureFormat::Rgba8Unorm);
+    }
+
+    #[test]
+    fn test_srgb_downsample_is_gamma_correct() {
+        let texture = TextureData::from_rgba8(&checker(2), 2, 2, ColorSpace::Srgb, true).unwrap();
+        // リニアの0.5はsRGBで約188
+        assert_eq!(texture.mips[1].data, [188, 188, 188, 255]);
+    }
+
+    #[test]
+    fn test_non_power_of_two_and_oversized() {
+        let rgba = vec![255; 3 * 5 * 4];
+        let texture = TextureData::from_rgba8(&rgba, 3, 5, ColorSpace::Srgb, true).unwrap();
+        let sizes: Vec<(u32, u32)> = texture.mips.iter().map(|m| (m.width, m.height)).collect();
+        assert_eq!(sizes, [(3, 5), (1, 2), (1, 1)]);
+        assert!(texture.mips.iter().all(|m| m.data.iter().all(|&c| c == 255)));
+
+        // 上限を超える画像は半分ずつ縮小される
+        let wide = vec![0; MAX_TEXTURE_SIZE as usize * 3 * 4];
+        let texture =
+            TextureData::from_rgba8(&wide, MAX_TEXTURE_SIZE * 3, 1, ColorSpace::Linear, false).unwrap();
+        assert_eq!((texture.width(), texture.height()), (MAX_TEXTURE_SIZE * 3 / 4, 1));
+        assert_eq!(texture.mip_level_count(), 1);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            TextureData::from_rgba8(&[], 0, 4, ColorSpace::Srgb, false).unwrap_err(),
+            TextureError::EmptySize { width: 0, height: 4 }
+        );
+        assert_eq!(
+            TextureData::from_rgba8(&[0; 7], 1, 2, ColorSpace::Srgb, false).unwrap_err(),
+            TextureError::InvalidDataLength { expected: 8, len: 7 }
+        );
+        assert_eq!(
+            TextureData::from_rgba8(&[], u32::MAX, u32::MAX, ColorSpace::Srgb, false).unwrap_err(),
+            TextureError::TooLarge { width: u32::MAX, height: u32::MAX }
+        );
+        assert_eq!(
+            TextureData::from_rgba_f32(&[], 65536, 65536, false).unwrap_err(),
+            if usize::BITS > 32 {
+                TextureError::InvalidDataLength { expected: 1 << 34, len: 0 }
+            } else {
+                TextureError::TooLarge { width: 65536, height: 65536 }
+            }
+        );
+    }
+
+    #[test]
+    fn test_hdr_texture() {
+        let rgba = [4.0, 0.5, 0.0, 1.0, 0.0, 0.5, 1e6, 1.0];
+        let texture = TextureData::from_rgba_f32(&rgba, 2, 1, true).unwrap();
+        assert_eq!(texture.format(), wgpu::TextureFormat::Rgba16Float);
+        assert_eq!(texture.bytes_per_row(0), 16);
+        assert_eq!(texture.mips[1].data.len(), 8);
+
+        let texel = |data: &[u8], i: usize| f16_to_f32(u16::from_le_bytes([data[i * 2], data[i * 2 + 1]]));
+        // 範囲外の値は半精度の最大値に丸められる
+        assert_eq!(texel(&texture.mips[0].data, 6), F16_MAX);
+        // 1x1のミップは2テクセルの平均
+        assert_eq!(texel(&texture.mips[1].data, 0), 2.0);
+        assert_eq!(texel(&texture.mips[1].data, 1), 0.5);
+    }
+
+    #[test]
+    fn test_f16_conversion() {
+        assert_eq!(f32_to_f16(1.0), 0x3c00);
+        assert_eq!(f32_to_f16(-2.0), 0xc000);
+        assert_eq!(f32_to_f16(F16_MAX), 0x7bff);
+        assert_eq!(f32_to_f16(1e6), 0x7c00);
+        assert_eq!(f32_to_f16(0.0), 0);
+        // 最小の非正規化数
+        assert_eq!(f32_to_f16(2.0f32.powi(-24)), 1);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+        for v in [0.5, 0.1, 3.25, 1000.0, 2.0f32.powi(-20)] {
+            let round_trip = f16_to_f32(f32_to_f16(v));
+            assert!((round_trip - v).abs() <= v * 1e-3, "{} -> {}", v, round_trip);
+        }
+    }
+
+    #[test]
+    fn test_sampler_desc_json() {
+        let desc: SamplerDesc =
+            serde_json::from_str(r#"{ "address_mode_u": "clamp_to_edge", "mag_filter": "nearest" }"#).unwrap();
+        assert_eq!(desc.address_mode_u, AddressMode::ClampToEdge);
+        assert_eq!(desc.address_mode_v, AddressMode::Repeat);
+        let sampler = desc.to_wgpu();
+        assert_eq!(sampler.mag_filter, wgpu::FilterMode::Nearest);
+        assert_eq!(sampler.min_filter, wgpu::FilterMode::Linear);
+        assert!(serde_json::from_str::<SamplerDesc>(r#"{ "wrap": "repeat" }"#).is_err());
+    }
+}
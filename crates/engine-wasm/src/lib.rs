@@ -1,22 +1,44 @@
 // crates/engine-wasm/src/lib.rs
 
+mod light;
+mod material;
+mod primitive;
 mod renderer;
 mod shaders;
+mod query_task;
 mod subscription;
+mod texture;
 mod utils;
 
+use light::{Light, LightDesc, ShadowFlagsDesc};
+use material::MaterialDesc;
+use primitive::PrimitiveDesc;
 use renderer::Renderer;
-use subscription::{calculate_hash, QuerySubscriptionManager};
-use utils::console_log;
+use query_task::QueryTaskManager;
+use subscription::{QuerySubscriptionManager, SubscriptionMode};
+use texture::TextureOptions;
+use utils::{columnar_to_js, console_log, console_warn, now_ms, query_to_js, validation_error_to_js};
 
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlCanvasElement;
 use js_sys::Function;
 
-use engine_core::{EntityId, Name, QueryDescriptor, Transform, World};
-use engine_renderer::{GizmoAxis, GizmoMode, Ray};
+use engine_core::{
+    parse_query, EntityId, FilterValue, IndexKind, Material, MeshHandle, Name, PreparedQuery,
+    QueryDescriptor, ShadowFlags, TextureHandle, Transform, World,
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use engine_renderer::{export, import, AmbientLight, GizmoAxis, GizmoMode, Mesh, NormalMode, Ray};
 use glam::{Quat, Vec3};
 
+/// インポートしたメッシュ（JSへ返す）
+#[derive(Serialize)]
+struct ImportedMesh {
+    name: String,
+    mesh: u32,
+}
+
 // パニック時のスタックトレース表示
 #[wasm_bindgen(start)]
 pub fn init_panic_hook() {
@@ -30,6 +52,17 @@ pub struct Engine {
     world: World,
     renderer: Renderer,
     subscriptions: QuerySubscriptionManager,
+    /// 分割実行中のクエリ
+    query_tasks: QueryTaskManager,
+    /// 準備済みクエリ（ハンドル → クエリ）
+    prepared_queries: HashMap<u32, PreparedQuery>,
+    next_query_handle: u32,
+    /// JSが作成し、まだ解放していないメッシュ（作成者の参照）
+    owned_meshes: HashSet<MeshHandle>,
+    /// JSがアップロードし、まだ解放していないテクスチャ（作成者の参照）
+    owned_textures: HashSet<TextureHandle>,
+    /// set_light が描画しないよう `MeshHandle::NONE` を付けたEntity
+    light_placeholders: HashSet<EntityId>,
     /// Gizmoドラッグ開始時のレイ
     gizmo_drag_ray: Option<Ray>,
     /// Gizmoドラッグ中の軸
@@ -42,13 +75,21 @@ impl Engine {
     pub async fn create(canvas: HtmlCanvasElement) -> Result<Engine, JsValue> {
         console_log!("Creating Engine...");
         let renderer = Renderer::create(canvas).await?;
-        let world = World::new();
+        let mut world = World::new();
+        // 購読の差分更新に使用
+        world.set_change_tracking(true);
         let subscriptions = QuerySubscriptionManager::new();
         console_log!("Engine created successfully");
         Ok(Self {
             world,
             renderer,
             subscriptions,
+            query_tasks: QueryTaskManager::new(),
+            prepared_queries: HashMap::new(),
+            next_query_handle: 1,
+            owned_meshes: HashSet::new(),
+            owned_textures: HashSet::new(),
+            light_placeholders: HashSet::new(),
             gizmo_drag_ray: None,
             gizmo_drag_axis: GizmoAxis::None,
         })
@@ -60,17 +101,21 @@ impl Engine {
         self.world.insert(entity, Name::new(name));
         self.world.insert(entity, Transform::identity());
         console_log!("Created entity: {} (id: {})", name, entity.to_u32());
-        self.check_subscriptions();
         entity.to_u32()
     }
 
     /// Entityを削除
     pub fn delete_entity(&mut self, id: u32) -> bool {
         let entity = EntityId::from_u32(id);
+        if let Some(handle) = self.world.get::<MeshHandle>(entity).copied() {
+            self.renderer.release_mesh(handle);
+        }
+        let material = self.world.get::<Material>(entity).cloned();
+        self.release_textures(material.as_ref());
+        self.light_placeholders.remove(&entity);
         let result = self.world.despawn(entity);
         if result {
             console_log!("Deleted entity: {}", id);
-            self.check_subscriptions();
         }
         result
     }
@@ -80,7 +125,6 @@ impl Engine {
         let entity = EntityId::from_u32(id);
         if let Some(transform) = self.world.get_mut::<Transform>(entity) {
             transform.position = Vec3::new(x, y, z);
-            self.check_subscriptions();
         }
     }
 
@@ -89,7 +133,6 @@ impl Engine {
         let entity = EntityId::from_u32(id);
         if let Some(transform) = self.world.get_mut::<Transform>(entity) {
             transform.rotation = Quat::from_xyzw(x, y, z, w);
-            self.check_subscriptions();
         }
     }
 
@@ -98,7 +141,6 @@ impl Engine {
         let entity = EntityId::from_u32(id);
         if let Some(transform) = self.world.get_mut::<Transform>(entity) {
             transform.scale = Vec3::new(x, y, z);
-            self.check_subscriptions();
         }
     }
 
@@ -144,10 +186,33 @@ impl Engine {
         let entity = EntityId::from_u32(id);
         if let Some(n) = self.world.get_mut::<Name>(entity) {
             *n = Name::new(name);
-            self.check_subscriptions();
         }
     }
 
+    /// 親を設定（parent_id 省略で親子関係を解除）
+    /// 無効なEntity、または自身・子孫を親にしようとした場合false
+    pub fn set_parent(&mut self, id: u32, parent_id: Option<u32>) -> bool {
+        let entity = EntityId::from_u32(id);
+        let parent = parent_id.map(EntityId::from_u32);
+        self.world.set_parent(entity, parent)
+    }
+
+    /// 親のIDを取得
+    pub fn get_parent(&self, id: u32) -> Option<u32> {
+        let entity = EntityId::from_u32(id);
+        self.world.parent(entity).map(|p| p.to_u32())
+    }
+
+    /// 子のID一覧を取得
+    pub fn get_children(&self, id: u32) -> Vec<u32> {
+        let entity = EntityId::from_u32(id);
+        self.world
+            .children(entity)
+            .iter()
+            .map(|c| c.to_u32())
+            .collect()
+    }
+
     /// Entityが生存しているか確認
     pub fn is_alive(&self, id: u32) -> bool {
         let entity = EntityId::from_u32(id);
@@ -159,8 +224,421 @@ impl Engine {
         self.world.entity_count()
     }
 
-    /// フレーム更新（レンダリング含む）
+    // ========================================================================
+    // Mesh API
+    // ========================================================================
+
+    /// メッシュを作成し、ハンドルを返す
+    ///
+    /// # Arguments
+    /// * `vertices` - 1頂点あたり position(3), normal(3), color(3) の9要素
+    /// * `indices` - 三角形リストのインデックス
+    /// * `uvs` - 1頂点あたり u, v の2要素（省略可、原点は画像の左上）
+    pub fn create_mesh(
+        &mut self,
+        vertices: Vec<f32>,
+        indices: Vec<u32>,
+        uvs: Option<Vec<f32>>,
+    ) -> Result<u32, JsValue> {
+        let mut mesh = Mesh::from_flat(&vertices, indices)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        if let Some(uvs) = uvs {
+            mesh = mesh.with_uvs(&uvs).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        }
+        if mesh.is_empty() {
+            return Err(JsValue::from_str("Mesh contains no triangles"));
+        }
+        let handle = self.renderer.create_mesh(&mesh);
+        self.owned_meshes.insert(handle);
+        console_log!(
+            "Created mesh: {} vertices, {} indices (handle: {})",
+            mesh.vertex_count(),
+            mesh.index_count(),
+            handle.id()
+        );
+        Ok(handle.id())
+    }
+
+    /// プリミティブ形状のメッシュを作成し、ハンドルを返す
+    ///
+    /// 例: `{ shape: "sphere", radius: 1.0 }`（省略したパラメータは既定値）
+    /// 分割数が上限を超える場合はエラー
+    pub fn create_primitive(&mut self, desc: JsValue) -> Result<u32, JsValue> {
+        let desc: PrimitiveDesc = serde_wasm_bindgen::from_value(desc)
+            .map_err(|e| JsValue::from_str(&format!("Invalid primitive: {}", e)))?;
+        let mesh = desc
+            .to_mesh()
+            .map_err(|e| JsValue::from_str(&format!("Invalid primitive: {}", e)))?;
+        let handle = self.renderer.create_mesh(&mesh);
+        self.owned_meshes.insert(handle);
+        Ok(handle.id())
+    }
+
+    /// OBJ（+MTL）ファイルの内容からメッシュを作成
+    ///
+    /// オブジェクト・グループごとに `{ name, mesh }` の配列を返す。
+    /// `smooth_normals` が false の場合、法線のない面はフラットシェーディングになる
+    pub fn import_obj(
+        &mut self,
+        obj_text: &str,
+        mtl_text: Option<String>,
+        smooth_normals: Option<bool>,
+    ) -> Result<JsValue, JsValue> {
+        let materials = match mtl_text {
+            Some(text) => import::parse_mtl(&text).map_err(|e| JsValue::from_str(&e.to_string()))?,
+            None => HashMap::new(),
+        };
+        let objects = import::parse_obj(obj_text, &materials, normal_mode(smooth_normals))
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+        let imported: Vec<ImportedMesh> = objects
+            .iter()
+            .map(|object| {
+                let handle = self.renderer.create_mesh(&object.mesh);
+                self.owned_meshes.insert(handle);
+                ImportedMesh {
+                    name: object.name.clone(),
+                    mesh: handle.id(),
+                }
+            })
+            .collect();
+        console_log!("Imported OBJ: {} object(s)", imported.len());
+
+        imported
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// STL（バイナリ/ASCII）からメッシュを作成
+    /// 同じ位置の頂点は溶接される。`smooth_normals` が false の場合はフラットシェーディング
+    pub fn import_stl(&mut self, bytes: &[u8], smooth_normals: Option<bool>) -> Result<u32, JsValue> {
+        let mesh = import::parse_stl(bytes, normal_mode(smooth_normals))
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        if mesh.indices.is_empty() {
+            return Err(JsValue::from_str("STL contains no triangles"));
+        }
+        let handle = self.renderer.create_mesh(&mesh);
+        self.owned_meshes.insert(handle);
+        console_log!("Imported STL: {} vertices", mesh.vertices.len());
+        Ok(handle.id())
+    }
+
+    /// PLY（ASCII/バイナリ）からメッシュを作成
+    /// 頂点色・法線があればそれを使う
+    pub fn import_ply(&mut self, bytes: &[u8], smooth_normals: Option<bool>) -> Result<u32, JsValue> {
+        let mesh = import::parse_ply(bytes, normal_mode(smooth_normals))
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        if mesh.indices.is_empty() {
+            return Err(JsValue::from_str("PLY contains no faces"));
+        }
+        let handle = self.renderer.create_mesh(&mesh);
+        self.owned_meshes.insert(handle);
+        console_log!("Imported PLY: {} vertices", mesh.vertices.len());
+        Ok(handle.id())
+    }
+
+    /// glTF 2.0（.gltf / .glb）を読み込み、ノード階層をEntityとして生成
+    /// ルートEntityのIDを返す。未対応の機能はコンソールに警告を出す
+    pub fn import_gltf(&mut self, bytes: &[u8]) -> Result<Vec<u32>, JsValue> {
+        let scene = import::parse_gltf(bytes).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        for warning in &scene.warnings {
+            console_warn!("glTF: {}", warning);
+        }
+
+        let handles: Vec<Option<MeshHandle>> = scene
+            .meshes
+            .iter()
+            .map(|mesh| (!mesh.mesh.indices.is_empty()).then(|| self.renderer.create_mesh(&mesh.mesh)))
+            .collect();
+        let roots = scene.spawn(&mut self.world, &handles);
+
+        // 各Entityが参照を1つずつ持ち、生成時の参照は手放す
+        for node in scene.nodes.iter() {
+            if let Some(handle) = node.mesh.and_then(|m| handles[m]) {
+                self.renderer.retain_mesh(handle);
+            }
+        }
+        for handle in handles.into_iter().flatten() {
+            self.renderer.release_mesh(handle);
+        }
+
+        console_log!(
+            "Imported glTF: {} node(s), {} mesh(es)",
+            scene.nodes.len(),
+            scene.meshes.len()
+        );
+        Ok(roots.into_iter().map(|entity| entity.to_u32()).collect())
+    }
+
+    /// シーン全体をGLB（glTF 2.0バイナリ）として書き出す
+    /// JSには `Uint8Array` として渡る
+    pub fn export_glb(&self) -> Vec<u8> {
+        let bytes = export::export_glb(&self.world, |handle| self.renderer.mesh_data(handle));
+        console_log!("Exported GLB: {} bytes", bytes.len());
+        bytes
+    }
+
+    /// Entityにメッシュを設定
+    /// 無効なEntityまたは未登録のハンドルの場合false
+    pub fn set_mesh(&mut self, id: u32, handle: u32) -> bool {
+        let entity = EntityId::from_u32(id);
+        let handle = MeshHandle::new(handle);
+        if !self.world.is_alive(entity) || !self.renderer.retain_mesh(handle) {
+            return false;
+        }
+        if let Some(previous) = self.world.get::<MeshHandle>(entity).copied() {
+            self.renderer.release_mesh(previous);
+        }
+        self.world.insert(entity, handle);
+        // 明示的に設定したメッシュは remove_light で外さない
+        self.light_placeholders.remove(&entity);
+        true
+    }
+
+    /// Entityのメッシュハンドルを取得（未設定なら組み込みCube扱いでNone）
+    pub fn get_mesh(&self, id: u32) -> Option<u32> {
+        let entity = EntityId::from_u32(id);
+        self.world.get::<MeshHandle>(entity).map(|h| h.id())
+    }
+
+    /// create_mesh で得た参照を解放
+    ///
+    /// Entityが参照している間はGPUバッファは保持され、
+    /// 最後の参照がなくなった時点で破棄される
+    pub fn release_mesh(&mut self, handle: u32) -> bool {
+        let handle = MeshHandle::new(handle);
+        if !self.owned_meshes.remove(&handle) {
+            return false;
+        }
+        self.renderer.release_mesh(handle);
+        true
+    }
+
+    // ========================================================================
+    // Texture API
+    // ========================================================================
+
+    /// RGBA8のピクセル列（行は上から下）からテクスチャを作成し、ハンドルを返す
+    ///
+    /// 例: `options = { color_space: "linear", mipmaps: false, sampler: { address_mode_u: "clamp_to_edge" } }`
+    /// 省略した設定は sRGB・ミップマップあり・リピート・リニア補間
+    pub fn upload_texture(
+        &mut self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        options: JsValue,
+    ) -> Result<u32, JsValue> {
+        let options = parse_texture_options(options)?;
+        let data = options
+            .texture_data(rgba, width, height)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let handle = self.renderer.create_texture(&data, &options.sampler);
+        self.owned_textures.insert(handle);
+        console_log!(
+            "Uploaded texture: {}x{}, {} mip level(s) (handle: {})",
+            data.width(),
+            data.height(),
+            data.mip_level_count(),
+            handle.id()
+        );
+        Ok(handle.id())
+    }
+
+    /// 画像ファイル（PNG・JPEG・Radiance HDR）をデコードしてテクスチャを作成し、ハンドルを返す
+    ///
+    /// 例: `options = { color_space: "srgb", premultiply_alpha: true }`
+    /// 設定は upload_texture と同じ。HDRは color_space によらずリニアな半精度浮動小数点になる
+    pub fn load_texture(&mut self, bytes: &[u8], options: JsValue) -> Result<u32, JsValue> {
+        let options = parse_texture_options(options)?;
+        let data = options
+            .decode(bytes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to decode image: {}", e)))?;
+        let handle = self.renderer.create_texture(&data, &options.sampler);
+        self.owned_textures.insert(handle);
+        console_log!(
+            "Loaded texture: {}x{} {:?}, {} mip level(s) (handle: {})",
+            data.width(),
+            data.height(),
+            data.format(),
+            data.mip_level_count(),
+            handle.id()
+        );
+        Ok(handle.id())
+    }
+
+    /// upload_texture / load_texture で得た参照を解放
+    ///
+    /// マテリアルが参照している間はGPUテクスチャは保持され、
+    /// 最後の参照がなくなった時点で破棄される
+    pub fn release_texture(&mut self, handle: u32) -> bool {
+        let handle = TextureHandle::new(handle);
+        if !self.owned_textures.remove(&handle) {
+            return false;
+        }
+        self.renderer.release_texture(handle);
+        true
+    }
+
+    // ========================================================================
+    // Material API
+    // ========================================================================
+
+    /// Entityのマテリアルを設定
+    ///
+    /// 例: `{ base_color: [1, 0, 0, 1], metallic: 0.0, roughness: 0.4, albedo_texture: 2 }`
+    /// 省略したフィールドは現在の値（Materialがなければ既定値）のまま。
+    /// テクスチャはEntityごとに参照され、nullで参照を外す。
+    /// 1フレームで使える異なるマテリアルは `MAX_MATERIALS`（256、影を受けるかどうかで別に数える）までで、
+    /// 超えた分は既定のマテリアルで描画され警告が出る
+    pub fn set_material(&mut self, id: u32, desc: JsValue) -> Result<(), JsValue> {
+        let entity = EntityId::from_u32(id);
+        if !self.world.is_alive(entity) {
+            return Err(JsValue::from_str(&format!("Entity {} does not exist", id)));
+        }
+        let desc: MaterialDesc = serde_wasm_bindgen::from_value(desc)
+            .map_err(|e| JsValue::from_str(&format!("Invalid material: {}", e)))?;
+
+        let previous = self.world.get::<Material>(entity).cloned();
+        let mut material = previous.clone().unwrap_or_default();
+        desc.apply(&mut material).map_err(|e| JsValue::from_str(&e))?;
+
+        let old_textures = previous.as_ref().map_or([None; 3], Material::textures);
+        for (new, old) in material.textures().into_iter().zip(old_textures) {
+            if let Some(handle) = new
+                && new != old
+                && !self.renderer.has_texture(handle)
+            {
+                return Err(JsValue::from_str(&format!("Unknown texture handle: {}", handle.id())));
+            }
+        }
+        for handle in material.textures().into_iter().flatten() {
+            self.renderer.retain_texture(handle);
+        }
+        self.release_textures(previous.as_ref());
+        self.world.insert(entity, material);
+        Ok(())
+    }
+
+    /// Entityのマテリアルを取得（未設定ならundefined）
+    pub fn get_material(&self, id: u32) -> Result<JsValue, JsValue> {
+        let entity = EntityId::from_u32(id);
+        let Some(material) = self.world.get::<Material>(entity) else {
+            return Ok(JsValue::UNDEFINED);
+        };
+        MaterialDesc::from_material(material)
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Entityのマテリアルを削除（既定のマテリアルに戻る）
+    pub fn remove_material(&mut self, id: u32) -> bool {
+        let entity = EntityId::from_u32(id);
+        let material = self.world.remove::<Material>(entity);
+        self.release_textures(material.as_ref());
+        material.is_some()
+    }
+
+    // ========================================================================
+    // Light API
+    // ========================================================================
+
+    /// Entityのライトを設定
+    ///
+    /// 例: `{ kind: "point", color: [1, 0.8, 0.6], intensity: 20, range: 8 }`
+    /// 省略したフィールドは現在の値のまま。種類を変えると以前のライトは置き換えられる。
+    /// 光の向きはEntityのローカル -Z 方向。メッシュ未設定のEntityは描画されなくなる
+    pub fn set_light(&mut self, id: u32, desc: JsValue) -> Result<(), JsValue> {
+        let entity = EntityId::from_u32(id);
+        if !self.world.is_alive(entity) {
+            return Err(JsValue::from_str(&format!("Entity {} does not exist", id)));
+        }
+        let desc: LightDesc = serde_wasm_bindgen::from_value(desc)
+            .map_err(|e| JsValue::from_str(&format!("Invalid light: {}", e)))?;
+        let light = desc
+            .apply(Light::get(&self.world, entity))
+            .map_err(|e| JsValue::from_str(&format!("Invalid light: {}", e)))?;
+        light.insert(&mut self.world, entity);
+        // ライト用のEntityを組み込みのCubeとして描画しない
+        if self.world.get::<MeshHandle>(entity).is_none() {
+            self.world.insert(entity, MeshHandle::NONE);
+            self.light_placeholders.insert(entity);
+        }
+        Ok(())
+    }
+
+    /// Entityのライトを取得（未設定ならundefined）
+    pub fn get_light(&self, id: u32) -> Result<JsValue, JsValue> {
+        let entity = EntityId::from_u32(id);
+        let Some(light) = Light::get(&self.world, entity) else {
+            return Ok(JsValue::UNDEFINED);
+        };
+        LightDesc::from_light(&light)
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Entityのライトを削除
+    ///
+    /// set_light が付けた `MeshHandle::NONE` も外す（以降は組み込みのCubeとして描画される）
+    pub fn remove_light(&mut self, id: u32) -> bool {
+        let entity = EntityId::from_u32(id);
+        let removed = Light::remove(&mut self.world, entity);
+        if self.light_placeholders.remove(&entity)
+            && self.world.get::<MeshHandle>(entity) == Some(&MeshHandle::NONE)
+        {
+            self.world.remove::<MeshHandle>(entity);
+        }
+        removed
+    }
+
+    /// Entityが影を落とすか・受けるかを設定
+    pub fn set_shadow_flags(&mut self, id: u32, casts_shadows: bool, receives_shadows: bool) -> Result<(), JsValue> {
+        let entity = EntityId::from_u32(id);
+        if !self.world.is_alive(entity) {
+            return Err(JsValue::from_str(&format!("Entity {} does not exist", id)));
+        }
+        self.world.insert(entity, ShadowFlags { casts_shadows, receives_shadows });
+        Ok(())
+    }
+
+    /// Entityの影の設定を取得（`{ casts_shadows, receives_shadows }`、未設定なら既定値）
+    pub fn get_shadow_flags(&self, id: u32) -> Result<JsValue, JsValue> {
+        let flags = self
+            .world
+            .get::<ShadowFlags>(EntityId::from_u32(id))
+            .copied()
+            .unwrap_or_default();
+        ShadowFlagsDesc::from(flags)
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// 環境光を設定（色はリニアRGB、強さと乗算される）
+    pub fn set_ambient_light(&mut self, r: f32, g: f32, b: f32, intensity: f32) {
+        self.renderer.ambient = AmbientLight {
+            color: Vec3::new(r, g, b).max(Vec3::ZERO),
+            intensity: intensity.max(0.0),
+        };
+    }
+
+    /// 環境光を取得（r, g, b, intensityの配列）
+    pub fn get_ambient_light(&self) -> Vec<f32> {
+        let ambient = self.renderer.ambient;
+        vec![ambient.color.x, ambient.color.y, ambient.color.z, ambient.intensity]
+    }
+
+    /// フレーム更新（購読通知・レンダリング含む）
+    ///
+    /// 前回のtick以降の変更をまとめて購読に適用するため、
+    /// 通知は1フレームにつき購読ごとに最大1回。
+    /// 分割実行中のクエリは時間予算の範囲で進める
     pub fn tick(&mut self, _delta_time: f32) -> Result<(), JsValue> {
+        // distance_to_camera 用にカメラ位置を反映
+        self.world.set_camera_position(self.renderer.camera.position());
+        let changes = self.world.take_changes();
+        self.subscriptions.apply_changes(&self.world, &changes);
+        self.query_tasks.run(&self.world);
         self.renderer.render_world(&self.world)
     }
 
@@ -169,6 +647,16 @@ impl Engine {
         self.renderer.resize(width, height);
     }
 
+    /// MSAAのサンプル数を設定（1で無効、4で4x。既定は対応していれば4）
+    pub fn set_msaa(&mut self, samples: u32) -> Result<(), JsValue> {
+        self.renderer.set_msaa(samples).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// MSAAのサンプル数を取得
+    pub fn get_msaa(&self) -> u32 {
+        self.renderer.msaa()
+    }
+
     /// 幅取得
     pub fn width(&self) -> u32 {
         self.renderer.width()
@@ -185,42 +673,197 @@ impl Engine {
     /// * `query_json` - QueryDescriptor の JSON文字列
     ///
     /// # Returns
-    /// QueryResult の JsValue（JSON形式）
+    /// QueryResult の JsValue（JSON形式）。
+    /// 検証エラー時は `{ message, errors }` オブジェクトを返す
     pub fn execute_query(&self, query_json: &str) -> Result<JsValue, JsValue> {
-        // JSONパース
-        let query: QueryDescriptor = serde_json::from_str(query_json)
-            .map_err(|e| JsValue::from_str(&format!("Invalid query JSON: {}", e)))?;
+        // JSONパース・検証
+        let query = self.parse_query_json(query_json)?;
 
         // クエリ実行
         let result = self.world.execute_query(&query);
 
         // JsValueに変換
-        serde_wasm_bindgen::to_value(&result)
-            .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+        query_to_js(&result)
+    }
+
+    /// クエリを実行し、列指向の結果を返す
+    ///
+    /// # Returns
+    /// `{ ids: Uint32Array, columns: { [field]: Float32Array | Uint32Array | (string | null)[] | any[] }, total_count }`。
+    /// `position` などの複合値は `position.x` のような列に展開される。
+    /// select の2つのフィールドが同じ名前の列を作る場合は `duplicate_column` エラー
+    pub fn execute_query_columnar(&self, query_json: &str) -> Result<JsValue, JsValue> {
+        let query = self.parse_query_json(query_json)?;
+        let result = self
+            .world
+            .execute_query_columnar(&query)
+            .map_err(|e| validation_error_to_js(&e))?;
+        columnar_to_js(&result)
+    }
+
+    /// テキストクエリ実行
+    ///
+    /// # Arguments
+    /// * `query_text` - クエリ文字列 (e.g., `SELECT id, name WHERE Transform LIMIT 10`)
+    ///
+    /// # Returns
+    /// QueryResult の JsValue（構文エラー時は行・列番号付きのメッセージ）
+    pub fn execute_query_text(&self, query_text: &str) -> Result<JsValue, JsValue> {
+        let query = parse_query(query_text)
+            .map_err(|e| JsValue::from_str(&format!("Invalid query text: {}", e)))?;
+        query
+            .validate(&self.world)
+            .map_err(|e| validation_error_to_js(&e))?;
+
+        let result = self.world.execute_query(&query);
+
+        query_to_js(&result)
+    }
+
+    /// クエリの実行計画を取得
+    ///
+    /// # Arguments
+    /// * `query_json` - QueryDescriptor の JSON文字列
+    ///
+    /// # Returns
+    /// QueryPlan の JsValue（使用するインデックスと候補数）
+    pub fn explain_query(&self, query_json: &str) -> Result<JsValue, JsValue> {
+        let query = self.parse_query_json(query_json)?;
+        let plan = self.world.explain_query(&query);
+
+        query_to_js(&plan)
+    }
+
+    /// クエリの段階ごとの計画を取得（実行はしない）
+    ///
+    /// # Returns
+    /// `{ plan, indexes_used, stages: [{ stage, detail, rows, time_ms, fields, extracted }], ... }`。
+    /// rows は推定できる段階（候補取得・limit）のみ
+    pub fn explain(&self, query_json: &str) -> Result<JsValue, JsValue> {
+        let query = self.parse_query_json(query_json)?;
+        let profile = self.world.explain(&query);
+        query_to_js(&profile)
+    }
+
+    /// クエリを実行し、段階ごとの候補数・時間（ミリ秒）・フィールド取り出し回数を計測
+    ///
+    /// 戻り値の形式は `explain` と同じで、すべての段階の rows / time_ms / extracted が埋まる
+    pub fn profile(&self, query_json: &str) -> Result<JsValue, JsValue> {
+        let query = self.parse_query_json(query_json)?;
+        let profile = self.world.profile(&query, now_ms);
+        query_to_js(&profile)
+    }
+
+    // ========================================================================
+    // Prepared Query API
+    // ========================================================================
+
+    /// クエリを準備し、ハンドルを返す
+    ///
+    /// フィルター値に `"$minY"` のような文字列を指定するとパラメータになる
+    pub fn prepare_query(&mut self, query_json: &str) -> Result<u32, JsValue> {
+        // パラメータを含むため、検証は PreparedQuery::prepare が行う
+        let query = deserialize_query_json(query_json)?;
+        let prepared = PreparedQuery::prepare(&self.world, query)
+            .map_err(|e| validation_error_to_js(&e))?;
+
+        let handle = self.next_query_handle;
+        self.next_query_handle += 1;
+        self.prepared_queries.insert(handle, prepared);
+        Ok(handle)
+    }
+
+    /// 準備済みクエリを実行
+    ///
+    /// # Arguments
+    /// * `handle` - prepare_query のハンドル
+    /// * `params` - パラメータ値のオブジェクト (e.g., `{ minY: 0 }`)。パラメータがなければ undefined
+    pub fn run_prepared(&self, handle: u32, params: JsValue) -> Result<JsValue, JsValue> {
+        let prepared = self.prepared_query(handle)?;
+        let result = prepared
+            .execute(&self.world, &parse_params(params)?)
+            .map_err(|e| validation_error_to_js(&e))?;
+
+        query_to_js(&result)
+    }
+
+    /// 準備済みクエリを実行し、列指向の結果を返す
+    pub fn run_prepared_columnar(&self, handle: u32, params: JsValue) -> Result<JsValue, JsValue> {
+        let prepared = self.prepared_query(handle)?;
+        let result = prepared
+            .execute_columnar(&self.world, &parse_params(params)?)
+            .map_err(|e| validation_error_to_js(&e))?;
+        columnar_to_js(&result)
+    }
+
+    /// 準備済みクエリのパラメータ名
+    pub fn prepared_params(&self, handle: u32) -> Result<Vec<String>, JsValue> {
+        Ok(self.prepared_query(handle)?.params().to_vec())
+    }
+
+    /// 準備済みクエリを解放
+    pub fn release_prepared(&mut self, handle: u32) -> bool {
+        self.prepared_queries.remove(&handle).is_some()
+    }
+
+    // ========================================================================
+    // Index API
+    // ========================================================================
+
+    /// フィールドインデックスを作成
+    ///
+    /// # Arguments
+    /// * `field` - フィールド名 (e.g., "name", "position.x")
+    /// * `kind` - "hash" | "btree"
+    ///
+    /// # Returns
+    /// 作成した場合true（未知のフィールド・作成済みの場合false）
+    pub fn create_index(&mut self, field: &str, kind: &str) -> Result<bool, JsValue> {
+        let kind: IndexKind = kind.parse().map_err(|e: String| JsValue::from_str(&e))?;
+        Ok(self.world.create_index(field, kind))
+    }
+
+    /// コンポーネントのビットセットインデックスを作成
+    pub fn create_component_index(&mut self, component: &str) -> bool {
+        self.world.create_component_index(component)
+    }
+
+    /// インデックスを削除（フィールド名またはコンポーネント名）
+    pub fn drop_index(&mut self, name: &str) -> bool {
+        self.world.drop_index(name)
+    }
+
+    /// 作成済みインデックスの一覧
+    pub fn index_names(&self) -> Vec<String> {
+        self.world.index_names()
     }
 
     /// クエリを購読
     ///
     /// # Arguments
     /// * `query_json` - QueryDescriptor の JSON文字列
-    /// * `callback` - 結果変更時に呼ばれる関数
+    /// * `callback` - 登録時と、結果変更時（tick毎に最大1回）に呼ばれる関数
     ///
     /// # Returns
-    /// subscription_id
+    /// subscription_id（検証エラー時は `{ message, errors }` オブジェクト）
     pub fn subscribe_query(
         &mut self,
         query_json: &str,
         callback: Function,
     ) -> Result<u32, JsValue> {
-        let query: QueryDescriptor = serde_json::from_str(query_json)
-            .map_err(|e| JsValue::from_str(&format!("Invalid query JSON: {}", e)))?;
-
-        let id = self.subscriptions.subscribe(query, callback);
-
-        // 初回実行
-        self.notify_subscription(id);
+        self.subscribe(query_json, callback, SubscriptionMode::Full)
+    }
 
-        Ok(id)
+    /// クエリを差分モードで購読
+    ///
+    /// コールバックには `{added, removed, updated, moved, total_count}` が渡される。
+    /// 登録時は空の結果からの差分（全行が added）を通知する
+    pub fn subscribe_query_delta(
+        &mut self,
+        query_json: &str,
+        callback: Function,
+    ) -> Result<u32, JsValue> {
+        self.subscribe(query_json, callback, SubscriptionMode::Delta)
     }
 
     /// 購読解除
@@ -228,6 +871,38 @@ impl Engine {
         self.subscriptions.unsubscribe(subscription_id)
     }
 
+    // ========================================================================
+    // Sliced Query API
+    // ========================================================================
+
+    /// クエリを複数フレームに分けて実行し、タスクIDを返す
+    ///
+    /// tickごとに時間予算の範囲で処理を進め、完了時に `callback(result)` を呼び出す。
+    /// `on_progress` には各tickの終わりに `{stage, processed, total}` が渡される。
+    ///
+    /// どちらのコールバックも `tick` の実行中に呼ばれるため、その中から
+    /// `start_query` や `cancel_query` などEngineのメソッドを呼ぶと再帰使用エラーになる。
+    /// 必要なら `queueMicrotask` などで `tick` が戻った後に呼び出すこと
+    pub fn start_query(
+        &mut self,
+        query_json: &str,
+        callback: Function,
+        on_progress: Option<Function>,
+    ) -> Result<u32, JsValue> {
+        let query = self.parse_query_json(query_json)?;
+        Ok(self.query_tasks.start(&self.world, query, callback, on_progress))
+    }
+
+    /// 分割実行中のクエリを中止（完了済みの場合false）
+    pub fn cancel_query(&mut self, task_id: u32) -> bool {
+        self.query_tasks.cancel(task_id)
+    }
+
+    /// 分割実行クエリの1フレームあたりの時間予算を設定（ミリ秒、デフォルト4ms）
+    pub fn set_query_time_budget(&mut self, budget_ms: f64) {
+        self.query_tasks.set_budget_ms(budget_ms);
+    }
+
     // ========================================================================
     // カメラ操作 API
     // ========================================================================
@@ -295,9 +970,13 @@ impl Engine {
 
         // 全Entityをチェック
         for entity_id in self.world.iter_entities() {
-            if let Some(transform) = self.world.get::<Transform>(entity_id) {
-                // 簡易Bounding Box (1x1x1 cube at transform position)
-                let aabb = engine_renderer::AABB::unit_cube(transform.position, transform.scale);
+            if let Some(matrix) = self.world.world_matrix(entity_id) {
+                // メッシュのBounding Boxをワールド空間に変換
+                let handle = self.world.get::<MeshHandle>(entity_id).copied().unwrap_or_default();
+                let Some(bounds) = self.renderer.mesh_bounds(handle) else {
+                    continue;
+                };
+                let aabb = bounds.transformed(matrix);
 
                 if let Some(t) = ray.intersect_aabb(&aabb) {
                     match closest {
@@ -460,28 +1139,71 @@ impl Engine {
         self.renderer.gizmo_state.active_axis = GizmoAxis::None;
     }
 
-    /// 全購読のチェック・通知
-    fn check_subscriptions(&mut self) {
-        let ids = self.subscriptions.subscription_ids();
-        for id in ids {
-            self.notify_subscription(id);
+    /// 購読を登録
+    fn subscribe(
+        &mut self,
+        query_json: &str,
+        callback: Function,
+        mode: SubscriptionMode,
+    ) -> Result<u32, JsValue> {
+        let query = self.parse_query_json(query_json)?;
+        // 未通知の変更はtickで適用する。初回結果は現在の状態から作るため、
+        // 反映済みの変更を再適用しても結果は変わらず二重に通知されない
+        Ok(self.subscriptions.subscribe(&self.world, query, callback, mode))
+    }
+
+    /// QueryDescriptor のJSONをパースし、Worldに対して検証
+    fn parse_query_json(&self, query_json: &str) -> Result<QueryDescriptor, JsValue> {
+        let query = deserialize_query_json(query_json)?;
+        query
+            .validate(&self.world)
+            .map_err(|e| validation_error_to_js(&e))?;
+        Ok(query)
+    }
+
+    /// マテリアルが保持するテクスチャの参照を解放
+    fn release_textures(&mut self, material: Option<&Material>) {
+        for handle in material.map_or([None; 3], Material::textures).into_iter().flatten() {
+            self.renderer.release_texture(handle);
         }
     }
 
-    /// 単一の購読を通知
-    fn notify_subscription(&mut self, id: u32) {
-        if let Some(sub) = self.subscriptions.get_mut(id) {
-            let result = self.world.execute_query(&sub.query);
-            let hash = calculate_hash(&result);
+    /// ハンドルから準備済みクエリを取得
+    fn prepared_query(&self, handle: u32) -> Result<&PreparedQuery, JsValue> {
+        self.prepared_queries
+            .get(&handle)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown query handle: {}", handle)))
+    }
+}
 
-            if hash != sub.last_result_hash {
-                sub.last_result_hash = hash;
+/// クエリJSONを読み込む（検証はしない）
+fn deserialize_query_json(query_json: &str) -> Result<QueryDescriptor, JsValue> {
+    serde_json::from_str(query_json).map_err(|e| JsValue::from_str(&format!("Invalid query JSON: {}", e)))
+}
 
-                // コールバック呼び出し
-                if let Ok(js_result) = serde_wasm_bindgen::to_value(&result) {
-                    let _ = sub.callback.call1(&JsValue::NULL, &js_result);
-                }
-            }
-        }
+/// `smooth_normals` 引数（既定: true）を法線生成方法に変換
+fn normal_mode(smooth_normals: Option<bool>) -> NormalMode {
+    if smooth_normals.unwrap_or(true) {
+        NormalMode::Smooth
+    } else {
+        NormalMode::Flat
+    }
+}
+
+/// JSのテクスチャ設定を変換（undefined / null は既定値）
+fn parse_texture_options(options: JsValue) -> Result<TextureOptions, JsValue> {
+    if options.is_undefined() || options.is_null() {
+        return Ok(TextureOptions::default());
+    }
+    serde_wasm_bindgen::from_value(options)
+        .map_err(|e| JsValue::from_str(&format!("Invalid texture options: {}", e)))
+}
+
+/// JSのパラメータオブジェクトを変換（undefined / null は空）
+fn parse_params(params: JsValue) -> Result<HashMap<String, FilterValue>, JsValue> {
+    if params.is_undefined() || params.is_null() {
+        return Ok(HashMap::new());
     }
+    serde_wasm_bindgen::from_value(params)
+        .map_err(|e| JsValue::from_str(&format!("Invalid query params: {}", e)))
 }
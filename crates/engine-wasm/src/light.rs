@@ -0,0 +1,262 @@
+//! ライト記述モジュール
+//!
+//! JSとライトコンポーネント（DirectionalLight / PointLight / SpotLight）を相互に変換する。
+//! 1つのEntityが持つライトは1つで、種類を変えると以前のライトは置き換えられる。
+//! 省略したフィールドは現在の値（種類が変わる場合は既定値）のまま残る。
+//! 影の設定は影を落とせる平行光源とスポットライトのみ
+
+use serde::{Deserialize, Serialize};
+
+use engine_core::{DirectionalLight, EntityId, PointLight, ShadowFlags, ShadowSettings, SpotLight, World};
+use glam::Vec3;
+
+/// ライトの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LightKind {
+    Directional,
+    Point,
+    Spot,
+}
+
+impl LightKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Directional => "directional",
+            Self::Point => "point",
+            Self::Spot => "spot",
+        }
+    }
+}
+
+/// Entityが持つライトコンポーネント
+#[derive(Debug, Clone, PartialEq)]
+pub enum Light {
+    Directional(DirectionalLight),
+    Point(PointLight),
+    Spot(SpotLight),
+}
+
+impl Light {
+    /// 種類の既定値
+    fn new(kind: LightKind) -> Self {
+        match kind {
+            LightKind::Directional => Self::Directional(DirectionalLight::default()),
+            LightKind::Point => Self::Point(PointLight::default()),
+            LightKind::Spot => Self::Spot(SpotLight::default()),
+        }
+    }
+
+    pub fn kind(&self) -> LightKind {
+        match self {
+            Self::Directional(_) => LightKind::Directional,
+            Self::Point(_) => LightKind::Point,
+            Self::Spot(_) => LightKind::Spot,
+        }
+    }
+
+    /// Entityのライトを取得
+    pub fn get(world: &World, entity: EntityId) -> Option<Self> {
+        if let Some(light) = world.get::<DirectionalLight>(entity) {
+            return Some(Self::Directional(light.clone()));
+        }
+        if let Some(light) = world.get::<PointLight>(entity) {
+            return Some(Self::Point(light.clone()));
+        }
+        world.get::<SpotLight>(entity).map(|light| Self::Spot(light.clone()))
+    }
+
+    /// Entityのライトを置き換える
+    pub fn insert(self, world: &mut World, entity: EntityId) {
+        Self::remove(world, entity);
+        match self {
+            Self::Directional(light) => world.insert(entity, light),
+            Self::Point(light) => world.insert(entity, light),
+            Self::Spot(light) => world.insert(entity, light),
+        }
+    }
+
+    /// Entityのライトを削除（持っていればtrue）
+    pub fn remove(world: &mut World, entity: EntityId) -> bool {
+        let directional = world.remove::<DirectionalLight>(entity).is_some();
+        let point = world.remove::<PointLight>(entity).is_some();
+        let spot = world.remove::<SpotLight>(entity).is_some();
+        directional || point || spot
+    }
+}
+
+/// ライトの記述
+///
+/// 例: `{ "kind": "spot", "color": [1, 0.9, 0.8], "intensity": 20, "range": 15, "outer_cone_angle": 0.6 }`
+/// 角度は中心軸からのラジアン。影のバイアスはシャドウマップのテクセル単位
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LightDesc {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<LightKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<[f32; 3]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intensity: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inner_cone_angle: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outer_cone_angle: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cast_shadows: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shadow_bias: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shadow_normal_bias: Option<f32>,
+}
+
+impl LightDesc {
+    /// ライトの全フィールドを持つ記述を作成
+    pub fn from_light(light: &Light) -> Self {
+        let mut desc = Self {
+            kind: Some(light.kind()),
+            ..Self::default()
+        };
+        match light {
+            Light::Directional(light) => {
+                desc.color = Some(light.color.to_array());
+                desc.intensity = Some(light.intensity);
+                desc.set_shadow(&light.shadow);
+            }
+            Light::Point(light) => {
+                desc.color = Some(light.color.to_array());
+                desc.intensity = Some(light.intensity);
+                desc.range = Some(light.range);
+            }
+            Light::Spot(light) => {
+                desc.color = Some(light.color.to_array());
+                desc.intensity = Some(light.intensity);
+                desc.range = Some(light.range);
+                desc.inner_cone_angle = Some(light.inner_cone_angle);
+                desc.outer_cone_angle = Some(light.outer_cone_angle);
+                desc.set_shadow(&light.shadow);
+            }
+        }
+        desc
+    }
+
+    fn set_shadow(&mut self, shadow: &ShadowSettings) {
+        self.cast_shadows = Some(shadow.enabled);
+        self.shadow_bias = Some(shadow.bias);
+        self.shadow_normal_bias = Some(shadow.normal_bias);
+    }
+
+    /// 影の設定に指定されたフィールドを反映
+    fn apply_shadow(&self, shadow: &mut ShadowSettings) {
+        shadow.enabled = self.cast_shadows.unwrap_or(shadow.enabled);
+        shadow.bias = self.shadow_bias.unwrap_or(shadow.bias);
+        shadow.normal_bias = self.shadow_normal_bias.unwrap_or(shadow.normal_bias);
+    }
+
+    /// 指定されたフィールドを現在のライトに反映した新しいライトを返す
+    ///
+    /// 種類を省略した場合は現在の種類のまま。値が不正な場合や
+    /// 種類にないフィールド（平行光源のrangeなど）を指定した場合はエラー
+    pub fn apply(&self, current: Option<Light>) -> Result<Light, String> {
+        let kind = self
+            .kind
+            .or(current.as_ref().map(Light::kind))
+            .ok_or("kind is required for an entity without a light")?;
+        let mut light = current
+            .filter(|light| light.kind() == kind)
+            .unwrap_or_else(|| Light::new(kind));
+
+        if let Some(color) = &self.color
+            && !color.iter().all(|c| c.is_finite() && *c >= 0.0)
+        {
+            return Err("color must be finite and non-negative".to_string());
+        }
+        if let Some(intensity) = self.intensity
+            && !(intensity.is_finite() && intensity >= 0.0)
+        {
+            return Err(format!("intensity must be finite and non-negative, got {}", intensity));
+        }
+        if let Some(range) = self.range
+            && !(range.is_finite() && range > 0.0)
+        {
+            return Err(format!("range must be positive, got {}", range));
+        }
+        for (name, angle) in [
+            ("inner_cone_angle", self.inner_cone_angle),
+            ("outer_cone_angle", self.outer_cone_angle),
+        ] {
+            if let Some(angle) = angle
+                && !(0.0..=std::f32::consts::FRAC_PI_2).contains(&angle)
+            {
+                return Err(format!("{} must be between 0 and PI/2, got {}", name, angle));
+            }
+        }
+        for (name, bias) in [("shadow_bias", self.shadow_bias), ("shadow_normal_bias", self.shadow_normal_bias)] {
+            if let Some(bias) = bias
+                && !(bias.is_finite() && bias >= 0.0)
+            {
+                return Err(format!("{} must be finite and non-negative, got {}", name, bias));
+            }
+        }
+
+        let unused = |name: &str| Err(format!("{} is not used by {} lights", name, kind.name()));
+        let color = self.color.map(Vec3::from_array);
+        match &mut light {
+            Light::Directional(light) => {
+                if self.range.is_some() {
+                    return unused("range");
+                }
+                if self.inner_cone_angle.is_some() || self.outer_cone_angle.is_some() {
+                    return unused("cone angle");
+                }
+                light.color = color.unwrap_or(light.color);
+                light.intensity = self.intensity.unwrap_or(light.intensity);
+                self.apply_shadow(&mut light.shadow);
+            }
+            Light::Point(light) => {
+                if self.inner_cone_angle.is_some() || self.outer_cone_angle.is_some() {
+                    return unused("cone angle");
+                }
+                if self.cast_shadows.is_some() || self.shadow_bias.is_some() || self.shadow_normal_bias.is_some() {
+                    return unused("shadow");
+                }
+                light.color = color.unwrap_or(light.color);
+                light.intensity = self.intensity.unwrap_or(light.intensity);
+                light.range = self.range.unwrap_or(light.range);
+            }
+            Light::Spot(light) => {
+                light.color = color.unwrap_or(light.color);
+                light.intensity = self.intensity.unwrap_or(light.intensity);
+                light.range = self.range.unwrap_or(light.range);
+                light.inner_cone_angle = self.inner_cone_angle.unwrap_or(light.inner_cone_angle);
+                light.outer_cone_angle = self.outer_cone_angle.unwrap_or(light.outer_cone_angle);
+                self.apply_shadow(&mut light.shadow);
+                if light.inner_cone_angle > light.outer_cone_angle {
+                    return Err(format!(
+                        "inner_cone_angle ({}) must not exceed outer_cone_angle ({})",
+                        light.inner_cone_angle, light.outer_cone_angle
+                    ));
+                }
+            }
+        }
+        Ok(light)
+    }
+}
+
+/// Entityの影の設定（JSに返す形）
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ShadowFlagsDesc {
+    pub casts_shadows: bool,
+    pub receives_shadows: bool,
+}
+
+impl From<ShadowFlags> for ShadowFlagsDesc {
+    fn from(flags: ShadowFlags) -> Self {
+        Self {
+            casts_shadows: flags.casts_shadows,
+            receives_shadows: flags.receives_shadows,
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! マテリアル記述モジュール
+//!
+//! JSとMaterialコンポーネントを相互に変換する。
+//! 省略したフィールドは現在の値（Materialがなければ既定値）のまま残る。
+//! テクスチャはハンドルで指定し、nullで参照を外す
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use engine_core::{Material, TextureHandle};
+use glam::{Vec3, Vec4};
+
+/// マテリアルの記述
+///
+/// 例: `{ "base_color": [1, 0, 0, 1], "metallic": 1.0, "roughness": 0.2, "albedo_texture": 3 }`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MaterialDesc {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_color: Option<[f32; 4]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metallic: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roughness: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emissive: Option<[f32; 3]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unlit: Option<bool>,
+    #[serde(default, deserialize_with = "nullable", skip_serializing_if = "Option::is_none")]
+    pub albedo_texture: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "nullable", skip_serializing_if = "Option::is_none")]
+    pub normal_texture: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "nullable", skip_serializing_if = "Option::is_none")]
+    pub roughness_texture: Option<Option<u32>>,
+}
+
+/// 省略（None）とnull（Some(None)）を区別して読む
+fn nullable<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Option<u32>>, D::Error> {
+    Option::<u32>::deserialize(deserializer).map(Some)
+}
+
+impl MaterialDesc {
+    /// Materialの全フィールドを持つ記述を作成
+    pub fn from_material(material: &Material) -> Self {
+        Self {
+            base_color: Some(material.base_color.to_array()),
+            metallic: Some(material.metallic),
+            roughness: Some(material.roughness),
+            emissive: Some(material.emissive.to_array()),
+            unlit: Some(material.unlit),
+            albedo_texture: Some(material.albedo_texture.map(|t| t.id())),
+            normal_texture: Some(material.normal_texture.map(|t| t.id())),
+            roughness_texture: Some(material.roughness_texture.map(|t| t.id())),
+        }
+    }
+
+    /// 指定されたフィールドをMaterialに反映
+    ///
+    /// 値が不正な場合はMaterialを変更せずにエラーを返す
+    pub fn apply(&self, material: &mut Material) -> Result<(), String> {
+        let finite = |name: &str, values: &[f32]| {
+            if values.iter().all(|v| v.is_finite()) {
+                Ok(())
+            } else {
+                Err(format!("{} must be finite", name))
+            }
+        };
+        let unit = |name: &str, value: f32| {
+            if (0.0..=1.0).contains(&value) {
+                Ok(())
+            } else {
+                Err(format!("{} must be between 0 and 1, got {}", name, value))
+            }
+        };
+
+        if let Some(color) = &self.base_color {
+            finite("base_color", color)?;
+        }
+        if let Some(metallic) = self.metallic {
+            unit("metallic", metallic)?;
+        }
+        if let Some(roughness) = self.roughness {
+            unit("roughness", roughness)?;
+        }
+        if let Some(emissive) = &self.emissive {
+            finite("emissive", emissive)?;
+        }
+
+        if let Some(color) = self.base_color {
+            material.base_color = Vec4::from_array(color);
+        }
+        if let Some(metallic) = self.metallic {
+            material.metallic = metallic;
+        }
+        if let Some(roughness) = self.roughness {
+            material.roughness = roughness;
+        }
+        if let Some(emissive) = self.emissive {
+            material.emissive = Vec3::from_array(emissive);
+        }
+        if let Some(unlit) = self.unlit {
+            material.unlit = unlit;
+        }
+        let slots = [
+            (self.albedo_texture, &mut material.albedo_texture),
+            (self.normal_texture, &mut material.normal_texture),
+            (self.roughness_texture, &mut material.roughness_texture),
+        ];
+        for (texture, slot) in slots {
+            if let Some(texture) = texture {
+                *slot = texture.map(TextureHandle::new);
+            }
+        }
+        Ok(())
+    }
+}
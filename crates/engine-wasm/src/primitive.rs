@@ -0,0 +1,156 @@
+//! プリミティブ記述モジュール
+//!
+//! JSから渡された形状記述をメッシュに変換する。
+//! 省略したパラメータは既定値（組み込みCubeと同程度の大きさ）になる
+
+use serde::Deserialize;
+
+use engine_renderer::{Mesh, MAX_SEGMENTS, MAX_SUBDIVISIONS};
+
+/// プリミティブ形状の記述
+///
+/// 例: `{ "shape": "sphere", "radius": 1.0 }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case", deny_unknown_fields)]
+pub enum PrimitiveDesc {
+    Cube,
+    Sphere {
+        radius: Option<f32>,
+        segments: Option<u32>,
+        rings: Option<u32>,
+    },
+    Icosphere {
+        radius: Option<f32>,
+        subdivisions: Option<u32>,
+    },
+    Plane {
+        width: Option<f32>,
+        depth: Option<f32>,
+        segments_x: Option<u32>,
+        segments_z: Option<u32>,
+    },
+    Quad {
+        width: Option<f32>,
+        height: Option<f32>,
+    },
+    Disc {
+        radius: Option<f32>,
+        segments: Option<u32>,
+    },
+    Cylinder {
+        radius: Option<f32>,
+        height: Option<f32>,
+        segments: Option<u32>,
+    },
+    Cone {
+        radius: Option<f32>,
+        height: Option<f32>,
+        segments: Option<u32>,
+    },
+    Torus {
+        major_radius: Option<f32>,
+        minor_radius: Option<f32>,
+        major_segments: Option<u32>,
+        minor_segments: Option<u32>,
+    },
+    Capsule {
+        radius: Option<f32>,
+        height: Option<f32>,
+        segments: Option<u32>,
+        rings: Option<u32>,
+    },
+}
+
+impl PrimitiveDesc {
+    /// 記述からメッシュを生成
+    ///
+    /// 分割数が上限（`MAX_SEGMENTS`・`MAX_SUBDIVISIONS`）を超える場合はエラー
+    pub fn to_mesh(&self) -> Result<Mesh, String> {
+        let segments = |value, default, name| count(value, default, MAX_SEGMENTS, name);
+        let mesh = match *self {
+            Self::Cube => Mesh::cube(),
+            Self::Sphere {
+                radius,
+                segments: s,
+                rings,
+            } => Mesh::uv_sphere(
+                radius.unwrap_or(0.5),
+                segments(s, 32, "segments")?,
+                segments(rings, 16, "rings")?,
+            ),
+            Self::Icosphere {
+                radius,
+                subdivisions,
+            } => Mesh::icosphere(
+                radius.unwrap_or(0.5),
+                count(subdivisions, 2, MAX_SUBDIVISIONS, "subdivisions")?,
+            ),
+            Self::Plane {
+                width,
+                depth,
+                segments_x,
+                segments_z,
+            } => Mesh::plane(
+                width.unwrap_or(1.0),
+                depth.unwrap_or(1.0),
+                segments(segments_x, 1, "segments_x")?,
+                segments(segments_z, 1, "segments_z")?,
+            ),
+            Self::Quad { width, height } => {
+                Mesh::quad(width.unwrap_or(1.0), height.unwrap_or(1.0))
+            }
+            Self::Disc { radius, segments: s } => {
+                Mesh::disc(radius.unwrap_or(0.5), segments(s, 32, "segments")?)
+            }
+            Self::Cylinder {
+                radius,
+                height,
+                segments: s,
+            } => Mesh::cylinder(
+                radius.unwrap_or(0.5),
+                height.unwrap_or(1.0),
+                segments(s, 32, "segments")?,
+            ),
+            Self::Cone {
+                radius,
+                height,
+                segments: s,
+            } => Mesh::cone(
+                radius.unwrap_or(0.5),
+                height.unwrap_or(1.0),
+                segments(s, 32, "segments")?,
+            ),
+            Self::Torus {
+                major_radius,
+                minor_radius,
+                major_segments,
+                minor_segments,
+            } => Mesh::torus(
+                major_radius.unwrap_or(0.4),
+                minor_radius.unwrap_or(0.1),
+                segments(major_segments, 32, "major_segments")?,
+                segments(minor_segments, 16, "minor_segments")?,
+            ),
+            Self::Capsule {
+                radius,
+                height,
+                segments: s,
+                rings,
+            } => Mesh::capsule(
+                radius.unwrap_or(0.25),
+                height.unwrap_or(0.5),
+                segments(s, 32, "segments")?,
+                segments(rings, 8, "rings")?,
+            ),
+        };
+        Ok(mesh)
+    }
+}
+
+/// 分割数を取得（省略時は既定値、上限を超える場合はエラー）
+fn count(value: Option<u32>, default: u32, max: u32, name: &str) -> Result<u32, String> {
+    match value {
+        Some(value) if value > max => Err(format!("{} must be at most {} (got {})", name, max, value)),
+        value => Ok(value.unwrap_or(default)),
+    }
+}
@@ -0,0 +1,125 @@
+//! 分割実行クエリの管理モジュール
+//!
+//! tickごとに時間予算を実行中のクエリで分け合って進め、
+//! 完了したクエリの結果をコールバックで返す。
+//! コールバックは `Engine::tick` の中で呼ばれるため、その中からEngineを呼び出してはならない
+
+use js_sys::Function;
+use std::collections::BTreeMap;
+use wasm_bindgen::JsValue;
+
+use engine_core::{QueryDescriptor, SlicedQuery, World};
+
+use crate::utils::{now_ms, query_to_js};
+
+/// 1回の `step` で処理する候補数（この単位で経過時間を確認する）
+const CHUNK_SIZE: usize = 2048;
+
+/// デフォルトの1フレームあたりの時間予算（ミリ秒）
+const DEFAULT_BUDGET_MS: f64 = 4.0;
+
+/// 実行中のクエリ
+struct QueryTask {
+    query: SlicedQuery,
+    callback: Function,
+    on_progress: Option<Function>,
+}
+
+/// 分割実行クエリのマネージャー
+pub struct QueryTaskManager {
+    /// 開始順に実行するためBTreeMap
+    tasks: BTreeMap<u32, QueryTask>,
+    next_id: u32,
+    budget_ms: f64,
+}
+
+impl QueryTaskManager {
+    pub fn new() -> Self {
+        Self {
+            tasks: BTreeMap::new(),
+            next_id: 1,
+            budget_ms: DEFAULT_BUDGET_MS,
+        }
+    }
+
+    /// 1フレームあたりの時間予算を設定（ミリ秒）
+    pub fn set_budget_ms(&mut self, budget_ms: f64) {
+        self.budget_ms = budget_ms.max(0.0);
+    }
+
+    /// クエリの実行を開始し、タスクIDを返す
+    pub fn start(
+        &mut self,
+        world: &World,
+        query: QueryDescriptor,
+        callback: Function,
+        on_progress: Option<Function>,
+    ) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.tasks.insert(
+            id,
+            QueryTask {
+                query: SlicedQuery::new(world, query),
+                callback,
+                on_progress,
+            },
+        );
+        id
+    }
+
+    /// 実行を中止（完了済み・未知のIDはfalse）
+    pub fn cancel(&mut self, id: u32) -> bool {
+        self.tasks.remove(&id).is_some()
+    }
+
+    /// 時間予算を各タスクで等分し、開始順に進める
+    /// 先に終わったタスクの残り時間は後のタスクに回す。
+    /// 各タスクが少なくとも1チャンクは処理するため、予算0でも停止せず、後のタスクも待たされない
+    pub fn run(&mut self, world: &World) {
+        if self.tasks.is_empty() {
+            return;
+        }
+        let deadline = now_ms() + self.budget_ms;
+        let task_count = self.tasks.len();
+        let mut finished = Vec::new();
+
+        for (i, (&id, task)) in self.tasks.iter_mut().enumerate() {
+            let now = now_ms();
+            let slice_end = now + (deadline - now).max(0.0) / (task_count - i) as f64;
+            let mut progress = task.query.step(world, CHUNK_SIZE);
+            while !task.query.is_done() && now_ms() < slice_end {
+                progress = task.query.step(world, CHUNK_SIZE);
+            }
+            if let Some(on_progress) = &task.on_progress {
+                notify(on_progress, &progress);
+            }
+            if task.query.is_done() {
+                finished.push(id);
+            }
+        }
+
+        // 完了したタスクは通知前に取り除く（コールバックはtick中に呼ばれるため、
+        // Engineを呼び出すとwasm-bindgenの再帰使用エラーになる。開始・中止はtickの後に行う）
+        for id in finished {
+            if let Some(task) = self.tasks.remove(&id)
+                && let Some(result) = task.query.into_result()
+            {
+                notify(&task.callback, &result);
+            }
+        }
+    }
+}
+
+impl Default for QueryTaskManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// コールバックを呼び出す（`execute_query` と同じ形に変換する）
+fn notify<T: serde::Serialize>(callback: &Function, value: &T) {
+    if let Ok(js_value) = query_to_js(value) {
+        let _ = callback.call1(&JsValue::NULL, &js_value);
+    }
+}
@@ -6,10 +6,13 @@
 pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 
 /// Depth Textureを作成
+///
+/// マルチサンプルの場合は描画にのみ使う（WebGL2はマルチサンプルテクスチャを参照できない）
 pub fn create_texture(
     device: &wgpu::Device,
     width: u32,
     height: u32,
+    sample_count: u32,
 ) -> (wgpu::Texture, wgpu::TextureView) {
     let size = wgpu::Extent3d {
         width,
@@ -17,14 +20,20 @@ pub fn create_texture(
         depth_or_array_layers: 1,
     };
 
+    let usage = if sample_count > 1 {
+        wgpu::TextureUsages::RENDER_ATTACHMENT
+    } else {
+        wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING
+    };
+
     let texture = device.create_texture(&wgpu::TextureDescriptor {
         label: Some("Depth Texture"),
         size,
         mip_level_count: 1,
-        sample_count: 1,
+        sample_count,
         dimension: wgpu::TextureDimension::D2,
         format: DEPTH_FORMAT,
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        usage,
         view_formats: &[],
     });
 
@@ -32,3 +41,46 @@ pub fn create_texture(
 
     (texture, view)
 }
+
+/// シャドウマップ用のDepth Texture配列を作成
+///
+/// 各レイヤーに描画するためのビューと、シェーダーで参照する配列ビューを返す
+pub fn create_array_texture(
+    device: &wgpu::Device,
+    size: u32,
+    layers: u32,
+) -> (wgpu::Texture, Vec<wgpu::TextureView>, wgpu::TextureView) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Shadow Map Texture"),
+        size: wgpu::Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: layers,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+
+    let layer_views = (0..layers)
+        .map(|layer| {
+            texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Shadow Map Layer"),
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_array_layer: layer,
+                array_layer_count: Some(1),
+                ..Default::default()
+            })
+        })
+        .collect();
+    let array_view = texture.create_view(&wgpu::TextureViewDescriptor {
+        label: Some("Shadow Map Array"),
+        dimension: Some(wgpu::TextureViewDimension::D2Array),
+        ..Default::default()
+    });
+
+    (texture, layer_views, array_view)
+}
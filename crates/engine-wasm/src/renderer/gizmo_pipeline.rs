@@ -31,6 +31,9 @@ impl GizmoUniform {
 /// Gizmo描画パイプライン
 pub struct GizmoPipeline {
     pub pipeline: wgpu::RenderPipeline,
+    /// MSAA設定の変更時にパイプラインを作り直すために保持する
+    pipeline_layout: wgpu::PipelineLayout,
+    shader: wgpu::ShaderModule,
     pub uniform_buffer: wgpu::Buffer,
     pub bind_group: wgpu::BindGroup,
 }
@@ -86,49 +89,57 @@ impl GizmoPipeline {
                 push_constant_ranges: &[],
             });
 
-        // Gizmo Render Pipeline（深度テスト無効で常に手前に描画）
-        let pipeline = ctx.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Gizmo Render Pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &gizmo_shader,
-                entry_point: Some("vs_main"),
-                buffers: &[GizmoVertex::desc()],
-                compilation_options: Default::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &gizmo_shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: ctx.config.format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: Default::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None, // 両面描画
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None, // 深度テスト無効（常に手前）
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-            cache: None,
-        });
+        // Gizmo Render Pipeline
+        let pipeline = create_pipeline(ctx, &pipeline_layout, &gizmo_shader);
 
         Self {
             pipeline,
+            pipeline_layout,
+            shader: gizmo_shader,
             uniform_buffer,
             bind_group,
         }
     }
+
+    /// コンテキストのサンプル数でパイプラインを作り直す
+    pub fn rebuild_pipeline(&mut self, ctx: &GpuContext) {
+        self.pipeline = create_pipeline(ctx, &self.pipeline_layout, &self.shader);
+    }
+}
+
+/// Gizmo描画パイプラインを作成（深度テスト無効で常に手前に描画）
+fn create_pipeline(ctx: &GpuContext, layout: &wgpu::PipelineLayout, shader: &wgpu::ShaderModule) -> wgpu::RenderPipeline {
+    ctx.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Gizmo Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            buffers: &[GizmoVertex::desc()],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: ctx.config.format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None, // 両面描画
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: None, // 深度テスト無効（常に手前）
+        multisample: ctx.multisample_state(),
+        multiview: None,
+        cache: None,
+    })
 }
@@ -5,6 +5,8 @@
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlCanvasElement;
 
+use super::depth::DEPTH_FORMAT;
+use super::msaa::DEFAULT_SAMPLE_COUNT;
 use crate::utils::console_log;
 
 /// GPUコンテキスト
@@ -15,6 +17,10 @@ pub struct GpuContext {
     pub queue: wgpu::Queue,
     pub config: wgpu::SurfaceConfiguration,
     pub size: (u32, u32),
+    /// シーンとGizmoのMSAAサンプル数（1ならMSAAなし）
+    pub sample_count: u32,
+    /// アダプターが対応する最大のサンプル数
+    pub max_sample_count: u32,
 }
 
 impl GpuContext {
@@ -63,11 +69,18 @@ impl GpuContext {
         console_log!("Adapter: {:?}", adapter.get_info());
 
         // Device & Queue 作成
+        // WebGL2の制限を基本とし、アダプターが対応していればストレージバッファを使う
+        let supported = adapter.limits();
+        let required_limits = wgpu::Limits {
+            max_storage_buffers_per_shader_stage: supported.max_storage_buffers_per_shader_stage.min(4),
+            max_storage_buffer_binding_size: supported.max_storage_buffer_binding_size,
+            ..wgpu::Limits::downlevel_webgl2_defaults()
+        };
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 label: Some("Device"),
                 required_features: wgpu::Features::empty(),
-                required_limits: wgpu::Limits::downlevel_webgl2_defaults(),
+                required_limits,
                 memory_hints: Default::default(),
                 experimental_features: Default::default(),
                 trace: Default::default(),
@@ -107,12 +120,21 @@ impl GpuContext {
         };
         surface.configure(&device, &config);
 
+        // カラーと深度の両方が4xに対応していればMSAAを既定で有効にする
+        let supports_x4 = [surface_format, DEPTH_FORMAT]
+            .iter()
+            .all(|format| adapter.get_texture_format_features(*format).flags.sample_count_supported(4));
+        let max_sample_count = if supports_x4 { 4 } else { 1 };
+        console_log!("MSAA: max {}x", max_sample_count);
+
         Ok(Self {
             surface,
             device,
             queue,
             config,
             size: (width, height),
+            sample_count: DEFAULT_SAMPLE_COUNT.min(max_sample_count),
+            max_sample_count,
         })
     }
 
@@ -136,6 +158,20 @@ impl GpuContext {
         self.size.1
     }
 
+    /// 描画パイプラインのマルチサンプル設定
+    pub fn multisample_state(&self) -> wgpu::MultisampleState {
+        wgpu::MultisampleState {
+            count: self.sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        }
+    }
+
+    /// フラグメントシェーダーでストレージバッファを使えるか（WebGL2ではfalse）
+    pub fn supports_storage_buffers(&self) -> bool {
+        self.device.limits().max_storage_buffers_per_shader_stage > 0
+    }
+
     /// アスペクト比を取得
     pub fn aspect(&self) -> f32 {
         self.size.0 as f32 / self.size.1 as f32
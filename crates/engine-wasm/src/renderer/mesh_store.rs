@@ -0,0 +1,88 @@
+//! メッシュストアモジュール
+//!
+//! メッシュを一度だけGPUにアップロードし、ハンドルの参照カウントで管理する
+
+use wgpu::util::DeviceExt;
+
+use engine_core::MeshHandle;
+use engine_renderer::{AABB, AssetStore, Mesh};
+
+/// GPUにアップロード済みのメッシュ
+pub struct GpuMesh {
+    pub vertex_buffer: wgpu::Buffer,
+    /// UVストリーム（UVのないメッシュは0で埋める）
+    pub uv_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub num_indices: u32,
+    /// ローカル空間のバウンディングボックス（ピッキング用）
+    pub bounds: Option<AABB>,
+    /// CPU側の頂点データ（エクスポート用）
+    pub source: Mesh,
+}
+
+impl GpuMesh {
+    /// メッシュをGPUにアップロード
+    fn upload(device: &wgpu::Device, mesh: &Mesh) -> Self {
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Mesh Vertex Buffer"),
+            contents: bytemuck::cast_slice(&mesh.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let uv_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Mesh UV Buffer"),
+            contents: bytemuck::cast_slice(&mesh.uv_stream()),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Mesh Index Buffer"),
+            contents: bytemuck::cast_slice(&mesh.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Self {
+            vertex_buffer,
+            uv_buffer,
+            index_buffer,
+            num_indices: mesh.index_count() as u32,
+            bounds: mesh.bounds(),
+            source: mesh.clone(),
+        }
+    }
+}
+
+/// メッシュストア
+///
+/// 組み込みのCubeは `MeshHandle::CUBE` としてストア自身が参照を保持する
+pub struct MeshStore {
+    meshes: AssetStore<GpuMesh>,
+}
+
+impl MeshStore {
+    /// 組み込みメッシュを登録したストアを作成
+    pub fn new(device: &wgpu::Device) -> Self {
+        let mut meshes = AssetStore::new();
+        let cube = meshes.insert(GpuMesh::upload(device, &Mesh::cube()));
+        debug_assert_eq!(cube, MeshHandle::CUBE.id());
+        Self { meshes }
+    }
+
+    /// メッシュをアップロードして登録（参照カウント1）
+    pub fn create(&mut self, device: &wgpu::Device, mesh: &Mesh) -> MeshHandle {
+        MeshHandle::new(self.meshes.insert(GpuMesh::upload(device, mesh)))
+    }
+
+    /// 参照を追加
+    pub fn retain(&mut self, handle: MeshHandle) -> bool {
+        self.meshes.retain(handle.id())
+    }
+
+    /// 参照を解放（カウントが0になるとGPUバッファも破棄される）
+    pub fn release(&mut self, handle: MeshHandle) {
+        self.meshes.release(handle.id());
+    }
+
+    /// メッシュを取得
+    pub fn get(&self, handle: MeshHandle) -> Option<&GpuMesh> {
+        self.meshes.get(handle.id())
+    }
+}
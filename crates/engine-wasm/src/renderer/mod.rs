@@ -5,7 +5,11 @@
 mod depth;
 mod gizmo_pipeline;
 mod gpu_context;
+mod mesh_store;
+mod msaa;
 mod scene_pipeline;
+mod shadow_pipeline;
+mod texture_store;
 
 pub use gizmo_pipeline::GizmoUniform;
 pub use gpu_context::GpuContext;
@@ -14,33 +18,64 @@ use wasm_bindgen::prelude::*;
 use wgpu::util::DeviceExt;
 use web_sys::HtmlCanvasElement;
 
-use crate::utils::console_log;
+use crate::utils::{console_log, console_warn};
 
-use engine_core::{ModelUniform, Transform, World};
+use engine_core::{Material, MeshHandle, ShadowFlags, TextureHandle, Transform, World};
 use engine_renderer::{
-    glam, Camera, GizmoAxis, GizmoMode, GizmoState, GizmoVertex,
+    glam, AmbientLight, Camera, GizmoAxis, InstanceBatches, InstanceData, LightSet, MaterialTable, MaterialUniform, MAX_MATERIALS, Mesh, SamplerDesc, ShadowFrame, TextureData, AABB, GizmoMode, GizmoState, GizmoVertex,
     create_arrow_vertices, create_center_box_vertices, create_circle_vertices,
     create_plane_vertices, create_scale_axis_vertices,
 };
 use glam::{Quat, Vec3};
 
 use gizmo_pipeline::GizmoPipeline;
+use mesh_store::MeshStore;
 use scene_pipeline::ScenePipeline;
+use shadow_pipeline::ShadowPipeline;
+use texture_store::{TextureSet, TextureStore};
+
+/// 描画キー
+/// 同じメッシュ・テクスチャの組のEntityは1回のインスタンス描画にまとめる
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct DrawKey {
+    mesh: MeshHandle,
+    textures: TextureSet,
+}
+
+/// サンプル数が1より大きければMSAAのカラーターゲットを作成
+fn create_msaa_target(ctx: &GpuContext) -> Option<(wgpu::Texture, wgpu::TextureView)> {
+    (ctx.sample_count > 1).then(|| {
+        msaa::create_color_texture(&ctx.device, ctx.config.format, ctx.width(), ctx.height(), ctx.sample_count)
+    })
+}
 
 /// Renderer構造体
 pub struct Renderer {
     ctx: GpuContext,
     scene: ScenePipeline,
+    shadow: ShadowPipeline,
     gizmo: GizmoPipeline,
 
+    // Mesh assets
+    meshes: MeshStore,
+
+    // Texture assets
+    textures: TextureStore,
+
     // Camera
     pub camera: Camera,
 
+    // Lighting
+    pub ambient: AmbientLight,
+
     // Depth buffer
     #[allow(dead_code)]
     depth_texture: wgpu::Texture,
     depth_view: wgpu::TextureView,
 
+    // MSAA color target（サンプル数1ならNone）
+    msaa_target: Option<(wgpu::Texture, wgpu::TextureView)>,
+
     // Gizmo state
     pub gizmo_state: GizmoState,
 }
@@ -54,14 +89,30 @@ impl Renderer {
         let camera = Camera::new(ctx.aspect());
         let camera_uniform = camera.uniform();
 
+        // Texture Store 作成（既定テクスチャを登録）
+        let textures = TextureStore::new(&ctx.device, &ctx.queue);
+
+        // Shadow Pipeline 作成（シャドウマップはシーン描画で参照する）
+        let shadow = ShadowPipeline::new(&ctx);
+
         // Scene Pipeline 作成
-        let scene = ScenePipeline::new(&ctx, bytemuck::bytes_of(&camera_uniform));
+        let scene = ScenePipeline::new(&ctx, bytemuck::bytes_of(&camera_uniform), textures.layout(), &shadow);
+        if ctx.supports_storage_buffers() {
+            console_log!("Lights: storage buffer (max {})", scene.light_capacity());
+        } else {
+            console_log!("Lights: uniform buffer fallback for WebGL2 (max {})", scene.light_capacity());
+        }
 
         // Gizmo Pipeline 作成
         let gizmo = GizmoPipeline::new(&ctx);
 
-        // Depth Texture 作成
-        let (depth_texture, depth_view) = depth::create_texture(&ctx.device, ctx.width(), ctx.height());
+        // Mesh Store 作成（組み込みCubeを登録）
+        let meshes = MeshStore::new(&ctx.device);
+
+        // Depth Texture・MSAAターゲット作成
+        let (depth_texture, depth_view) =
+            depth::create_texture(&ctx.device, ctx.width(), ctx.height(), ctx.sample_count);
+        let msaa_target = create_msaa_target(&ctx);
 
         let gizmo_state = GizmoState::default();
 
@@ -70,98 +121,26 @@ impl Renderer {
         Ok(Self {
             ctx,
             scene,
+            shadow,
             gizmo,
+            meshes,
+            textures,
             camera,
+            ambient: AmbientLight::default(),
             depth_texture,
             depth_view,
+            msaa_target,
             gizmo_state,
         })
     }
 
-    /// 単一のCubeをレンダリング（固定位置）
-    #[allow(dead_code)]
-    pub fn render(&self) -> Result<(), JsValue> {
-        let output = self
-            .ctx
-            .surface
-            .get_current_texture()
-            .map_err(|e| JsValue::from_str(&format!("Failed to get surface texture: {:?}", e)))?;
-
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-
-        let mut encoder = self
-            .ctx
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            });
-
-        // Camera uniform更新
-        self.ctx.queue.write_buffer(
-            &self.scene.camera_buffer,
-            0,
-            bytemuck::bytes_of(&self.camera.uniform()),
-        );
-
-        // Default model (identity)
-        let model_uniform = ModelUniform::identity();
-        self.ctx.queue
-            .write_buffer(&self.scene.model_buffer, 0, bytemuck::bytes_of(&model_uniform));
-
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                    depth_slice: None,
-                })],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &self.depth_view,
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: wgpu::StoreOp::Store,
-                    }),
-                    stencil_ops: None,
-                }),
-                occlusion_query_set: None,
-                timestamp_writes: None,
-            });
-
-            render_pass.set_pipeline(&self.scene.pipeline);
-            render_pass.set_bind_group(0, &self.scene.camera_bind_group, &[]);
-            render_pass.set_bind_group(1, &self.scene.model_bind_group, &[]);
-            render_pass.set_vertex_buffer(0, self.scene.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(self.scene.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-            render_pass.draw_indexed(0..self.scene.num_indices, 0, 0..1);
-        }
-
-        self.ctx.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
-
-        Ok(())
-    }
-
     /// Canvas サイズ変更
     pub fn resize(&mut self, width: u32, height: u32) {
         if width > 0 && height > 0 {
             self.ctx.resize(width, height);
 
-            // Depth Texture再作成
-            let (depth_texture, depth_view) = depth::create_texture(&self.ctx.device, width, height);
-            self.depth_texture = depth_texture;
-            self.depth_view = depth_view;
+            // Depth Texture・MSAAターゲット再作成
+            self.recreate_targets();
 
             // Camera aspect更新
             self.camera.set_aspect(width as f32 / height as f32);
@@ -170,6 +149,41 @@ impl Renderer {
         }
     }
 
+    /// サイズ・サンプル数に合わせて深度とMSAAのターゲットを作り直す
+    fn recreate_targets(&mut self) {
+        let (depth_texture, depth_view) =
+            depth::create_texture(&self.ctx.device, self.ctx.width(), self.ctx.height(), self.ctx.sample_count);
+        self.depth_texture = depth_texture;
+        self.depth_view = depth_view;
+        self.msaa_target = create_msaa_target(&self.ctx);
+    }
+
+    /// MSAAのサンプル数を設定（1で無効、4で4x）
+    ///
+    /// パイプラインとターゲットを作り直す。デバイスが対応しない場合はエラー
+    pub fn set_msaa(&mut self, samples: u32) -> Result<(), String> {
+        if !msaa::SAMPLE_COUNTS.contains(&samples) {
+            return Err(format!("MSAA sample count must be 1 or 4, got {}", samples));
+        }
+        if samples > self.ctx.max_sample_count {
+            return Err(format!("{}x MSAA is not supported by this device", samples));
+        }
+        if samples == self.ctx.sample_count {
+            return Ok(());
+        }
+        self.ctx.sample_count = samples;
+        self.scene.rebuild_pipeline(&self.ctx);
+        self.gizmo.rebuild_pipeline(&self.ctx);
+        self.recreate_targets();
+        console_log!("MSAA: {}x", samples);
+        Ok(())
+    }
+
+    /// MSAAのサンプル数
+    pub fn msaa(&self) -> u32 {
+        self.ctx.sample_count
+    }
+
     /// 現在のサイズ取得
     pub fn width(&self) -> u32 {
         self.ctx.width()
@@ -185,10 +199,57 @@ impl Renderer {
         &self.ctx.queue
     }
 
-    /// Model Buffer参照を取得
-    #[allow(dead_code)]
-    pub fn model_buffer(&self) -> &wgpu::Buffer {
-        &self.scene.model_buffer
+    // ========================================================================
+    // メッシュ管理
+    // ========================================================================
+
+    /// メッシュをGPUにアップロードしてハンドルを返す（参照カウント1）
+    pub fn create_mesh(&mut self, mesh: &Mesh) -> MeshHandle {
+        self.meshes.create(&self.ctx.device, mesh)
+    }
+
+    /// メッシュの参照を追加
+    pub fn retain_mesh(&mut self, handle: MeshHandle) -> bool {
+        self.meshes.retain(handle)
+    }
+
+    /// メッシュの参照を解放
+    pub fn release_mesh(&mut self, handle: MeshHandle) {
+        self.meshes.release(handle);
+    }
+
+    /// メッシュのローカル空間バウンディングボックス
+    pub fn mesh_bounds(&self, handle: MeshHandle) -> Option<AABB> {
+        self.meshes.get(handle).and_then(|mesh| mesh.bounds)
+    }
+
+    /// メッシュの頂点データを取得
+    pub fn mesh_data(&self, handle: MeshHandle) -> Option<&Mesh> {
+        self.meshes.get(handle).map(|mesh| &mesh.source)
+    }
+
+    // ========================================================================
+    // テクスチャ管理
+    // ========================================================================
+
+    /// テクスチャをGPUにアップロードしてハンドルを返す（参照カウント1）
+    pub fn create_texture(&mut self, data: &TextureData, sampler: &SamplerDesc) -> TextureHandle {
+        self.textures.create(&self.ctx.device, &self.ctx.queue, data, sampler)
+    }
+
+    /// テクスチャの参照を追加
+    pub fn retain_texture(&mut self, handle: TextureHandle) -> bool {
+        self.textures.retain(handle)
+    }
+
+    /// テクスチャの参照を解放
+    pub fn release_texture(&mut self, handle: TextureHandle) {
+        self.textures.release(handle);
+    }
+
+    /// テクスチャが登録されているか
+    pub fn has_texture(&self, handle: TextureHandle) -> bool {
+        self.textures.contains(handle)
     }
 
     // ========================================================================
@@ -236,12 +297,53 @@ impl Renderer {
     }
 
     /// Worldの全Transformを持つEntityをレンダリング
-    pub fn render_world(&self, world: &World) -> Result<(), JsValue> {
-        // 先に全Transformを収集
-        let transforms: Vec<ModelUniform> = world
-            .iter_with::<Transform>()
-            .map(|(_, t)| ModelUniform::from_transform(t))
-            .collect();
+    ///
+    /// 全Entityのモデル行列とマテリアル番号を1つのインスタンスバッファにまとめ、
+    /// 1つのエンコーダ・1つのパスで描画キーごとにインスタンス描画する。
+    /// ライトは上限を超えた分を、平行光源を優先してカメラから遠いものから省く。
+    /// 影を落とすEntityは先にシャドウマップへ深度のみ描画する
+    pub fn render_world(&mut self, world: &World) -> Result<(), JsValue> {
+        // 親子関係を合成したワールド行列とマテリアル番号でインスタンスを収集
+        let mut materials = MaterialTable::new();
+        let mut casters = Vec::new();
+        let mut caster_bounds: Option<AABB> = None;
+        let batches = InstanceBatches::build(world.iter_with::<Transform>().map(|(entity, t)| {
+            let matrix = world.world_matrix(entity).unwrap_or_else(|| t.to_matrix());
+            let mesh = world.get::<MeshHandle>(entity).copied().unwrap_or_default();
+            let flags = world.get::<ShadowFlags>(entity).copied().unwrap_or_default();
+            let material = world.get::<Material>(entity);
+            let textures = material.map_or([None; 3], Material::textures);
+            let uniform = material
+                .map_or_else(MaterialUniform::default, MaterialUniform::from_material)
+                .with_receives_shadows(flags.receives_shadows);
+            let instance = InstanceData::from_matrix(matrix).with_material(materials.insert_uniform(uniform));
+            if flags.casts_shadows
+                && let Some(bounds) = self.meshes.get(mesh).and_then(|m| m.bounds)
+            {
+                let bounds = bounds.transformed(matrix);
+                caster_bounds = Some(caster_bounds.map_or(bounds, |b| b.union(&bounds)));
+                casters.push((mesh, instance));
+            }
+            (DrawKey { mesh, textures }, instance)
+        }));
+        if materials.overflow() > 0 {
+            console_warn!(
+                "Materials: {} instances exceeded the limit of {} distinct materials and use the default material",
+                materials.overflow(),
+                MAX_MATERIALS
+            );
+        }
+        let casters = InstanceBatches::build(casters);
+        for batch in batches.batches() {
+            self.textures.prepare(&self.ctx.device, batch.key.textures);
+        }
+        self.scene.upload_instances(&self.ctx, batches.instances());
+        self.scene.upload_materials(&self.ctx, materials.uniforms());
+        let lights = LightSet::gather(world, self.ambient, self.camera.position(), self.scene.light_capacity());
+        self.scene.upload_lights(&self.ctx, &lights);
+        let shadows = ShadowFrame::build(lights.lights(), &self.camera, caster_bounds.as_ref());
+        self.shadow.upload_instances(&self.ctx, casters.instances());
+        self.shadow.upload_frame(&self.ctx, &shadows);
 
         let output = self
             .ctx
@@ -252,6 +354,11 @@ impl Renderer {
         let view = output
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
+        // MSAAではマルチサンプルのターゲットに描画してサーフェスへ解決する
+        let (color_view, resolve_target) = match &self.msaa_target {
+            Some((_, msaa_view)) => (msaa_view, Some(&view)),
+            None => (&view, None),
+        };
 
         // Camera uniform更新
         self.ctx.queue.write_buffer(
@@ -260,130 +367,94 @@ impl Renderer {
             bytemuck::bytes_of(&self.camera.uniform()),
         );
 
-        // 各Entityを個別のコマンドで描画
-        let mut commands = Vec::new();
-
-        for (i, model_uniform) in transforms.iter().enumerate() {
-            // Model uniform更新
-            self.ctx.queue
-                .write_buffer(&self.scene.model_buffer, 0, bytemuck::bytes_of(model_uniform));
+        let mut encoder = self
+            .ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
 
-            let mut encoder = self
-                .ctx
-                .device
-                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                    label: Some(&format!("Render Encoder {}", i)),
-                });
+        // シャドウマップ描画
+        self.shadow.render(&mut encoder, &shadows, &casters, &self.meshes);
 
-            {
-                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some(&format!("Render Pass {}", i)),
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &view,
-                        resolve_target: None,
-                        ops: wgpu::Operations {
-                            // 最初のパスのみClear、それ以降はLoad
-                            load: if i == 0 {
-                                wgpu::LoadOp::Clear(wgpu::Color {
-                                    r: 0.1,
-                                    g: 0.2,
-                                    b: 0.3,
-                                    a: 1.0,
-                                })
-                            } else {
-                                wgpu::LoadOp::Load
-                            },
-                            store: wgpu::StoreOp::Store,
-                        },
-                        depth_slice: None,
-                    })],
-                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                        view: &self.depth_view,
-                        depth_ops: Some(wgpu::Operations {
-                            load: if i == 0 {
-                                wgpu::LoadOp::Clear(1.0)
-                            } else {
-                                wgpu::LoadOp::Load
-                            },
-                            store: wgpu::StoreOp::Store,
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: color_view,
+                    resolve_target,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.1,
+                            g: 0.2,
+                            b: 0.3,
+                            a: 1.0,
                         }),
-                        stencil_ops: None,
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
                     }),
-                    occlusion_query_set: None,
-                    timestamp_writes: None,
-                });
+                    stencil_ops: None,
+                }),
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
 
+            // Entityが0の場合は背景のみ描画
+            if !batches.is_empty() {
                 render_pass.set_pipeline(&self.scene.pipeline);
                 render_pass.set_bind_group(0, &self.scene.camera_bind_group, &[]);
-                render_pass.set_bind_group(1, &self.scene.model_bind_group, &[]);
-                render_pass.set_vertex_buffer(0, self.scene.vertex_buffer.slice(..));
-                render_pass.set_index_buffer(self.scene.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-                render_pass.draw_indexed(0..self.scene.num_indices, 0, 0..1);
-            }
-
-            commands.push(encoder.finish());
-            // 各コマンドを個別にsubmitしてバッファ更新を反映
-            self.ctx.queue.submit(commands.drain(..));
-        }
-
-        // Entityが0の場合は背景のみ描画
-        if transforms.is_empty() {
-            let mut encoder = self
-                .ctx
-                .device
-                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                    label: Some("Clear Encoder"),
-                });
-
-            {
-                let _render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some("Clear Pass"),
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &view,
-                        resolve_target: None,
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color {
-                                r: 0.1,
-                                g: 0.2,
-                                b: 0.3,
-                                a: 1.0,
-                            }),
-                            store: wgpu::StoreOp::Store,
-                        },
-                        depth_slice: None,
-                    })],
-                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                        view: &self.depth_view,
-                        depth_ops: Some(wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(1.0),
-                            store: wgpu::StoreOp::Store,
-                        }),
-                        stencil_ops: None,
-                    }),
-                    occlusion_query_set: None,
-                    timestamp_writes: None,
-                });
+                render_pass.set_bind_group(1, &self.scene.material_bind_group, &[]);
+                render_pass.set_bind_group(3, &self.scene.light_bind_group, &[]);
+                render_pass.set_vertex_buffer(1, self.scene.instance_buffer.slice(..));
+
+                for batch in batches.batches() {
+                    // 解放済み・三角形のないメッシュを参照するEntityは描画しない（空のバッファはスライスできない）
+                    let Some(mesh) = self.meshes.get(batch.key.mesh).filter(|m| m.num_indices > 0) else {
+                        continue;
+                    };
+                    let Some(textures) = self.textures.bind_group(&batch.key.textures) else {
+                        continue;
+                    };
+                    render_pass.set_bind_group(2, textures, &[]);
+                    render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                    render_pass.set_vertex_buffer(2, mesh.uv_buffer.slice(..));
+                    render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                    render_pass.draw_indexed(0..mesh.num_indices, 0, batch.instances.clone());
+                }
             }
-
-            self.ctx.queue.submit(std::iter::once(encoder.finish()));
         }
 
-        // Gizmo描画
+        // Gizmo描画（同じエンコーダに記録）
         if self.gizmo_state.visible {
-            self.render_gizmo(&view)?;
+            self.render_gizmo(&mut encoder, color_view, resolve_target);
         }
 
+        self.ctx.queue.submit(std::iter::once(encoder.finish()));
         output.present();
 
         Ok(())
     }
 
     /// Gizmoを描画
-    fn render_gizmo(&self, view: &wgpu::TextureView) -> Result<(), JsValue> {
+    ///
+    /// MSAAではシーンと同じマルチサンプルのターゲットに重ねて描画し、再度解決する
+    fn render_gizmo(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        resolve_target: Option<&wgpu::TextureView>,
+    ) {
         // Gizmoの頂点を生成
         let vertices = self.build_gizmo_vertices();
         if vertices.is_empty() {
-            return Ok(());
+            return;
         }
 
         // 一時的な頂点バッファを作成
@@ -405,39 +476,26 @@ impl Renderer {
             bytemuck::bytes_of(&gizmo_uniform),
         );
 
-        let mut encoder = self
-            .ctx
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Gizmo Encoder"),
-            });
-
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Gizmo Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load, // 既存の描画を保持
-                        store: wgpu::StoreOp::Store,
-                    },
-                    depth_slice: None,
-                })],
-                depth_stencil_attachment: None, // 深度テストなし
-                occlusion_query_set: None,
-                timestamp_writes: None,
-            });
-
-            render_pass.set_pipeline(&self.gizmo.pipeline);
-            render_pass.set_bind_group(0, &self.gizmo.bind_group, &[]);
-            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-            render_pass.draw(0..vertices.len() as u32, 0..1);
-        }
-
-        self.ctx.queue.submit(std::iter::once(encoder.finish()));
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Gizmo Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load, // 既存の描画を保持
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None, // 深度テストなし
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
 
-        Ok(())
+        render_pass.set_pipeline(&self.gizmo.pipeline);
+        render_pass.set_bind_group(0, &self.gizmo.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.draw(0..vertices.len() as u32, 0..1);
     }
 
     /// 現在のモードに応じたGizmo頂点を生成
@@ -0,0 +1,40 @@
+//! MSAAモジュール
+//!
+//! マルチサンプルのカラーターゲットを作成する。
+//! シーンとGizmoはマルチサンプルのターゲットに描画し、サーフェスへ解決（resolve）する
+
+/// 使えるサンプル数（WebGPU・WebGL2の両方で保証されるのは1と4）
+pub const SAMPLE_COUNTS: [u32; 2] = [1, 4];
+
+/// 既定のサンプル数
+pub const DEFAULT_SAMPLE_COUNT: u32 = 4;
+
+/// マルチサンプルのカラーターゲットを作成
+///
+/// 描画にのみ使うため、WebGL2ではレンダーバッファになる
+pub fn create_color_texture(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    sample_count: u32,
+) -> (wgpu::Texture, wgpu::TextureView) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("MSAA Color Texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    (texture, view)
+}
@@ -6,26 +6,45 @@ use wgpu::util::DeviceExt;
 
 use super::depth::DEPTH_FORMAT;
 use super::gpu_context::GpuContext;
+use super::shadow_pipeline::ShadowPipeline;
 use crate::shaders;
 
-use engine_core::ModelUniform;
-use engine_renderer::{Mesh, Vertex};
+use engine_renderer::{
+    light, InstanceData, LightSet, MaterialUniform, Mesh, Vertex, MAX_LIGHTS, MAX_MATERIALS, MAX_UNIFORM_LIGHTS,
+};
+
+/// インスタンスバッファの初期容量
+const INITIAL_INSTANCE_CAPACITY: usize = 256;
 
 /// シーン描画パイプライン
 pub struct ScenePipeline {
     pub pipeline: wgpu::RenderPipeline,
+    /// MSAA設定の変更時にパイプラインを作り直すために保持する
+    pipeline_layout: wgpu::PipelineLayout,
+    shader: wgpu::ShaderModule,
     pub camera_buffer: wgpu::Buffer,
     pub camera_bind_group: wgpu::BindGroup,
-    pub model_buffer: wgpu::Buffer,
-    pub model_bind_group: wgpu::BindGroup,
-    pub vertex_buffer: wgpu::Buffer,
-    pub index_buffer: wgpu::Buffer,
-    pub num_indices: u32,
+    pub material_buffer: wgpu::Buffer,
+    pub material_bind_group: wgpu::BindGroup,
+    pub light_buffer: wgpu::Buffer,
+    pub light_bind_group: wgpu::BindGroup,
+    /// シェーダーのライト配列の長さ（ストレージバッファかUniformバッファかで異なる）
+    light_capacity: usize,
+    pub instance_buffer: wgpu::Buffer,
+    instance_capacity: usize,
 }
 
 impl ScenePipeline {
     /// 新しいシーンパイプラインを作成
-    pub fn new(ctx: &GpuContext, camera_uniform: &[u8]) -> Self {
+    ///
+    /// `texture_layout` はマテリアルのテクスチャ（group 2）のレイアウト。
+    /// `shadow` のシャドウマップはライトと同じgroup 3で参照する
+    pub fn new(
+        ctx: &GpuContext,
+        camera_uniform: &[u8],
+        texture_layout: &wgpu::BindGroupLayout,
+        shadow: &ShadowPipeline,
+    ) -> Self {
         // Camera Uniform Buffer
         let camera_buffer = ctx.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Camera Buffer"),
@@ -39,7 +58,8 @@ impl ScenePipeline {
                 label: Some("Camera Bind Group Layout"),
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    // フラグメントシェーダーはカメラ位置をライティングに使う
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
@@ -59,21 +79,20 @@ impl ScenePipeline {
             }],
         });
 
-        // Model Uniform Buffer (for per-entity transform)
-        let model_uniform = ModelUniform::identity();
-        let model_buffer = ctx.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Model Buffer"),
-            contents: bytemuck::bytes_of(&model_uniform),
+        // Material Uniform Buffer（フレームごとのマテリアルテーブル）
+        let material_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Material Buffer"),
+            size: (MAX_MATERIALS * std::mem::size_of::<MaterialUniform>()) as wgpu::BufferAddress,
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
         });
 
-        // Model Bind Group Layout
-        let model_bind_group_layout =
+        let material_bind_group_layout =
             ctx.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Model Bind Group Layout"),
+                label: Some("Material Bind Group Layout"),
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
@@ -83,98 +102,244 @@ impl ScenePipeline {
                 }],
             });
 
-        // Model Bind Group
-        let model_bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Model Bind Group"),
-            layout: &model_bind_group_layout,
+        let material_bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Material Bind Group"),
+            layout: &material_bind_group_layout,
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
-                resource: model_buffer.as_entire_binding(),
+                resource: material_buffer.as_entire_binding(),
             }],
         });
 
-        // Cube Mesh 作成
-        let cube = Mesh::cube();
-        let vertex_buffer = ctx.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Cube Vertex Buffer"),
-            contents: bytemuck::cast_slice(&cube.vertices),
-            usage: wgpu::BufferUsages::VERTEX,
+        // Light Buffer（フレームごとのライト）
+        // WebGL2ではストレージバッファが使えないため、上限を下げてUniformバッファに置く
+        let storage_lights = ctx.supports_storage_buffers();
+        let light_capacity = if storage_lights { MAX_LIGHTS } else { MAX_UNIFORM_LIGHTS };
+        let (light_usage, light_binding) = if storage_lights {
+            (wgpu::BufferUsages::STORAGE, wgpu::BufferBindingType::Storage { read_only: true })
+        } else {
+            (wgpu::BufferUsages::UNIFORM, wgpu::BufferBindingType::Uniform)
+        };
+        let light_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Light Buffer"),
+            size: light::light_buffer_size(light_capacity) as wgpu::BufferAddress,
+            usage: light_usage | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
         });
-        let index_buffer = ctx.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Cube Index Buffer"),
-            contents: bytemuck::cast_slice(&cube.indices),
-            usage: wgpu::BufferUsages::INDEX,
+
+        let light_bind_group_layout =
+            ctx.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Light Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: light_binding,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    // シャドウマップの変換行列
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Depth,
+                            view_dimension: wgpu::TextureViewDimension::D2Array,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                        count: None,
+                    },
+                ],
+            });
+
+        let light_bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Light Bind Group"),
+            layout: &light_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: light_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: shadow.uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&shadow.array_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::Sampler(&shadow.sampler),
+                },
+            ],
         });
-        let num_indices = cube.index_count() as u32;
+
+        // Instance Buffer（Entityごとのモデル行列）
+        let instance_buffer = create_instance_buffer(&ctx.device, INITIAL_INSTANCE_CAPACITY);
 
         // シェーダーモジュール作成
         let shader = ctx.device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(shaders::MAIN_SHADER.into()),
+            source: wgpu::ShaderSource::Wgsl(shaders::main_shader(storage_lights).into()),
         });
 
         // Render Pipeline Layout (with bind groups)
         let render_pipeline_layout =
             ctx.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[&camera_bind_group_layout, &model_bind_group_layout],
+                bind_group_layouts: &[
+                    &camera_bind_group_layout,
+                    &material_bind_group_layout,
+                    texture_layout,
+                    &light_bind_group_layout,
+                ],
                 push_constant_ranges: &[],
             });
 
         // Render Pipeline
-        let pipeline = ctx.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[Vertex::desc()],
-                compilation_options: Default::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: ctx.config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: Default::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: DEPTH_FORMAT,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-            cache: None,
-        });
+        let pipeline = create_pipeline(ctx, &render_pipeline_layout, &shader);
 
         Self {
             pipeline,
+            pipeline_layout: render_pipeline_layout,
+            shader,
             camera_buffer,
             camera_bind_group,
-            model_buffer,
-            model_bind_group,
-            vertex_buffer,
-            index_buffer,
-            num_indices,
+            material_buffer,
+            material_bind_group,
+            light_buffer,
+            light_bind_group,
+            light_capacity,
+            instance_buffer,
+            instance_capacity: INITIAL_INSTANCE_CAPACITY,
+        }
+    }
+
+    /// コンテキストのサンプル数でパイプラインを作り直す
+    pub fn rebuild_pipeline(&mut self, ctx: &GpuContext) {
+        self.pipeline = create_pipeline(ctx, &self.pipeline_layout, &self.shader);
+    }
+
+    /// 全インスタンスをアップロード
+    ///
+    /// 容量が足りなければ2倍ずつ拡張してバッファを作り直す
+    pub fn upload_instances(&mut self, ctx: &GpuContext, instances: &[InstanceData]) {
+        if instances.len() > self.instance_capacity {
+            let mut capacity = self.instance_capacity.max(1);
+            while capacity < instances.len() {
+                capacity *= 2;
+            }
+            self.instance_buffer = create_instance_buffer(&ctx.device, capacity);
+            self.instance_capacity = capacity;
+        }
+
+        if !instances.is_empty() {
+            ctx.queue
+                .write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(instances));
+        }
+    }
+
+    /// マテリアルテーブルをアップロード（上限を超えた分は切り捨て）
+    pub fn upload_materials(&self, ctx: &GpuContext, materials: &[MaterialUniform]) {
+        let materials = &materials[..materials.len().min(MAX_MATERIALS)];
+        ctx.queue
+            .write_buffer(&self.material_buffer, 0, bytemuck::cast_slice(materials));
+    }
+
+    /// フレームのライトをアップロード
+    pub fn upload_lights(&self, ctx: &GpuContext, lights: &LightSet) {
+        ctx.queue
+            .write_buffer(&self.light_buffer, 0, bytemuck::bytes_of(lights.header()));
+        if !lights.lights().is_empty() {
+            ctx.queue.write_buffer(
+                &self.light_buffer,
+                std::mem::size_of_val(lights.header()) as wgpu::BufferAddress,
+                bytemuck::cast_slice(lights.lights()),
+            );
         }
     }
+
+    /// 1フレームで使えるライト数
+    pub fn light_capacity(&self) -> usize {
+        self.light_capacity
+    }
+
+    /// インスタンスバッファの容量
+    #[allow(dead_code)]
+    pub fn instance_capacity(&self) -> usize {
+        self.instance_capacity
+    }
+}
+
+/// 描画パイプラインを作成（MSAAのサンプル数はコンテキストに従う）
+fn create_pipeline(ctx: &GpuContext, layout: &wgpu::PipelineLayout, shader: &wgpu::ShaderModule) -> wgpu::RenderPipeline {
+    ctx.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            buffers: &[Vertex::desc(), InstanceData::desc(), Mesh::uv_desc()],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: ctx.config.format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: ctx.multisample_state(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+/// 指定容量のインスタンスバッファを作成
+pub(super) fn create_instance_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Instance Buffer"),
+        size: (capacity * std::mem::size_of::<InstanceData>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
 }
@@ -0,0 +1,234 @@
+//! シャドウパイプラインモジュール
+//!
+//! 影を落とすEntityをライトから見た深度だけでシャドウマップに描画する。
+//! シャドウマップの配列とUniformはシーンパイプラインのgroup 3で参照される
+
+use super::depth::{self, DEPTH_FORMAT};
+use super::gpu_context::GpuContext;
+use super::mesh_store::MeshStore;
+use super::scene_pipeline::create_instance_buffer;
+use crate::shaders;
+
+use engine_core::MeshHandle;
+use engine_renderer::{
+    InstanceBatches, InstanceData, ShadowFrame, ShadowUniform, Vertex, MAX_SHADOW_MAPS, SHADOW_MAP_SIZE,
+};
+
+/// インスタンスバッファの初期容量
+const INITIAL_INSTANCE_CAPACITY: usize = 256;
+/// パスごとの変換行列の間隔（動的オフセットのアライメント）
+const PASS_STRIDE: u64 = 256;
+
+/// シャドウパイプライン
+pub struct ShadowPipeline {
+    pub pipeline: wgpu::RenderPipeline,
+    /// パスごとの変換行列（動的オフセットで切り替える）
+    pass_buffer: wgpu::Buffer,
+    pass_bind_group: wgpu::BindGroup,
+    /// シーン描画で参照するUniform
+    pub uniform_buffer: wgpu::Buffer,
+    #[allow(dead_code)]
+    texture: wgpu::Texture,
+    layer_views: Vec<wgpu::TextureView>,
+    /// シーン描画で参照する配列ビュー
+    pub array_view: wgpu::TextureView,
+    /// 深度比較サンプラー（線形補間でハードウェアPCF）
+    pub sampler: wgpu::Sampler,
+    pub instance_buffer: wgpu::Buffer,
+    instance_capacity: usize,
+}
+
+impl ShadowPipeline {
+    /// 新しいシャドウパイプラインを作成
+    pub fn new(ctx: &GpuContext) -> Self {
+        let pass_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shadow Pass Buffer"),
+            size: PASS_STRIDE * MAX_SHADOW_MAPS as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let pass_bind_group_layout =
+            ctx.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Shadow Pass Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(64),
+                    },
+                    count: None,
+                }],
+            });
+
+        let pass_bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shadow Pass Bind Group"),
+            layout: &pass_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &pass_buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(64),
+                }),
+            }],
+        });
+
+        let uniform_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shadow Uniform Buffer"),
+            size: std::mem::size_of::<ShadowUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let (texture, layer_views, array_view) =
+            depth::create_array_texture(&ctx.device, SHADOW_MAP_SIZE, MAX_SHADOW_MAPS as u32);
+
+        let sampler = ctx.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+
+        let shader = ctx.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shadow Shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::SHADOW_SHADER.into()),
+        });
+
+        let pipeline_layout = ctx.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shadow Pipeline Layout"),
+            bind_group_layouts: &[&pass_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        // 深度のみ（フラグメントシェーダーなし）
+        let pipeline = ctx.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Shadow Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex::desc(), InstanceData::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: None,
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                // 片面の平面も影を落とすようにカリングしない
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            pass_buffer,
+            pass_bind_group,
+            uniform_buffer,
+            texture,
+            layer_views,
+            array_view,
+            sampler,
+            instance_buffer: create_instance_buffer(&ctx.device, INITIAL_INSTANCE_CAPACITY),
+            instance_capacity: INITIAL_INSTANCE_CAPACITY,
+        }
+    }
+
+    /// 影を落とすインスタンスをアップロード
+    ///
+    /// 容量が足りなければ2倍ずつ拡張してバッファを作り直す
+    pub fn upload_instances(&mut self, ctx: &GpuContext, instances: &[InstanceData]) {
+        if instances.len() > self.instance_capacity {
+            let mut capacity = self.instance_capacity.max(1);
+            while capacity < instances.len() {
+                capacity *= 2;
+            }
+            self.instance_buffer = create_instance_buffer(&ctx.device, capacity);
+            self.instance_capacity = capacity;
+        }
+
+        if !instances.is_empty() {
+            ctx.queue
+                .write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(instances));
+        }
+    }
+
+    /// フレームのシャドウマップの変換行列をアップロード
+    pub fn upload_frame(&self, ctx: &GpuContext, frame: &ShadowFrame) {
+        ctx.queue
+            .write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(frame.uniform()));
+        for (layer, view_proj) in frame.passes() {
+            ctx.queue.write_buffer(
+                &self.pass_buffer,
+                layer as u64 * PASS_STRIDE,
+                bytemuck::bytes_of(&view_proj.to_cols_array_2d()),
+            );
+        }
+    }
+
+    /// 割り当てられたシャドウマップを描画
+    ///
+    /// 影を落とすEntityがなくてもシャドウマップはクリアする
+    pub fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        frame: &ShadowFrame,
+        casters: &InstanceBatches<MeshHandle>,
+        meshes: &MeshStore,
+    ) {
+        for (layer, _) in frame.passes() {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Shadow Pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.layer_views[layer],
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            if casters.is_empty() {
+                continue;
+            }
+
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(0, &self.pass_bind_group, &[(layer as u64 * PASS_STRIDE) as u32]);
+            render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+            for batch in casters.batches() {
+                // 三角形のないメッシュは空のバッファをスライスできないので飛ばす
+                let Some(mesh) = meshes.get(batch.key).filter(|m| m.num_indices > 0) else {
+                    continue;
+                };
+                render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..mesh.num_indices, 0, batch.instances.clone());
+            }
+        }
+    }
+}
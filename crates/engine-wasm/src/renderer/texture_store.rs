@@ -0,0 +1,197 @@
+//! テクスチャストアモジュール
+//!
+//! テクスチャをGPUにアップロードしてハンドルの参照カウントで管理し、
+//! マテリアルが参照するテクスチャの組ごとにバインドグループをキャッシュする
+
+use std::collections::HashMap;
+
+use engine_core::TextureHandle;
+use engine_renderer::{AssetStore, ColorSpace, SamplerDesc, TextureData};
+
+/// マテリアルが参照するテクスチャの組（albedo, normal, roughness）
+pub type TextureSet = [Option<TextureHandle>; 3];
+
+/// GPUにアップロード済みのテクスチャ
+pub struct GpuTexture {
+    #[allow(dead_code)]
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+}
+
+impl GpuTexture {
+    /// テクスチャを全ミップレベルごとアップロード
+    fn upload(device: &wgpu::Device, queue: &wgpu::Queue, data: &TextureData, sampler: &SamplerDesc) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Texture"),
+            size: wgpu::Extent3d {
+                width: data.width(),
+                height: data.height(),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: data.mip_level_count(),
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: data.format(),
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        for (level, mip) in data.mips.iter().enumerate() {
+            queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &texture,
+                    mip_level: level as u32,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &mip.data,
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(data.bytes_per_row(level)),
+                    rows_per_image: Some(mip.height),
+                },
+                wgpu::Extent3d {
+                    width: mip.width,
+                    height: mip.height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&sampler.to_wgpu());
+        Self { texture, view, sampler }
+    }
+}
+
+/// テクスチャストア
+///
+/// 参照のないスロットや解放済みのハンドルには既定のテクスチャ
+/// （albedo・roughnessは白、normalは平坦な法線）を使う
+pub struct TextureStore {
+    textures: AssetStore<GpuTexture>,
+    /// albedo, normal, roughness スロットの既定テクスチャ
+    defaults: [GpuTexture; 3],
+    layout: wgpu::BindGroupLayout,
+    bind_groups: HashMap<TextureSet, wgpu::BindGroup>,
+}
+
+impl TextureStore {
+    /// 既定テクスチャとバインドグループレイアウトを持つストアを作成
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let sampler = SamplerDesc::default();
+        let white = TextureData::solid([255; 4], ColorSpace::Srgb);
+        let flat_normal = TextureData::solid([128, 128, 255, 255], ColorSpace::Linear);
+        let white_linear = TextureData::solid([255; 4], ColorSpace::Linear);
+        let defaults = [&white, &flat_normal, &white_linear]
+            .map(|data| GpuTexture::upload(device, queue, data, &sampler));
+
+        // スロットごとに (テクスチャ, サンプラー) の2バインディング
+        let entries: Vec<wgpu::BindGroupLayoutEntry> = (0..3)
+            .flat_map(|slot| {
+                [
+                    wgpu::BindGroupLayoutEntry {
+                        binding: slot * 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: slot * 2 + 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ]
+            })
+            .collect();
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Texture Bind Group Layout"),
+            entries: &entries,
+        });
+
+        Self {
+            textures: AssetStore::new(),
+            defaults,
+            layout,
+            bind_groups: HashMap::new(),
+        }
+    }
+
+    /// バインドグループレイアウト（シーンパイプラインのgroup 2）
+    pub fn layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    /// テクスチャをアップロードして登録（参照カウント1）
+    pub fn create(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        data: &TextureData,
+        sampler: &SamplerDesc,
+    ) -> TextureHandle {
+        TextureHandle::new(self.textures.insert(GpuTexture::upload(device, queue, data, sampler)))
+    }
+
+    /// 参照を追加
+    pub fn retain(&mut self, handle: TextureHandle) -> bool {
+        self.textures.retain(handle.id())
+    }
+
+    /// 参照を解放（カウントが0になるとテクスチャと関連するバインドグループも破棄される）
+    pub fn release(&mut self, handle: TextureHandle) {
+        if self.textures.release(handle.id()).is_some() {
+            self.bind_groups.retain(|set, _| !set.contains(&Some(handle)));
+        }
+    }
+
+    /// テクスチャが登録されているか
+    pub fn contains(&self, handle: TextureHandle) -> bool {
+        self.textures.contains(handle.id())
+    }
+
+    /// テクスチャの組のバインドグループを用意する（描画パスの前に呼ぶ）
+    pub fn prepare(&mut self, device: &wgpu::Device, set: TextureSet) {
+        if self.bind_groups.contains_key(&set) {
+            return;
+        }
+        let slots: [&GpuTexture; 3] = std::array::from_fn(|slot| {
+            set[slot]
+                .and_then(|handle| self.textures.get(handle.id()))
+                .unwrap_or(&self.defaults[slot])
+        });
+        let entries: Vec<wgpu::BindGroupEntry> = slots
+            .iter()
+            .enumerate()
+            .flat_map(|(slot, texture)| {
+                [
+                    wgpu::BindGroupEntry {
+                        binding: slot as u32 * 2,
+                        resource: wgpu::BindingResource::TextureView(&texture.view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: slot as u32 * 2 + 1,
+                        resource: wgpu::BindingResource::Sampler(&texture.sampler),
+                    },
+                ]
+            })
+            .collect();
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Texture Bind Group"),
+            layout: &self.layout,
+            entries: &entries,
+        });
+        self.bind_groups.insert(set, bind_group);
+    }
+
+    /// 用意済みのバインドグループを取得
+    pub fn bind_group(&self, set: &TextureSet) -> Option<&wgpu::BindGroup> {
+        self.bind_groups.get(set)
+    }
+}
@@ -1,44 +1,332 @@
 struct CameraUniform {
     view_proj: mat4x4<f32>,
-}
-
-struct ModelUniform {
-    model: mat4x4<f32>,
+    position: vec4<f32>,
 }
 
 @group(0) @binding(0)
 var<uniform> camera: CameraUniform;
 
+// MaterialUniform（engine_renderer::material と同じレイアウト）
+struct Material {
+    base_color: vec4<f32>,
+    emissive: vec3<f32>,
+    metallic: f32,
+    roughness: f32,
+    unlit: f32,
+    normal_map: f32,
+    receives_shadows: f32,
+}
+
+const MAX_MATERIALS: u32 = 256u;
+
 @group(1) @binding(0)
-var<uniform> model: ModelUniform;
+var<uniform> materials: array<Material, MAX_MATERIALS>;
+
+// マテリアルのテクスチャ（テクスチャのないスロットは白または平坦な法線）
+@group(2) @binding(0) var albedo_texture: texture_2d<f32>;
+@group(2) @binding(1) var albedo_sampler: sampler;
+@group(2) @binding(2) var normal_texture: texture_2d<f32>;
+@group(2) @binding(3) var normal_sampler: sampler;
+// glTFと同じくG=粗さ、B=金属度
+@group(2) @binding(4) var roughness_texture: texture_2d<f32>;
+@group(2) @binding(5) var roughness_sampler: sampler;
+
+// ライトバッファ（engine_renderer::light と同じレイアウト）
+struct Light {
+    position: vec3<f32>,
+    range: f32,
+    direction: vec3<f32>,
+    kind: u32,
+    color: vec3<f32>,
+    intensity: f32,
+    inner_cos: f32,
+    outer_cos: f32,
+    shadow_bias: f32,
+    shadow_normal_bias: f32,
+    // シャドウマップの番号（-1なら影なし）
+    shadow_map: i32,
+    _padding0: u32,
+    _padding1: u32,
+    _padding2: u32,
+}
+
+// WebGPUではストレージバッファ（MAX_LIGHTS = 256）に置き換えて使う
+const MAX_LIGHTS: u32 = 64u;
+
+struct Lights {
+    ambient: vec4<f32>,
+    count: u32,
+    _padding0: u32,
+    _padding1: u32,
+    _padding2: u32,
+    lights: array<Light, MAX_LIGHTS>,
+}
+
+@group(3) @binding(0)
+var<uniform> lights: Lights;
+
+// シャドウマップ（engine_renderer::shadow と同じレイアウト）
+struct ShadowMap {
+    view_proj: mat4x4<f32>,
+    texel_size: f32,
+    perspective: f32,
+    _padding0: f32,
+    _padding1: f32,
+}
+
+const MAX_SHADOW_MAPS: u32 = 7u;
+
+struct Shadows {
+    maps: array<ShadowMap, MAX_SHADOW_MAPS>,
+    cascade_splits: vec4<f32>,
+    // xyz: カメラの視線方向、w: カスケード数
+    camera_forward: vec4<f32>,
+}
+
+@group(3) @binding(1)
+var<uniform> shadows: Shadows;
+@group(3) @binding(2) var shadow_maps: texture_depth_2d_array;
+@group(3) @binding(3) var shadow_sampler: sampler_comparison;
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
     @location(2) color: vec3<f32>,
+    // 別ストリームのUV（engine_renderer::UV_LOCATION）
+    @location(8) uv: vec2<f32>,
+}
+
+// インスタンスごとのモデル行列（列ベクトル）とマテリアル番号
+struct InstanceInput {
+    @location(3) model_0: vec4<f32>,
+    @location(4) model_1: vec4<f32>,
+    @location(5) model_2: vec4<f32>,
+    @location(6) model_3: vec4<f32>,
+    @location(7) material: u32,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) color: vec3<f32>,
     @location(1) normal: vec3<f32>,
+    @location(2) world_position: vec3<f32>,
+    @location(3) @interpolate(flat) material: u32,
+    @location(4) uv: vec2<f32>,
 }
 
 @vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
+fn vs_main(in: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    let world_position = model * vec4<f32>(in.position, 1.0);
     var out: VertexOutput;
-    out.clip_position = camera.view_proj * model.model * vec4<f32>(in.position, 1.0);
+    out.clip_position = camera.view_proj * world_position;
     out.color = in.color;
     // Transform normal to world space (simplified, assumes no non-uniform scale)
-    out.normal = (model.model * vec4<f32>(in.normal, 0.0)).xyz;
+    out.normal = (model * vec4<f32>(in.normal, 0.0)).xyz;
+    out.world_position = world_position.xyz;
+    out.material = instance.material;
+    out.uv = in.uv;
     return out;
 }
 
+// ============================================================================
+// Metallic-Roughness BRDF（Cook-Torrance: GGX / Smith-Schlick / Fresnel-Schlick）
+// ============================================================================
+
+const PI: f32 = 3.14159265;
+
+// engine_renderer::light の LIGHT_* と同じ値
+const LIGHT_DIRECTIONAL: u32 = 0u;
+const LIGHT_SPOT: u32 = 2u;
+
+fn distribution_ggx(n_dot_h: f32, roughness: f32) -> f32 {
+    let a = roughness * roughness;
+    let a2 = a * a;
+    let d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / max(PI * d * d, 1e-6);
+}
+
+fn geometry_schlick(n_dot_x: f32, k: f32) -> f32 {
+    return n_dot_x / (n_dot_x * (1.0 - k) + k);
+}
+
+fn geometry_smith(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let r = roughness + 1.0;
+    let k = r * r / 8.0;
+    return geometry_schlick(n_dot_v, k) * geometry_schlick(n_dot_l, k);
+}
+
+fn fresnel_schlick(cos_theta: f32, f0: vec3<f32>) -> vec3<f32> {
+    return f0 + (vec3<f32>(1.0) - f0) * pow(1.0 - cos_theta, 5.0);
+}
+
+/// 1つの光源による反射光
+fn brdf(
+    n: vec3<f32>,
+    v: vec3<f32>,
+    l: vec3<f32>,
+    radiance: vec3<f32>,
+    albedo: vec3<f32>,
+    metallic: f32,
+    roughness: f32,
+) -> vec3<f32> {
+    let h = normalize(v + l);
+    let n_dot_l = max(dot(n, l), 0.0);
+    let n_dot_v = max(dot(n, v), 1e-4);
+    let n_dot_h = max(dot(n, h), 0.0);
+    let h_dot_v = max(dot(h, v), 0.0);
+
+    let f0 = mix(vec3<f32>(0.04), albedo, metallic);
+    let f = fresnel_schlick(h_dot_v, f0);
+    let d = distribution_ggx(n_dot_h, roughness);
+    let g = geometry_smith(n_dot_v, n_dot_l, roughness);
+    let specular = d * g * f / max(4.0 * n_dot_v * n_dot_l, 1e-4);
+
+    // 金属は拡散反射を持たない
+    let k_d = (vec3<f32>(1.0) - f) * (1.0 - metallic);
+    return (k_d * albedo / PI + specular) * radiance * n_dot_l;
+}
+
+/// 距離による減衰（逆2乗、rangeで0になるよう滑らかに打ち切る）
+fn range_attenuation(distance: f32, range: f32) -> f32 {
+    let ratio = distance / range;
+    let falloff = clamp(1.0 - ratio * ratio * ratio * ratio, 0.0, 1.0);
+    return falloff * falloff / max(distance * distance, 1e-4);
+}
+
+/// 1つのライトによる反射光
+fn shade_light(
+    light: Light,
+    position: vec3<f32>,
+    n: vec3<f32>,
+    v: vec3<f32>,
+    albedo: vec3<f32>,
+    metallic: f32,
+    roughness: f32,
+) -> vec3<f32> {
+    var l = -light.direction;
+    var attenuation = 1.0;
+    if (light.kind != LIGHT_DIRECTIONAL) {
+        let to_light = light.position - position;
+        let distance = length(to_light);
+        l = to_light / max(distance, 1e-4);
+        attenuation = range_attenuation(distance, light.range);
+        if (light.kind == LIGHT_SPOT) {
+            attenuation *= smoothstep(light.outer_cos, light.inner_cos, dot(-l, light.direction));
+        }
+    }
+    let radiance = light.color * (light.intensity * attenuation);
+    return brdf(n, v, l, radiance, albedo, metallic, roughness);
+}
+
+/// 影の割合（1なら影なし）
+///
+/// 平行光源はカメラからの距離でカスケードを選ぶ。表面をバイアス分ずらしてから
+/// 3x3のPCFで深度を比較する。シャドウマップの範囲外は影なしとする
+fn shadow_factor(light: Light, position: vec3<f32>, normal: vec3<f32>) -> f32 {
+    var index = u32(light.shadow_map);
+    var l = -light.direction;
+    if (light.kind == LIGHT_DIRECTIONAL) {
+        let depth = dot(position - camera.position.xyz, shadows.camera_forward.xyz);
+        let cascades = u32(shadows.camera_forward.w);
+        var cascade = cascades;
+        for (var c = 0u; c < cascades; c++) {
+            if (cascade == cascades && depth <= shadows.cascade_splits[c]) {
+                cascade = c;
+            }
+        }
+        if (cascade == cascades) {
+            return 1.0;
+        }
+        index += cascade;
+    } else {
+        l = normalize(light.position - position);
+    }
+
+    let map = shadows.maps[index];
+    var texel = map.texel_size;
+    if (map.perspective > 0.5) {
+        texel *= distance(light.position, position);
+    }
+    let offset = normal * (light.shadow_normal_bias * texel) + l * (light.shadow_bias * texel);
+    let clip = map.view_proj * vec4<f32>(position + offset, 1.0);
+    if (clip.w <= 0.0) {
+        return 1.0;
+    }
+    let ndc = clip.xyz / clip.w;
+    if (abs(ndc.x) > 1.0 || abs(ndc.y) > 1.0 || ndc.z > 1.0) {
+        return 1.0;
+    }
+
+    let uv = ndc.xy * vec2<f32>(0.5, -0.5) + vec2<f32>(0.5);
+    let step = 1.0 / vec2<f32>(textureDimensions(shadow_maps));
+    var lit = 0.0;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let sample_uv = uv + vec2<f32>(f32(x), f32(y)) * step;
+            lit += textureSampleCompareLevel(shadow_maps, shadow_sampler, sample_uv, index, ndc.z);
+        }
+    }
+    return lit / 9.0;
+}
+
+/// 法線マップで法線を傾ける
+///
+/// 接線を頂点に持たないため、位置とUVの画面微分から接空間を求める。
+/// UVが退化している場合は元の法線を返す
+fn perturb_normal(n: vec3<f32>, position: vec3<f32>, uv: vec2<f32>, texel: vec3<f32>) -> vec3<f32> {
+    let dp1 = dpdx(position);
+    let dp2 = dpdy(position);
+    let duv1 = dpdx(uv);
+    let duv2 = dpdy(uv);
+
+    let dp2_perp = cross(dp2, n);
+    let dp1_perp = cross(n, dp1);
+    let t = dp2_perp * duv1.x + dp1_perp * duv2.x;
+    // UVのvは画像の下向きなので、緑チャンネル（上向き）に合わせて反転する
+    let b = -(dp2_perp * duv1.y + dp1_perp * duv2.y);
+    let scale = max(dot(t, t), dot(b, b));
+
+    let m = texel * 2.0 - vec3<f32>(1.0);
+    let inv = inverseSqrt(max(scale, 1e-20));
+    let perturbed = normalize(mat3x3<f32>(t * inv, b * inv, n) * m);
+    return select(n, perturbed, scale > 1e-20);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    // Simple directional lighting
-    let light_dir = normalize(vec3<f32>(1.0, 1.0, 1.0));
-    let normal = normalize(in.normal);
-    let diffuse = max(dot(normal, light_dir), 0.3);
-    return vec4<f32>(in.color * diffuse, 1.0);
+    let material = materials[min(in.material, MAX_MATERIALS - 1u)];
+
+    // テクスチャ参照と微分は分岐の前に行う（一様な制御フローが必要）
+    let albedo_texel = textureSample(albedo_texture, albedo_sampler, in.uv);
+    let normal_texel = textureSample(normal_texture, normal_sampler, in.uv).rgb;
+    let roughness_texel = textureSample(roughness_texture, roughness_sampler, in.uv);
+    let geometric_normal = normalize(in.normal);
+    let mapped_normal = perturb_normal(geometric_normal, in.world_position, in.uv, normal_texel);
+
+    let albedo = material.base_color.rgb * in.color * albedo_texel.rgb;
+
+    if (material.unlit > 0.5) {
+        return vec4<f32>(albedo + material.emissive, 1.0);
+    }
+
+    let n = select(geometric_normal, mapped_normal, material.normal_map > 0.5);
+    let v = normalize(camera.position.xyz - in.world_position);
+    // 粗さ0はハイライトが点になるため下限を設ける
+    let roughness = clamp(material.roughness * roughness_texel.g, 0.045, 1.0);
+    let metallic = material.metallic * roughness_texel.b;
+
+    var direct = vec3<f32>(0.0);
+    let count = min(lights.count, MAX_LIGHTS);
+    let receives_shadows = material.receives_shadows > 0.5;
+    for (var i = 0u; i < count; i++) {
+        let light = lights.lights[i];
+        var shadow = 1.0;
+        if (receives_shadows && light.shadow_map >= 0) {
+            shadow = shadow_factor(light, in.world_position, geometric_normal);
+        }
+        direct += shade_light(light, in.world_position, n, v, albedo, metallic, roughness) * shadow;
+    }
+    let ambient = lights.ambient.rgb * albedo * (1.0 - 0.5 * metallic);
+    return vec4<f32>(direct + ambient + material.emissive, 1.0);
 }
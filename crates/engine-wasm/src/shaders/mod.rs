@@ -5,5 +5,53 @@
 /// メインシェーダー（3Dオブジェクト描画用）
 pub const MAIN_SHADER: &str = include_str!("main.wgsl");
 
+/// ライトバッファをストレージバッファに置き換えたメインシェーダー
+///
+/// `storage_lights` がfalse（WebGL2）ならUniformバッファのまま返す
+pub fn main_shader(storage_lights: bool) -> String {
+    let mut shader = MAIN_SHADER.to_string();
+    if !storage_lights {
+        return shader;
+    }
+    for (uniform, storage) in lights_declarations(false).iter().zip(lights_declarations(true)) {
+        assert!(shader.contains(uniform.as_str()), "main.wgsl must declare '{}'", uniform);
+        shader = shader.replace(uniform.as_str(), &storage);
+    }
+    shader
+}
+
+/// ライトバッファの宣言（main.wgsl にはUniform版を書く）
+fn lights_declarations(storage_lights: bool) -> [String; 2] {
+    let (max_lights, address_space) = if storage_lights {
+        (engine_renderer::MAX_LIGHTS, "storage, read")
+    } else {
+        (engine_renderer::MAX_UNIFORM_LIGHTS, "uniform")
+    };
+    [
+        format!("const MAX_LIGHTS: u32 = {}u;", max_lights),
+        format!("var<{}> lights: Lights;", address_space),
+    ]
+}
+
+/// シャドウマップ描画シェーダー（深度のみ）
+pub const SHADOW_SHADER: &str = include_str!("shadow.wgsl");
+
 /// Gizmoシェーダー（Gizmo描画用）
 pub const GIZMO_SHADER: &str = include_str!("gizmo.wgsl");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_main_shader_lights() {
+        let uniform = main_shader(false);
+        assert!(uniform.contains("var<uniform> lights:"));
+        assert!(uniform.contains(&format!("MAX_LIGHTS: u32 = {}u", engine_renderer::MAX_UNIFORM_LIGHTS)));
+
+        let storage = main_shader(true);
+        assert!(storage.contains("var<storage, read> lights:"));
+        assert!(storage.contains("MAX_LIGHTS: u32 = 256u"));
+        assert!(!storage.contains("var<uniform> lights:"));
+    }
+}
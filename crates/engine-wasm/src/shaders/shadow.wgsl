@@ -0,0 +1,22 @@
+// シャドウマップ描画（深度のみ）
+
+struct ShadowPass {
+    view_proj: mat4x4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> pass_data: ShadowPass;
+
+// インスタンスごとのモデル行列（列ベクトル）
+struct InstanceInput {
+    @location(3) model_0: vec4<f32>,
+    @location(4) model_1: vec4<f32>,
+    @location(5) model_2: vec4<f32>,
+    @location(6) model_3: vec4<f32>,
+}
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>, instance: InstanceInput) -> @builtin(position) vec4<f32> {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    return pass_data.view_proj * model * vec4<f32>(position, 1.0);
+}
@@ -1,19 +1,65 @@
 //! クエリ購読管理モジュール
 //!
-//! クエリ結果の変更を監視し、コールバックを呼び出す
+//! Worldの変更追跡をもとにクエリ結果を差分更新し、
+//! 結果が変わった購読のコールバックを呼び出す
 
 use js_sys::Function;
-use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
-use std::hash::{Hash, Hasher};
+use wasm_bindgen::JsValue;
 
-use engine_core::{QueryDescriptor, QueryResult};
+use crate::utils::query_to_js;
+
+use engine_core::{ChangeSet, LiveQuery, QueryDelta, QueryDescriptor, QueryResult, World};
+
+/// 通知形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionMode {
+    /// 結果全体を通知
+    Full,
+    /// 前回の結果との差分を通知
+    Delta,
+}
 
 /// クエリ購読情報
 pub struct QuerySubscription {
-    pub query: QueryDescriptor,
+    pub query: LiveQuery,
     pub callback: Function,
-    pub last_result_hash: u64,
+    pub mode: SubscriptionMode,
+}
+
+impl QuerySubscription {
+    /// コールバックを呼び出す（`execute_query` と同じ形に変換する）
+    fn notify<T: serde::Serialize>(&self, value: &T) {
+        if let Ok(js_value) = query_to_js(value) {
+            let _ = self.callback.call1(&JsValue::NULL, &js_value);
+        }
+    }
+
+    /// 初回通知（差分モードでは空の結果からの差分）
+    fn notify_initial(&self) {
+        match self.mode {
+            SubscriptionMode::Full => self.notify(self.query.result()),
+            SubscriptionMode::Delta => {
+                self.notify(&QueryDelta::between(&QueryResult::empty(), self.query.result()))
+            }
+        }
+    }
+
+    /// 変更を適用し、結果が変わっていれば通知
+    fn apply(&mut self, world: &World, changes: &ChangeSet) {
+        match self.mode {
+            SubscriptionMode::Full => {
+                if self.query.apply(world, changes) {
+                    self.notify(self.query.result());
+                }
+            }
+            SubscriptionMode::Delta => {
+                if let Some(delta) = self.query.apply_delta(world, changes) {
+                    self.notify(&delta);
+                }
+            }
+        }
+    }
 }
 
 /// 購読マネージャー
@@ -30,18 +76,24 @@ impl QuerySubscriptionManager {
         }
     }
 
-    pub fn subscribe(&mut self, query: QueryDescriptor, callback: Function) -> u32 {
+    /// 購読を登録し、初回結果を通知
+    pub fn subscribe(
+        &mut self,
+        world: &World,
+        query: QueryDescriptor,
+        callback: Function,
+        mode: SubscriptionMode,
+    ) -> u32 {
         let id = self.next_id;
         self.next_id += 1;
 
-        self.subscriptions.insert(
-            id,
-            QuerySubscription {
-                query,
-                callback,
-                last_result_hash: 0,
-            },
-        );
+        let subscription = QuerySubscription {
+            query: LiveQuery::new(world, query),
+            callback,
+            mode,
+        };
+        subscription.notify_initial();
+        self.subscriptions.insert(id, subscription);
 
         id
     }
@@ -50,20 +102,14 @@ impl QuerySubscriptionManager {
         self.subscriptions.remove(&id).is_some()
     }
 
-    /// 全購読の可変参照を取得
-    #[allow(dead_code)]
-    pub fn subscriptions_mut(&mut self) -> &mut HashMap<u32, QuerySubscription> {
-        &mut self.subscriptions
-    }
-
-    /// 全購読のキーを取得
-    pub fn subscription_ids(&self) -> Vec<u32> {
-        self.subscriptions.keys().copied().collect()
-    }
-
-    /// 特定の購読を取得
-    pub fn get_mut(&mut self, id: u32) -> Option<&mut QuerySubscription> {
-        self.subscriptions.get_mut(&id)
+    /// 変更を各購読に適用し、結果が変わった購読のみ通知
+    pub fn apply_changes(&mut self, world: &World, changes: &ChangeSet) {
+        if changes.is_empty() {
+            return;
+        }
+        for subscription in self.subscriptions.values_mut() {
+            subscription.apply(world, changes);
+        }
     }
 }
 
@@ -72,11 +118,3 @@ impl Default for QuerySubscriptionManager {
         Self::new()
     }
 }
-
-/// クエリ結果のハッシュを計算
-pub fn calculate_hash(result: &QueryResult) -> u64 {
-    let json = serde_json::to_string(result).unwrap_or_default();
-    let mut hasher = DefaultHasher::new();
-    json.hash(&mut hasher);
-    hasher.finish()
-}
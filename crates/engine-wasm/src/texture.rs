@@ -0,0 +1,47 @@
+//! テクスチャ記述モジュール
+//!
+//! JSから渡されたアップロード設定をテクスチャデータとサンプラー設定に変換する。
+//! 省略した設定は既定値（sRGB、ミップマップあり、アルファ乗算なし、リピート・リニア補間）になる
+
+use serde::Deserialize;
+
+use engine_renderer::{
+    decode_texture, premultiply_alpha, ColorSpace, DecodeOptions, ImageError, SamplerDesc, TextureData,
+    TextureError,
+};
+
+/// テクスチャのアップロード設定
+///
+/// 例: `{ "color_space": "linear", "sampler": { "mag_filter": "nearest" } }`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TextureOptions {
+    pub color_space: ColorSpace,
+    /// falseならミップマップを生成しない
+    pub mipmaps: Option<bool>,
+    /// trueなら色にアルファを乗算しておく
+    pub premultiply_alpha: bool,
+    pub sampler: SamplerDesc,
+}
+
+impl TextureOptions {
+    /// RGBA8のピクセル列からテクスチャデータを作成
+    pub fn texture_data(&self, rgba: &[u8], width: u32, height: u32) -> Result<TextureData, TextureError> {
+        if self.premultiply_alpha {
+            let mut rgba = rgba.to_vec();
+            premultiply_alpha(&mut rgba, self.color_space);
+            return TextureData::from_rgba8(&rgba, width, height, self.color_space, self.mipmaps.unwrap_or(true));
+        }
+        TextureData::from_rgba8(rgba, width, height, self.color_space, self.mipmaps.unwrap_or(true))
+    }
+
+    /// 画像ファイル（PNG・JPEG・HDR）をデコードしてテクスチャデータを作成
+    pub fn decode(&self, bytes: &[u8]) -> Result<TextureData, ImageError> {
+        let options = DecodeOptions {
+            color_space: self.color_space,
+            premultiply_alpha: self.premultiply_alpha,
+            generate_mipmaps: self.mipmaps.unwrap_or(true),
+        };
+        decode_texture(bytes, &options)
+    }
+}
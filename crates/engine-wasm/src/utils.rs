@@ -3,12 +3,18 @@
 //! console_log マクロ、ヘルパー関数など
 
 use wasm_bindgen::prelude::*;
+use engine_core::{Column, ColumnarResult, QueryValidationError};
+use js_sys::{Array, Float32Array, Object, Reflect, Uint32Array};
 use engine_renderer::GizmoAxis;
+use serde::Serialize;
 
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console)]
     pub fn log(s: &str);
+
+    #[wasm_bindgen(js_namespace = console, js_name = warn)]
+    pub fn warn(s: &str);
 }
 
 /// コンソールにログ出力するマクロ
@@ -17,6 +23,26 @@ macro_rules! console_log {
 }
 pub(crate) use console_log;
 
+/// コンソールに警告を出力するマクロ
+macro_rules! console_warn {
+    ($($t:tt)*) => (crate::utils::warn(&format_args!($($t)*).to_string()))
+}
+pub(crate) use console_warn;
+
+/// 現在時刻（ミリ秒）
+/// `performance.now()` が使えない環境では `Date.now()` を使う
+pub fn now_ms() -> f64 {
+    Reflect::get(&js_sys::global(), &JsValue::from_str("performance"))
+        .ok()
+        .filter(|performance| performance.is_object())
+        .and_then(|performance| {
+            let now = Reflect::get(&performance, &JsValue::from_str("now")).ok()?;
+            let now: js_sys::Function = now.dyn_into().ok()?;
+            now.call0(&performance).ok()?.as_f64()
+        })
+        .unwrap_or_else(js_sys::Date::now)
+}
+
 /// GizmoAxis を文字列に変換
 pub fn axis_to_string(axis: GizmoAxis) -> String {
     match axis {
@@ -30,3 +56,50 @@ pub fn axis_to_string(axis: GizmoAxis) -> String {
         GizmoAxis::None => "".to_string(),
     }
 }
+
+/// クエリ結果・差分・計画をJSオブジェクトに変換
+///
+/// 行の `fields` はMapではなくプレーンなオブジェクトになる。
+/// クエリ結果を返す全てのAPI・通知で同じ形になるようにこの関数を使う
+pub fn query_to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// クエリ検証エラーを `{ message, errors: [{ kind, ... }] }` 形式のJSオブジェクトに変換
+pub fn validation_error_to_js(error: &QueryValidationError) -> JsValue {
+    let value = serde_json::json!({
+        "message": error.to_string(),
+        "errors": error.errors,
+    });
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .unwrap_or_else(|_| JsValue::from_str(&error.to_string()))
+}
+
+/// 列指向のクエリ結果をJSオブジェクトに変換（数値列は型付き配列）
+pub fn columnar_to_js(result: &ColumnarResult) -> Result<JsValue, JsValue> {
+    let columns = Object::new();
+    for (name, column) in &result.columns {
+        let value: JsValue = match column {
+            Column::Float32(values) => Float32Array::from(values.as_slice()).into(),
+            Column::Uint32(values) => Uint32Array::from(values.as_slice()).into(),
+            Column::String(values) => values
+                .iter()
+                .map(|v| v.as_deref().map_or(JsValue::NULL, JsValue::from_str))
+                .collect::<Array>()
+                .into(),
+            Column::Json(values) => values
+                .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+                .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))?,
+        };
+        Reflect::set(&columns, &JsValue::from_str(name), &value)?;
+    }
+
+    let output = Object::new();
+    Reflect::set(&output, &"ids".into(), &Uint32Array::from(result.ids.as_slice()))?;
+    Reflect::set(&output, &"columns".into(), &columns)?;
+    Reflect::set(&output, &"total_count".into(), &(result.total_count as f64).into())?;
+    Ok(output.into())
+}
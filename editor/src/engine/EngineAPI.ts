@@ -1,4 +1,4 @@
-import type { EntityId, Vec3, Quat, EntityData, Transform, QueryDescriptor, QueryResult, GizmoMode, GizmoAxis } from './types';
+import type { EntityId, MeshHandle, TextureHandle, TextureOptions, Material, Light, AmbientLight, ShadowFlags, PrimitiveDesc, ImportedMesh, Vec3, Quat, EntityData, Transform, QueryDescriptor, QueryResult, ColumnarQueryResult, QueryHandle, QueryParams, QueryDelta, QueryProgress, AsyncQueryOptions, QueryProfile, QueryPlan, IndexKind, GizmoMode, GizmoAxis } from './types';
 import { Vec3 as Vec3Helper, Quat as QuatHelper } from './types';
 import { EntityQueryBuilder } from './query';
 
@@ -14,15 +14,69 @@ interface WasmEngine {
   get_rotation(id: number): number[] | undefined;
   get_scale(id: number): number[] | undefined;
   get_name(id: number): string | undefined;
+  set_parent(id: number, parent_id?: number): boolean;
+  get_parent(id: number): number | undefined;
+  get_children(id: number): Uint32Array;
   is_alive(id: number): boolean;
   entity_count(): number;
+  // Mesh API
+  create_mesh(vertices: Float32Array, indices: Uint32Array, uvs?: Float32Array): number;
+  create_primitive(desc: PrimitiveDesc): number;
+  import_obj(obj_text: string, mtl_text?: string, smooth_normals?: boolean): ImportedMesh[];
+  import_stl(bytes: Uint8Array, smooth_normals?: boolean): number;
+  import_ply(bytes: Uint8Array, smooth_normals?: boolean): number;
+  import_gltf(bytes: Uint8Array): Uint32Array;
+  export_glb(): Uint8Array;
+  set_mesh(id: number, handle: number): boolean;
+  get_mesh(id: number): number | undefined;
+  release_mesh(handle: number): boolean;
+  // Texture API
+  upload_texture(rgba: Uint8Array, width: number, height: number, options?: TextureOptions): number;
+  load_texture(bytes: Uint8Array, options?: TextureOptions): number;
+  release_texture(handle: number): boolean;
+  // Material API
+  set_material(id: number, desc: Material): void;
+  get_material(id: number): Material | undefined;
+  remove_material(id: number): boolean;
+  // Light API
+  set_light(id: number, desc: Light): void;
+  get_light(id: number): Light | undefined;
+  remove_light(id: number): boolean;
+  set_shadow_flags(id: number, casts_shadows: boolean, receives_shadows: boolean): void;
+  get_shadow_flags(id: number): ShadowFlags;
+  set_ambient_light(r: number, g: number, b: number, intensity: number): void;
+  get_ambient_light(): number[];
   tick(delta_time: number): void;
   resize(width: number, height: number): void;
+  set_msaa(samples: number): void;
+  get_msaa(): number;
   width(): number;
   height(): number;
   execute_query(query_json: string): QueryResult;
+  execute_query_columnar(query_json: string): ColumnarQueryResult;
+  execute_query_text(query_text: string): QueryResult;
+  prepare_query(query_json: string): QueryHandle;
+  run_prepared(handle: QueryHandle, params?: QueryParams): QueryResult;
+  run_prepared_columnar(handle: QueryHandle, params?: QueryParams): ColumnarQueryResult;
+  prepared_params(handle: QueryHandle): string[];
+  release_prepared(handle: QueryHandle): boolean;
+  explain_query(query_json: string): QueryPlan;
+  explain(query_json: string): QueryProfile;
+  profile(query_json: string): QueryProfile;
+  create_index(field: string, kind: IndexKind): boolean;
+  create_component_index(component: string): boolean;
+  drop_index(name: string): boolean;
+  index_names(): string[];
   subscribe_query(query_json: string, callback: (result: QueryResult) => void): number;
+  subscribe_query_delta(query_json: string, callback: (delta: QueryDelta) => void): number;
   unsubscribe_query(subscription_id: number): boolean;
+  start_query(
+    query_json: string,
+    callback: (result: QueryResult) => void,
+    on_progress?: (progress: QueryProgress) => void
+  ): number;
+  cancel_query(task_id: number): boolean;
+  set_query_time_budget(budget_ms: number): void;
   // Camera API
   orbit_camera(delta_x: number, delta_y: number): void;
   pan_camera(delta_x: number, delta_y: number): void;
@@ -175,6 +229,28 @@ export class EngineAPI {
     this.entities.set(id, name);
   }
 
+  /**
+   * 親を設定（null で親子関係を解除）
+   * 自身や子孫を親にしようとした場合は false
+   */
+  setParent(id: EntityId, parent: EntityId | null): boolean {
+    return this.getEngine().set_parent(id, parent ?? undefined);
+  }
+
+  /**
+   * 親のIDを取得
+   */
+  getParent(id: EntityId): EntityId | null {
+    return this.getEngine().get_parent(id) ?? null;
+  }
+
+  /**
+   * 子のID一覧を取得
+   */
+  getChildren(id: EntityId): EntityId[] {
+    return Array.from(this.getEngine().get_children(id));
+  }
+
   /**
    * Entityが生存しているか確認
    */
@@ -182,6 +258,282 @@ export class EngineAPI {
     return this.getEngine().is_alive(id);
   }
 
+  // ========================================================================
+  // Mesh API
+  // ========================================================================
+
+  /**
+   * メッシュを作成
+   * @param vertices 1頂点あたり position(3), normal(3), color(3) の9要素
+   * @param indices 三角形リストのインデックス
+   * @param uvs 1頂点あたり u, v の2要素（省略可、原点は画像の左上）
+   * @throws 頂点数・インデックス・UV数が不正な場合、三角形が1つもない場合
+   */
+  createMesh(
+    vertices: Float32Array | number[],
+    indices: Uint32Array | number[],
+    uvs?: Float32Array | number[]
+  ): MeshHandle {
+    return this.getEngine().create_mesh(
+      vertices instanceof Float32Array ? vertices : new Float32Array(vertices),
+      indices instanceof Uint32Array ? indices : new Uint32Array(indices),
+      uvs === undefined || uvs instanceof Float32Array ? uvs : new Float32Array(uvs)
+    );
+  }
+
+  /**
+   * プリミティブ形状のメッシュを作成
+   * @example api.createPrimitive({ shape: 'sphere', radius: 1 })
+   */
+  createPrimitive(desc: PrimitiveDesc): MeshHandle {
+    return this.getEngine().create_primitive(desc);
+  }
+
+  /**
+   * プリミティブ形状のEntityを作成
+   * メッシュはEntityが所有し、Entity削除時に解放される
+   * @example api.spawnPrimitive('Ball', { shape: 'sphere' })
+   */
+  spawnPrimitive(name: string, desc: PrimitiveDesc): EntityId {
+    const id = this.createEntity(name);
+    const handle = this.createPrimitive(desc);
+    this.setMesh(id, handle);
+    this.releaseMesh(handle);
+    return id;
+  }
+
+  /**
+   * OBJ（+MTL）ファイルの内容からメッシュを作成
+   * @param options.smoothNormals 法線のない面をスムースにするか（既定: true）
+   * @throws 解析エラー（行番号付きのメッセージ）
+   */
+  importObj(objText: string, mtlText?: string, options?: { smoothNormals?: boolean }): ImportedMesh[] {
+    return this.getEngine().import_obj(objText, mtlText, options?.smoothNormals);
+  }
+
+  /**
+   * OBJ（+MTL）をオブジェクトごとのEntityとして作成
+   * メッシュはEntityが所有し、Entity削除時に解放される
+   */
+  spawnObj(objText: string, mtlText?: string, options?: { smoothNormals?: boolean }): EntityId[] {
+    return this.importObj(objText, mtlText, options).map(({ name, mesh }) => {
+      const id = this.createEntity(name);
+      this.setMesh(id, mesh);
+      this.releaseMesh(mesh);
+      return id;
+    });
+  }
+
+  /**
+   * STL（バイナリ/ASCII）からメッシュを作成
+   * 同じ位置の頂点は溶接され、法線は再計算される
+   * @param options.smoothNormals スムースシェーディングにするか（既定: true）
+   * @throws 解析エラー
+   */
+  importStl(bytes: Uint8Array | ArrayBuffer, options?: { smoothNormals?: boolean }): MeshHandle {
+    const data = bytes instanceof Uint8Array ? bytes : new Uint8Array(bytes);
+    return this.getEngine().import_stl(data, options?.smoothNormals);
+  }
+
+  /**
+   * PLY（ASCII/バイナリ）からメッシュを作成
+   * 頂点色と法線はファイルにあればそれを使う
+   * @param options.smoothNormals 法線がない場合にスムースにするか（既定: true）
+   * @throws 解析エラー
+   */
+  importPly(bytes: Uint8Array | ArrayBuffer, options?: { smoothNormals?: boolean }): MeshHandle {
+    const data = bytes instanceof Uint8Array ? bytes : new Uint8Array(bytes);
+    return this.getEngine().import_ply(data, options?.smoothNormals);
+  }
+
+  /**
+   * glTF 2.0（.gltf / .glb）を読み込み、ノード階層をEntityとして作成
+   * 未対応の拡張などはコンソールに警告される
+   * @returns ルートEntityのID
+   * @throws 解析エラー
+   */
+  importGltf(bytes: Uint8Array | ArrayBuffer): EntityId[] {
+    const data = bytes instanceof Uint8Array ? bytes : new Uint8Array(bytes);
+    const roots = Array.from(this.getEngine().import_gltf(data));
+
+    // 子孫を含めてEntityキャッシュに登録
+    const register = (id: EntityId) => {
+      this.entities.set(id, this.getName(id) ?? '');
+      this.getChildren(id).forEach(register);
+    };
+    roots.forEach(register);
+    return roots;
+  }
+
+  /**
+   * シーン全体をGLB（glTF 2.0バイナリ）として書き出す
+   * Name・Transform階層・メッシュ（頂点色はCOLOR_0）が含まれる
+   */
+  exportGlb(): Uint8Array {
+    return this.getEngine().export_glb();
+  }
+
+  /**
+   * シーンをGLBファイルとしてダウンロード
+   */
+  downloadGlb(filename = 'scene.glb'): void {
+    const blob = new Blob([this.exportGlb()], { type: 'model/gltf-binary' });
+    const url = URL.createObjectURL(blob);
+    const link = document.createElement('a');
+    link.href = url;
+    link.download = filename;
+    link.click();
+    URL.revokeObjectURL(url);
+  }
+
+  /**
+   * Entityにメッシュを設定
+   * @returns 無効なEntityまたは未登録のハンドルの場合false
+   */
+  setMesh(id: EntityId, handle: MeshHandle): boolean {
+    return this.getEngine().set_mesh(id, handle);
+  }
+
+  /**
+   * Entityのメッシュハンドルを取得（未設定ならnull）
+   */
+  getMesh(id: EntityId): MeshHandle | null {
+    return this.getEngine().get_mesh(id) ?? null;
+  }
+
+  /**
+   * createMeshで得たメッシュを解放
+   * Entityが使用中の間はGPUリソースは保持される
+   */
+  releaseMesh(handle: MeshHandle): boolean {
+    return this.getEngine().release_mesh(handle);
+  }
+
+  // ========================================================================
+  // Texture API
+  // ========================================================================
+
+  /**
+   * RGBA8のピクセル列（行は上から下）からテクスチャを作成
+   * @example api.uploadTexture(imageData.data, imageData.width, imageData.height)
+   * @throws データ長が width * height * 4 と一致しない場合
+   */
+  uploadTexture(
+    rgba: Uint8Array | Uint8ClampedArray,
+    width: number,
+    height: number,
+    options?: TextureOptions
+  ): TextureHandle {
+    const bytes = rgba instanceof Uint8Array ? rgba : new Uint8Array(rgba.buffer, rgba.byteOffset, rgba.byteLength);
+    return this.getEngine().upload_texture(bytes, width, height, options);
+  }
+
+  /**
+   * 画像ファイル（PNG・JPEG・Radiance HDR）をエンジン側でデコードしてテクスチャを作成
+   * HDRは color_space によらずリニアな浮動小数点テクスチャになる
+   * @example api.loadTexture(new Uint8Array(await file.arrayBuffer()), { premultiply_alpha: true })
+   * @throws 形式が不明な場合や画像が壊れている場合
+   */
+  loadTexture(bytes: Uint8Array | ArrayBuffer, options?: TextureOptions): TextureHandle {
+    const data = bytes instanceof Uint8Array ? bytes : new Uint8Array(bytes);
+    return this.getEngine().load_texture(data, options);
+  }
+
+  /**
+   * uploadTexture / loadTextureで得たテクスチャを解放
+   * マテリアルが使用中の間はGPUリソースは保持される
+   */
+  releaseTexture(handle: TextureHandle): boolean {
+    return this.getEngine().release_texture(handle);
+  }
+
+  // ========================================================================
+  // Material API
+  // ========================================================================
+
+  /**
+   * Entityのマテリアルを設定（省略したフィールドは現在の値のまま）
+   * @example api.setMaterial(id, { base_color: [1, 0, 0, 1], roughness: 0.3 })
+   * @throws 値が範囲外の場合
+   */
+  setMaterial(id: EntityId, material: Material): void {
+    this.getEngine().set_material(id, material);
+  }
+
+  /**
+   * Entityのマテリアルを取得（未設定ならnull = 既定のマテリアル）
+   */
+  getMaterial(id: EntityId): Required<Material> | null {
+    return (this.getEngine().get_material(id) as Required<Material> | undefined) ?? null;
+  }
+
+  /**
+   * Entityのマテリアルを削除し、既定のマテリアルに戻す
+   */
+  removeMaterial(id: EntityId): boolean {
+    return this.getEngine().remove_material(id);
+  }
+
+  // ========================================================================
+  // Light API
+  // ========================================================================
+
+  /**
+   * Entityのライトを設定（種類を変えると以前のライトは置き換えられる）
+   * メッシュ未設定のEntityはCubeとして描画されなくなる
+   * @example api.setLight(id, { kind: 'spot', intensity: 20, outer_cone_angle: 0.5 })
+   * @example api.setLight(sunId, { cast_shadows: true, shadow_bias: 1.5 })
+   * @throws 値が範囲外の場合や、種類にないフィールドを指定した場合
+   */
+  setLight(id: EntityId, light: Light): void {
+    this.getEngine().set_light(id, light);
+  }
+
+  /**
+   * Entityのライトを取得（未設定ならnull）
+   */
+  getLight(id: EntityId): Light | null {
+    return this.getEngine().get_light(id) ?? null;
+  }
+
+  /**
+   * Entityのライトを削除
+   * setLight がメッシュを外していた場合は組み込みのCubeの描画に戻る
+   */
+  removeLight(id: EntityId): boolean {
+    return this.getEngine().remove_light(id);
+  }
+
+  /**
+   * Entityが影を落とすか・受けるかを設定
+   */
+  setShadowFlags(id: EntityId, flags: ShadowFlags): void {
+    this.getEngine().set_shadow_flags(id, flags.casts_shadows, flags.receives_shadows);
+  }
+
+  /**
+   * Entityが影を落とすか・受けるかを取得
+   */
+  getShadowFlags(id: EntityId): ShadowFlags {
+    return this.getEngine().get_shadow_flags(id);
+  }
+
+  /**
+   * 環境光を設定
+   */
+  setAmbientLight(ambient: AmbientLight): void {
+    const [r, g, b] = ambient.color;
+    this.getEngine().set_ambient_light(r, g, b, ambient.intensity);
+  }
+
+  /**
+   * 環境光を取得
+   */
+  getAmbientLight(): AmbientLight {
+    const [r, g, b, intensity] = this.getEngine().get_ambient_light();
+    return { color: [r, g, b], intensity };
+  }
+
   /**
    * EntityのTransformを取得
    */
@@ -239,6 +591,21 @@ export class EngineAPI {
     this.getEngine().resize(width, height);
   }
 
+  /**
+   * MSAAのサンプル数を設定（1で無効、4で4x）
+   * @throws 1・4以外の値や、デバイスが対応しないサンプル数の場合
+   */
+  setMsaa(samples: 1 | 4): void {
+    this.getEngine().set_msaa(samples);
+  }
+
+  /**
+   * MSAAのサンプル数を取得
+   */
+  getMsaa(): number {
+    return this.getEngine().get_msaa();
+  }
+
   /**
    * Canvas幅を取得
    */
@@ -262,6 +629,7 @@ export class EngineAPI {
 
   /**
    * クエリを実行
+   * @throws {QueryValidationError} 未知のフィールド・コンポーネントや型の合わない比較を含む場合
    */
   executeQuery(query: EntityQueryBuilder | QueryDescriptor): QueryResult {
     const json = query instanceof EntityQueryBuilder
@@ -271,6 +639,129 @@ export class EngineAPI {
     return this.getEngine().execute_query(json);
   }
 
+  /**
+   * クエリを実行し、列指向の結果を取得（大量の数値データ向け）
+   * @example const { ids, columns } = api.executeQueryColumnar(api.query().select('position.x', 'position.y'));
+   */
+  executeQueryColumnar(query: EntityQueryBuilder | QueryDescriptor): ColumnarQueryResult {
+    const json = query instanceof EntityQueryBuilder
+      ? query.toJSON()
+      : JSON.stringify(query);
+
+    return this.getEngine().execute_query_columnar(json);
+  }
+
+  /**
+   * テキストクエリを実行
+   * @param text クエリ文字列 (例: "SELECT id, name WHERE Transform LIMIT 10")
+   */
+  executeQueryText(text: string): QueryResult {
+    return this.getEngine().execute_query_text(text);
+  }
+
+  /**
+   * クエリを準備（毎フレーム実行するクエリ向け）
+   * フィルター値に '$minY' のような文字列を指定するとパラメータになる
+   * @example
+   * const handle = api.prepareQuery(api.query().select('name').where('position.y', '>', '$minY'));
+   * api.runPrepared(handle, { minY: 0 });
+   */
+  prepareQuery(query: EntityQueryBuilder | QueryDescriptor): QueryHandle {
+    const json = query instanceof EntityQueryBuilder
+      ? query.toJSON()
+      : JSON.stringify(query);
+
+    return this.getEngine().prepare_query(json);
+  }
+
+  /**
+   * 準備済みクエリを実行
+   */
+  runPrepared(handle: QueryHandle, params?: QueryParams): QueryResult {
+    return this.getEngine().run_prepared(handle, params);
+  }
+
+  /**
+   * 準備済みクエリを実行し、列指向の結果を取得
+   */
+  runPreparedColumnar(handle: QueryHandle, params?: QueryParams): ColumnarQueryResult {
+    return this.getEngine().run_prepared_columnar(handle, params);
+  }
+
+  /**
+   * 準備済みクエリのパラメータ名を取得
+   */
+  preparedParams(handle: QueryHandle): string[] {
+    return this.getEngine().prepared_params(handle);
+  }
+
+  /**
+   * 準備済みクエリを解放
+   */
+  releasePrepared(handle: QueryHandle): boolean {
+    return this.getEngine().release_prepared(handle);
+  }
+
+  /**
+   * クエリの実行計画を取得
+   */
+  explainQuery(query: EntityQueryBuilder | QueryDescriptor): QueryPlan {
+    const json = query instanceof EntityQueryBuilder
+      ? query.toJSON()
+      : JSON.stringify(query);
+
+    return this.getEngine().explain_query(json);
+  }
+
+  /**
+   * クエリの段階ごとの計画を取得（実行はしない）
+   * 候補取得方法・使用インデックス・各段階で取り出すフィールドを返す
+   */
+  explain(query: EntityQueryBuilder | QueryDescriptor): QueryProfile {
+    const json = query instanceof EntityQueryBuilder
+      ? query.toJSON()
+      : JSON.stringify(query);
+
+    return this.getEngine().explain(json);
+  }
+
+  /**
+   * クエリを実行し、段階ごとの候補数・時間・フィールド取り出し回数を計測
+   * @example
+   * const profile = api.profile(api.query().with('Transform').where('position.y', '>', 0));
+   * console.table(profile.stages);
+   */
+  profile(query: EntityQueryBuilder | QueryDescriptor): QueryProfile {
+    const json = query instanceof EntityQueryBuilder
+      ? query.toJSON()
+      : JSON.stringify(query);
+
+    return this.getEngine().profile(json);
+  }
+
+  /**
+   * フィールドインデックスを作成
+   * @param field フィールド名 (例: "name", "position.x")
+   * @param kind 'hash'（等価検索）| 'btree'（範囲検索）
+   */
+  createIndex(field: string, kind: IndexKind): boolean {
+    return this.getEngine().create_index(field, kind);
+  }
+
+  /**
+   * コンポーネントのビットセットインデックスを作成
+   */
+  createComponentIndex(component: string): boolean {
+    return this.getEngine().create_component_index(component);
+  }
+
+  /**
+   * インデックスを削除
+   */
+  dropIndex(name: string): boolean {
+    return this.getEngine().drop_index(name);
+  }
+
   /**
    * クエリを購読
    */
@@ -285,6 +776,21 @@ export class EngineAPI {
     return this.getEngine().subscribe_query(json, callback);
   }
 
+  /**
+   * クエリを差分モードで購読
+   * 登録時は全行が added として通知される
+   */
+  subscribeQueryDelta(
+    query: EntityQueryBuilder | QueryDescriptor,
+    callback: (delta: QueryDelta) => void
+  ): number {
+    const json = query instanceof EntityQueryBuilder
+      ? query.toJSON()
+      : JSON.stringify(query);
+
+    return this.getEngine().subscribe_query_delta(json, callback);
+  }
+
   /**
    * 購読解除
    */
@@ -292,6 +798,54 @@ export class EngineAPI {
     return this.getEngine().unsubscribe_query(subscriptionId);
   }
 
+  /**
+   * クエリを複数フレームに分けて実行（大規模なWorld向け）
+   * tickごとに時間予算（setQueryTimeBudget）の範囲で処理を進め、完了時に resolve する
+   * @example
+   * const controller = new AbortController();
+   * const result = await api.executeQueryAsync(api.query().where('position.y', '>', 0), {
+   *   onProgress: (p) => console.log(p.stage, p.processed / p.total),
+   *   signal: controller.signal,
+   * });
+   */
+  executeQueryAsync(
+    query: EntityQueryBuilder | QueryDescriptor,
+    options: AsyncQueryOptions = {}
+  ): Promise<QueryResult> {
+    const json = query instanceof EntityQueryBuilder
+      ? query.toJSON()
+      : JSON.stringify(query);
+    const { onProgress, signal } = options;
+
+    return new Promise((resolve, reject) => {
+      if (signal?.aborted) {
+        reject(new DOMException('Query aborted', 'AbortError'));
+        return;
+      }
+      const onAbort = () => {
+        this.getEngine().cancel_query(taskId);
+        reject(new DOMException('Query aborted', 'AbortError'));
+      };
+      const taskId = this.getEngine().start_query(
+        json,
+        (result) => {
+          signal?.removeEventListener('abort', onAbort);
+          resolve(result);
+        },
+        onProgress
+      );
+      signal?.addEventListener('abort', onAbort, { once: true });
+    });
+  }
+
+  /**
+   * 分割実行クエリの1フレームあたりの時間予算を設定（ミリ秒、デフォルト4ms）
+   * 実行中のクエリで等分し、各クエリは毎フレーム少なくとも1チャンク進む
+   */
+  setQueryTimeBudget(budgetMs: number): void {
+    this.getEngine().set_query_time_budget(budgetMs);
+  }
+
   // ========================================================================
   // カメラ操作 API
   // ========================================================================
@@ -4,11 +4,19 @@
 
 import { useState, useEffect, useCallback, useRef, useMemo } from 'react';
 import type { QueryResult, QueryDescriptor, QueryResultRow } from './types';
-import { EntityQueryBuilder } from './query';
+import { isQueryValidationError } from './types';
+import { EntityQueryBuilder, applyQueryDelta } from './query';
 import { useEngine } from './context';
 
 type QueryFactory = () => EntityQueryBuilder | QueryDescriptor;
 
+/** throw された値を Error に変換（検証エラーはメッセージを使用） */
+function toError(e: unknown): Error {
+  if (e instanceof Error) return e;
+  if (isQueryValidationError(e)) return new Error(e.message);
+  return new Error(String(e));
+}
+
 export interface UseEntityQueryOptions {
   /** 自動購読を無効化（手動で refetch を呼ぶ） */
   manual?: boolean;
@@ -70,7 +78,7 @@ export function useEntityQuery(
       setData(result);
       setError(null);
     } catch (e) {
-      const err = e instanceof Error ? e : new Error(String(e));
+      const err = toError(e);
       setError(err);
       onErrorRef.current?.(err);
     } finally {
@@ -91,14 +99,20 @@ export function useEntityQuery(
     try {
       const query = queryFactory();
 
-      // 購読開始
-      subscriptionRef.current = engine.subscribeQuery(query, (result) => {
-        setData(result);
+      // 購読開始（差分を適用し、変更のない行の再レンダーを避ける）
+      // 初回通知は全行が added なので、以前のクエリの結果は使わない
+      let initial = true;
+      subscriptionRef.current = engine.subscribeQueryDelta(query, (delta) => {
+        const reset = initial;
+        initial = false;
+        setData((prev) =>
+          applyQueryDelta(reset || !prev ? { rows: [], total_count: 0 } : prev, delta)
+        );
         setLoading(false);
         setError(null);
       });
     } catch (e) {
-      const err = e instanceof Error ? e : new Error(String(e));
+      const err = toError(e);
       setError(err);
       setLoading(false);
       onErrorRef.current?.(err);
@@ -1,6 +1,6 @@
 // Types
-export type { Vec3, Quat, EntityId, Transform, EntityData } from './types';
-export { Vec3 as Vec3Helper, Quat as QuatHelper } from './types';
+export type { Vec3, Quat, EntityId, MeshHandle, TextureHandle, TextureOptions, SamplerDesc, Material, Light, AmbientLight, ShadowFlags, PrimitiveDesc, ImportedMesh, Transform, EntityData } from './types';
+export { Vec3 as Vec3Helper, Quat as QuatHelper, isQueryValidationError } from './types';
 
 // Query Types
 export type {
@@ -9,12 +9,29 @@ export type {
   FilterExpr,
   OrderBy,
   QueryDescriptor,
+  RelationFilter,
   QueryResultRow,
   QueryResult,
+  ColumnarQueryResult,
+  QueryHandle,
+  QueryParams,
+  QueryDelta,
+  AddedRow,
+  UpdatedRow,
+  MovedRow,
+  ValueType,
+  QueryError,
+  QueryValidationError,
+  QueryStage,
+  QueryProgress,
+  AsyncQueryOptions,
+  ProfileStage,
+  StageReport,
+  QueryProfile,
 } from './types';
 
 // Query Builder
-export { EntityQueryBuilder } from './query';
+export { EntityQueryBuilder, applyQueryDelta } from './query';
 
 // Context
 export { EngineProvider, useEngine } from './context';
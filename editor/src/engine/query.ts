@@ -4,8 +4,13 @@
 
 import type {
   QueryDescriptor,
+  QueryDelta,
+  QueryResult,
+  QueryResultRow,
   CompareOp,
+  EntityId,
   SortDirection,
+  Vec3,
 } from './types';
 
 export class EntityQueryBuilder {
@@ -25,6 +30,7 @@ export class EntityQueryBuilder {
   /**
    * 取得するフィールドを指定
    * @example query.select('name', 'position', 'health')
+   * @example query.select('name', 'rotation.euler.y', 'distance_to_camera', 'component_count')
    */
   select(...fields: string[]): this {
     this.descriptor.select = fields;
@@ -58,6 +64,70 @@ export class EntityQueryBuilder {
     return this;
   }
 
+  /**
+   * 位置が球の内側にあるEntityに絞り込む
+   * @example query.withinSphere({ x: 0, y: 0, z: 0 }, 5)
+   */
+  withinSphere(center: Vec3, radius: number): this {
+    this.spatial().push({ within_sphere: { center: [center.x, center.y, center.z], radius } });
+    return this;
+  }
+
+  /**
+   * 位置がAABBの内側にあるEntityに絞り込む
+   */
+  withinAabb(min: Vec3, max: Vec3): this {
+    this.spatial().push({ within_aabb: { min: [min.x, min.y, min.z], max: [max.x, max.y, max.z] } });
+    return this;
+  }
+
+  /**
+   * バウンディングボックスがレイと交差するEntityに絞り込む
+   */
+  intersectsRay(origin: Vec3, direction: Vec3, maxDistance?: number): this {
+    this.spatial().push({
+      intersects_ray: {
+        origin: [origin.x, origin.y, origin.z],
+        direction: [direction.x, direction.y, direction.z],
+        max_distance: maxDistance,
+      },
+    });
+    return this;
+  }
+
+  /**
+   * 指定したEntityの直接の子に絞り込む
+   * @example query.childOf(12)
+   */
+  childOf(parent: EntityId): this {
+    this.relations().push({ child_of: { parent } });
+    return this;
+  }
+
+  /**
+   * 指定したEntityの子孫に絞り込む（自身は含まない）
+   */
+  descendantOf(ancestor: EntityId): this {
+    this.relations().push({ descendant_of: { ancestor } });
+    return this;
+  }
+
+  /**
+   * 親を持たないEntityに絞り込む
+   */
+  rootsOnly(): this {
+    this.relations().push('root');
+    return this;
+  }
+
+  /**
+   * 指定点からの距離フィールド名を作成（select / where / orderBy で使用可能）
+   * @example query.select('name', EntityQueryBuilder.distanceTo(cursor))
+   */
+  static distanceTo(point: Vec3): string {
+    return `distance_to(${point.x}, ${point.y}, ${point.z})`;
+  }
+
   /**
    * ソート条件を指定
    * @example query.orderBy('name', 'asc')
@@ -95,6 +165,16 @@ export class EntityQueryBuilder {
     return this;
   }
 
+  private spatial() {
+    this.descriptor.spatial ??= [];
+    return this.descriptor.spatial;
+  }
+
+  private relations() {
+    this.descriptor.relations ??= [];
+    return this.descriptor.relations;
+  }
+
   /**
    * QueryDescriptor を構築
    */
@@ -109,3 +189,48 @@ export class EntityQueryBuilder {
     return JSON.stringify(this.descriptor);
   }
 }
+
+/**
+ * クエリ結果に差分を適用した新しい結果を返す
+ * 変更のない行はオブジェクトをそのまま再利用する
+ */
+export function applyQueryDelta(result: QueryResult, delta: QueryDelta): QueryResult {
+  const removed = new Set(delta.removed);
+  const movedIds = new Set(delta.moved.map((m) => m.id));
+  const updates = new Map(delta.updated.map((row) => [row.id, row]));
+
+  const patch = (row: QueryResultRow): QueryResultRow => {
+    const update = updates.get(row.id);
+    if (!update) return row;
+    const fields = { ...row.fields, ...update.fields };
+    for (const name of update.removed ?? []) {
+      delete fields[name];
+    }
+    return { id: row.id, fields };
+  };
+
+  const movedRows = new Map<number, QueryResultRow>();
+  const rows: QueryResultRow[] = [];
+  for (const row of result.rows) {
+    if (removed.has(row.id)) continue;
+    if (movedIds.has(row.id)) {
+      movedRows.set(row.id, patch(row));
+    } else {
+      rows.push(patch(row));
+    }
+  }
+
+  const inserts: [number, QueryResultRow][] = [
+    ...delta.added.map((a): [number, QueryResultRow] => [a.index, { id: a.id, fields: a.fields }]),
+    ...delta.moved.flatMap((m): [number, QueryResultRow][] => {
+      const row = movedRows.get(m.id);
+      return row ? [[m.to, row]] : [];
+    }),
+  ];
+  inserts.sort((a, b) => a[0] - b[0]);
+  for (const [index, row] of inserts) {
+    rows.splice(Math.min(index, rows.length), 0, row);
+  }
+
+  return { rows, total_count: delta.total_count };
+}
@@ -35,6 +35,144 @@ export const EntityId = {
   pack: (index: number, generation: number): EntityId => (generation << 20) | index,
 };
 
+/**
+ * メッシュハンドル（Rustのu32）
+ * 0 は組み込みのCube（メッシュ未設定のEntityもCubeで描画される）
+ * 4294967295 はメッシュなし（glTFのグループノードなど、描画されない）
+ */
+export type MeshHandle = number;
+
+/**
+ * テクスチャハンドル（Rustのu32）
+ */
+export type TextureHandle = number;
+
+/** テクスチャのフィルタ */
+export type FilterMode = 'nearest' | 'linear';
+
+/** UVが0〜1の外に出たときの扱い */
+export type AddressMode = 'repeat' | 'mirror_repeat' | 'clamp_to_edge';
+
+/**
+ * サンプラー設定（省略時はリピート・リニア補間）
+ */
+export interface SamplerDesc {
+  address_mode_u?: AddressMode;
+  address_mode_v?: AddressMode;
+  mag_filter?: FilterMode;
+  min_filter?: FilterMode;
+  mipmap_filter?: FilterMode;
+}
+
+/**
+ * テクスチャのアップロード設定
+ */
+export interface TextureOptions {
+  /** 色画像は 'srgb'（既定）、法線マップなどのデータ画像は 'linear' */
+  color_space?: 'srgb' | 'linear';
+  /** false ならミップマップを生成しない（既定: true） */
+  mipmaps?: boolean;
+  /** true なら色にアルファを乗算しておく（既定: false） */
+  premultiply_alpha?: boolean;
+  sampler?: SamplerDesc;
+}
+
+/**
+ * インポートしたメッシュ
+ */
+export interface ImportedMesh {
+  /** オブジェクト・グループ名 */
+  name: string;
+  mesh: MeshHandle;
+}
+
+/**
+ * プリミティブ形状の記述（省略したパラメータは既定値）
+ * 分割数（segments・rings など）は1024まで、subdivisions は6まで（超えるとエラー）
+ */
+export type PrimitiveDesc =
+  | { shape: 'cube' }
+  | { shape: 'sphere'; radius?: number; segments?: number; rings?: number }
+  | { shape: 'icosphere'; radius?: number; subdivisions?: number }
+  | { shape: 'plane'; width?: number; depth?: number; segments_x?: number; segments_z?: number }
+  | { shape: 'quad'; width?: number; height?: number }
+  | { shape: 'disc'; radius?: number; segments?: number }
+  | { shape: 'cylinder'; radius?: number; height?: number; segments?: number }
+  | { shape: 'cone'; radius?: number; height?: number; segments?: number }
+  | {
+      shape: 'torus';
+      major_radius?: number;
+      minor_radius?: number;
+      major_segments?: number;
+      minor_segments?: number;
+    }
+  | { shape: 'capsule'; radius?: number; height?: number; segments?: number; rings?: number };
+
+/**
+ * PBRマテリアル（メタリック・ラフネス）
+ * setMaterial では省略したフィールドは現在の値のまま
+ */
+export interface Material {
+  /** リニアRGBA（頂点色と乗算される） */
+  base_color?: [number, number, number, number];
+  /** 0〜1 */
+  metallic?: number;
+  /** 0〜1 */
+  roughness?: number;
+  /** リニアRGB */
+  emissive?: [number, number, number];
+  /** ライティングなしでベースカラーを出力 */
+  unlit?: boolean;
+  /** ベースカラーテクスチャ（null で外す） */
+  albedo_texture?: TextureHandle | null;
+  /** 接空間の法線マップ（color_space: 'linear' でアップロード） */
+  normal_texture?: TextureHandle | null;
+  /** G=粗さ、B=金属度のテクスチャ（color_space: 'linear' でアップロード） */
+  roughness_texture?: TextureHandle | null;
+}
+
+/**
+ * ライト（平行光源・点光源・スポットライト）
+ * 光はEntityのローカル -Z 方向に進む。setLight では省略したフィールドは現在の値のまま
+ */
+export interface Light {
+  /** 未設定のEntityでは必須 */
+  kind?: 'directional' | 'point' | 'spot';
+  /** リニアRGB */
+  color?: [number, number, number];
+  /** 色と乗算される強さ */
+  intensity?: number;
+  /** 光が届く距離（point / spot） */
+  range?: number;
+  /** 減衰が始まる角度（spot、中心軸からのラジアン） */
+  inner_cone_angle?: number;
+  /** 光が届かなくなる角度（spot、π/2以下） */
+  outer_cone_angle?: number;
+  /** 影を落とすか（directional / spot） */
+  cast_shadows?: boolean;
+  /** 光の方向の影のバイアス（シャドウマップのテクセル単位） */
+  shadow_bias?: number;
+  /** 法線方向の影のバイアス（シャドウマップのテクセル単位） */
+  shadow_normal_bias?: number;
+}
+
+/**
+ * Entityが影を落とすか・受けるか（未設定ならどちらもtrue）
+ */
+export interface ShadowFlags {
+  casts_shadows: boolean;
+  receives_shadows: boolean;
+}
+
+/**
+ * 環境光
+ */
+export interface AmbientLight {
+  /** リニアRGB */
+  color: [number, number, number];
+  intensity: number;
+}
+
 /**
  * Transformコンポーネントデータ
  */
@@ -101,6 +239,29 @@ export interface OrderBy {
   direction: SortDirection;
 }
 
+/**
+ * 空間フィルター（位置 or バウンディングボックスで判定）
+ */
+export type SpatialFilter =
+  | { within_sphere: { center: [number, number, number]; radius: number } }
+  | { within_aabb: { min: [number, number, number]; max: [number, number, number] } }
+  | { in_frustum: { view_proj: number[][] } }
+  | {
+      intersects_ray: {
+        origin: [number, number, number];
+        direction: [number, number, number];
+        max_distance?: number;
+      };
+    };
+
+/**
+ * 親子関係フィルター（Entityは EntityId で指定）
+ */
+export type RelationFilter =
+  | { child_of: { parent: EntityId } }
+  | { descendant_of: { ancestor: EntityId } }
+  | 'root';
+
 /**
  * クエリ記述子
  */
@@ -109,6 +270,8 @@ export interface QueryDescriptor {
   with_components: string[];
   without_components: string[];
   filters: FilterExpr[];
+  spatial?: SpatialFilter[];
+  relations?: RelationFilter[];
   order_by: OrderBy | null;
   limit: number | null;
   offset?: number | null;
@@ -130,6 +293,181 @@ export interface QueryResult {
   total_count: number;
 }
 
+/**
+ * 準備済みクエリのハンドル
+ */
+export type QueryHandle = number;
+
+/**
+ * 準備済みクエリのパラメータ値（キーは `$` を除いた名前）
+ */
+export type QueryParams = Record<string, number | string | boolean | null>;
+
+/**
+ * 列指向のクエリ結果
+ * 数値列は Float32Array、id・parent などのEntityIdは Uint32Array
+ * 値がない行は NaN / null（EntityId列は 0xFFFFFFFF）
+ * position などの複合値は 'position.x' のような列に展開される
+ */
+export interface ColumnarQueryResult {
+  ids: Uint32Array;
+  columns: Record<string, Float32Array | Uint32Array | (string | null)[] | unknown[]>;
+  total_count: number;
+}
+
+/**
+ * 分割実行クエリの実行段階
+ */
+export type QueryStage = 'filtering' | 'sorting' | 'building' | 'done';
+
+/**
+ * 分割実行クエリの進捗（processed / total は現在の段階での件数）
+ */
+export interface QueryProgress {
+  stage: QueryStage;
+  processed: number;
+  total: number;
+}
+
+/**
+ * executeQueryAsync のオプション
+ */
+export interface AsyncQueryOptions {
+  /** tickごとに進捗を通知 */
+  onProgress?: (progress: QueryProgress) => void;
+  /** 中止用シグナル（中止時は AbortError で reject） */
+  signal?: AbortSignal;
+}
+
+/**
+ * 差分で追加された行（index は新しい結果での位置）
+ */
+export interface AddedRow extends QueryResultRow {
+  index: number;
+}
+
+/**
+ * 差分で更新された行
+ * fields は変更されたフィールドの新しい値（null の値も含む）、removed は無くなったフィールド
+ */
+export interface UpdatedRow extends QueryResultRow {
+  removed: string[];
+}
+
+/**
+ * 差分で位置が変わった行
+ */
+export interface MovedRow {
+  id: number;
+  from: number;
+  to: number;
+}
+
+/**
+ * クエリ結果の差分
+ * updated は変更されたフィールドのみを持つ
+ */
+export interface QueryDelta {
+  added: AddedRow[];
+  removed: number[];
+  updated: UpdatedRow[];
+  moved: MovedRow[];
+  total_count: number;
+}
+
+/**
+ * フィールド・値の型
+ */
+export type ValueType = 'number' | 'string' | 'bool' | 'null' | 'object' | 'entity_id';
+
+/**
+ * クエリ検証エラーの詳細
+ */
+export type QueryError =
+  | { kind: 'unknown_field'; field: string; clause: 'select' | 'filter' | 'order_by' }
+  | { kind: 'unknown_component'; component: string }
+  | { kind: 'type_mismatch'; field: string; expected: ValueType; found: ValueType }
+  | { kind: 'unsupported_operator'; field: string; op: CompareOp; value_type: ValueType }
+  | { kind: 'duplicate_column'; column: string; field: string; other: string }
+  | { kind: 'non_finite_number'; field: string }
+  | { kind: 'limit_out_of_range'; limit: number }
+  | { kind: 'unsortable_field'; field: string; value_type: ValueType }
+  | { kind: 'missing_parameter'; name: string };
+
+/**
+ * クエリ検証エラー（execute_query / subscribe_query が throw する）
+ */
+export interface QueryValidationError {
+  message: string;
+  errors: QueryError[];
+}
+
+/**
+ * QueryValidationError かどうか
+ */
+export function isQueryValidationError(e: unknown): e is QueryValidationError {
+  return typeof e === 'object' && e !== null && 'errors' in e && 'message' in e;
+}
+
+/**
+ * インデックス種別
+ */
+export type IndexKind = 'hash' | 'btree';
+
+/**
+ * 候補Entityの取得方法
+ */
+export type AccessPath =
+  | { type: 'full_scan' }
+  | { type: 'field_index'; field: string; kind: IndexKind; op: CompareOp; value: number | string | boolean | null }
+  | { type: 'component_bitset'; component: string };
+
+/**
+ * クエリ実行計画
+ */
+export interface QueryPlan {
+  access: AccessPath;
+  estimated_candidates: number;
+  total_entities: number;
+}
+
+/**
+ * クエリの段階
+ */
+export type ProfileStage =
+  | 'scan'
+  | 'with'
+  | 'without'
+  | 'filter'
+  | 'spatial'
+  | 'relation'
+  | 'order_by'
+  | 'limit'
+  | 'select';
+
+/**
+ * 1段階の報告（explain では rows は推定できる段階のみ、time_ms / extracted は null）
+ */
+export interface StageReport {
+  stage: ProfileStage;
+  detail: string;
+  rows: number | null;
+  time_ms: number | null;
+  fields: string[];
+  extracted: number | null;
+}
+
+/**
+ * クエリのプロファイル（explain / profile の結果）
+ */
+export interface QueryProfile {
+  plan: QueryPlan;
+  indexes_used: string[];
+  stages: StageReport[];
+  total_time_ms: number | null;
+  total_count: number | null;
+}
+
 // ========== Gizmo Types ==========
 
 /**